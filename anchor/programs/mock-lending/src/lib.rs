@@ -0,0 +1,285 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+
+declare_id!("ECFHCKVw46UhH3yusSqfJpAPnw3LtgMrk8ssVw16rrjB");
+
+/// precision of `Reserve::exchange_rate`
+pub const EXCHANGE_RATE_PRECISION: u128 = 1_000_000_000_000;
+
+/// A single-asset lending reserve for local tests. Depositors get shares that redeem pro rata
+/// against the reserve's liquidity, and `accrue_yield` stands in for borrower interest.
+#[program]
+pub mod mock_lending {
+	use super::*;
+
+	pub fn initialize_reserve(ctx: Context<InitializeReserve>) -> Result<()> {
+		let reserve = &mut ctx.accounts.reserve;
+		reserve.liquidity_mint = ctx.accounts.liquidity_mint.key();
+		reserve.liquidity_vault = ctx.accounts.liquidity_vault.key();
+		reserve.total_liquidity = 0;
+		reserve.total_shares = 0;
+		reserve.bump = ctx.bumps.reserve;
+		Ok(())
+	}
+
+	pub fn initialize_obligation(ctx: Context<InitializeObligation>) -> Result<()> {
+		let obligation = &mut ctx.accounts.obligation;
+		obligation.owner = ctx.accounts.owner.key();
+		obligation.reserve = ctx.accounts.reserve.key();
+		obligation.shares = 0;
+		Ok(())
+	}
+
+	pub fn deposit(ctx: Context<ModifyObligation>, amount: u64) -> Result<()> {
+		let reserve = &mut ctx.accounts.reserve;
+		let shares = reserve.liquidity_to_shares(amount)?;
+		require!(shares > 0, MockLendingError::ZeroShares);
+
+		token_interface::transfer_checked(
+			CpiContext::new(ctx.accounts.token_program.to_account_info(), TransferChecked {
+				from: ctx.accounts.owner_token_account.to_account_info(),
+				mint: ctx.accounts.liquidity_mint.to_account_info(),
+				to: ctx.accounts.liquidity_vault.to_account_info(),
+				authority: ctx.accounts.owner.to_account_info(),
+			}),
+			amount,
+			ctx.accounts.liquidity_mint.decimals
+		)?;
+
+		reserve.total_liquidity = reserve.total_liquidity
+			.checked_add(amount)
+			.ok_or(MockLendingError::MathError)?;
+		reserve.total_shares = reserve.total_shares
+			.checked_add(shares)
+			.ok_or(MockLendingError::MathError)?;
+
+		let obligation = &mut ctx.accounts.obligation;
+		obligation.shares = obligation.shares.checked_add(shares).ok_or(MockLendingError::MathError)?;
+
+		Ok(())
+	}
+
+	pub fn withdraw(ctx: Context<ModifyObligation>, shares: u64) -> Result<()> {
+		let obligation = &mut ctx.accounts.obligation;
+		require!(shares <= obligation.shares, MockLendingError::InsufficientShares);
+
+		let reserve = &mut ctx.accounts.reserve;
+		let amount = reserve.shares_to_liquidity(shares)?;
+
+		reserve.total_liquidity = reserve.total_liquidity
+			.checked_sub(amount)
+			.ok_or(MockLendingError::MathError)?;
+		reserve.total_shares = reserve.total_shares
+			.checked_sub(shares)
+			.ok_or(MockLendingError::MathError)?;
+		obligation.shares -= shares;
+
+		let mint_key = reserve.liquidity_mint;
+		let seeds: &[&[u8]] = &[b"reserve", mint_key.as_ref(), &[reserve.bump]];
+
+		token_interface::transfer_checked(
+			CpiContext::new_with_signer(
+				ctx.accounts.token_program.to_account_info(),
+				TransferChecked {
+					from: ctx.accounts.liquidity_vault.to_account_info(),
+					mint: ctx.accounts.liquidity_mint.to_account_info(),
+					to: ctx.accounts.owner_token_account.to_account_info(),
+					authority: ctx.accounts.reserve.to_account_info(),
+				},
+				&[seeds]
+			),
+			amount,
+			ctx.accounts.liquidity_mint.decimals
+		)?;
+
+		Ok(())
+	}
+
+	/// Donates `amount` to the reserve, raising the exchange rate for every share holder
+	pub fn accrue_yield(ctx: Context<AccrueYield>, amount: u64) -> Result<()> {
+		token_interface::transfer_checked(
+			CpiContext::new(ctx.accounts.token_program.to_account_info(), TransferChecked {
+				from: ctx.accounts.payer_token_account.to_account_info(),
+				mint: ctx.accounts.liquidity_mint.to_account_info(),
+				to: ctx.accounts.liquidity_vault.to_account_info(),
+				authority: ctx.accounts.payer.to_account_info(),
+			}),
+			amount,
+			ctx.accounts.liquidity_mint.decimals
+		)?;
+
+		let reserve = &mut ctx.accounts.reserve;
+		reserve.total_liquidity = reserve.total_liquidity
+			.checked_add(amount)
+			.ok_or(MockLendingError::MathError)?;
+
+		Ok(())
+	}
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct Reserve {
+	pub liquidity_mint: Pubkey,
+	pub liquidity_vault: Pubkey,
+	pub total_liquidity: u64,
+	pub total_shares: u64,
+	pub bump: u8,
+}
+
+impl Reserve {
+	pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 1;
+
+	/// liquidity per share
+	/// precision: EXCHANGE_RATE_PRECISION
+	pub fn exchange_rate(&self) -> Result<u128> {
+		if self.total_shares == 0 {
+			return Ok(EXCHANGE_RATE_PRECISION);
+		}
+
+		(self.total_liquidity as u128)
+			.checked_mul(EXCHANGE_RATE_PRECISION)
+			.and_then(|v| v.checked_div(self.total_shares as u128))
+			.ok_or(MockLendingError::MathError.into())
+	}
+
+	pub fn liquidity_to_shares(&self, amount: u64) -> Result<u64> {
+		if self.total_shares == 0 || self.total_liquidity == 0 {
+			return Ok(amount);
+		}
+
+		(amount as u128)
+			.checked_mul(self.total_shares as u128)
+			.and_then(|v| v.checked_div(self.total_liquidity as u128))
+			.and_then(|v| u64::try_from(v).ok())
+			.ok_or(MockLendingError::MathError.into())
+	}
+
+	pub fn shares_to_liquidity(&self, shares: u64) -> Result<u64> {
+		if self.total_shares == 0 {
+			return Ok(0);
+		}
+
+		(shares as u128)
+			.checked_mul(self.total_liquidity as u128)
+			.and_then(|v| v.checked_div(self.total_shares as u128))
+			.and_then(|v| u64::try_from(v).ok())
+			.ok_or(MockLendingError::MathError.into())
+	}
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct Obligation {
+	pub owner: Pubkey,
+	pub reserve: Pubkey,
+	pub shares: u64,
+}
+
+impl Obligation {
+	pub const SIZE: usize = 8 + 32 + 32 + 8;
+}
+
+#[derive(Accounts)]
+pub struct InitializeReserve<'info> {
+	#[account(mut)]
+	pub payer: Signer<'info>,
+	#[account(
+		init,
+		seeds = [b"reserve", liquidity_mint.key().as_ref()],
+		space = Reserve::SIZE,
+		bump,
+		payer = payer
+	)]
+	pub reserve: Account<'info, Reserve>,
+	pub liquidity_mint: InterfaceAccount<'info, Mint>,
+	#[account(
+		init,
+		seeds = [b"reserve_vault", liquidity_mint.key().as_ref()],
+		bump,
+		payer = payer,
+		token::mint = liquidity_mint,
+		token::authority = reserve
+	)]
+	pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeObligation<'info> {
+	#[account(mut)]
+	pub payer: Signer<'info>,
+	/// CHECK: any account, usually a program signer, may own an obligation
+	pub owner: AccountInfo<'info>,
+	pub reserve: Account<'info, Reserve>,
+	#[account(
+		init,
+		seeds = [b"obligation", reserve.key().as_ref(), owner.key().as_ref()],
+		space = Obligation::SIZE,
+		bump,
+		payer = payer
+	)]
+	pub obligation: Account<'info, Obligation>,
+	pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyObligation<'info> {
+	pub owner: Signer<'info>,
+	#[account(mut, has_one = liquidity_mint, has_one = liquidity_vault)]
+	pub reserve: Account<'info, Reserve>,
+	#[account(mut, has_one = owner, has_one = reserve)]
+	pub obligation: Account<'info, Obligation>,
+	pub liquidity_mint: InterfaceAccount<'info, Mint>,
+	#[account(mut)]
+	pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+	#[account(mut, token::mint = liquidity_mint)]
+	pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueYield<'info> {
+	pub payer: Signer<'info>,
+	#[account(mut, has_one = liquidity_mint, has_one = liquidity_vault)]
+	pub reserve: Account<'info, Reserve>,
+	pub liquidity_mint: InterfaceAccount<'info, Mint>,
+	#[account(mut)]
+	pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+	#[account(mut, token::mint = liquidity_mint)]
+	pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum MockLendingError {
+	#[msg("Math Error")]
+	MathError,
+	#[msg("Deposit too small to mint a share")]
+	ZeroShares,
+	#[msg("Obligation has insufficient shares")]
+	InsufficientShares,
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn shares_redeem_with_accrued_yield() {
+		let mut reserve = Reserve::default();
+
+		let shares = reserve.liquidity_to_shares(1_000_000).unwrap();
+		assert_eq!(shares, 1_000_000);
+		reserve.total_liquidity = 1_000_000;
+		reserve.total_shares = shares;
+
+		// 5% yield
+		reserve.total_liquidity += 50_000;
+
+		assert_eq!(reserve.exchange_rate().unwrap(), 1_050_000_000_000);
+		assert_eq!(reserve.shares_to_liquidity(shares).unwrap(), 1_050_000);
+		assert_eq!(reserve.liquidity_to_shares(1_050_000).unwrap(), 1_000_000);
+	}
+}
@@ -7,6 +7,9 @@ pub const EMERGENCY_SHUTDOWN_PAUSED_OPERATIONS: u8 = 0b00101011;
 
 // INDEX MARKET CONSTANTS
 pub const MAX_INDEX_MARKET_EXPENSE_RATIO: u16 = 0;
+pub const INDEX_WEIGHT_PRECISION: u16 = 10_000; // bps
+pub const MAX_COLLATERAL_ASSETS: usize = 4;
+pub const DEFAULT_INDEX_REBALANCE_INTERVAL: i64 = TWENTY_FOUR_HOUR;
 
 
 // SPOT MARKET CONSTANTS
@@ -51,6 +54,7 @@ pub const LIQUIDATION_PCT_PRECISION: u128 = 10_000;
 pub const SPOT_BALANCE_PRECISION: u128 = 1_000_000_000; // expo = -9
 pub const SPOT_BALANCE_PRECISION_U64: u64 = 1_000_000_000; // expo = -9
 pub const SPOT_CUMULATIVE_INTEREST_PRECISION: u128 = 10_000_000_000; // expo = -10
+pub const LENDING_EXCHANGE_RATE_PRECISION: u128 = 1_000_000_000_000; // expo = -12
 
 pub const PERCENTAGE_PRECISION: u128 = 1_000_000; // expo -6 (represents 100%)
 pub const PERCENTAGE_PRECISION_I128: i128 = PERCENTAGE_PRECISION as i128;
@@ -212,3 +216,38 @@ pub const AUCTION_DERIVE_PRICE_FRACTION: i64 = 200;
 
 // WITHDRAWS
 pub const SPOT_MARKET_TOKEN_TWAP_WINDOW: i64 = TWENTY_FOUR_HOUR;
+
+// AUCTIONS
+pub const AUCTION_BID_DECREASE_RATE_PRECISION: u128 = 10_000; // expo = -4 (bps of start price per second)
+pub const COLLATERAL_AUCTION_START_PREMIUM: u128 = PERCENTAGE_PRECISION / 10; // 10% above oracle
+pub const COLLATERAL_AUCTION_PRICE_FLOOR: u128 = (PERCENTAGE_PRECISION * 8) / 10; // 80% of start price
+pub const DEFAULT_AUCTION_DURATION: i64 = ONE_HOUR * 6;
+
+// MINT LIMITS
+pub const DEFAULT_DEBT_CEILING_RAMP_DURATION: i64 = ONE_HOUR * 6;
+pub const DEFAULT_MINT_LIMIT_WINDOW: i64 = ONE_HOUR;
+
+// DYNAMIC FEES
+pub const DYNAMIC_FEE_TICK_VOLATILITY: u64 = PERCENTAGE_PRECISION_U64 / 10_000; // one tick is ~1 bp of price
+pub const DYNAMIC_FEE_TICK_REFERENCE_PERIOD: u64 = 300; // tick movement is measured over ~5 minutes
+
+// AMM ORACLE
+pub const AMM_ORACLE_TWAP_MAX_STALENESS: i64 = 600; // the band and dynamic fees need the twap cranked within 10 minutes
+
+// AMM OBSERVATIONS
+pub const MAX_AMM_OBSERVATION_CARDINALITY: u16 = 200; // keeps the account under the 10KB init limit
+pub const AMM_TWAP_PERIOD: u64 = 600; // routes check the pool against its own 10 minute twap
+pub const AMM_MAX_TWAP_DIVERGENCE: u16 = 500; // 5%, in bps
+
+// AMM REBALANCING
+pub const AMM_REBALANCE_WINDOW: i64 = ONE_HOUR * 24;
+
+// REDEMPTIONS
+pub const REDEMPTION_FEE_FLOOR: u128 = PERCENTAGE_PRECISION / 200; // 0.5%
+pub const REDEMPTION_BASE_RATE_HALF_LIFE: i64 = ONE_HOUR * 12;
+pub const REDEMPTION_BASE_RATE_BETA: u128 = 2;
+pub const REDEMPTION_BUCKETS: usize = 192; // 4 per doubling of the nominal collateral ratio from 2^-16 to 2^32
+
+// DELEVERAGING
+pub const DELEVERAGE_KEEPER_TIP_PENALTY_DIVISOR: u32 = 4; // keeper tip <= 1/4 of the liquidation penalty
+pub const MAX_DELEVERAGE_SLIPPAGE: u32 = (PERCENTAGE_PRECISION / 10) as u32; // 10%
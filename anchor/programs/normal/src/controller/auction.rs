@@ -0,0 +1,667 @@
+use anchor_lang::prelude::*;
+use solana_program::msg;
+
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::auction::{
+	calculate_auction_fill,
+	calculate_collateral_auction_floor_price,
+	calculate_collateral_auction_start_price,
+	calculate_debt_auction_max_lot,
+	calculate_surplus_auction_min_bid,
+};
+use crate::math::safe_math::SafeMath;
+use crate::state::auction::{ Auction, AuctionStatus };
+use crate::state::events::{
+	AuctionAction,
+	AuctionRecord,
+	DebtAuctionSettleRecord,
+	SurplusAuctionSettleRecord,
+};
+use crate::state::synth_market::{ AuctionConfig, AuctionType, SynthMarket };
+use crate::validate;
+
+/// Opens a collateral auction raising `debt_amount` of synthetic. The lot is sized for the
+/// liquidation fees too, which are paid in collateral out of whatever is left of it on settlement
+#[allow(clippy::too_many_arguments)]
+pub fn start_collateral_auction(
+	auction: &mut Auction,
+	auction_key: &Pubkey,
+	market: &SynthMarket,
+	user_key: &Pubkey,
+	initializer_key: &Pubkey,
+	liquidation_id: u16,
+	collateral_amount: u64,
+	debt_amount: u64,
+	liquidator_fee: u64,
+	if_fee: u64,
+	collateral_price: i64,
+	synthetic_price: i64,
+	now: i64
+) -> NormalResult {
+	validate!(
+		collateral_amount > 0 && debt_amount > 0,
+		ErrorCode::AuctionBidZero,
+		"collateral_amount={} debt_amount={} must be positive to start auction",
+		collateral_amount,
+		debt_amount
+	)?;
+
+	let config = market.collateral_action_config;
+	let start_price = calculate_collateral_auction_start_price(
+		collateral_price,
+		market.collateral_decimals,
+		synthetic_price,
+		market.decimals
+	)?;
+
+	*auction = Auction {
+		pubkey: *auction_key,
+		user: *user_key,
+		initializer: *initializer_key,
+		highest_bidder: Pubkey::default(),
+		auction_type: AuctionType::Collateral,
+		status: AuctionStatus::Active,
+		market_index: market.market_index,
+		liquidation_id,
+		start_ts: now,
+		end_ts: now.safe_add(config.auction_duration as i64)?,
+		start_price,
+		floor_price: calculate_collateral_auction_floor_price(start_price)?,
+		bid_decrease_rate: config.auction_bid_decrease_rate,
+		max_lot_size: config.max_auction_lot_size,
+		lot_amount: collateral_amount,
+		lot_sold: 0,
+		debt_amount,
+		debt_raised: 0,
+		liquidator_fee,
+		if_fee,
+		padding: [0; 16],
+	};
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: *auction_key,
+		auction_type: AuctionType::Collateral,
+		action: AuctionAction::Start,
+		market_index: market.market_index,
+		user: *user_key,
+		authority: *initializer_key,
+		price: start_price,
+		lot_remaining: collateral_amount,
+		debt_remaining: debt_amount,
+		..AuctionRecord::default()
+	});
+
+	Ok(())
+}
+
+/// Fills as much of the bid as the lot, max lot size and remaining debt allow.
+/// Returns the (collateral_amount, synthetic_amount) exchanged.
+pub fn bid_collateral_auction(
+	auction: &mut Auction,
+	bidder_key: &Pubkey,
+	collateral_amount: u64,
+	limit_price: Option<u64>,
+	now: i64
+) -> NormalResult<(u64, u64)> {
+	validate!(
+		auction.auction_type == AuctionType::Collateral,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a collateral auction"
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		!auction.is_expired(now),
+		ErrorCode::AuctionExpired,
+		"auction expired at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	validate!(collateral_amount > 0, ErrorCode::AuctionBidZero)?;
+
+	let price = auction.get_price(now)?;
+
+	if let Some(limit_price) = limit_price {
+		validate!(
+			price <= limit_price,
+			ErrorCode::AuctionPriceAboveLimit,
+			"auction price ({}) > limit price ({})",
+			price,
+			limit_price
+		)?;
+	}
+
+	let (lot_amount, bid_amount) = calculate_auction_fill(
+		collateral_amount,
+		auction.lot_amount,
+		auction.max_lot_size,
+		auction.debt_remaining()?,
+		price
+	)?;
+
+	validate!(lot_amount > 0, ErrorCode::AuctionBidZero, "bid fills no collateral")?;
+
+	auction.record_bid(lot_amount, bid_amount)?;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Bid,
+		market_index: auction.market_index,
+		user: auction.user,
+		authority: *bidder_key,
+		price,
+		lot_amount,
+		bid_amount,
+		lot_remaining: auction.lot_amount,
+		debt_remaining: auction.debt_remaining()?,
+		..AuctionRecord::default()
+	});
+
+	Ok((lot_amount, bid_amount))
+}
+
+/// Restarts the price curve from current oracle prices once an auction that still has collateral
+/// to sell has expired or decayed to its floor
+pub fn reset_collateral_auction(
+	auction: &mut Auction,
+	market: &SynthMarket,
+	keeper_key: &Pubkey,
+	collateral_price: i64,
+	synthetic_price: i64,
+	now: i64
+) -> NormalResult {
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		auction.auction_type == AuctionType::Collateral,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a collateral auction"
+	)?;
+
+	validate!(
+		auction.needs_reset(now)?,
+		ErrorCode::AuctionNotComplete,
+		"only incomplete auctions that expired or reached their floor can be reset"
+	)?;
+
+	let start_price = calculate_collateral_auction_start_price(
+		collateral_price,
+		market.collateral_decimals,
+		synthetic_price,
+		market.decimals
+	)?;
+
+	msg!("auction.start_price {} -> {}", auction.start_price, start_price);
+
+	auction.start_price = start_price;
+	auction.floor_price = calculate_collateral_auction_floor_price(start_price)?;
+	auction.start_ts = now;
+	auction.end_ts = now.safe_add(
+		market.collateral_action_config.auction_duration as i64
+	)?;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Reset,
+		market_index: auction.market_index,
+		user: auction.user,
+		authority: *keeper_key,
+		price: start_price,
+		lot_remaining: auction.lot_amount,
+		debt_remaining: auction.debt_remaining()?,
+		..AuctionRecord::default()
+	});
+
+	Ok(())
+}
+
+/// Closes out a complete auction. The vault's debt is removed from the market, any part of it
+/// the bids did not cover becomes protocol debt. The unsold lot pays the liquidator fee, then the
+/// insurance fund fee, and the rest goes back to the vault.
+/// Returns the (synthetic_to_burn, liquidator_fee, if_fee, collateral_to_return) for the
+/// instruction to move.
+pub fn settle_collateral_auction(
+	auction: &mut Auction,
+	market: &mut SynthMarket,
+	keeper_key: &Pubkey,
+	now: i64
+) -> NormalResult<(u64, u64, u64, u64)> {
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		auction.is_complete(),
+		ErrorCode::AuctionNotComplete,
+		"lot_amount={} debt_remaining={}",
+		auction.lot_amount,
+		auction.debt_remaining()?
+	)?;
+
+	let shortfall = auction.shortfall()?;
+
+	market.outstanding_debt = market.outstanding_debt.safe_sub(
+		auction.debt_raised as u128
+	)?;
+
+	if shortfall > 0 {
+		msg!(
+			"auction shortfall {} added to market {} protocol debt",
+			shortfall,
+			market.market_index
+		);
+		market.protocol_debt = market.protocol_debt.safe_add(shortfall)?;
+	}
+
+	// unsold collateral pays the fees once the debt is covered, what's left goes back to the vault
+	let liquidator_fee = auction.lot_amount.min(auction.liquidator_fee);
+	let if_fee = auction.lot_amount.safe_sub(liquidator_fee)?.min(auction.if_fee);
+	let collateral_to_return = auction.lot_amount.safe_sub(liquidator_fee)?.safe_sub(if_fee)?;
+	let synthetic_to_burn = auction.debt_raised;
+
+	auction.status = AuctionStatus::Settled;
+	auction.lot_amount = 0;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Settle,
+		market_index: auction.market_index,
+		user: auction.user,
+		authority: *keeper_key,
+		lot_amount: collateral_to_return,
+		bid_amount: synthetic_to_burn,
+		shortfall,
+		liquidator_fee,
+		if_fee,
+		..AuctionRecord::default()
+	});
+
+	Ok((synthetic_to_burn, liquidator_fee, if_fee, collateral_to_return))
+}
+
+/// Opens a debt auction for the market's protocol debt once its insurance claim is used up.
+/// The lot starts at the config's max lot size of governance tokens and is bid down.
+pub fn start_debt_auction(
+	auction: &mut Auction,
+	auction_key: &Pubkey,
+	market: &SynthMarket,
+	config: &AuctionConfig,
+	initializer_key: &Pubkey,
+	now: i64
+) -> NormalResult {
+	let insurance_claim = &market.insurance_claim;
+	let quote_max_insurance = market.get_quote_max_insurance();
+
+	// lower tiers get no insurance, so their deficits go straight to a debt auction
+	validate!(
+		insurance_claim.quote_settled_insurance >= quote_max_insurance,
+		ErrorCode::InsuranceNotExhausted,
+		"quote_settled_insurance={} < quote_max_insurance={}",
+		insurance_claim.quote_settled_insurance,
+		quote_max_insurance
+	)?;
+
+	validate!(
+		market.protocol_debt > 0,
+		ErrorCode::InvalidAuctionType,
+		"market {} has no protocol debt to auction",
+		market.market_index
+	)?;
+
+	validate!(
+		config.max_auction_lot_size > 0,
+		ErrorCode::AuctionBidZero,
+		"debt auction config has no lot size"
+	)?;
+
+	*auction = Auction {
+		pubkey: *auction_key,
+		initializer: *initializer_key,
+		auction_type: AuctionType::Debt,
+		status: AuctionStatus::Active,
+		market_index: market.market_index,
+		bid_decrease_rate: config.auction_bid_decrease_rate,
+		start_ts: now,
+		end_ts: now.safe_add(config.auction_duration as i64)?,
+		lot_amount: config.max_auction_lot_size,
+		debt_amount: market.protocol_debt,
+		..Auction::default()
+	};
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: *auction_key,
+		auction_type: AuctionType::Debt,
+		action: AuctionAction::Start,
+		market_index: market.market_index,
+		authority: *initializer_key,
+		lot_remaining: auction.lot_amount,
+		debt_remaining: auction.debt_amount,
+		..AuctionRecord::default()
+	});
+
+	Ok(())
+}
+
+/// Every bid pays the full protocol debt in synthetic and asks for fewer governance tokens
+/// than the last one. Returns the bidder that was outbid, who is owed a refund.
+pub fn bid_debt_auction(
+	auction: &mut Auction,
+	bidder_key: &Pubkey,
+	lot_amount: u64,
+	now: i64
+) -> NormalResult<Option<Pubkey>> {
+	validate!(
+		auction.auction_type == AuctionType::Debt,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a debt auction"
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		!auction.is_expired(now),
+		ErrorCode::AuctionExpired,
+		"auction expired at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	validate!(lot_amount > 0, ErrorCode::AuctionBidZero)?;
+
+	let outbid = if auction.has_bid() {
+		let max_lot_amount = calculate_debt_auction_max_lot(
+			auction.lot_amount,
+			auction.bid_decrease_rate
+		)?;
+
+		validate!(
+			lot_amount <= max_lot_amount && lot_amount < auction.lot_amount,
+			ErrorCode::AuctionBidNotImproved,
+			"lot_amount={} must be <= {}",
+			lot_amount,
+			max_lot_amount
+		)?;
+
+		Some(auction.highest_bidder)
+	} else {
+		validate!(
+			lot_amount <= auction.lot_amount,
+			ErrorCode::AuctionBidNotImproved,
+			"lot_amount={} > max lot {}",
+			lot_amount,
+			auction.lot_amount
+		)?;
+
+		None
+	};
+
+	auction.lot_amount = lot_amount;
+	auction.highest_bidder = *bidder_key;
+	auction.debt_raised = auction.debt_amount;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Bid,
+		market_index: auction.market_index,
+		authority: *bidder_key,
+		lot_amount,
+		bid_amount: auction.debt_amount,
+		lot_remaining: auction.lot_amount,
+		..AuctionRecord::default()
+	});
+
+	Ok(outbid)
+}
+
+/// Closes a debt auction once bidding has ended. The winning bid is burned against the
+/// market's protocol debt and the lot is minted to the winner as new governance tokens.
+/// Returns the (gov_tokens_to_mint, synthetic_to_burn) for the instruction to move.
+pub fn settle_debt_auction(
+	auction: &mut Auction,
+	market: &mut SynthMarket,
+	keeper_key: &Pubkey,
+	now: i64
+) -> NormalResult<(u64, u64)> {
+	validate!(
+		auction.auction_type == AuctionType::Debt,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a debt auction"
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		auction.is_expired(now),
+		ErrorCode::AuctionNotComplete,
+		"debt auction bidding ends at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	auction.status = AuctionStatus::Settled;
+
+	if !auction.has_bid() {
+		msg!("debt auction for market {} ended without bids", market.market_index);
+		return Ok((0, 0));
+	}
+
+	let gov_tokens_to_mint = auction.lot_amount;
+	let synthetic_to_burn = auction.debt_raised;
+
+	market.protocol_debt = market.protocol_debt.safe_sub(synthetic_to_burn)?;
+	market.total_gov_token_inflation = market.total_gov_token_inflation.safe_add(
+		gov_tokens_to_mint as u128
+	)?;
+
+	auction.lot_amount = 0;
+	auction.lot_sold = gov_tokens_to_mint;
+
+	emit!(DebtAuctionSettleRecord {
+		ts: now,
+		auction: auction.pubkey,
+		market_index: market.market_index,
+		winner: auction.highest_bidder,
+		debt_covered: synthetic_to_burn,
+		gov_tokens_minted: gov_tokens_to_mint,
+		protocol_debt_after: market.protocol_debt,
+		total_gov_token_inflation_after: market.total_gov_token_inflation,
+	});
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Settle,
+		market_index: auction.market_index,
+		authority: *keeper_key,
+		lot_amount: gov_tokens_to_mint,
+		bid_amount: synthetic_to_burn,
+		..AuctionRecord::default()
+	});
+
+	Ok((gov_tokens_to_mint, synthetic_to_burn))
+}
+
+/// Opens a surplus auction selling the insurance fund's excess over max_insurance
+/// for governance tokens, which are burned on settlement
+pub fn start_surplus_auction(
+	auction: &mut Auction,
+	auction_key: &Pubkey,
+	market_index: u16,
+	config: &AuctionConfig,
+	initializer_key: &Pubkey,
+	surplus: u64,
+	now: i64
+) -> NormalResult {
+	validate!(
+		surplus > 0,
+		ErrorCode::AuctionBidZero,
+		"no insurance fund surplus to auction"
+	)?;
+
+	let lot_amount = if config.max_auction_lot_size > 0 {
+		surplus.min(config.max_auction_lot_size)
+	} else {
+		surplus
+	};
+
+	*auction = Auction {
+		pubkey: *auction_key,
+		initializer: *initializer_key,
+		auction_type: AuctionType::Surplus,
+		status: AuctionStatus::Active,
+		market_index,
+		bid_decrease_rate: config.auction_bid_decrease_rate,
+		start_ts: now,
+		end_ts: now.safe_add(config.auction_duration as i64)?,
+		lot_amount,
+		..Auction::default()
+	};
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: *auction_key,
+		auction_type: AuctionType::Surplus,
+		action: AuctionAction::Start,
+		market_index,
+		authority: *initializer_key,
+		lot_remaining: lot_amount,
+		..AuctionRecord::default()
+	});
+
+	Ok(())
+}
+
+/// Every bid takes the whole lot and must offer more governance tokens than the last one.
+/// Returns the outbid bidder and their bid, which are owed a refund.
+pub fn bid_surplus_auction(
+	auction: &mut Auction,
+	bidder_key: &Pubkey,
+	bid_amount: u64,
+	now: i64
+) -> NormalResult<Option<(Pubkey, u64)>> {
+	validate!(
+		auction.auction_type == AuctionType::Surplus,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a surplus auction"
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		!auction.is_expired(now),
+		ErrorCode::AuctionExpired,
+		"auction expired at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	validate!(bid_amount > 0, ErrorCode::AuctionBidZero)?;
+
+	let outbid = if auction.has_bid() {
+		let min_bid_amount = calculate_surplus_auction_min_bid(
+			auction.debt_raised,
+			auction.bid_decrease_rate
+		)?;
+
+		validate!(
+			bid_amount >= min_bid_amount && bid_amount > auction.debt_raised,
+			ErrorCode::AuctionBidNotImproved,
+			"bid_amount={} must be >= {}",
+			bid_amount,
+			min_bid_amount
+		)?;
+
+		Some((auction.highest_bidder, auction.debt_raised))
+	} else {
+		None
+	};
+
+	auction.highest_bidder = *bidder_key;
+	auction.debt_raised = bid_amount;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Bid,
+		market_index: auction.market_index,
+		authority: *bidder_key,
+		lot_amount: auction.lot_amount,
+		bid_amount,
+		lot_remaining: auction.lot_amount,
+		..AuctionRecord::default()
+	});
+
+	Ok(outbid)
+}
+
+/// Closes a surplus auction once bidding has ended.
+/// Returns the (lot_to_send, gov_tokens_to_burn) for the instruction to move. Without a
+/// winner the lot goes back to the insurance fund.
+pub fn settle_surplus_auction(
+	auction: &mut Auction,
+	keeper_key: &Pubkey,
+	now: i64
+) -> NormalResult<(u64, u64)> {
+	validate!(
+		auction.auction_type == AuctionType::Surplus,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a surplus auction"
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		auction.is_expired(now),
+		ErrorCode::AuctionNotComplete,
+		"surplus auction bidding ends at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	auction.status = AuctionStatus::Settled;
+
+	let lot_amount = auction.lot_amount;
+	let gov_tokens_to_burn = auction.debt_raised;
+
+	if auction.has_bid() {
+		auction.lot_sold = lot_amount;
+		auction.lot_amount = 0;
+
+		emit!(SurplusAuctionSettleRecord {
+			ts: now,
+			auction: auction.pubkey,
+			market_index: auction.market_index,
+			winner: auction.highest_bidder,
+			lot_amount,
+			gov_tokens_burned: gov_tokens_to_burn,
+		});
+	} else {
+		msg!("surplus auction for market {} ended without bids", auction.market_index);
+	}
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Settle,
+		market_index: auction.market_index,
+		authority: *keeper_key,
+		lot_amount,
+		bid_amount: gov_tokens_to_burn,
+		..AuctionRecord::default()
+	});
+
+	Ok((lot_amount, gov_tokens_to_burn))
+}
@@ -0,0 +1,219 @@
+use std::iter::Peekable;
+use std::slice::Iter;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::constants::main::MAX_COLLATERAL_ASSETS;
+use crate::error::{ ErrorCode, NormalResult };
+use crate::math::casting::Cast;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::redemption::{
+	calculate_collateral_asset_seizure,
+	calculate_redemption_collateral,
+};
+use crate::math::safe_math::SafeMath;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::oracle_map::OracleMap;
+use crate::state::position::Position;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, validate };
+
+/// Valid oracle prices of the registry assets `position` holds, 0 for the slots it doesn't
+pub fn get_collateral_asset_prices(
+	position: &Position,
+	synth_market: &SynthMarket,
+	oracle_map: &mut OracleMap
+) -> NormalResult<[i64; MAX_COLLATERAL_ASSETS]> {
+	let mut prices = [0_i64; MAX_COLLATERAL_ASSETS];
+
+	for (i, collateral) in synth_market.collateral_assets.iter().enumerate() {
+		if position.collateral_balances[i] == 0 || !collateral.is_active() {
+			continue;
+		}
+
+		let oracle_price_data = *oracle_map.get_price_data(&collateral.oracle)?;
+		prices[i] = get_valid_oracle_price(
+			&oracle_price_data,
+			synth_market.market_index,
+			None,
+			&oracle_map.oracle_guard_rails.validity,
+			None
+		)?;
+	}
+
+	Ok(prices)
+}
+
+/// The position's registry collateral valued in the market's primary collateral at `prices`,
+/// unweighted so it adds straight onto the primary collateral in a collateral ratio
+pub fn get_collateral_assets_in_collateral(
+	position: &Position,
+	synth_market: &SynthMarket,
+	prices: &[i64; MAX_COLLATERAL_ASSETS],
+	collateral_price: i64
+) -> NormalResult<u128> {
+	let mut total = 0_u128;
+
+	for (i, collateral) in synth_market.collateral_assets.iter().enumerate() {
+		if position.collateral_balances[i] == 0 || prices[i] <= 0 {
+			continue;
+		}
+
+		total = total.safe_add(
+			calculate_redemption_collateral(
+				position.collateral_balances[i].cast()?,
+				collateral.decimals,
+				prices[i],
+				synth_market.collateral_decimals,
+				collateral_price
+			)?
+		)?;
+	}
+
+	Ok(total)
+}
+
+/// Seizes registry collateral worth `synthetic_amount` from `position` for debt its primary
+/// collateral couldn't cover. Returns the tokens taken from each registry slot and the synthetic
+/// they cover
+pub fn seize_collateral_assets(
+	position: &mut Position,
+	synth_market: &mut SynthMarket,
+	synthetic_amount: u128,
+	synthetic_price: i64,
+	prices: &[i64; MAX_COLLATERAL_ASSETS]
+) -> NormalResult<([u64; MAX_COLLATERAL_ASSETS], u128)> {
+	if synthetic_amount == 0 {
+		return Ok(([0; MAX_COLLATERAL_ASSETS], 0));
+	}
+
+	let mut decimals = [0_u32; MAX_COLLATERAL_ASSETS];
+	for (i, collateral) in synth_market.collateral_assets.iter().enumerate() {
+		decimals[i] = collateral.decimals;
+	}
+
+	let (seized, covered) = calculate_collateral_asset_seizure(
+		synthetic_amount,
+		synth_market.decimals,
+		synthetic_price,
+		&position.collateral_balances,
+		&decimals,
+		prices
+	)?;
+
+	for (i, amount) in seized.iter().enumerate() {
+		if *amount == 0 {
+			continue;
+		}
+
+		position.collateral_balances[i] = position.collateral_balances[i].safe_sub(*amount)?;
+		let collateral = &mut synth_market.collateral_assets[i];
+		collateral.deposit_amount = collateral.deposit_amount.safe_sub(*amount)?;
+	}
+
+	Ok((seized, covered))
+}
+
+/// Sends seized registry collateral out of the registry vaults. For every slot with an amount,
+/// the next remaining accounts are the slot's vault then a token account owned by `recipient`
+#[allow(clippy::too_many_arguments)]
+pub fn send_collateral_assets<'info>(
+	remaining_accounts_iter: &mut Peekable<Iter<'info, AccountInfo<'info>>>,
+	synth_market: &SynthMarket,
+	amounts: &[u64; MAX_COLLATERAL_ASSETS],
+	recipient: &Pubkey,
+	token_program: &Interface<'info, TokenInterface>,
+	normal_signer: &AccountInfo<'info>,
+	signer_nonce: u8
+) -> Result<()> {
+	for (i, amount) in amounts.iter().enumerate() {
+		if *amount == 0 {
+			continue;
+		}
+
+		let collateral = &synth_market.collateral_assets[i];
+		let collateral_vault = InterfaceAccount::<TokenAccount>::try_from(
+			remaining_accounts_iter.next().safe_unwrap()?
+		)?;
+		let recipient_token_account = InterfaceAccount::<TokenAccount>::try_from(
+			remaining_accounts_iter.next().safe_unwrap()?
+		)?;
+
+		validate!(
+			collateral_vault.key() == collateral.vault &&
+				recipient_token_account.mint == collateral.mint &&
+				recipient_token_account.owner == *recipient,
+			ErrorCode::CollateralAssetNotFound,
+			"token accounts for registry slot {} dont match market {}",
+			i,
+			synth_market.market_index
+		)?;
+
+		controller::token::send_from_program_vault(
+			token_program,
+			&collateral_vault,
+			&recipient_token_account,
+			normal_signer,
+			signer_nonce,
+			*amount,
+			&None
+		)?;
+	}
+
+	Ok(())
+}
+
+/// Sends seized registry collateral to the insurance fund, which backs the debt it covered. For
+/// every slot with an amount, the next remaining accounts are the slot's vault then the insurance
+/// fund's vault for the asset
+pub fn send_collateral_assets_to_insurance_fund<'info>(
+	remaining_accounts_iter: &mut Peekable<Iter<'info, AccountInfo<'info>>>,
+	synth_market: &SynthMarket,
+	amounts: &[u64; MAX_COLLATERAL_ASSETS],
+	token_program: &Interface<'info, TokenInterface>,
+	normal_signer: &AccountInfo<'info>,
+	signer_nonce: u8
+) -> Result<()> {
+	let market_index_bytes = synth_market.market_index.to_le_bytes();
+
+	for (i, amount) in amounts.iter().enumerate() {
+		if *amount == 0 {
+			continue;
+		}
+
+		let collateral = &synth_market.collateral_assets[i];
+		let collateral_vault = InterfaceAccount::<TokenAccount>::try_from(
+			remaining_accounts_iter.next().safe_unwrap()?
+		)?;
+		let insurance_fund_vault = InterfaceAccount::<TokenAccount>::try_from(
+			remaining_accounts_iter.next().safe_unwrap()?
+		)?;
+
+		let (insurance_fund_vault_key, _) = Pubkey::find_program_address(
+			&[b"insurance_fund_vault", &market_index_bytes, collateral.mint.as_ref()],
+			&crate::ID
+		);
+
+		validate!(
+			collateral_vault.key() == collateral.vault &&
+				insurance_fund_vault.key() == insurance_fund_vault_key,
+			ErrorCode::CollateralAssetNotFound,
+			"token accounts for registry slot {} dont match market {}",
+			i,
+			synth_market.market_index
+		)?;
+
+		controller::token::send_from_program_vault(
+			token_program,
+			&collateral_vault,
+			&insurance_fund_vault,
+			normal_signer,
+			signer_nonce,
+			*amount,
+			&None
+		)?;
+	}
+
+	Ok(())
+}
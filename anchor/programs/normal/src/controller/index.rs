@@ -1,16 +1,370 @@
-use crate::math;
-use crate::state::index_market::IndexMarket;
+use anchor_lang::prelude::*;
+use solana_program::msg;
+
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::casting::Cast;
+use crate::math::index::{
+	calculate_creation_deposit,
+	calculate_expense_accrual,
+	calculate_fee_split,
+	calculate_index_nav,
+	calculate_market_cap,
+	calculate_redemption_amount,
+	generate_weights,
+};
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::{ IndexFeeClaimRecord, IndexMarketRebalanceRecord };
+use crate::state::index_market::{ IndexMarket, WeightingMethod };
+use crate::state::index_oracle::IndexOracle;
+use crate::state::oracle::OraclePriceData;
+use crate::state::oracle_map::OracleMap;
 use crate::state::synth_market::SynthMarket;
-use crate::state::events::IndexFundRebalanceRecord;
+use crate::state::synth_market_map::SynthMarketMap;
+use crate::validate;
+
+/// Rewrites the index's asset weights using its weighting method.
+/// `custom_weights` is only read for WeightingMethod::Custom and is in the index's asset order.
+pub fn rebalance(
+	index_market: &mut IndexMarket,
+	synth_market_map: &SynthMarketMap,
+	oracle_map: &mut OracleMap,
+	custom_weights: &[u16],
+	now: i64
+) -> NormalResult<()> {
+	validate!(
+		index_market.can_rebalance(now),
+		ErrorCode::IndexRebalanceTooSoon,
+		"index market {} can't be rebalanced until {}",
+		index_market.market_index,
+		index_market.min_rebalance_ts
+	)?;
+
+	let method = index_market.weighting_method;
+
+	if method == WeightingMethod::Custom {
+		validate!(
+			custom_weights.len() == index_market.assets.len(),
+			ErrorCode::InvalidIndexWeights,
+			"expected {} custom weights, got {}",
+			index_market.assets.len(),
+			custom_weights.len()
+		)?;
+	}
+
+	let mut market_caps = Vec::with_capacity(index_market.assets.len());
+	for asset in index_market.assets.iter() {
+		let market = synth_market_map.get_ref(&asset.market_index)?;
+		let market_cap = match method {
+			WeightingMethod::MarketCap | WeightingMethod::SquareRootMarketCap => {
+				let oracle_price = get_valid_constituent_price_data(&market, oracle_map)?.price;
+				calculate_market_cap(market.synthetic_tokens_minted, market.decimals, oracle_price)?
+			}
+			_ => 0,
+		};
+		market_caps.push(market_cap);
+	}
+
+	let weights = generate_weights(method, &market_caps, custom_weights)?;
+
+	for (asset, weight) in index_market.assets.iter_mut().zip(weights.iter()) {
+		msg!("index asset {} weight: {} -> {}", asset.market_index, asset.weight, weight);
+		asset.weight = *weight;
+		asset.last_updated_ts = now;
+	}
+
+	index_market.rebalanced_ts = now;
+	index_market.min_rebalance_ts = now.safe_add(index_market.rebalance_interval)?;
+	index_market.updated_ts = now;
+
+	emit!(IndexMarketRebalanceRecord {
+		ts: now,
+		market_index: index_market.market_index,
+		weighting_method: method,
+		weights,
+	});
+
+	Ok(())
+}
+
+/// Accrues the expense ratio since the last accrual into the owed fees, split between the
+/// protocol, the referrer and the manager. Returns the index tokens accrued.
+pub fn accrue_expenses(
+	index_market: &mut IndexMarket,
+	index_token_supply: u64,
+	now: i64
+) -> NormalResult<u64> {
+	let elapsed = now.safe_sub(index_market.fees_accrued_ts)?;
+	let total_shares = index_market.get_total_shares(index_token_supply)?;
+
+	let accrued = calculate_expense_accrual(total_shares, index_market.expense_ratio, elapsed)?;
+
+	index_market.fees_accrued_ts = now;
+
+	if accrued == 0 {
+		return Ok(0);
+	}
+
+	let referral_share = if index_market.referrer == Pubkey::default() {
+		0
+	} else {
+		index_market.referral_share
+	};
+
+	let (protocol_fee, manager_fee, referral_fee) = calculate_fee_split(
+		accrued,
+		index_market.revenue_share,
+		referral_share
+	)?;
+
+	index_market.protocol_fee_owed = index_market.protocol_fee_owed.safe_add(protocol_fee)?;
+	index_market.manager_fee_owed = index_market.manager_fee_owed.safe_add(manager_fee)?;
+	index_market.referral_fee_owed = index_market.referral_fee_owed.safe_add(referral_fee)?;
+	index_market.total_fees = index_market.total_fees.safe_add(accrued)?;
+
+	Ok(accrued)
+}
+
+/// Takes the manager's and/or referrer's owed fees for `recipient`. Returns
+/// (manager fees, referral fees) in index tokens; the caller pays them out by
+/// the market's FeeAccrualMethod.
+pub fn claim_index_fees(
+	index_market: &mut IndexMarket,
+	recipient: &Pubkey,
+	now: i64
+) -> NormalResult<(u64, u64)> {
+	let is_manager = recipient == &index_market.fee_authority;
+	let is_referrer = index_market.referrer != Pubkey::default() &&
+		recipient == &index_market.referrer;
+
+	validate!(
+		is_manager || is_referrer,
+		ErrorCode::InvalidIndexFeeRecipient,
+		"{} is not index market {} fee authority or referrer",
+		recipient,
+		index_market.market_index
+	)?;
+
+	let manager_fee = if is_manager { index_market.manager_fee_owed } else { 0 };
+	let referral_fee = if is_referrer { index_market.referral_fee_owed } else { 0 };
+
+	index_market.manager_fee_owed = index_market.manager_fee_owed.safe_sub(manager_fee)?;
+	index_market.referral_fee_owed = index_market.referral_fee_owed.safe_sub(referral_fee)?;
+
+	emit!(IndexFeeClaimRecord {
+		ts: now,
+		market_index: index_market.market_index,
+		recipient: *recipient,
+		fee_accrual_method: index_market.fee_accrual_method,
+		manager_fee,
+		referral_fee,
+		protocol_fee: 0,
+	});
 
-pub fn rebalance(market: &mut Market, now: i64) -> NormalResult<()> {
-	// rebalance...
+	Ok((manager_fee, referral_fee))
+}
+
+/// Takes the protocol's owed fees for the treasury. Returns the protocol fees in index tokens;
+/// the caller pays them out by the market's FeeAccrualMethod.
+pub fn settle_index_protocol_fees(
+	index_market: &mut IndexMarket,
+	treasury: &Pubkey,
+	now: i64
+) -> NormalResult<u64> {
+	let protocol_fee = index_market.protocol_fee_owed;
 
-	let weights = math::index::generate_weights(method);
+	index_market.protocol_fee_owed = 0;
 
-	emit!(IndexFundRebalanceRecord {
+	emit!(IndexFeeClaimRecord {
 		ts: now,
+		market_index: index_market.market_index,
+		recipient: *treasury,
+		fee_accrual_method: index_market.fee_accrual_method,
+		manager_fee: 0,
+		referral_fee: 0,
+		protocol_fee,
 	});
 
+	Ok(protocol_fee)
+}
+
+/// The constituent's oracle price data. Fails if the oracle isn't valid, so the basket is
+/// never valued with a stale or uncertain constituent price
+pub fn get_valid_constituent_price_data(
+	market: &SynthMarket,
+	oracle_map: &mut OracleMap
+) -> NormalResult<OraclePriceData> {
+	let validity_guard_rails = oracle_map.oracle_guard_rails.validity;
+	let oracle_price_data = *oracle_map.get_price_data(&market.oracle)?;
+
+	get_valid_oracle_price(
+		&oracle_price_data,
+		market.market_index,
+		Some(market.historical_oracle_data.last_oracle_price_twap),
+		&validity_guard_rails,
+		None
+	)?;
+
+	Ok(oracle_price_data)
+}
+
+/// Values the basket at the valid oracle prices. Returns (value, confidence value, the delay of
+/// the stalest constituent oracle).
+/// precision: PRICE_PRECISION
+pub fn calculate_basket_value(
+	index_market: &IndexMarket,
+	synth_market_map: &SynthMarketMap,
+	oracle_map: &mut OracleMap,
+	vault_amounts: &[u64]
+) -> NormalResult<(u128, u128, i64)> {
+	let mut basket_value = 0_u128;
+	let mut confidence_value = 0_u128;
+	let mut max_delay = 0_i64;
+
+	for (asset, vault_amount) in index_market.assets.iter().zip(vault_amounts.iter()) {
+		let market = synth_market_map.get_ref(&asset.market_index)?;
+		let oracle_price_data = get_valid_constituent_price_data(&market, oracle_map)?;
+
+		basket_value = basket_value.safe_add(
+			calculate_market_cap(*vault_amount, market.decimals, oracle_price_data.price)?
+		)?;
+		confidence_value = confidence_value.safe_add(
+			calculate_market_cap(
+				*vault_amount,
+				market.decimals,
+				oracle_price_data.confidence.cast()?
+			)?
+		)?;
+		max_delay = max_delay.max(oracle_price_data.delay);
+	}
+
+	Ok((basket_value, confidence_value, max_delay))
+}
+
+/// NAV per index token at the valid oracle prices
+/// precision: PRICE_PRECISION
+pub fn calculate_nav(
+	index_market: &IndexMarket,
+	synth_market_map: &SynthMarketMap,
+	oracle_map: &mut OracleMap,
+	vault_amounts: &[u64],
+	index_token_supply: u64,
+	index_decimals: u32
+) -> NormalResult<u64> {
+	let (basket_value, _, _) = calculate_basket_value(
+		index_market,
+		synth_market_map,
+		oracle_map,
+		vault_amounts
+	)?;
+
+	calculate_index_nav(
+		basket_value,
+		index_market.get_total_shares(index_token_supply)?,
+		index_decimals
+	)
+}
+
+/// Publishes the index NAV, with the constituents' confidence intervals carried through the
+/// basket the same way as their prices
+#[allow(clippy::too_many_arguments)]
+pub fn update_index_oracle(
+	index_oracle: &mut IndexOracle,
+	index_market: &IndexMarket,
+	synth_market_map: &SynthMarketMap,
+	oracle_map: &mut OracleMap,
+	vault_amounts: &[u64],
+	index_token_supply: u64,
+	index_decimals: u32,
+	slot: u64,
+	now: i64
+) -> NormalResult<()> {
+	let (basket_value, confidence_value, max_delay) = calculate_basket_value(
+		index_market,
+		synth_market_map,
+		oracle_map,
+		vault_amounts
+	)?;
+
+	let total_shares = index_market.get_total_shares(index_token_supply)?;
+
+	let nav = calculate_index_nav(basket_value, total_shares, index_decimals)?;
+	let confidence = if total_shares == 0 {
+		0
+	} else {
+		calculate_index_nav(confidence_value, total_shares, index_decimals)?
+	};
+
+	msg!(
+		"index market {} nav: {} -> {} (confidence {})",
+		index_market.market_index,
+		index_oracle.price,
+		nav,
+		confidence
+	);
+
+	index_oracle.price = nav.cast()?;
+	index_oracle.confidence = confidence;
+	index_oracle.max_constituent_delay = max_delay;
+	index_oracle.last_update_slot = slot;
+	index_oracle.last_update_ts = now;
+
 	Ok(())
 }
+
+/// The constituent amounts to deposit to create `index_amount` at NAV, in proportion to
+/// the asset weights. Returns (deposit amounts in asset order, nav).
+pub fn calculate_creation_basket(
+	index_market: &IndexMarket,
+	synth_market_map: &SynthMarketMap,
+	oracle_map: &mut OracleMap,
+	vault_amounts: &[u64],
+	index_token_supply: u64,
+	index_decimals: u32,
+	index_amount: u64
+) -> NormalResult<(Vec<u64>, u64)> {
+	index_market.validate_weights()?;
+
+	let nav = calculate_nav(
+		index_market,
+		synth_market_map,
+		oracle_map,
+		vault_amounts,
+		index_token_supply,
+		index_decimals
+	)?;
+
+	let mut deposit_amounts = Vec::with_capacity(index_market.assets.len());
+	for asset in index_market.assets.iter() {
+		let market = synth_market_map.get_ref(&asset.market_index)?;
+		let oracle_price = get_valid_constituent_price_data(&market, oracle_map)?.price;
+
+		deposit_amounts.push(
+			calculate_creation_deposit(
+				index_amount,
+				index_decimals,
+				nav,
+				asset.weight,
+				oracle_price,
+				market.decimals
+			)?
+		);
+	}
+
+	Ok((deposit_amounts, nav))
+}
+
+/// The pro rata share of each basket vault returned for redeeming `index_amount`
+pub fn calculate_redemption_basket(
+	index_market: &IndexMarket,
+	vault_amounts: &[u64],
+	index_token_supply: u64,
+	index_amount: u64
+) -> NormalResult<Vec<u64>> {
+	let total_shares = index_market.get_total_shares(index_token_supply)?;
+
+	vault_amounts
+		.iter()
+		.map(|vault_amount| calculate_redemption_amount(*vault_amount, index_amount, total_shares))
+		.collect()
+}
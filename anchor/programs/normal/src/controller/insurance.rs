@@ -30,7 +30,6 @@ use crate::math::insurance::{
 };
 use crate::math::safe_math::SafeMath;
 use crate::math::synth_balance::get_token_amount;
-use crate::math::spot_withdraw::validate_spot_market_vault_amount;
 use crate::state::events::{
 	InsuranceFundRecord,
 	InsuranceFundStakeRecord,
@@ -41,6 +40,7 @@ use crate::state::insurance_fund_stake::InsuranceFundStake;
 use crate::state::perp_market::PerpMarket;
 use crate::state::spot_market::{ SpotBalanceType, SpotMarket };
 use crate::state::state::State;
+use crate::state::synth_market::SynthMarket;
 use crate::state::user::UserStats;
 use crate::{
 	emit,
@@ -641,15 +641,53 @@ pub fn attempt_settle_revenue_to_insurance_fund<'info>(
 	Ok(())
 }
 
+/// Brings the stability fees accrued since the last settle into the revenue pool. They were only
+/// booked as vault debt, so the instruction must mint the returned amount into the market's
+/// synthetic vault for the pool to be held in full
+pub fn mint_stability_fees_to_revenue_pool(
+	spot_market: &mut SynthMarket,
+	now: i64
+) -> NormalResult<u64> {
+	update_synth_market_cumulative_interest(spot_market, None, now)?;
+
+	let stability_fees = spot_market.unminted_stability_fees.cast::<u64>()?;
+
+	spot_market.unminted_stability_fees = 0;
+	spot_market.revenue_pool = spot_market.revenue_pool.safe_add(stability_fees.cast()?)?;
+
+	Ok(stability_fees)
+}
+
+/// Revenue backs the market's unbacked synthetic before anything is paid to the insurance fund.
+/// Returns the synthetic the instruction must burn from the market's synthetic vault
+pub fn cover_protocol_debt_with_revenue(spot_market: &mut SynthMarket) -> NormalResult<u64> {
+	let synthetic_to_burn = spot_market.revenue_pool
+		.min(spot_market.protocol_debt.cast()?)
+		.cast::<u64>()?;
+
+	if synthetic_to_burn > 0 {
+		spot_market.revenue_pool = spot_market.revenue_pool.safe_sub(synthetic_to_burn.cast()?)?;
+		spot_market.protocol_debt = spot_market.protocol_debt.safe_sub(synthetic_to_burn)?;
+
+		msg!(
+			"market {} revenue covered {} protocol debt",
+			spot_market.market_index,
+			synthetic_to_burn
+		);
+	}
+
+	Ok(synthetic_to_burn)
+}
+
+/// Settles the insurance fund's share of the revenue pool. The pool is synthetic held in the
+/// market's synthetic vault, so it's paid out as synthetic and never out of depositors' collateral
 pub fn settle_revenue_to_insurance_fund(
-	spot_market_vault_amount: u64,
+	synthetic_vault_amount: u64,
 	insurance_vault_amount: u64,
-	spot_market: &mut SpotMarket,
+	spot_market: &mut SynthMarket,
 	now: i64,
 	check_invariants: bool
 ) -> NormalResult<u64> {
-	update_synth_market_cumulative_interest(spot_market, None, now)?;
-
 	if spot_market.insurance_fund.revenue_settle_period == 0 {
 		// revenue pool not configured to settle, ending early
 		return Ok(0);
@@ -662,21 +700,15 @@ pub fn settle_revenue_to_insurance_fund(
 		"invalid if_factor settings on spot market"
 	)?;
 
-	let depositors_claim = validate_spot_market_vault_amount(
-		spot_market,
-		spot_market_vault_amount
-	)?;
-
-	let mut token_amount = get_token_amount(
-		spot_market.revenue_pool.scaled_balance,
-		spot_market,
-		&SpotBalanceType::Deposit
+	validate!(
+		synthetic_vault_amount.cast::<u128>()? >= spot_market.revenue_pool,
+		ErrorCode::InvalidSpotMarketState,
+		"synthetic vault holds {} < revenue_pool {}",
+		synthetic_vault_amount,
+		spot_market.revenue_pool
 	)?;
 
-	if depositors_claim < token_amount.cast()? {
-		// only allow half of withdraw available when utilization is high
-		token_amount = depositors_claim.max(0).cast::<u128>()?.safe_div(2)?;
-	}
+	let mut token_amount = spot_market.revenue_pool;
 
 	if spot_market.insurance_fund.user_shares > 0 {
 		// only allow MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT or 1/10th of revenue pool to be settled
@@ -731,10 +763,8 @@ pub fn settle_revenue_to_insurance_fund(
 
 	let total_if_shares_before = spot_market.insurance_fund.total_shares;
 
-	update_revenue_pool_balances(
-		insurance_fund_token_amount.cast::<u128>()?,
-		&SpotBalanceType::Borrow,
-		spot_market
+	spot_market.revenue_pool = spot_market.revenue_pool.safe_sub(
+		insurance_fund_token_amount.cast::<u128>()?
 	)?;
 
 	emit!(InsuranceFundRecord {
@@ -745,7 +775,7 @@ pub fn settle_revenue_to_insurance_fund(
 
 		user_if_factor: spot_market.insurance_fund.user_factor,
 		total_if_factor: spot_market.insurance_fund.total_factor,
-		vault_amount_before: spot_market_vault_amount,
+		vault_amount_before: synthetic_vault_amount,
 		insurance_vault_amount_before: insurance_vault_amount,
 		total_if_shares_before,
 		total_if_shares_after: spot_market.insurance_fund.total_shares,
@@ -817,8 +847,9 @@ pub fn resolve_perp_pnl_deficit(
 		max_revenue_withdraw_per_period
 	)?;
 
-	let max_insurance_withdraw = market.insurance_claim.quote_max_insurance
-		.safe_sub(market.insurance_claim.quote_settled_insurance)?
+	let quote_max_insurance = market.get_quote_max_insurance();
+	let max_insurance_withdraw = quote_max_insurance
+		.saturating_sub(market.insurance_claim.quote_settled_insurance)
 		.cast::<i128>()?;
 
 	validate!(
@@ -826,7 +857,7 @@ pub fn resolve_perp_pnl_deficit(
 		ErrorCode::MaxIFWithdrawReached,
 		"max_insurance_withdraw={}/{} as already been reached",
 		market.insurance_claim.quote_settled_insurance,
-		market.insurance_claim.quote_max_insurance
+		quote_max_insurance
 	)?;
 
 	let insurance_withdraw = excess_user_pnl_imbalance
@@ -856,12 +887,11 @@ pub fn resolve_perp_pnl_deficit(
 		)?;
 
 	validate!(
-		market.insurance_claim.quote_settled_insurance <=
-			market.insurance_claim.quote_max_insurance,
+		market.insurance_claim.quote_settled_insurance <= quote_max_insurance,
 		ErrorCode::MaxIFWithdrawReached,
 		"quote_settled_insurance breached its max {}/{}",
 		market.insurance_claim.quote_settled_insurance,
-		market.insurance_claim.quote_max_insurance
+		quote_max_insurance
 	)?;
 
 	market.insurance_claim.last_revenue_withdraw_ts = now;
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+
+use crate::controller::lending_adapter::CollateralLendingAdapter;
+use crate::error::{ ErrorCode, NormalResult };
+use crate::math::lending::{
+	calculate_lendable_amount,
+	calculate_lending_utilization,
+	calculate_loan_value,
+	calculate_lp_interest_delta,
+	calculate_shares_to_redeem,
+};
+use crate::math::safe_math::SafeMath;
+use crate::math::synth_balance::get_token_amount;
+use crate::state::events::{ CollateralLendingAction, CollateralLendingRecord };
+use crate::state::spot_market::SpotBalanceType;
+use crate::state::synth_market::SynthMarket;
+use crate::validate;
+
+/// Marks the loan to the lending program's exchange rate and credits any yield to depositors
+/// through cumulative_lp_interest. Losses stay unrealized until the collateral is unlent.
+pub fn accrue_lending_yield(
+	market: &mut SynthMarket,
+	exchange_rate: u128,
+	now: i64
+) -> NormalResult<u64> {
+	if market.collateral_loan_shares == 0 {
+		return Ok(0);
+	}
+
+	let loan_value = calculate_loan_value(market.collateral_loan_shares, exchange_rate)?;
+	let lending_yield = loan_value.saturating_sub(market.collateral_loan_balance);
+
+	if lending_yield == 0 {
+		return Ok(0);
+	}
+
+	let deposit_token_amount = get_token_amount(
+		market.collateral_balance,
+		market,
+		&SpotBalanceType::Deposit
+	)?;
+
+	let lp_interest_delta = calculate_lp_interest_delta(
+		lending_yield,
+		deposit_token_amount,
+		market.cumulative_lp_interest
+	)?;
+
+	market.cumulative_lp_interest = market.cumulative_lp_interest.safe_add(lp_interest_delta)?;
+	market.collateral_loan_balance = loan_value;
+
+	update_lending_utilization(market)?;
+	emit_lending_record(market, CollateralLendingAction::AccrueYield, lending_yield, 0, now);
+
+	Ok(lending_yield)
+}
+
+pub fn lend_collateral<A: CollateralLendingAdapter>(
+	adapter: &mut A,
+	market: &mut SynthMarket,
+	amount: u64,
+	now: i64
+) -> Result<u64> {
+	validate!(
+		market.lending_program != Pubkey::default(),
+		ErrorCode::CollateralLendingDisabled,
+		"collateral lending not configured for market {}",
+		market.market_index
+	)?;
+
+	accrue_lending_yield(market, adapter.exchange_rate()?, now)?;
+
+	let deposit_token_amount = get_token_amount(
+		market.collateral_balance,
+		market,
+		&SpotBalanceType::Deposit
+	)?;
+
+	let lendable_amount = calculate_lendable_amount(
+		deposit_token_amount,
+		market.collateral_loan_balance,
+		market.max_collateral_lending_utilization
+	)?;
+
+	validate!(
+		amount <= lendable_amount,
+		ErrorCode::CollateralLendingCapExceeded,
+		"lending {} exceeds the {} left under the cap for market {}",
+		amount,
+		lendable_amount,
+		market.market_index
+	)?;
+
+	let shares = adapter.deposit(amount)?;
+
+	market.collateral_loan_balance = market.collateral_loan_balance.safe_add(amount)?;
+	market.collateral_loan_shares = market.collateral_loan_shares.safe_add(shares)?;
+
+	update_lending_utilization(market)?;
+	emit_lending_record(market, CollateralLendingAction::Lend, amount, shares, now);
+
+	Ok(shares)
+}
+
+/// Brings at least `amount` of collateral back to the market vault, or everything lent if
+/// less is outstanding. Returns the collateral received
+pub fn unlend_collateral<A: CollateralLendingAdapter>(
+	adapter: &mut A,
+	market: &mut SynthMarket,
+	amount: u64,
+	now: i64
+) -> Result<u64> {
+	let exchange_rate = adapter.exchange_rate()?;
+	accrue_lending_yield(market, exchange_rate, now)?;
+
+	let shares = calculate_shares_to_redeem(amount, exchange_rate, market.collateral_loan_shares)?;
+
+	if shares == 0 {
+		return Ok(0);
+	}
+
+	let collateral_received = adapter.withdraw(shares)?;
+
+	market.collateral_loan_shares = market.collateral_loan_shares.safe_sub(shares)?;
+	market.collateral_loan_balance = if market.collateral_loan_shares == 0 {
+		0
+	} else {
+		market.collateral_loan_balance.saturating_sub(collateral_received)
+	};
+
+	update_lending_utilization(market)?;
+	emit_lending_record(market, CollateralLendingAction::Unlend, collateral_received, shares, now);
+
+	Ok(collateral_received)
+}
+
+fn update_lending_utilization(market: &mut SynthMarket) -> NormalResult {
+	let deposit_token_amount = get_token_amount(
+		market.collateral_balance,
+		market,
+		&SpotBalanceType::Deposit
+	)?;
+
+	market.collateral_lending_utilization = calculate_lending_utilization(
+		deposit_token_amount,
+		market.collateral_loan_balance
+	)?;
+
+	Ok(())
+}
+
+fn emit_lending_record(
+	market: &SynthMarket,
+	action: CollateralLendingAction,
+	amount: u64,
+	shares: u64,
+	now: i64
+) {
+	emit!(CollateralLendingRecord {
+		ts: now,
+		market_index: market.market_index,
+		action,
+		lending_program: market.lending_program,
+		amount,
+		shares,
+		collateral_loan_balance: market.collateral_loan_balance,
+		collateral_loan_shares: market.collateral_loan_shares,
+		cumulative_lp_interest: market.cumulative_lp_interest,
+		utilization: market.collateral_lending_utilization,
+	});
+}
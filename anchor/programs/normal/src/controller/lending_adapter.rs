@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+use solana_program::instruction::{ AccountMeta, Instruction };
+use solana_program::program::invoke_signed;
+
+use crate::constants::main::LENDING_EXCHANGE_RATE_PRECISION;
+use crate::controller;
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::synth_market::SynthMarket;
+use crate::validate;
+
+/// A lending venue vault collateral can be lent to
+pub trait CollateralLendingAdapter {
+	/// Lends `amount` of collateral and returns the shares received
+	fn deposit(&mut self, amount: u64) -> Result<u64>;
+	/// Redeems `shares` and returns the collateral received
+	fn withdraw(&mut self, shares: u64) -> Result<u64>;
+	/// Collateral one share redeems for
+	/// precision: LENDING_EXCHANGE_RATE_PRECISION
+	fn exchange_rate(&self) -> Result<u128>;
+}
+
+/// The reserve account of a lending program, after the 8 byte discriminator
+#[derive(AnchorDeserialize)]
+struct LendingReserve {
+	pub liquidity_mint: Pubkey,
+	pub liquidity_vault: Pubkey,
+	pub total_liquidity: u64,
+	pub total_shares: u64,
+}
+
+impl LendingReserve {
+	/// precision: LENDING_EXCHANGE_RATE_PRECISION
+	fn exchange_rate(&self) -> Result<u128> {
+		if self.total_shares == 0 {
+			return Ok(LENDING_EXCHANGE_RATE_PRECISION);
+		}
+
+		Ok(
+			self.total_liquidity
+				.cast::<u128>()?
+				.safe_mul(LENDING_EXCHANGE_RATE_PRECISION)?
+				.safe_div(self.total_shares.cast()?)?
+		)
+	}
+}
+
+/// Lends through a CPI into a reserve-style lending program (see programs/mock-lending).
+/// The market's lending signer, a PDA of [b"lending_signer", market_index], owns the obligation
+/// and is the only signer the lending program sees: deposits are pulled from the market vault
+/// through a delegation of exactly the amount lent. Amounts are measured from the market vault
+/// and reserve before and after each call rather than trusted from the lending program.
+pub struct CpiLendingAdapter<'a, 'info> {
+	pub lending_program: &'a AccountInfo<'info>,
+	pub reserve: &'a AccountInfo<'info>,
+	pub obligation: &'a AccountInfo<'info>,
+	pub liquidity_mint: &'a AccountInfo<'info>,
+	pub liquidity_vault: &'a AccountInfo<'info>,
+	pub collateral_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+	pub lending_signer: &'a AccountInfo<'info>,
+	pub lending_signer_seeds: &'a [&'a [u8]],
+	pub normal_signer: &'a AccountInfo<'info>,
+	pub token_program: &'a Interface<'info, TokenInterface>,
+	pub signer_nonce: u8,
+}
+
+impl<'a, 'info> CpiLendingAdapter<'a, 'info> {
+	fn load_reserve(&self) -> Result<LendingReserve> {
+		let data = self.reserve.try_borrow_data()?;
+		validate_reserve_data(&data)?;
+
+		let reserve = LendingReserve::deserialize(&mut &data[8..])?;
+
+		if
+			reserve.liquidity_mint != self.collateral_vault.mint ||
+			reserve.liquidity_vault != *self.liquidity_vault.key
+		{
+			msg!("lending reserve {} is not for the market's collateral", self.reserve.key);
+			return Err(ErrorCode::InvalidLendingAccount.into());
+		}
+
+		Ok(reserve)
+	}
+
+	fn invoke(&self, instruction_name: &str, arg: u64) -> Result<()> {
+		let mut data = hash(format!("global:{}", instruction_name).as_bytes()).to_bytes()[
+			..8
+		].to_vec();
+		data.extend_from_slice(&arg.to_le_bytes());
+
+		let account_infos = [
+			self.lending_signer.clone(),
+			self.reserve.clone(),
+			self.obligation.clone(),
+			self.liquidity_mint.clone(),
+			self.liquidity_vault.clone(),
+			self.collateral_vault.to_account_info(),
+			self.token_program.to_account_info(),
+		];
+
+		let instruction = Instruction {
+			program_id: *self.lending_program.key,
+			accounts: vec![
+				AccountMeta::new_readonly(*self.lending_signer.key, true),
+				AccountMeta::new(*self.reserve.key, false),
+				AccountMeta::new(*self.obligation.key, false),
+				AccountMeta::new_readonly(*self.liquidity_mint.key, false),
+				AccountMeta::new(*self.liquidity_vault.key, false),
+				AccountMeta::new(self.collateral_vault.key(), false),
+				AccountMeta::new_readonly(self.token_program.key(), false)
+			],
+			data,
+		};
+
+		invoke_signed(&instruction, &account_infos, &[self.lending_signer_seeds])?;
+
+		Ok(())
+	}
+}
+
+impl<'a, 'info> CollateralLendingAdapter for CpiLendingAdapter<'a, 'info> {
+	fn deposit(&mut self, amount: u64) -> Result<u64> {
+		let vault_before = self.collateral_vault.amount;
+		let shares_before = self.load_reserve()?.total_shares;
+
+		controller::token::approve_from_program_vault(
+			self.token_program,
+			self.collateral_vault,
+			self.lending_signer,
+			self.normal_signer,
+			self.signer_nonce,
+			amount
+		)?;
+
+		self.invoke("deposit", amount)?;
+
+		// nothing past the deposit is left delegated
+		controller::token::revoke_from_program_vault(
+			self.token_program,
+			self.collateral_vault,
+			self.normal_signer,
+			self.signer_nonce
+		)?;
+
+		self.collateral_vault.reload()?;
+		let collateral_lent = vault_before.safe_sub(self.collateral_vault.amount)?;
+		let shares = self.load_reserve()?.total_shares.safe_sub(shares_before)?;
+
+		if collateral_lent != amount || shares == 0 {
+			msg!(
+				"lending program took {} collateral for {} shares, expected {}",
+				collateral_lent,
+				shares,
+				amount
+			);
+			return Err(ErrorCode::InvalidLendingAccount.into());
+		}
+
+		Ok(shares)
+	}
+
+	fn withdraw(&mut self, shares: u64) -> Result<u64> {
+		let vault_before = self.collateral_vault.amount;
+		let shares_before = self.load_reserve()?.total_shares;
+
+		self.invoke("withdraw", shares)?;
+
+		self.collateral_vault.reload()?;
+		let collateral_received = self.collateral_vault.amount.safe_sub(vault_before)?;
+		let shares_redeemed = shares_before.safe_sub(self.load_reserve()?.total_shares)?;
+
+		if shares_redeemed != shares {
+			msg!("lending program redeemed {} shares, expected {}", shares_redeemed, shares);
+			return Err(ErrorCode::InvalidLendingAccount.into());
+		}
+
+		Ok(collateral_received)
+	}
+
+	fn exchange_rate(&self) -> Result<u128> {
+		self.load_reserve()?.exchange_rate()
+	}
+}
+
+/// The lending venue accounts an instruction paying out of the market vault can be given, to
+/// unlend collateral when the vault is short. Checked against the market's lending config
+pub struct OptionalLendingAccounts<'a, 'info> {
+	pub lending_program: Option<&'a AccountInfo<'info>>,
+	pub lending_reserve: Option<&'a AccountInfo<'info>>,
+	pub lending_obligation: Option<&'a AccountInfo<'info>>,
+	pub lending_vault: Option<&'a AccountInfo<'info>>,
+	pub collateral_mint: Option<AccountInfo<'info>>,
+	pub lending_signer: Option<&'a AccountInfo<'info>>,
+}
+
+/// Unlends what the market vault is short of `amount`, so collateral that is lent out never
+/// blocks a payout. Does nothing if the vault already holds `amount`
+#[allow(clippy::too_many_arguments)]
+pub fn unlend_market_vault_shortfall<'info>(
+	lending_accounts: &OptionalLendingAccounts<'_, 'info>,
+	market_vault: &mut InterfaceAccount<'info, TokenAccount>,
+	market: &mut SynthMarket,
+	amount: u64,
+	normal_signer: &AccountInfo<'info>,
+	token_program: &Interface<'info, TokenInterface>,
+	signer_nonce: u8,
+	now: i64
+) -> Result<()> {
+	if market_vault.amount >= amount {
+		return Ok(());
+	}
+
+	let shortfall = amount.safe_sub(market_vault.amount)?;
+
+	let lending_program = lending_accounts.lending_program.safe_unwrap()?;
+	let lending_reserve = lending_accounts.lending_reserve.safe_unwrap()?;
+	let lending_obligation = lending_accounts.lending_obligation.safe_unwrap()?;
+	let lending_vault = lending_accounts.lending_vault.safe_unwrap()?;
+	let collateral_mint = lending_accounts.collateral_mint.as_ref().safe_unwrap()?;
+	let lending_signer = lending_accounts.lending_signer.safe_unwrap()?;
+
+	let market_index_bytes = market.market_index.to_le_bytes();
+	let (lending_signer_key, lending_signer_bump) = Pubkey::find_program_address(
+		&[b"lending_signer", &market_index_bytes],
+		&crate::ID
+	);
+
+	validate!(
+		lending_program.key() == market.lending_program &&
+			lending_reserve.key() == market.lending_reserve &&
+			lending_obligation.key() == market.lending_obligation &&
+			collateral_mint.key() == market.token_mint_collateral &&
+			lending_signer.key() == lending_signer_key,
+		ErrorCode::InvalidLendingAccount,
+		"lending accounts dont match market {}",
+		market.market_index
+	)?;
+
+	let lending_signer_bump = [lending_signer_bump];
+	let lending_signer_seeds: &[&[u8]] = &[
+		b"lending_signer",
+		&market_index_bytes,
+		&lending_signer_bump,
+	];
+
+	let mut adapter = CpiLendingAdapter {
+		lending_program,
+		reserve: lending_reserve,
+		obligation: lending_obligation,
+		liquidity_mint: collateral_mint,
+		liquidity_vault: lending_vault,
+		collateral_vault: market_vault,
+		lending_signer,
+		lending_signer_seeds,
+		normal_signer,
+		token_program,
+		signer_nonce,
+	};
+
+	controller::lending::unlend_collateral(&mut adapter, market, shortfall, now)?;
+
+	Ok(())
+}
+
+fn validate_reserve_data(data: &[u8]) -> Result<()> {
+	// discriminator + 2 pubkeys + 2 u64s
+	if data.len() < 8 + 32 + 32 + 8 + 8 {
+		msg!("lending reserve account too small: {}", data.len());
+		return Err(ErrorCode::InvalidLendingAccount.into());
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn mock_reserve(total_liquidity: u64, total_shares: u64) -> mock_lending::Reserve {
+		mock_lending::Reserve {
+			liquidity_mint: Pubkey::new_unique(),
+			liquidity_vault: Pubkey::new_unique(),
+			total_liquidity,
+			total_shares,
+			bump: 255,
+		}
+	}
+
+	#[test]
+	fn reads_mock_lending_reserve() {
+		let mock = mock_reserve(1_050_000, 1_000_000);
+
+		let mut data = vec![];
+		mock.try_serialize(&mut data).unwrap();
+		validate_reserve_data(&data).unwrap();
+
+		let reserve = LendingReserve::deserialize(&mut &data[8..]).unwrap();
+		assert_eq!(reserve.liquidity_mint, mock.liquidity_mint);
+		assert_eq!(reserve.liquidity_vault, mock.liquidity_vault);
+		assert_eq!(reserve.total_liquidity, mock.total_liquidity);
+		assert_eq!(reserve.total_shares, mock.total_shares);
+	}
+
+	#[test]
+	fn exchange_rate_matches_mock_lending() {
+		for (total_liquidity, total_shares) in [
+			(0, 0),
+			(1_000_000, 1_000_000),
+			(1_050_000, 1_000_000),
+			(999_999, 1_000_003),
+		] {
+			let mock = mock_reserve(total_liquidity, total_shares);
+
+			let mut data = vec![];
+			mock.try_serialize(&mut data).unwrap();
+			let reserve = LendingReserve::deserialize(&mut &data[8..]).unwrap();
+
+			assert_eq!(LENDING_EXCHANGE_RATE_PRECISION, mock_lending::EXCHANGE_RATE_PRECISION);
+			assert_eq!(reserve.exchange_rate().unwrap(), mock.exchange_rate().unwrap());
+		}
+	}
+
+	#[test]
+	fn rejects_short_reserve() {
+		assert!(validate_reserve_data(&[0; 8 + 32 + 32 + 8]).is_err());
+	}
+}
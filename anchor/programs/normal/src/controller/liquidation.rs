@@ -3,7 +3,9 @@ use std::ops::{ Deref, DerefMut };
 use anchor_lang::prelude::*;
 use solana_program::msg;
 
+use crate::constants::main::MAX_COLLATERAL_ASSETS;
 use crate::controller::amm::get_fee_pool_tokens;
+use crate::controller::collateral::{ get_collateral_asset_prices, seize_collateral_assets };
 use crate::controller::spot_balance::{
 	update_revenue_pool_balances,
 	update_spot_balances,
@@ -11,15 +13,23 @@ use crate::controller::spot_balance::{
 	update_synth_market_cumulative_interest,
 };
 use crate::controller::spot_position::update_spot_balances_and_cumulative_deposits;
+use crate::controller::synth_balance::{
+	update_number_of_vaults_with_debt,
+	update_vault_redemption_bucket,
+};
+use crate::controller::swap_adapter::{ CollateralSwapAdapter, ExternalFill };
 use crate::error::{ NormalResult, ErrorCode };
+use crate::math::auction::{
+	calculate_auction_fill,
+	calculate_external_auction_min_price,
+};
 use crate::math::bankruptcy::is_user_bankrupt;
 use crate::math::casting::Cast;
 use crate::math::constants::{
 	LIQUIDATION_FEE_PRECISION_U128,
-	LIQUIDATION_PCT_PRECISION,
+	PRICE_PRECISION,
 	QUOTE_PRECISION,
 	QUOTE_PRECISION_I128,
-	QUOTE_PRECISION_U64,
 	QUOTE_SPOT_MARKET_INDEX,
 	SPOT_WEIGHT_PRECISION,
 };
@@ -31,10 +41,13 @@ use crate::math::liquidation::{
 	calculate_liability_transfer_to_cover_margin_shortage,
 	calculate_liquidation_multiplier,
 	calculate_max_pct_to_liquidate,
+	calculate_vault_debt_to_liquidate,
 	calculate_vault_if_fee,
 	get_liquidation_fee,
 	get_liquidation_order_params,
+	validate_liability_tier_liquidation_order,
 	validate_transfer_satisfies_limit_price,
+	validate_vault_liquidatable,
 	LiquidationMultiplierType,
 };
 use crate::math::margin::{
@@ -51,13 +64,17 @@ use crate::math::orders::{
 	standardize_base_asset_amount_ceil,
 };
 use crate::math::position::calculate_base_asset_value_with_oracle_price;
+use crate::math::redemption::{ calculate_redemption_collateral, calculate_scaled_balance_delta };
 use crate::math::safe_math::SafeMath;
 
 use crate::math::synth_balance::get_token_value;
+use crate::state::auction::Auction;
 use crate::state::events::{
 	emit_stack,
 	LPAction,
 	LPRecord,
+	AuctionAction,
+	AuctionRecord,
 	LiquidateVaultRecord,
 	LiquidationRecord,
 	LiquidationType,
@@ -71,14 +88,19 @@ use crate::state::margin_calculation::{
 };
 use crate::state::oracle_map::OracleMap;
 use crate::state::paused_operations::SynthOperation;
-use crate::state::synth_market::MarketStatus;
+use crate::state::synth_market::{
+	AuctionPreference,
+	AuctionType,
+	MarketStatus,
+	SynthMarket,
+};
 use crate::state::synth_market_map::SynthMarketMap;
 use crate::state::state::State;
 use crate::state::traits::Size;
 use crate::state::user::{ MarketType, User, UserStats };
 use crate::state::user_map::{ UserMap, UserStatsMap };
 use crate::validate;
-use crate::{ get_then_update_id, load_mut };
+use crate::{ controller, get_then_update_id, load_mut };
 
 pub fn liquidate_vault(
 	vault_index: u16,
@@ -86,17 +108,19 @@ pub fn liquidate_vault(
 	limit_price: Option<u64>,
 	user: &mut User,
 	user_key: &Pubkey,
-	user_stats: &mut UserStats,
-	liquidator: &mut User,
+	liquidator: &User,
 	liquidator_key: &Pubkey,
-	liquidator_stats: &mut UserStats,
 	market_map: &SynthMarketMap,
 	vault_map: &VaultMap,
 	oracle_map: &mut OracleMap,
 	slot: u64,
 	now: i64,
-	state: &State
-) -> NormalResult {
+	state: &State,
+	auction: &mut Auction,
+	auction_key: &Pubkey
+) -> NormalResult<[u64; MAX_COLLATERAL_ASSETS]> {
+	// a user has one vault per market, its position in that market
+	let market_index = vault_index;
 	let liquidation_margin_buffer_ratio = state.liquidation_margin_buffer_ratio;
 	let initial_pct_to_liquidate = state.initial_pct_to_liquidate as u128;
 	let liquidation_duration = state.liquidation_duration as u128;
@@ -105,10 +129,8 @@ pub fn liquidate_vault(
 	 * - Ensure Vault can be liqudated
 	 		- LTV <= market.margin_ratio_maintenance
 
-	- Init the Collateral Auction process
-		- update status
-		- set auction params
-		- ...
+	- Seize collateral worth the debt being liquidated plus fees
+	- Init the Collateral Auction process for the seized collateral
 	 */
 
 	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt, "user bankrupt")?;
@@ -129,8 +151,22 @@ pub fn liquidate_vault(
 		market_index
 	)?;
 
+	let liability_tier = market.synthetic_tier;
+
 	drop(market);
 
+	// less speculative markets liquidate first
+	let mut other_liability_tiers = Vec::with_capacity(user.positions.len());
+	for position in user.positions.iter() {
+		if position.market_index == market_index || !position.has_debt() {
+			continue;
+		}
+
+		other_liability_tiers.push(market_map.get_ref(&position.market_index)?.synthetic_tier);
+	}
+
+	validate_liability_tier_liquidation_order(liability_tier, other_liability_tiers)?;
+
 	let margin_calculation =
 		calculate_margin_requirement_and_total_collateral_and_liability_info(
 			user,
@@ -142,317 +178,169 @@ pub fn liquidate_vault(
 			).track_market_margin_requirement(MarketIdentifier::perp(market_index))?
 		)?;
 
-	if
-		!user.is_being_liquidated() &&
-		margin_calculation.meets_margin_requirement()
-	{
-		msg!("margin calculation: {:?}", margin_calculation);
-		return Err(ErrorCode::SufficientCollateral);
-	} else if
-		user.is_being_liquidated() &&
-		margin_calculation.can_exit_liquidation()?
-	{
-		user.exit_liquidation();
-		return Ok(());
-	}
-
-	user.get_vault_position(vault_index).map_err(|e| {
-		msg!("User does not have a position for vault {}", vault_index);
-		e
-	})?;
-
-	liquidator.force_get_vault_position_mut(vault_index).map_err(|e| {
-		msg!("Liquidator has no available positions to take on vault position in market {}", vault_index);
-		e
-	})?;
+	validate_vault_liquidatable(user.is_being_liquidated(), &margin_calculation)?;
 
 	let liquidation_id = user.enter_liquidation(slot)?;
-	let mut margin_freed = 0_u64;
 
-	let position_index = get_position_index(&user.vault_positions, vault_index)?;
-	validate!(
-		user.vault_positions[position_index].is_open_position() ||
-			user.vault_positions[position_index].has_open_order() ||
-			user.vault_positions[position_index].is_lp(),
-		ErrorCode::PositionDoesntHaveOpenPositionOrOrders
+	let margin_shortage = margin_calculation.margin_shortage()?;
+	let max_pct_allowed = calculate_max_pct_to_liquidate(
+		user,
+		margin_shortage,
+		slot,
+		initial_pct_to_liquidate,
+		liquidation_duration
 	)?;
 
 	let mut market = market_map.get_ref_mut(&market_index)?;
-	let oracle_price_data = oracle_map.get_price_data(&amm.oracle)?;
-
-	update_amm_and_check_validity(
-		&mut market,
-		oracle_price_data,
-		state,
-		now,
-		slot,
-		Some(NormalAction::Liquidate)
-	)?;
 
-	let oracle_price = if market.status == MarketStatus::Settlement {
-		market.expiry_price
+	let (oracle_price, collateral_price) = if market.status == MarketStatus::Settlement {
+		(market.expiry_price, market.expiry_collateral_price)
 	} else {
-		oracle_price_data.price
+		(
+			oracle_map.get_price_data(&market.oracle)?.price,
+			oracle_map.get_price_data(&market.collateral_oracle)?.price,
+		)
 	};
 
-	drop(market);
-
-	// TODO: should we burn LP tokens in a liquidation?
-
-	// check if user exited liquidation territory
-	let intermediate_margin_calculation = margin_calculation;
-
-	if user.vault_positions[position_index].base_asset_amount == 0 {
-		msg!("User has no base asset amount");
-		return Ok(());
-	}
-
-	let liquidator_max_base_asset_amount = standardize_base_asset_amount(
-		liquidator_max_base_asset_amount,
-		market_map.get_ref(&market_index)?.amm.order_step_size
-	)?;
-
 	validate!(
-		liquidator_max_base_asset_amount != 0,
-		ErrorCode::InvalidBaseAssetAmountForLiquidatePerp,
-		"liquidator_max_base_asset_amount must be greater or equal to the step size"
+		oracle_price > 0 && collateral_price > 0,
+		ErrorCode::OracleNonPositive,
+		"liquidation prices must be positive: oracle_price={} collateral_price={}",
+		oracle_price,
+		collateral_price
 	)?;
 
 	let oracle_price_too_divergent = is_oracle_too_divergent_with_twap_5min(
 		oracle_price,
-		market_map.get_ref(
-			&market_index
-		)?.amm.historical_oracle_data.last_oracle_price_twap_5min,
+		market.historical_oracle_data.last_oracle_price_twap_5min,
 		state.oracle_guard_rails.max_oracle_twap_5min_percent_divergence().cast()?
 	)?;
 
 	validate!(!oracle_price_too_divergent, ErrorCode::PriceBandsBreached)?;
 
-	let user_base_asset_amount =
-		user.perp_positions[position_index].base_asset_amount.unsigned_abs();
-
-	let margin_ratio = market_map
-		.get_ref(&market_index)?
-		.get_margin_ratio(
-			user_base_asset_amount.cast()?,
-			MarginRequirementType::Maintenance
+	// Make sure the debt is liquidated at no worse than the liquidator's limit price
+	if let Some(limit_price) = limit_price {
+		validate!(
+			oracle_price <= limit_price.cast()?,
+			ErrorCode::LiquidationDoesntSatisfyLimitPrice,
+			"limit price ({}) < oracle price ({})",
+			limit_price,
+			oracle_price
 		)?;
+	}
+
+	let position = user.get_position_mut(market_index)?;
 
-	let margin_ratio_with_buffer = margin_ratio.safe_add(
-		liquidation_margin_buffer_ratio
+	validate!(
+		position.has_debt(),
+		ErrorCode::PositionDoesntHaveOpenPositionOrOrders,
+		"vault {} has no debt to liquidate",
+		vault_index
 	)?;
 
-	let margin_shortage = intermediate_margin_calculation.margin_shortage()?;
+	let collateral_amount = position.get_collateral_amount(&market)?;
+	let debt_amount = position.get_debt_amount(&market)?;
 
-	let market = market_map.get_ref(&market_index)?;
+	let debt_value = calculate_base_asset_value_with_oracle_price(
+		debt_amount.cast()?,
+		oracle_price
+	)?;
+
+	let debt_to_liquidate = calculate_vault_debt_to_liquidate(
+		debt_amount,
+		debt_value.cast()?,
+		max_pct_allowed,
+		liquidator_max_base_asset_amount
+	)?;
 
-	let quote_oracle_price = oracle_map.get_price_data(
-		&quote_spot_market.oracle // TODO: update
-	)?.price;
-	let liquidator_fee = market.liquidator_fee;
-	let if_liquidation_fee = calculate_vault_if_fee(
-		intermediate_margin_calculation.tracked_market_margin_shortage(
-			margin_shortage
-		)?,
-		user_base_asset_amount,
-		margin_ratio_with_buffer,
+	// the auction only raises the debt. The liquidation fees are owed in collateral, paid out of
+	// the lot left once the debt is covered
+	let liquidator_fee = debt_to_liquidate
+		.safe_mul(market.liquidator_fee.cast()?)?
+		.safe_div(LIQUIDATION_FEE_PRECISION_U128)?;
+	let if_fee = debt_to_liquidate
+		.safe_mul(market.if_liquidation_fee.cast()?)?
+		.safe_div(LIQUIDATION_FEE_PRECISION_U128)?;
+
+	let collateral_for_debt = calculate_redemption_collateral(
+		debt_to_liquidate,
+		market.decimals,
+		oracle_price,
+		market.collateral_decimals,
+		collateral_price
+	)?;
+	let liquidator_fee_collateral = calculate_redemption_collateral(
 		liquidator_fee,
+		market.decimals,
 		oracle_price,
-		quote_oracle_price,
-		market.if_liquidation_fee
-	)?;
-	let base_asset_amount_to_cover_margin_shortage =
-		standardize_base_asset_amount_ceil(
-			calculate_base_asset_amount_to_cover_margin_shortage(
-				margin_shortage,
-				margin_ratio_with_buffer,
-				liquidator_fee,
-				if_liquidation_fee,
-				oracle_price,
-				quote_oracle_price
-			)?,
-			amm.order_step_size
-		)?;
-	drop(market);
-
-	let max_pct_allowed = calculate_max_pct_to_liquidate(
-		user,
-		margin_shortage,
-		slot,
-		initial_pct_to_liquidate,
-		liquidation_duration
+		market.collateral_decimals,
+		collateral_price
+	)?;
+	let if_fee_collateral = calculate_redemption_collateral(
+		if_fee,
+		market.decimals,
+		oracle_price,
+		market.collateral_decimals,
+		collateral_price
 	)?;
-	let max_base_asset_amount_allowed_to_be_transferred =
-		base_asset_amount_to_cover_margin_shortage
-			.cast::<u128>()?
-			.saturating_mul(max_pct_allowed)
-			.safe_div(LIQUIDATION_PCT_PRECISION)?
-			.cast::<u64>()?;
-
-	if max_base_asset_amount_allowed_to_be_transferred == 0 {
-		msg!("max_base_asset_amount_allowed_to_be_transferred == 0");
-		return Ok(());
-	}
-
-	let base_asset_value = calculate_base_asset_value_with_oracle_price(
-		user_base_asset_amount.cast()?,
-		oracle_price
-	)?.cast::<u64>()?;
 
-	// if position is less than $50, liquidator can liq all of it
-	let min_base_asset_amount = if base_asset_value > 50 * QUOTE_PRECISION_U64 {
-		0_u64
+	// seize the collateral worth the debt and fees at oracle prices, or all of it if the vault is
+	// short
+	let collateral_to_seize = collateral_for_debt
+		.safe_add(liquidator_fee_collateral)?
+		.safe_add(if_fee_collateral)?
+		.min(collateral_amount);
+
+	// debt the primary collateral can't cover is taken from the vault's registry collateral,
+	// which goes to the insurance fund. The debt it covers is left out of the auction, so only
+	// what neither can cover ends up as protocol debt
+	let uncovered_debt = if collateral_for_debt > collateral_amount {
+		debt_to_liquidate.saturating_sub(
+			calculate_redemption_collateral(
+				collateral_amount,
+				market.collateral_decimals,
+				collateral_price,
+				market.decimals,
+				oracle_price
+			)?
+		)
 	} else {
-		user_base_asset_amount
+		0
 	};
-
-	let base_asset_amount = user_base_asset_amount
-		.min(liquidator_max_base_asset_amount)
-		.min(
-			max_base_asset_amount_allowed_to_be_transferred.max(min_base_asset_amount)
-		);
-	let base_asset_amount = standardize_base_asset_amount_ceil(
-		base_asset_amount,
-		perp_market_map.get_ref(&market_index)?.amm.order_step_size
+	let collateral_asset_prices = get_collateral_asset_prices(position, &market, oracle_map)?;
+	let (collateral_assets_seized, debt_covered) = seize_collateral_assets(
+		position,
+		&mut market,
+		uncovered_debt,
+		oracle_price,
+		&collateral_asset_prices
 	)?;
 
-	// Make sure liquidator enters at better than limit price
-	if let Some(limit_price) = limit_price {
-		match user.perp_positions[position_index].get_direction() {
-			PositionDirection::Long =>
-				validate!(
-					oracle_price <= limit_price.cast()?,
-					ErrorCode::LiquidationDoesntSatisfyLimitPrice,
-					"limit price ({}) > oracle price ({})",
-					limit_price,
-					oracle_price
-				)?,
-			PositionDirection::Short =>
-				validate!(
-					oracle_price >= limit_price.cast()?,
-					ErrorCode::LiquidationDoesntSatisfyLimitPrice,
-					"limit price ({}) < oracle price ({})",
-					limit_price,
-					oracle_price
-				)?,
-		}
-	}
-
-	let base_asset_value = calculate_base_asset_value_with_oracle_price(
-		base_asset_amount.cast()?,
-		oracle_price
-	)?.cast::<u64>()?;
-
-	let liquidator_fee = -base_asset_value
-		.cast::<u128>()?
-		.safe_mul(liquidator_fee.cast()?)?
-		.safe_div(LIQUIDATION_FEE_PRECISION_U128)?
-		.cast::<i64>()?;
-
-	let if_fee = -base_asset_value
-		.cast::<u128>()?
-		.safe_mul(if_liquidation_fee.cast()?)?
-		.safe_div(LIQUIDATION_FEE_PRECISION_U128)?
-		.cast::<i64>()?;
-
-	user_stats.update_taker_volume_30d(
-		perp_market_map.get_ref(&market_index)?.fuel_boost_taker,
-		base_asset_value,
-		now
+	let scaled_debt_delta = calculate_scaled_balance_delta(
+		position.scaled_debt,
+		debt_amount,
+		debt_to_liquidate
 	)?;
-	liquidator_stats.update_maker_volume_30d(
-		perp_market_map.get_ref(&market_index)?.fuel_boost_maker,
-		base_asset_value,
-		now
+	let scaled_collateral_delta = calculate_scaled_balance_delta(
+		position.scaled_balance,
+		collateral_amount,
+		collateral_to_seize
 	)?;
 
-	let user_position_delta = get_position_delta_for_fill(
-		base_asset_amount,
-		base_asset_value,
-		user.perp_positions[position_index].get_direction_to_close()
-	)?;
+	let scaled_debt_before = position.scaled_debt;
+	position.scaled_debt = position.scaled_debt.safe_sub(scaled_debt_delta)?;
+	position.scaled_balance = position.scaled_balance.safe_sub(scaled_collateral_delta)?;
 
-	let liquidator_position_delta = get_position_delta_for_fill(
-		base_asset_amount,
-		base_asset_value,
-		user.perp_positions[position_index].get_direction()
+	market.debt_balance = market.debt_balance.safe_sub(scaled_debt_delta.cast()?)?;
+	market.collateral_balance = market.collateral_balance.safe_sub(
+		scaled_collateral_delta.cast()?
 	)?;
+	update_number_of_vaults_with_debt(&mut market, scaled_debt_before, position.scaled_debt)?;
+	update_vault_redemption_bucket(&mut market, position)?;
 
-	let (
-		user_existing_position_direction,
-		user_position_direction_to_close,
-		liquidator_existing_position_direction,
-	) = {
-		let mut market = perp_market_map.get_ref_mut(&market_index)?;
-
-		let user_position = user.get_perp_position_mut(market_index)?;
-		let user_existing_position_direction = user_position.get_direction();
-		let user_position_direction_to_close =
-			user_position.get_direction_to_close();
-		update_position_and_market(
-			user_position,
-			&mut market,
-			&user_position_delta
-		)?;
-		update_quote_asset_and_break_even_amount(
-			user_position,
-			&mut market,
-			liquidator_fee
-		)?;
-		update_quote_asset_and_break_even_amount(
-			user_position,
-			&mut market,
-			if_fee
-		)?;
-
-		validate!(
-			is_multiple_of_step_size(
-				user_position.base_asset_amount.unsigned_abs(),
-				amm.order_step_size
-			)?,
-			ErrorCode::InvalidPerpPosition,
-			"base asset amount {} step size {}",
-			user_position.base_asset_amount,
-			amm.order_step_size
-		)?;
-
-		let liquidator_position =
-			liquidator.force_get_perp_position_mut(market_index)?;
-		let liquidator_existing_position_direction =
-			liquidator_position.get_direction();
-		update_position_and_market(
-			liquidator_position,
-			&mut market,
-			&liquidator_position_delta
-		)?;
-		update_quote_asset_and_break_even_amount(
-			liquidator_position,
-			&mut market,
-			-liquidator_fee
-		)?;
-
-		validate!(
-			is_multiple_of_step_size(
-				liquidator_position.base_asset_amount.unsigned_abs(),
-				amm.order_step_size
-			)?,
-			ErrorCode::InvalidPerpPosition,
-			"base asset amount {} step size {}",
-			liquidator_position.base_asset_amount,
-			amm.order_step_size
-		)?;
-
-		amm.total_liquidation_fee =
-			amm.total_liquidation_fee.safe_add(if_fee.unsigned_abs().cast()?)?;
-
-		(
-			user_existing_position_direction,
-			user_position_direction_to_close,
-			liquidator_existing_position_direction,
-		)
-	};
+	drop(market);
 
-	let (margin_freed_for_perp_position, _) = calculate_margin_freed(
+	let (margin_freed, _) = calculate_margin_freed(
 		user,
 		market_map,
 		vault_map,
@@ -460,34 +348,40 @@ pub fn liquidate_vault(
 		liquidation_margin_buffer_ratio,
 		margin_shortage
 	)?;
-	margin_freed = margin_freed.safe_add(margin_freed_for_perp_position)?;
-	user.increment_margin_freed(margin_freed_for_perp_position)?;
+	user.increment_margin_freed(margin_freed)?;
 
-	if base_asset_amount >= base_asset_amount_to_cover_margin_shortage {
+	let margin_calculation_after =
+		calculate_margin_requirement_and_total_collateral_and_liability_info(
+			user,
+			market_map,
+			vault_map,
+			oracle_map,
+			MarginContext::liquidation(liquidation_margin_buffer_ratio)
+		)?;
+
+	if margin_calculation_after.can_exit_liquidation()? {
 		user.exit_liquidation();
 	} else if is_user_bankrupt(user) {
 		user.enter_bankruptcy();
 	}
 
-	let liquidator_meets_initial_margin_requirement =
-		meets_initial_margin_requirement(liquidator, perp_market_map, oracle_map)?;
-
-	validate!(
-		liquidator_meets_initial_margin_requirement,
-		ErrorCode::InsufficientCollateral,
-		"Liquidator doesnt have enough collateral to take over perp position"
+	// the seized collateral is auctioned for the synthetic the vault owed
+	controller::auction::start_collateral_auction(
+		auction,
+		auction_key,
+		&market_map.get_ref(&market_index)?,
+		user_key,
+		liquidator_key,
+		liquidation_id,
+		collateral_to_seize.cast()?,
+		debt_to_liquidate.safe_sub(debt_covered)?.cast()?,
+		liquidator_fee_collateral.cast()?,
+		if_fee_collateral.cast()?,
+		collateral_price,
+		oracle_price,
+		now
 	)?;
 
-	// get ids for order fills
-	let user_order_id = get_then_update_id!(user, next_order_id);
-	let liquidator_order_id = get_then_update_id!(liquidator, next_order_id);
-	let fill_record_id = {
-		let mut market = perp_market_map.get_ref_mut(&market_index)?;
-		get_then_update_id!(market, next_fill_record_id)
-	};
-
-	// TODO: orders?
-
 	emit!(LiquidationRecord {
 		ts: now,
 		liquidation_id,
@@ -503,15 +397,129 @@ pub fn liquidate_vault(
 			market_index,
 			vault_index,
 			oracle_price,
-			base_asset_amount: user_position_delta.base_asset_amount,
-			quote_asset_amount: user_position_delta.quote_asset_amount,
-			liquidator_fee: liquidator_fee.abs().cast()?,
-			if_fee: if_fee.abs().cast()?,
+			base_asset_amount: debt_to_liquidate.cast()?,
+			quote_asset_amount: collateral_to_seize.cast()?,
+			liquidator_fee: liquidator_fee_collateral.cast()?,
+			if_fee: if_fee_collateral.cast()?,
 		},
 		..LiquidationRecord::default()
 	});
 
-	Ok(())
+	Ok(collateral_assets_seized)
+}
+
+/// Sells an External market's collateral auction into its configured venue at or above the
+/// oracle price less the liquidation penalty. Whatever the venue doesn't fill stays up for
+/// private bidding.
+pub fn route_collateral_auction_externally<A: CollateralSwapAdapter>(
+	adapter: &mut A,
+	auction: &mut Auction,
+	market: &SynthMarket,
+	venue_key: &Pubkey,
+	collateral_price: i64,
+	synthetic_price: i64,
+	now: i64
+) -> Result<Option<ExternalFill>> {
+	validate!(
+		market.collateral_action_config.auction_location == AuctionPreference::External,
+		ErrorCode::ExternalAuctionNotConfigured,
+		"market {} auctions collateral privately",
+		market.market_index
+	)?;
+
+	validate!(
+		auction.auction_type == AuctionType::Collateral &&
+			auction.market_index == market.market_index,
+		ErrorCode::InvalidAuctionType,
+		"auction is not a collateral auction for market {}",
+		market.market_index
+	)?;
+
+	validate!(auction.is_active(), ErrorCode::AuctionNotActive)?;
+
+	validate!(
+		!auction.is_expired(now),
+		ErrorCode::AuctionExpired,
+		"auction expired at {}, now {}",
+		auction.end_ts,
+		now
+	)?;
+
+	let min_price = calculate_external_auction_min_price(
+		collateral_price,
+		market.collateral_decimals,
+		synthetic_price,
+		market.decimals,
+		market.liquidation_penalty
+	)?;
+
+	let (lot_amount, min_synthetic_out) = calculate_auction_fill(
+		auction.lot_amount,
+		auction.lot_amount,
+		auction.max_lot_size,
+		auction.debt_remaining()?,
+		min_price
+	)?;
+
+	if lot_amount == 0 {
+		return Ok(None);
+	}
+
+	let fill = match adapter.sell_collateral(lot_amount, min_synthetic_out)? {
+		Some(fill) => fill,
+		None => {
+			msg!(
+				"venue {} could not fill at min price {}, auction stays private",
+				venue_key,
+				min_price
+			);
+			return Ok(None);
+		}
+	};
+
+	// partial fills must still clear the min price on the amount sold
+	let min_synthetic_for_fill = fill.collateral_sold
+		.cast::<u128>()?
+		.safe_mul(min_price.cast()?)?
+		.safe_div(PRICE_PRECISION)?;
+
+	validate!(
+		fill.collateral_sold <= lot_amount &&
+			fill.synthetic_received.cast::<u128>()? >= min_synthetic_for_fill,
+		ErrorCode::ExternalAuctionSlippage,
+		"sold {} for {}, min {}",
+		fill.collateral_sold,
+		fill.synthetic_received,
+		min_synthetic_for_fill
+	)?;
+
+	// anything raised past the debt stays in the synthetic vault
+	let bid_amount = fill.synthetic_received.min(auction.debt_remaining()?);
+	auction.record_bid(fill.collateral_sold, bid_amount)?;
+
+	let price = fill.synthetic_received
+		.cast::<u128>()?
+		.safe_mul(PRICE_PRECISION)?
+		.safe_div(fill.collateral_sold.cast()?)?
+		.cast::<u64>()?;
+
+	emit!(AuctionRecord {
+		ts: now,
+		auction: auction.pubkey,
+		auction_type: auction.auction_type,
+		action: AuctionAction::Bid,
+		market_index: auction.market_index,
+		user: auction.user,
+		authority: *venue_key,
+		price,
+		lot_amount: fill.collateral_sold,
+		bid_amount,
+		lot_remaining: auction.lot_amount,
+		debt_remaining: auction.debt_remaining()?,
+		..AuctionRecord::default()
+	});
+
+	Ok(Some(fill))
 }
 
 pub fn resolve_vault_bankruptcy(
@@ -588,8 +596,10 @@ pub fn resolve_vault_bankruptcy(
 
 	let if_payment = {
 		let mut market = market_map.get_ref_mut(&market_index)?;
-		let max_insurance_withdraw = market.insurance_claim.quote_max_insurance
-			.safe_sub(market.insurance_claim.quote_settled_insurance)?
+		// the tier cap can drop below what was already settled after a tier downgrade
+		let max_insurance_withdraw = market
+			.get_quote_max_insurance()
+			.saturating_sub(market.insurance_claim.quote_settled_insurance)
 			.cast::<u128>()?;
 
 		let if_payment = loss
@@ -1,11 +1,16 @@
 pub mod amm;
+pub mod auction;
+pub mod collateral;
 pub mod index;
 pub mod insurance;
+pub mod lending;
+pub mod lending_adapter;
 pub mod liquidity;
 pub mod liquidation;
 pub mod pda;
 pub mod position;
 pub mod swap;
+pub mod swap_adapter;
 pub mod tick;
 pub mod token;
 pub mod vault;
@@ -61,13 +61,20 @@ pub fn swap(
 		return Err(ErrorCode::ZeroTradableAmount.into());
 	}
 
-	if synthetic_to_quote && adjusted_sqrt_price_limit > amm.historical_oracle_data.last_oracle_price_twap_5min {
-		// TODO: apply penalty
-	}
-	
+	// a thin pool can't be pushed further than max_price_variance from the oracle twap
+	let adjusted_sqrt_price_limit = match amm.get_oracle_price_band(timestamp)? {
+		Some(band) =>
+			math::price_band::clip_sqrt_price_limit_to_band(
+				amm.sqrt_price,
+				adjusted_sqrt_price_limit,
+				band,
+				synthetic_to_quote
+			)?,
+		None => adjusted_sqrt_price_limit,
+	};
 
 	let tick_spacing = amm.tick_spacing;
-	let fee_rate = amm.fee_rate;
+	let fee_rate = amm.get_swap_fee_rate(timestamp)?;
 	let protocol_fee_rate = amm.protocol_fee_rate;
 	let next_reward_infos = next_amm_reward_infos(amm, timestamp)?;
 
@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+use solana_program::instruction::{ AccountMeta, Instruction };
+use solana_program::program::invoke_signed;
+
+use crate::controller;
+use crate::math::auction::calculate_sqrt_price_limit_for_min_out;
+use crate::math::safe_math::SafeMath;
+use crate::state::amm::AMM;
+use crate::util::SwapTickSequence;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExternalFill {
+	/// precision: collateral mint precision
+	pub collateral_sold: u64,
+	/// precision: synthetic mint precision
+	pub synthetic_received: u64,
+}
+
+/// A venue an External collateral auction can sell into
+pub trait CollateralSwapAdapter {
+	/// Sells up to `collateral_amount` for at least `min_synthetic_out`, pro rata on partial fills.
+	/// Returns None, with no state changed, if nothing can be sold at that price.
+	fn sell_collateral(
+		&mut self,
+		collateral_amount: u64,
+		min_synthetic_out: u64
+	) -> Result<Option<ExternalFill>>;
+}
+
+/// Sells into the market's own AMM. The swap stops at the min price, so a thin pool gives a
+/// partial fill rather than a failed one. Token movement is left to the instruction.
+pub struct AmmSwapAdapter<'a> {
+	pub amm: &'a mut AMM,
+	pub swap_tick_sequence: SwapTickSequence<'a>,
+	pub timestamp: u64,
+}
+
+impl<'a> CollateralSwapAdapter for AmmSwapAdapter<'a> {
+	fn sell_collateral(
+		&mut self,
+		collateral_amount: u64,
+		min_synthetic_out: u64
+	) -> Result<Option<ExternalFill>> {
+		let sqrt_price_limit = calculate_sqrt_price_limit_for_min_out(
+			collateral_amount,
+			min_synthetic_out
+		)?;
+
+		// pool is already priced past the limit, nothing to sell
+		if sqrt_price_limit <= self.amm.sqrt_price {
+			return Ok(None);
+		}
+
+		// or already at the top of the oracle band
+		if let Some((_, upper_sqrt_price)) = self.amm.get_oracle_price_band(self.timestamp)? {
+			if upper_sqrt_price <= self.amm.sqrt_price {
+				return Ok(None);
+			}
+		}
+
+		let swap_update = controller::swap::swap(
+			self.amm,
+			&mut self.swap_tick_sequence,
+			collateral_amount,
+			sqrt_price_limit,
+			true,
+			false,
+			self.timestamp
+		)?;
+
+		if swap_update.amount_quote == 0 || swap_update.amount_synthetic == 0 {
+			return Ok(None);
+		}
+
+		self.amm.update_after_swap(
+			swap_update.next_liquidity,
+			swap_update.next_tick_index,
+			swap_update.next_sqrt_price,
+			swap_update.next_fee_growth_global,
+			swap_update.next_reward_infos,
+			swap_update.next_protocol_fee,
+			false,
+			self.timestamp
+		);
+
+		Ok(
+			Some(ExternalFill {
+				collateral_sold: swap_update.amount_quote,
+				synthetic_received: swap_update.amount_synthetic,
+			})
+		)
+	}
+}
+
+/// Sells through a CPI into the market's configured swap program. The keeper supplies the
+/// venue's instruction data and accounts. The lot is moved into vaults owned by the auction's
+/// own signer, which is the only authority the venue is handed, and the fill is measured from
+/// those vaults before they are swept back to the market.
+/// A CPI can't be retried in the same transaction, so a fill below the min price fails the
+/// route and the auction stays up for private bidding.
+pub struct CpiSwapAdapter<'a, 'info> {
+	pub swap_program: &'a AccountInfo<'info>,
+	pub accounts: &'a [AccountInfo<'info>],
+	pub data: Vec<u8>,
+	pub token_program: &'a Interface<'info, TokenInterface>,
+	pub token_vault_collateral: &'a InterfaceAccount<'info, TokenAccount>,
+	pub token_vault_synthetic: &'a InterfaceAccount<'info, TokenAccount>,
+	pub normal_signer: &'a AccountInfo<'info>,
+	pub signer_nonce: u8,
+	pub auction_vault_collateral: &'a mut InterfaceAccount<'info, TokenAccount>,
+	pub auction_vault_synthetic: &'a mut InterfaceAccount<'info, TokenAccount>,
+	pub auction_signer: &'a AccountInfo<'info>,
+	pub auction_signer_seeds: &'a [&'a [u8]],
+}
+
+impl<'a, 'info> CollateralSwapAdapter for CpiSwapAdapter<'a, 'info> {
+	fn sell_collateral(
+		&mut self,
+		collateral_amount: u64,
+		min_synthetic_out: u64
+	) -> Result<Option<ExternalFill>> {
+		// the venue can only ever reach the lot on offer
+		controller::token::send_from_program_vault(
+			self.token_program,
+			self.token_vault_collateral,
+			self.auction_vault_collateral,
+			self.normal_signer,
+			self.signer_nonce,
+			collateral_amount,
+			&None
+		)?;
+
+		self.auction_vault_collateral.reload()?;
+		let collateral_before = self.auction_vault_collateral.amount;
+		let synthetic_before = self.auction_vault_synthetic.amount;
+
+		let account_metas = self.accounts
+			.iter()
+			.map(|account| {
+				// the auction signer authorizes the auction vault debit
+				let is_signer = account.is_signer || account.key == self.auction_signer.key;
+				if account.is_writable {
+					AccountMeta::new(*account.key, is_signer)
+				} else {
+					AccountMeta::new_readonly(*account.key, is_signer)
+				}
+			})
+			.collect();
+
+		let instruction = Instruction {
+			program_id: *self.swap_program.key,
+			accounts: account_metas,
+			data: self.data.clone(),
+		};
+
+		invoke_signed(&instruction, self.accounts, &[self.auction_signer_seeds])?;
+
+		self.auction_vault_collateral.reload()?;
+		self.auction_vault_synthetic.reload()?;
+
+		let collateral_sold = collateral_before.safe_sub(self.auction_vault_collateral.amount)?;
+		let synthetic_received = self.auction_vault_synthetic.amount.safe_sub(synthetic_before)?;
+
+		// unsold collateral and the proceeds go back to the market
+		controller::token::send_from_pda_vault(
+			self.token_program,
+			self.auction_vault_collateral,
+			self.token_vault_collateral,
+			self.auction_signer,
+			self.auction_signer_seeds,
+			self.auction_vault_collateral.amount
+		)?;
+
+		controller::token::send_from_pda_vault(
+			self.token_program,
+			self.auction_vault_synthetic,
+			self.token_vault_synthetic,
+			self.auction_signer,
+			self.auction_signer_seeds,
+			self.auction_vault_synthetic.amount
+		)?;
+
+		self.auction_vault_collateral.reload()?;
+		self.auction_vault_synthetic.reload()?;
+
+		if collateral_sold == 0 {
+			return Ok(None);
+		}
+
+		msg!(
+			"swap program sold {} collateral for {} synthetic (min {} for full lot)",
+			collateral_sold,
+			synthetic_received,
+			min_synthetic_out
+		);
+
+		Ok(
+			Some(ExternalFill {
+				collateral_sold,
+				synthetic_received,
+			})
+		)
+	}
+}
@@ -18,19 +18,22 @@ use crate::math::constants::{
 };
 use crate::math::synth_balance::{
 	calculate_accumulated_interest,
+	calculate_synth_market_utilization,
 	calculate_utilization,
 	get_interest_token_amount,
 	get_spot_balance,
 	get_token_amount,
 	InterestAccumulated,
 };
+use crate::math::redemption::{ calculate_redemption_bucket, calculate_scaled_balance_delta };
 use crate::math::stats::{ calculate_new_twap, calculate_weighted_average };
 
 use crate::math::oracle::{ is_oracle_valid_for_action, DriftAction };
 use crate::math::safe_math::SafeMath;
-use crate::state::events::SpotInterestRecord;
+use crate::state::events::StabilityFeeRecord;
 use crate::state::oracle::OraclePriceData;
 use crate::state::paused_operations::SpotOperation;
+use crate::state::position::Position;
 use crate::state::spot_market::{ SpotBalance, SpotBalanceType, SpotMarket };
 use crate::state::user::MarketType;
 use crate::validate;
@@ -139,57 +142,129 @@ pub fn update_synth_market_cumulative_interest(
 		return Ok(());
 	}
 
-	let InterestAccumulated { deposit_interest, borrow_interest } =
-		calculate_accumulated_interest(synth_market, now)?;
-
-	if deposit_interest > 0 && borrow_interest > 1 {
-		// borrowers -> lenders IF fee here
-		let deposit_interest_for_stakers = deposit_interest
-			.safe_mul(synth_market.insurance_fund.total_factor as u128)?
-			.safe_div(IF_FACTOR_PRECISION)?;
+	let InterestAccumulated { debt_interest } = calculate_accumulated_interest(
+		synth_market,
+		now
+	)?;
 
-		let deposit_interest_for_lenders = deposit_interest.safe_sub(
-			deposit_interest_for_stakers
+	if debt_interest > 0 {
+		// the debt growth is owed to the protocol as stability fees, minted when they're settled
+		let stability_fee = get_interest_token_amount(
+			synth_market.debt_balance,
+			synth_market,
+			debt_interest
 		)?;
 
-		if deposit_interest_for_lenders > 0 {
-			synth_market.cumulative_deposit_interest =
-				synth_market.cumulative_deposit_interest.safe_add(
-					deposit_interest_for_lenders
-				)?;
+		synth_market.cumulative_debt_interest =
+			synth_market.cumulative_debt_interest.safe_add(debt_interest)?;
+		synth_market.unminted_stability_fees =
+			synth_market.unminted_stability_fees.safe_add(stability_fee)?;
+
+		emit!(StabilityFeeRecord {
+			ts: now,
+			market_index: synth_market.market_index,
+			debt_balance: synth_market.debt_balance,
+			cumulative_debt_interest: synth_market.cumulative_debt_interest,
+			stability_fee,
+			unminted_stability_fees: synth_market.unminted_stability_fees,
+			utilization: calculate_synth_market_utilization(synth_market)?.cast()?,
+		});
+	}
 
-			synth_market.cumulative_borrow_interest =
-				synth_market.cumulative_borrow_interest.safe_add(borrow_interest)?;
-			synth_market.last_interest_ts = now.cast()?;
+	synth_market.last_interest_ts = now.cast()?;
 
-			// add deposit_interest_for_stakers as balance for revenue_pool
-			let token_amount = get_interest_token_amount(
-				synth_market.deposit_balance,
-				synth_market,
-				deposit_interest_for_stakers
-			)?;
+	update_synth_market_twap_stats(synth_market, oracle_price_data, now)?;
 
-			update_revenue_pool_balances(
-				token_amount,
-				&SpotBalanceType::Deposit,
-				synth_market
-			)?;
+	Ok(())
+}
 
-			emit!(SpotInterestRecord {
-				ts: now,
-				market_index: synth_market.market_index,
-				deposit_balance: synth_market.deposit_balance,
-				cumulative_deposit_interest: synth_market.cumulative_deposit_interest,
-				borrow_balance: synth_market.borrow_balance,
-				cumulative_borrow_interest: synth_market.cumulative_borrow_interest,
-				optimal_utilization: synth_market.optimal_utilization,
-				optimal_borrow_rate: synth_market.optimal_borrow_rate,
-				max_borrow_rate: synth_market.max_borrow_rate,
-			});
-		}
+/// Records `token_amount` of synthetic minted against `position` as debt, scaled at the market's
+/// cumulative debt interest so the stability fee accrues on it from now
+pub fn increase_position_debt(
+	token_amount: u64,
+	synth_market: &mut SynthMarket,
+	position: &mut Position
+) -> NormalResult {
+	let scaled_debt_delta = get_spot_balance(
+		token_amount.cast()?,
+		synth_market,
+		&SpotBalanceType::Borrow,
+		true
+	)?;
+
+	let scaled_debt_before = position.scaled_debt;
+	position.scaled_debt = position.scaled_debt.safe_add(scaled_debt_delta.cast()?)?;
+	synth_market.debt_balance = synth_market.debt_balance.safe_add(scaled_debt_delta)?;
+
+	update_number_of_vaults_with_debt(synth_market, scaled_debt_before, position.scaled_debt)?;
+	update_vault_redemption_bucket(synth_market, position)
+}
+
+/// Removes up to `token_amount` of burned synthetic from the position's debt, accrued stability
+/// fee included. Returns the debt repaid, which is less than `token_amount` if it clears the debt
+pub fn decrease_position_debt(
+	token_amount: u64,
+	synth_market: &mut SynthMarket,
+	position: &mut Position
+) -> NormalResult<u64> {
+	let debt_amount = position.get_debt_amount(synth_market)?;
+	let repaid = token_amount.cast::<u128>()?.min(debt_amount);
+
+	let scaled_debt_delta = calculate_scaled_balance_delta(
+		position.scaled_debt,
+		debt_amount,
+		repaid
+	)?;
+
+	let scaled_debt_before = position.scaled_debt;
+	position.scaled_debt = position.scaled_debt.safe_sub(scaled_debt_delta)?;
+	synth_market.debt_balance = synth_market.debt_balance.safe_sub(scaled_debt_delta.cast()?)?;
+
+	update_number_of_vaults_with_debt(synth_market, scaled_debt_before, position.scaled_debt)?;
+	update_vault_redemption_bucket(synth_market, position)?;
+
+	repaid.cast()
+}
+
+/// Counts a vault in when it takes on debt and out when its debt is cleared
+pub fn update_number_of_vaults_with_debt(
+	synth_market: &mut SynthMarket,
+	scaled_debt_before: u64,
+	scaled_debt_after: u64
+) -> NormalResult {
+	if scaled_debt_before == 0 && scaled_debt_after > 0 {
+		synth_market.number_of_vaults_with_debt =
+			synth_market.number_of_vaults_with_debt.safe_add(1)?;
+	} else if scaled_debt_before > 0 && scaled_debt_after == 0 {
+		synth_market.number_of_vaults_with_debt =
+			synth_market.number_of_vaults_with_debt.safe_sub(1)?;
 	}
 
-	update_synth_market_twap_stats(synth_market, oracle_price_data, now)?;
+	Ok(())
+}
+
+/// Moves the vault to the redemption bucket for its balances, or out of the buckets once its debt
+/// is cleared. Called whenever a vault's collateral or debt changes
+pub fn update_vault_redemption_bucket(
+	synth_market: &mut SynthMarket,
+	position: &mut Position
+) -> NormalResult {
+	if position.redemption_bucket > 0 {
+		let bucket = &mut synth_market.redemption_buckets[
+			(position.redemption_bucket - 1) as usize
+		];
+		*bucket = bucket.safe_sub(1)?;
+	}
+
+	position.redemption_bucket = if position.has_debt() {
+		let bucket = calculate_redemption_bucket(position.scaled_balance, position.scaled_debt)?;
+		synth_market.redemption_buckets[bucket as usize] = synth_market.redemption_buckets[
+			bucket as usize
+		].safe_add(1)?;
+		bucket.safe_add(1)?
+	} else {
+		0
+	};
 
 	Ok(())
 }
@@ -10,12 +10,16 @@ use anchor_spl::token_2022::spl_token_2022::extension::{
 use anchor_spl::token_2022::spl_token_2022::state::Mint as MintInner;
 use anchor_spl::token_interface::{
 	self,
+	Approve,
 	InitializeAccount,
 	CloseAccount,
 	InitializeMint,
 	Mint,
+	MintTo,
 	TokenAccount,
 	TokenInterface,
+	Burn,
+	Revoke,
 	Transfer,
 	TransferChecked,
 };
@@ -69,6 +73,27 @@ pub fn send_from_program_vault<'info>(
 	}
 }
 
+/// Sends out of a vault owned by a pda other than the normal signer, e.g. an AMM or an auction
+pub fn send_from_pda_vault<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	from: &InterfaceAccount<'info, TokenAccount>,
+	to: &InterfaceAccount<'info, TokenAccount>,
+	authority: &AccountInfo<'info>,
+	signer_seeds: &[&[u8]],
+	amount: u64
+) -> Result<()> {
+	let signers = &[signer_seeds];
+	let cpi_accounts = Transfer {
+		from: from.to_account_info(),
+		to: to.to_account_info(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signers);
+	#[allow(deprecated)]
+	token_interface::transfer(cpi_context, amount)
+}
+
 pub fn receive<'info>(
 	token_program: &Interface<'info, TokenInterface>,
 	from: &InterfaceAccount<'info, TokenAccount>,
@@ -104,6 +129,71 @@ pub fn receive<'info>(
 	}
 }
 
+pub fn mint_from_program<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	mint: &InterfaceAccount<'info, Mint>,
+	to: &InterfaceAccount<'info, TokenAccount>,
+	authority: &AccountInfo<'info>,
+	nonce: u8,
+	amount: u64
+) -> Result<()> {
+	let signature_seeds = get_signer_seeds(&nonce);
+	let signers = &[&signature_seeds[..]];
+	let cpi_accounts = MintTo {
+		mint: mint.to_account_info(),
+		to: to.to_account_info(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(
+		cpi_program,
+		cpi_accounts,
+		signers
+	);
+	token_interface::mint_to(cpi_context, amount)
+}
+
+pub fn burn_from_program_vault<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	from: &InterfaceAccount<'info, TokenAccount>,
+	mint: &InterfaceAccount<'info, Mint>,
+	authority: &AccountInfo<'info>,
+	nonce: u8,
+	amount: u64
+) -> Result<()> {
+	let signature_seeds = get_signer_seeds(&nonce);
+	let signers = &[&signature_seeds[..]];
+	let cpi_accounts = Burn {
+		mint: mint.to_account_info(),
+		from: from.to_account_info(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(
+		cpi_program,
+		cpi_accounts,
+		signers
+	);
+	token_interface::burn(cpi_context, amount)
+}
+
+pub fn burn<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	from: &InterfaceAccount<'info, TokenAccount>,
+	mint: &InterfaceAccount<'info, Mint>,
+	authority: &AccountInfo<'info>,
+	amount: u64
+) -> Result<()> {
+	let cpi_accounts = Burn {
+		mint: mint.to_account_info(),
+		from: from.to_account_info(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+	token_interface::burn(cpi_context, amount)
+}
+
 pub fn close_vault<'info>(
 	token_program: &Interface<'info, TokenInterface>,
 	account: &InterfaceAccount<'info, TokenAccount>,
@@ -126,3 +216,60 @@ pub fn close_vault<'info>(
 	);
 	token_interface::close_account(cpi_context)
 }
+
+pub fn close_pda_vault<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	account: &InterfaceAccount<'info, TokenAccount>,
+	destination: &AccountInfo<'info>,
+	authority: &AccountInfo<'info>,
+	signer_seeds: &[&[u8]]
+) -> Result<()> {
+	let signers = &[signer_seeds];
+	let cpi_accounts = CloseAccount {
+		account: account.to_account_info(),
+		destination: destination.clone(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signers);
+	token_interface::close_account(cpi_context)
+}
+
+/// Lets `delegate` move up to `amount` out of a program vault, for CPIs that shouldn't be
+/// handed the normal signer
+pub fn approve_from_program_vault<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	from: &InterfaceAccount<'info, TokenAccount>,
+	delegate: &AccountInfo<'info>,
+	authority: &AccountInfo<'info>,
+	nonce: u8,
+	amount: u64
+) -> Result<()> {
+	let signature_seeds = get_signer_seeds(&nonce);
+	let signers = &[&signature_seeds[..]];
+	let cpi_accounts = Approve {
+		to: from.to_account_info(),
+		delegate: delegate.clone(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signers);
+	token_interface::approve(cpi_context, amount)
+}
+
+pub fn revoke_from_program_vault<'info>(
+	token_program: &Interface<'info, TokenInterface>,
+	from: &InterfaceAccount<'info, TokenAccount>,
+	authority: &AccountInfo<'info>,
+	nonce: u8
+) -> Result<()> {
+	let signature_seeds = get_signer_seeds(&nonce);
+	let signers = &[&signature_seeds[..]];
+	let cpi_accounts = Revoke {
+		source: from.to_account_info(),
+		authority: authority.to_account_info(),
+	};
+	let cpi_program = token_program.to_account_info();
+	let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signers);
+	token_interface::revoke(cpi_context)
+}
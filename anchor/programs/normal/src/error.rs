@@ -733,6 +733,153 @@ pub enum ErrorCode {
 
 	#[msg("Trade resulted in partial fill")]
 	PartialFillError, // 0x17a9 (6057)
+
+	#[msg("Auction is not active")]
+	AuctionNotActive,
+	#[msg("Auction has expired")]
+	AuctionExpired,
+	#[msg("Auction type does not match instruction")]
+	InvalidAuctionType,
+	#[msg("Auction price exceeds bidder limit price")]
+	AuctionPriceAboveLimit,
+	#[msg("Auction bid amount is zero")]
+	AuctionBidZero,
+	#[msg("Market insurance has not been exhausted")]
+	InsuranceNotExhausted,
+	#[msg("Auction bid does not improve on the current bid")]
+	AuctionBidNotImproved,
+	#[msg("Market does not route auctions to an external venue")]
+	ExternalAuctionNotConfigured,
+	#[msg("External venue filled below the auction's min price")]
+	ExternalAuctionSlippage,
+	#[msg("Index weights must sum to 10000 bps")]
+	InvalidIndexWeights,
+	#[msg("Index market rebalanced too recently")]
+	IndexRebalanceTooSoon,
+	#[msg("Asset is not in the index")]
+	IndexAssetNotFound,
+	#[msg("Invalid index basket account")]
+	InvalidIndexBasketAccount,
+	#[msg("Expected a sqrt price limit per index asset")]
+	InvalidIndexPriceLimits,
+	#[msg("Account is not permitted to hold this index token")]
+	IndexHolderNotPermitted,
+	#[msg("Index token transfer hook called outside of a transfer")]
+	IndexTransferHookNotTransferring,
+	#[msg("Signer is not owed index fees")]
+	InvalidIndexFeeRecipient,
+	#[msg("Invalid stability fee curve")]
+	InvalidStabilityFeeCurve,
+	#[msg("Synth market has not been shut down")]
+	SynthMarketNotShutdown,
+	#[msg("Synth market expiry not reached")]
+	SynthMarketExpiryNotReached,
+	#[msg("Synth market not in settlement")]
+	SynthMarketNotInSettlement,
+	#[msg("Synth market has unsettled vaults")]
+	SynthMarketHasUnsettledVaults,
+	#[msg("Synth market has outstanding balances")]
+	SynthMarketHasOutstandingBalances,
+	#[msg("Invalid collateral weights")]
+	InvalidCollateralWeights,
+	#[msg("Collateral asset not found")]
+	CollateralAssetNotFound,
+	#[msg("Collateral registry full")]
+	CollateralRegistryFull,
+	#[msg("Collateral asset already registered")]
+	DuplicateCollateralAsset,
+	#[msg("Collateral lending not configured for market")]
+	CollateralLendingDisabled,
+	#[msg("Collateral lending cap exceeded")]
+	CollateralLendingCapExceeded,
+	#[msg("Invalid lending program account")]
+	InvalidLendingAccount,
+	#[msg("Invalid collateral lending config")]
+	InvalidCollateralLendingConfig,
+	#[msg("Collateral still lent to the lending program")]
+	CollateralStillLent,
+	#[msg("Market debt ceiling exceeded")]
+	DebtCeilingExceeded,
+	#[msg("Mint limit for the rolling window exceeded")]
+	MintLimitExceeded,
+	#[msg("Invalid mint limit")]
+	InvalidMintLimit,
+	#[msg("Peg stability module not initialized for market")]
+	PsmNotInitialized,
+	#[msg("Peg stability module already initialized for market")]
+	PsmAlreadyInitialized,
+	#[msg("Peg stability module reserve cap exceeded")]
+	PsmReserveCapExceeded,
+	#[msg("Peg stability module reserve has insufficient quote")]
+	PsmInsufficientReserve,
+	#[msg("Invalid peg stability module fee")]
+	InvalidPsmFee,
+	#[msg("Peg stability module swap too small")]
+	PsmSwapTooSmall,
+	#[msg("Invalid redemption amount")]
+	InvalidRedemptionAmount,
+	#[msg("Redemption fee exceeds max fee")]
+	RedemptionFeeExceedsMax,
+	#[msg("No redeemable vaults supplied")]
+	NoRedeemableVaults,
+	#[msg("Invalid deleverage order")]
+	InvalidDeleverageOrder,
+	#[msg("Vault collateral ratio above deleverage trigger")]
+	DeleverageTriggerNotReached,
+	#[msg("Deleverage could not sell collateral within slippage")]
+	DeleverageNoFill,
+	#[msg("Deleverage did not improve vault collateral ratio")]
+	DeleverageHealthNotImproved,
+	#[msg("Action violates the Isolated Synthetic Tier rules")]
+	IsolatedSyntheticTierViolation,
+	#[msg("Liability of a safer synthetic tier must be liquidated first")]
+	TierViolationLiquidatingSynthetic,
+	#[msg("quote_max_insurance above synthetic tier cap")]
+	InvalidQuoteMaxInsurance,
+	#[msg("Flash mint must be a top level instruction")]
+	FlashMintCpiNotAllowed,
+	#[msg("Flash mint has no matching repay later in the transaction")]
+	FlashMintMissingRepay,
+	#[msg("Flash mint already open for market")]
+	FlashMintAlreadyOpen,
+	#[msg("No open flash mint to repay")]
+	NoOpenFlashMint,
+	#[msg("Flash mint amount must be positive")]
+	InvalidFlashMintAmount,
+	#[msg("Invalid flash mint fee")]
+	InvalidFlashMintFee,
+	#[msg("AMM rebalancing disabled for market")]
+	AmmRebalanceDisabled,
+	#[msg("Invalid AMM rebalance config")]
+	InvalidAmmRebalanceConfig,
+	#[msg("AMM price is inside the oracle band")]
+	AmmPriceWithinBand,
+	#[msg("AMM rebalance limit reached")]
+	AmmRebalanceLimitReached,
+	#[msg("Not enough protocol owned collateral to buy back synthetic")]
+	AmmRebalanceInsufficientCollateral,
+	#[msg("Invalid dynamic fee config")]
+	InvalidDynamicFeeConfig,
+	#[msg("Invalid AMM observation cardinality")]
+	InvalidObservationCardinality,
+	#[msg("AMM observation older than the oldest recorded")]
+	ObservationTooOld,
+	#[msg("Invalid AMM TWAP period")]
+	InvalidTwapPeriod,
+	#[msg("Invalid auction config")]
+	InvalidAuctionConfig,
+	#[msg("Index token mint must be a token-2022 mint hooked to this program")]
+	InvalidIndexTokenMint,
+	#[msg("Peg stability module requires a USD-pegged synthetic")]
+	PsmMarketNotUsdPegged,
+	#[msg("Peg stability module fee withdrawal exceeds fees accrued")]
+	PsmInsufficientFees,
+	#[msg("Redemption requires every vault of the redemption buckets it draws from")]
+	RedemptionVaultsIncomplete,
+	#[msg("Index market rebalance interval must not be negative")]
+	InvalidIndexRebalanceInterval,
+	#[msg("AMM price diverged from its twap")]
+	AmmPriceDivergedFromTwap,
 }
 
 // Orca
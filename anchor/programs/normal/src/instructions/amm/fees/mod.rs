@@ -1,4 +1,5 @@
 pub mod collect_fees;
 pub mod collect_protocol_fees;
+pub mod set_dynamic_fee;
 pub mod set_fee_rate;
 pub mod set_protocol_fee_rate;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{ error::ErrorCode, math::amm::MAX_FEE_RATE, state::amm::AMM, validate };
+
+#[derive(Accounts)]
+pub struct SetAMMDynamicFee<'info> {
+	pub fee_authority: Signer<'info>,
+	#[account(mut, has_one = fee_authority)]
+	pub amm: Box<Account<'info, AMM>>,
+}
+
+pub fn handle_set_amm_dynamic_fee(
+	ctx: Context<SetAMMDynamicFee>,
+	min_dynamic_fee_rate: u16,
+	max_dynamic_fee_rate: u16,
+	volatility_fee_multiplier: u16
+) -> Result<()> {
+	let amm = &mut ctx.accounts.amm;
+
+	if max_dynamic_fee_rate > MAX_FEE_RATE {
+		return Err(ErrorCode::FeeRateMaxExceeded.into());
+	}
+
+	validate!(
+		min_dynamic_fee_rate <= max_dynamic_fee_rate,
+		ErrorCode::InvalidDynamicFeeConfig,
+		"min_dynamic_fee_rate={} must be <= max_dynamic_fee_rate={}",
+		min_dynamic_fee_rate,
+		max_dynamic_fee_rate
+	)?;
+
+	msg!("amm.min_dynamic_fee_rate: {:?} -> {:?}", amm.min_dynamic_fee_rate, min_dynamic_fee_rate);
+	msg!("amm.max_dynamic_fee_rate: {:?} -> {:?}", amm.max_dynamic_fee_rate, max_dynamic_fee_rate);
+	msg!(
+		"amm.volatility_fee_multiplier: {:?} -> {:?}",
+		amm.volatility_fee_multiplier,
+		volatility_fee_multiplier
+	);
+
+	amm.min_dynamic_fee_rate = min_dynamic_fee_rate;
+	amm.max_dynamic_fee_rate = max_dynamic_fee_rate;
+	amm.volatility_fee_multiplier = volatility_fee_multiplier;
+
+	Ok(())
+}
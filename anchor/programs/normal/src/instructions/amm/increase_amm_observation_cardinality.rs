@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::state::amm_observation::AMMObservations;
+
+#[derive(Accounts)]
+pub struct IncreaseAMMObservationCardinality<'info> {
+	#[account(mut)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+}
+
+pub fn handle_increase_amm_observation_cardinality(
+	ctx: Context<IncreaseAMMObservationCardinality>,
+	cardinality_next: u16
+) -> Result<()> {
+	let mut amm_observations = ctx.accounts.amm_observations.load_mut()?;
+
+	msg!(
+		"amm_observations.cardinality_next: {:?} -> {:?}",
+		amm_observations.cardinality_next,
+		cardinality_next.max(amm_observations.cardinality_next)
+	);
+
+	amm_observations.grow(cardinality_next)?;
+
+	Ok(())
+}
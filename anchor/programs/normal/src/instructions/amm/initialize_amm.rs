@@ -1,4 +1,7 @@
 use crate::state::*;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::traits::Size;
+use crate::util::to_timestamp_u64;
 use amm::AMM;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{ self, Mint, Token, TokenAccount };
@@ -42,6 +45,15 @@ pub struct InitializeAMM<'info> {
 	)]
 	pub token_vault_b: Box<Account<'info, TokenAccount>>,
 
+	#[account(
+		init,
+		payer = funder,
+		seeds = [b"amm_observations", whirlpool.key().as_ref()],
+		bump,
+		space = AMMObservations::SIZE
+	)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+
 	#[account(
 		has_one = whirlpools_config,
 		constraint = fee_tier.tick_spacing == tick_spacing
@@ -63,6 +75,7 @@ pub fn handle_initialize_amm(
 	protocol_fee_rate: u16,
     max_price_variance: u16,
 ) -> Result<()> {
+	let timestamp = to_timestamp_u64(Clock::get()?.unix_timestamp)?;
 	let token_mint_a = ctx.accounts.token_mint_a.key();
 	let token_mint_b = ctx.accounts.token_mint_b.key();
 
@@ -86,6 +99,8 @@ pub fn handle_initialize_amm(
 	}
 
 	**amm = AMM {
+		amm_bump: [ctx.bumps.whirlpool],
+
 		token_mint_synthetic: ctx.accounts.token_mint_synthetic.key(),
 		token_vault_synthetic: ctx.accounts.token_vault_synthetic.key(),
 		token_mint_quote: ctx.accounts.token_mint_quote.key(),
@@ -93,6 +108,8 @@ pub fn handle_initialize_amm(
 
         // Peg
         max_price_variance,
+        decimals_synthetic: ctx.accounts.token_mint_synthetic.decimals,
+        decimals_quote: ctx.accounts.token_mint_quote.decimals,
         liquidity_to_volume_multiplier: 0,
 
 		// Oracle
@@ -105,6 +122,18 @@ pub fn handle_initialize_amm(
 		last_oracle_reserve_price_spread_pct: 0,
 		oracle_std: 0,
 
+		// Dynamic fees, off until set_amm_dynamic_fee
+		min_dynamic_fee_rate: 0,
+		max_dynamic_fee_rate: 0,
+		volatility_fee_multiplier: 0,
+		tick_reference_index: math::amm::tick_index_from_sqrt_price(&initial_sqrt_price),
+		tick_reference_timestamp: 0,
+
+		// Observations, seeded into amm_observations below
+		tick_cumulative: 0,
+		seconds_per_liquidity_cumulative_x64: 0,
+		observation_timestamp: timestamp,
+
 		// Liquidity
 		sqrt_price: initial_sqrt_price,
 		liquidity: 0,
@@ -129,5 +158,11 @@ pub fn handle_initialize_amm(
 		],
 	};
 
+	let mut amm_observations = ctx.accounts.amm_observations.load_init()?;
+	amm_observations.initialize(
+		ctx.accounts.whirlpool.key(),
+		ctx.accounts.whirlpool.get_observation(timestamp)
+	);
+
 	Ok(())
 }
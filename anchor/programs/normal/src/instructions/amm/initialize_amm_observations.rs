@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::state::amm::AMM;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::traits::Size;
+use crate::util::to_timestamp_u64;
+
+#[derive(Accounts)]
+pub struct InitializeAMMObservations<'info> {
+	pub amm: Box<Account<'info, AMM>>,
+
+	#[account(mut)]
+	pub funder: Signer<'info>,
+
+	#[account(
+		init,
+		payer = funder,
+		seeds = [b"amm_observations", amm.key().as_ref()],
+		bump,
+		space = AMMObservations::SIZE
+	)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+
+	pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_amm_observations(ctx: Context<InitializeAMMObservations>) -> Result<()> {
+	let timestamp = to_timestamp_u64(Clock::get()?.unix_timestamp)?;
+	let amm = &ctx.accounts.amm;
+
+	let mut amm_observations = ctx.accounts.amm_observations.load_init()?;
+	amm_observations.initialize(amm.key(), amm.get_observation(timestamp));
+
+	Ok(())
+}
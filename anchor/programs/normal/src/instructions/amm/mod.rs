@@ -1,8 +1,13 @@
 #![allow(ambiguous_glob_reexports)]
 
 pub mod initialize_amm;
+pub mod initialize_amm_observations;
+pub mod increase_amm_observation_cardinality;
 pub mod initialize_tick_array;
 pub mod swap;
+pub mod two_hop_swap;
+pub mod multi_hop_swap;
+pub mod rebalance_amm;
 pub mod update_fees_and_rewards;
 pub mod reset_amm_oracle_twap;
 pub mod update_amm_oracle_twap;
@@ -11,8 +16,13 @@ pub mod lp;
 pub mod rewards;
 
 pub use initialize_amm::*;
+pub use initialize_amm_observations::*;
+pub use increase_amm_observation_cardinality::*;
 pub use initialize_tick_array::*;
 pub use swap::*;
+pub use two_hop_swap::*;
+pub use multi_hop_swap::*;
+pub use rebalance_amm::*;
 pub use update_fees_and_rewards::*;
 pub use reset_amm_oracle_twap::*;
 pub use update_amm_oracle_twap::*;
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ self, Token, TokenAccount };
+
+use crate::{
+	error::ErrorCode,
+	math::amm::NO_EXPLICIT_SQRT_PRICE_LIMIT,
+	state::{ amm_observation::AMMObservations, AMM },
+	util::{ swap_amm_exact_input, to_timestamp_u64 },
+	validate,
+};
+
+/// amm, amm_observations, token_owner_account_synthetic, token_vault_synthetic,
+/// token_owner_account_quote, token_vault_quote, tick_array_0, tick_array_1, tick_array_2
+pub const MULTI_HOP_ACCOUNTS_PER_HOP: usize = 9;
+
+#[derive(Accounts)]
+pub struct MultiHopSwap<'info> {
+	#[account(address = token::ID)]
+	pub token_program: Program<'info, Token>,
+
+	pub token_authority: Signer<'info>,
+	// Each hop's accounts are passed as remaining accounts, MULTI_HOP_ACCOUNTS_PER_HOP per hop
+}
+
+struct AmmHop<'info> {
+	amm: Account<'info, AMM>,
+	amm_observations: AccountLoader<'info, AMMObservations>,
+	token_owner_account_synthetic: Account<'info, TokenAccount>,
+	token_vault_synthetic: Account<'info, TokenAccount>,
+	token_owner_account_quote: Account<'info, TokenAccount>,
+	token_vault_quote: Account<'info, TokenAccount>,
+	tick_arrays: Vec<AccountInfo<'info>>,
+}
+
+/// Checks a hop's remaining accounts the way the Swap context constrains them
+fn load_amm_hop<'info>(accounts: &'info [AccountInfo<'info>]) -> Result<AmmHop<'info>> {
+	for account_info in accounts[..6].iter() {
+		validate!(
+			account_info.is_writable,
+			ErrorCode::RemainingAccountsInvalidSlice,
+			"hop account {} must be writable",
+			account_info.key()
+		)?;
+	}
+
+	let amm = Account::<AMM>::try_from(&accounts[0])?;
+	let amm_observations = AccountLoader::<AMMObservations>::try_from(&accounts[1])?;
+
+	validate!(
+		amm_observations.load()?.amm == amm.key(),
+		ErrorCode::RemainingAccountsInvalidSlice,
+		"amm_observations {} not for amm {}",
+		amm_observations.key(),
+		amm.key()
+	)?;
+
+	let token_owner_account_synthetic = Account::<TokenAccount>::try_from(&accounts[2])?;
+	let token_vault_synthetic = Account::<TokenAccount>::try_from(&accounts[3])?;
+	let token_owner_account_quote = Account::<TokenAccount>::try_from(&accounts[4])?;
+	let token_vault_quote = Account::<TokenAccount>::try_from(&accounts[5])?;
+
+	validate!(
+		token_owner_account_synthetic.mint == amm.token_mint_synthetic &&
+			token_owner_account_quote.mint == amm.token_mint_quote &&
+			token_vault_synthetic.key() == amm.token_vault_synthetic &&
+			token_vault_quote.key() == amm.token_vault_quote,
+		ErrorCode::RemainingAccountsInvalidSlice,
+		"token accounts don't match amm {}",
+		amm.key()
+	)?;
+
+	Ok(AmmHop {
+		amm,
+		amm_observations,
+		token_owner_account_synthetic,
+		token_vault_synthetic,
+		token_owner_account_quote,
+		token_vault_quote,
+		tick_arrays: accounts[6..MULTI_HOP_ACCOUNTS_PER_HOP].to_vec(),
+	})
+}
+
+/// Swaps an exact input through a route of AMMs, `synthetic_to_quote` giving the direction of
+/// each hop. Every hop must take in exactly what the previous one paid out, and only the
+/// final output is checked against `other_amount_threshold`
+pub fn handle_multi_hop_swap<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, MultiHopSwap<'info>>,
+	amount: u64,
+	other_amount_threshold: u64,
+	synthetic_to_quote: Vec<bool>
+) -> Result<()> {
+	let clock = Clock::get()?;
+	// Update the global reward growth which increases as a function of time.
+	let timestamp = to_timestamp_u64(clock.unix_timestamp)?;
+
+	let hops = synthetic_to_quote.len();
+
+	validate!(
+		hops >= 2,
+		ErrorCode::RemainingAccountsInsufficient,
+		"route needs at least two hops, got {}",
+		hops
+	)?;
+
+	validate!(
+		ctx.remaining_accounts.len() == hops * MULTI_HOP_ACCOUNTS_PER_HOP,
+		ErrorCode::RemainingAccountsInvalidSlice,
+		"expected {} remaining accounts for {} hops, got {}",
+		hops * MULTI_HOP_ACCOUNTS_PER_HOP,
+		hops,
+		ctx.remaining_accounts.len()
+	)?;
+
+	let mut amms_swapped: Vec<Pubkey> = Vec::with_capacity(hops);
+	let mut intermediate_mint: Option<Pubkey> = None;
+	let mut amount_in = amount;
+
+	for (i, (hop_accounts, synthetic_to_quote)) in ctx.remaining_accounts
+		.chunks(MULTI_HOP_ACCOUNTS_PER_HOP)
+		.zip(synthetic_to_quote)
+		.enumerate() {
+		let mut hop = load_amm_hop(hop_accounts)?;
+		let amm_key = hop.amm.key();
+
+		// an amm swapped twice would price its second hop off stale state
+		if amms_swapped.contains(&amm_key) {
+			return Err(ErrorCode::DuplicateTwoHopPool.into());
+		}
+		amms_swapped.push(amm_key);
+
+		let (input_mint, output_mint) = if synthetic_to_quote {
+			(hop.amm.token_mint_synthetic, hop.amm.token_mint_quote)
+		} else {
+			(hop.amm.token_mint_quote, hop.amm.token_mint_synthetic)
+		};
+
+		if let Some(intermediate_mint) = intermediate_mint {
+			if intermediate_mint != input_mint {
+				return Err(ErrorCode::InvalidIntermediaryMint.into());
+			}
+		}
+
+		let (amount_swapped, amount_out) = swap_amm_exact_input(
+			&mut hop.amm,
+			&ctx.accounts.token_authority,
+			&hop.token_owner_account_synthetic,
+			&hop.token_owner_account_quote,
+			&hop.token_vault_synthetic,
+			&hop.token_vault_quote,
+			&ctx.accounts.token_program,
+			hop.tick_arrays,
+			amount_in,
+			NO_EXPLICIT_SQRT_PRICE_LIMIT,
+			synthetic_to_quote,
+			timestamp
+		)?;
+
+		// past the first hop a partial fill would strand the intermediate token in the
+		// owner's account
+		if i > 0 && amount_swapped != amount_in {
+			return Err(ErrorCode::IntermediateTokenAmountMismatch.into());
+		}
+
+		hop.amm_observations.load_mut()?.write(hop.amm.get_observation(timestamp));
+		// remaining accounts aren't written back by anchor
+		hop.amm.exit(&crate::ID)?;
+
+		intermediate_mint = Some(output_mint);
+		amount_in = amount_out;
+	}
+
+	if amount_in < other_amount_threshold {
+		return Err(ErrorCode::AmountOutBelowMinimum.into());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,290 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::mint_limit::calculate_mint_value;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::price_band::{ is_sqrt_price_above_band, price_to_sqrt_price_x64 };
+use crate::math::safe_math::SafeMath;
+use crate::state::amm::AMM;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::events::{ AmmRebalanceDirection, AmmRebalanceRecord };
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::util::{ to_timestamp_u64, SparseSwapTickSequenceBuilder };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct RebalanceAMM<'info> {
+	#[account(mut)]
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	#[account(address = synth_market.load()?.oracle)]
+	pub oracle: AccountInfo<'info>,
+	#[account(mut, address = synth_market.load()?.vault)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.amm,
+		constraint = amm.token_mint_quote == synth_market.load()?.token_mint_collateral,
+		constraint = amm.vault_balance_authority.eq(&normal_signer.key())
+	)]
+	pub amm: Box<Account<'info, AMM>>,
+	#[account(mut, seeds = [b"amm_observations", amm.key().as_ref()], bump)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+	#[account(mut, address = amm.token_vault_synthetic)]
+	pub amm_token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = amm.token_vault_quote)]
+	pub amm_token_vault_quote: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_0: UncheckedAccount<'info>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_1: UncheckedAccount<'info>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_2: UncheckedAccount<'info>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pulls the market's AMM back to the oracle price once it has left the max_price_variance
+/// band around the oracle twap. Above the band protocol owned synthetic is minted and sold for collateral, which is
+/// held in the market vault; below it that collateral buys synthetic back to burn. Each call
+/// is capped by max_rebalance_per_call and the rolling daily limit
+pub fn handle_rebalance_amm(ctx: Context<RebalanceAMM>, market_index: u16) -> Result<()> {
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+	let timestamp = to_timestamp_u64(now)?;
+
+	let state = &mut ctx.accounts.state;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Active,
+		ErrorCode::MarketActionPaused,
+		"amm rebalancing paused for market {}",
+		market_index
+	)?;
+
+	validate!(
+		synth_market.amm_rebalancer.is_enabled(),
+		ErrorCode::AmmRebalanceDisabled,
+		"market {} has no amm rebalancer",
+		market_index
+	)?;
+
+	// the swap is anchored to the live oracle price, which must be fully valid
+	let oracle_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.oracle)?,
+		market_index,
+		Some(synth_market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	let amm = &mut ctx.accounts.amm;
+
+	let band = amm.get_oracle_price_band(timestamp)?.ok_or_else(|| {
+		msg!("amm for market {} has no max_price_variance", market_index);
+		ErrorCode::AmmRebalanceDisabled
+	})?;
+
+	let sqrt_price_before = amm.sqrt_price;
+
+	let above_band = is_sqrt_price_above_band(sqrt_price_before, band).ok_or_else(|| {
+		msg!(
+			"sqrt_price={} inside oracle band lower={} upper={}",
+			sqrt_price_before,
+			band.0,
+			band.1
+		);
+		ErrorCode::AmmPriceWithinBand
+	})?;
+
+	let oracle_sqrt_price = price_to_sqrt_price_x64(
+		oracle_price.unsigned_abs(),
+		amm.decimals_synthetic,
+		amm.decimals_quote
+	)?;
+
+	let mut max_rebalance = synth_market.amm_rebalancer.get_max_rebalance(now)?;
+	if !above_band {
+		// only synthetic the protocol sold can be bought back
+		max_rebalance = max_rebalance.min(synth_market.amm_rebalancer.synthetic_minted);
+	}
+
+	validate!(
+		max_rebalance > 0,
+		ErrorCode::AmmRebalanceLimitReached,
+		"market {} has nothing left to rebalance with",
+		market_index
+	)?;
+
+	let tick_arrays = vec![
+		ctx.accounts.tick_array_0.to_account_info(),
+		ctx.accounts.tick_array_1.to_account_info(),
+		ctx.accounts.tick_array_2.to_account_info()
+	];
+	let builder = SparseSwapTickSequenceBuilder::try_from(amm, above_band, tick_arrays, None)?;
+	let mut swap_tick_sequence = builder.build()?;
+
+	// above the band sell exactly up to the cap, below it buy exactly up to the cap. Either
+	// way the swap stops at the oracle price
+	let swap_update = controller::swap::swap(
+		amm,
+		&mut swap_tick_sequence,
+		max_rebalance,
+		oracle_sqrt_price,
+		above_band,
+		above_band,
+		timestamp
+	)?;
+
+	validate!(
+		swap_update.amount_synthetic > 0 && swap_update.amount_quote > 0,
+		ErrorCode::AmmRebalanceLimitReached,
+		"rebalance of market {} filled nothing",
+		market_index
+	)?;
+
+	amm.update_after_swap(
+		swap_update.next_liquidity,
+		swap_update.next_tick_index,
+		swap_update.next_sqrt_price,
+		swap_update.next_fee_growth_global,
+		swap_update.next_reward_infos,
+		swap_update.next_protocol_fee,
+		above_band,
+		timestamp
+	);
+
+	load_mut!(ctx.accounts.amm_observations)?.write(amm.get_observation(timestamp));
+
+	let synthetic_amount = swap_update.amount_synthetic;
+	let collateral_amount = swap_update.amount_quote;
+
+	synth_market.amm_rebalancer.limiter.record_mint(synthetic_amount, now)?;
+
+	let direction = if above_band {
+		// protocol owned synthetic is debt like any other
+		synth_market.record_mint(synthetic_amount, now)?;
+		state.mint_limiter.record_mint(
+			calculate_mint_value(synthetic_amount, synth_market.decimals, oracle_price)?,
+			now
+		)?;
+
+		synth_market.amm_rebalancer.synthetic_minted =
+			synth_market.amm_rebalancer.synthetic_minted.safe_add(synthetic_amount)?;
+		synth_market.amm_rebalancer.collateral_balance =
+			synth_market.amm_rebalancer.collateral_balance.safe_add(collateral_amount)?;
+
+		controller::token::mint_from_program(
+			&ctx.accounts.token_program,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.amm_token_vault_synthetic,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_amount
+		)?;
+
+		// the amm's vaults are owned by the amm, not the normal signer
+		controller::token::send_from_pda_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.amm_token_vault_quote,
+			&ctx.accounts.market_vault,
+			&amm.to_account_info(),
+			&amm.seeds(),
+			collateral_amount
+		)?;
+
+		AmmRebalanceDirection::Expand
+	} else {
+		validate!(
+			collateral_amount <= synth_market.amm_rebalancer.collateral_balance,
+			ErrorCode::AmmRebalanceInsufficientCollateral,
+			"buying back {} synthetic costs {} collateral, protocol owns {}",
+			synthetic_amount,
+			collateral_amount,
+			synth_market.amm_rebalancer.collateral_balance
+		)?;
+
+		synth_market.amm_rebalancer.synthetic_minted =
+			synth_market.amm_rebalancer.synthetic_minted.safe_sub(synthetic_amount)?;
+		synth_market.amm_rebalancer.collateral_balance =
+			synth_market.amm_rebalancer.collateral_balance.safe_sub(collateral_amount)?;
+		synth_market.outstanding_debt = synth_market.outstanding_debt.safe_sub(
+			synthetic_amount.cast()?
+		)?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.amm_token_vault_quote,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			collateral_amount,
+			&None
+		)?;
+
+		// bought back synthetic is moved out of the amm's vault to be burnt by the program
+		controller::token::send_from_pda_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.amm_token_vault_synthetic,
+			&ctx.accounts.token_vault_synthetic,
+			&amm.to_account_info(),
+			&amm.seeds(),
+			synthetic_amount
+		)?;
+
+		controller::token::burn_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_amount
+		)?;
+
+		AmmRebalanceDirection::Contract
+	};
+
+	emit!(AmmRebalanceRecord {
+		ts: now,
+		market_index,
+		keeper: ctx.accounts.keeper.key(),
+		direction,
+		synthetic_amount,
+		collateral_amount,
+		oracle_price,
+		sqrt_price_before,
+		sqrt_price_after: amm.sqrt_price,
+		synthetic_minted_after: synth_market.amm_rebalancer.synthetic_minted,
+		collateral_balance_after: synth_market.amm_rebalancer.collateral_balance,
+	});
+
+	Ok(())
+}
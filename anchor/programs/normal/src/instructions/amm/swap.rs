@@ -5,7 +5,7 @@ use crate::{
 	controller,
 	errors::ErrorCode,
 	manager::swap_manager::*,
-	state::{ synth_market::SynthMarket, AMM },
+	state::{ amm_observation::AMMObservations, synth_market::SynthMarket, AMM },
 	util::{
 		to_timestamp_u64,
 		update_and_swap_amm,
@@ -23,6 +23,9 @@ pub struct Swap<'info> {
 	#[account(mut)]
 	pub amm: Box<Account<'info, AMM>>,
 
+	#[account(mut, seeds = [b"amm_observations", amm.key().as_ref()], bump)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+
 	#[account(mut, constraint = token_owner_account_synthetic.mint == amm.token_mint_synthetic)]
 	pub token_owner_account_synthetic: Box<Account<'info, TokenAccount>>,
 	#[account(mut, address = amm.token_vault_synthetic)]
@@ -114,5 +117,9 @@ pub fn handle_swap(
 		synthetic_to_quote,
 		timestamp,
 		inside_range
-	)
+	)?;
+
+	ctx.accounts.amm_observations.load_mut()?.write(amm.get_observation(timestamp));
+
+	Ok(())
 }
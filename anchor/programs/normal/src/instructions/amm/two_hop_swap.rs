@@ -0,0 +1,288 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ self, Token, TokenAccount };
+
+use crate::{
+	controller,
+	error::ErrorCode,
+	state::{ amm_observation::AMMObservations, AMM },
+	util::{
+		to_timestamp_u64,
+		update_and_swap_amm,
+		SparseSwapTickSequenceBuilder,
+	},
+};
+
+#[derive(Accounts)]
+pub struct TwoHopSwap<'info> {
+	#[account(address = token::ID)]
+	pub token_program: Program<'info, Token>,
+
+	pub token_authority: Signer<'info>,
+
+	#[account(mut)]
+	pub amm_one: Box<Account<'info, AMM>>,
+
+	#[account(mut)]
+	pub amm_two: Box<Account<'info, AMM>>,
+
+	#[account(mut, seeds = [b"amm_observations", amm_one.key().as_ref()], bump)]
+	pub amm_observations_one: AccountLoader<'info, AMMObservations>,
+
+	#[account(mut, seeds = [b"amm_observations", amm_two.key().as_ref()], bump)]
+	pub amm_observations_two: AccountLoader<'info, AMMObservations>,
+
+	#[account(mut, constraint = token_owner_account_one_synthetic.mint == amm_one.token_mint_synthetic)]
+	pub token_owner_account_one_synthetic: Box<Account<'info, TokenAccount>>,
+	#[account(mut, address = amm_one.token_vault_synthetic)]
+	pub token_vault_one_synthetic: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, constraint = token_owner_account_one_quote.mint == amm_one.token_mint_quote)]
+	pub token_owner_account_one_quote: Box<Account<'info, TokenAccount>>,
+	#[account(mut, address = amm_one.token_vault_quote)]
+	pub token_vault_one_quote: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, constraint = token_owner_account_two_synthetic.mint == amm_two.token_mint_synthetic)]
+	pub token_owner_account_two_synthetic: Box<Account<'info, TokenAccount>>,
+	#[account(mut, address = amm_two.token_vault_synthetic)]
+	pub token_vault_two_synthetic: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, constraint = token_owner_account_two_quote.mint == amm_two.token_mint_quote)]
+	pub token_owner_account_two_quote: Box<Account<'info, TokenAccount>>,
+	#[account(mut, address = amm_two.token_vault_quote)]
+	pub token_vault_two_quote: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_one_0: UncheckedAccount<'info>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_one_1: UncheckedAccount<'info>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_one_2: UncheckedAccount<'info>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_two_0: UncheckedAccount<'info>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_two_1: UncheckedAccount<'info>,
+
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_two_2: UncheckedAccount<'info>,
+}
+
+/// Swaps through two AMMs in one instruction, usually one synthetic for another through the
+/// shared quote token. Routes longer than two hops go through multi_hop_swap
+#[allow(clippy::too_many_arguments)]
+pub fn handle_two_hop_swap(
+	ctx: Context<TwoHopSwap>,
+	amount: u64,
+	other_amount_threshold: u64,
+	amount_specified_is_input: bool,
+	synthetic_to_quote_one: bool,
+	synthetic_to_quote_two: bool,
+	sqrt_price_limit_one: u128,
+	sqrt_price_limit_two: u128
+) -> Result<()> {
+	let clock = Clock::get()?;
+	// Update the global reward growth which increases as a function of time.
+	let timestamp = to_timestamp_u64(clock.unix_timestamp)?;
+
+	let amm_one = &mut ctx.accounts.amm_one;
+	let amm_two = &mut ctx.accounts.amm_two;
+
+	// Don't allow swaps on the same amm
+	if amm_one.key() == amm_two.key() {
+		return Err(ErrorCode::DuplicateTwoHopPool.into());
+	}
+
+	// What the first hop pays out is what the second hop takes in. Every synth amm
+	// shares a quote token, so this is usually synthetic -> quote -> synthetic
+	let swap_one_output_mint = if synthetic_to_quote_one {
+		amm_one.token_mint_quote
+	} else {
+		amm_one.token_mint_synthetic
+	};
+
+	let swap_two_input_mint = if synthetic_to_quote_two {
+		amm_two.token_mint_synthetic
+	} else {
+		amm_two.token_mint_quote
+	};
+
+	if swap_one_output_mint != swap_two_input_mint {
+		return Err(ErrorCode::InvalidIntermediaryMint.into());
+	}
+
+	let builder_one = SparseSwapTickSequenceBuilder::try_from(
+		amm_one,
+		synthetic_to_quote_one,
+		vec![
+			ctx.accounts.tick_array_one_0.to_account_info(),
+			ctx.accounts.tick_array_one_1.to_account_info(),
+			ctx.accounts.tick_array_one_2.to_account_info()
+		],
+		None
+	)?;
+	let mut swap_tick_sequence_one = builder_one.build()?;
+
+	let builder_two = SparseSwapTickSequenceBuilder::try_from(
+		amm_two,
+		synthetic_to_quote_two,
+		vec![
+			ctx.accounts.tick_array_two_0.to_account_info(),
+			ctx.accounts.tick_array_two_1.to_account_info(),
+			ctx.accounts.tick_array_two_2.to_account_info()
+		],
+		None
+	)?;
+	let mut swap_tick_sequence_two = builder_two.build()?;
+
+	let (swap_update_one, swap_update_two) = if amount_specified_is_input {
+		// If the amount specified is input, this means we are doing exact-in
+		// and the swap calculations occur from Swap 1 => Swap 2
+		// and the swaps occur from Swap 1 => Swap 2
+		let swap_calc_one = controller::swap::swap(
+			amm_one,
+			&mut swap_tick_sequence_one,
+			amount,
+			sqrt_price_limit_one,
+			amount_specified_is_input, // true
+			synthetic_to_quote_one,
+			timestamp
+		)?;
+
+		// Swap two input is the output of swap one
+		let swap_two_input_amount = if synthetic_to_quote_one {
+			swap_calc_one.amount_quote
+		} else {
+			swap_calc_one.amount_synthetic
+		};
+
+		let swap_calc_two = controller::swap::swap(
+			amm_two,
+			&mut swap_tick_sequence_two,
+			swap_two_input_amount,
+			sqrt_price_limit_two,
+			amount_specified_is_input, // true
+			synthetic_to_quote_two,
+			timestamp
+		)?;
+
+		(swap_calc_one, swap_calc_two)
+	} else {
+		// If the amount specified is output, this means we need to invert the ordering of the calculations
+		// and the swap calculations occur from Swap 2 => Swap 1
+		// but the actual swaps occur from Swap 1 => Swap 2 (to ensure that the intermediate token exists in the account)
+		let swap_calc_two = controller::swap::swap(
+			amm_two,
+			&mut swap_tick_sequence_two,
+			amount,
+			sqrt_price_limit_two,
+			amount_specified_is_input, // false
+			synthetic_to_quote_two,
+			timestamp
+		)?;
+
+		// The output of swap 1 is input of swap_calc_two
+		let swap_one_output_amount = if synthetic_to_quote_two {
+			swap_calc_two.amount_synthetic
+		} else {
+			swap_calc_two.amount_quote
+		};
+
+		let swap_calc_one = controller::swap::swap(
+			amm_one,
+			&mut swap_tick_sequence_one,
+			swap_one_output_amount,
+			sqrt_price_limit_one,
+			amount_specified_is_input, // false
+			synthetic_to_quote_one,
+			timestamp
+		)?;
+
+		(swap_calc_one, swap_calc_two)
+	};
+
+	// All output token should be consumed by the second swap, a partial fill on either hop
+	// would strand the intermediate token in the owner's account
+	let swap_one_output_amount = if synthetic_to_quote_one {
+		swap_update_one.amount_quote
+	} else {
+		swap_update_one.amount_synthetic
+	};
+
+	let swap_two_input_amount = if synthetic_to_quote_two {
+		swap_update_two.amount_synthetic
+	} else {
+		swap_update_two.amount_quote
+	};
+
+	if swap_one_output_amount != swap_two_input_amount {
+		return Err(ErrorCode::IntermediateTokenAmountMismatch.into());
+	}
+
+	// One threshold covers the whole route
+	if amount_specified_is_input {
+		let output_amount = if synthetic_to_quote_two {
+			swap_update_two.amount_quote
+		} else {
+			swap_update_two.amount_synthetic
+		};
+
+		if output_amount < other_amount_threshold {
+			return Err(ErrorCode::AmountOutBelowMinimum.into());
+		}
+	} else {
+		let input_amount = if synthetic_to_quote_one {
+			swap_update_one.amount_synthetic
+		} else {
+			swap_update_one.amount_quote
+		};
+
+		if input_amount > other_amount_threshold {
+			return Err(ErrorCode::AmountInAboveMaximum.into());
+		}
+	}
+
+	let inside_range_one = amm_one.is_price_inside_range(swap_update_one.next_sqrt_price);
+	let inside_range_two = amm_two.is_price_inside_range(swap_update_two.next_sqrt_price);
+
+	update_and_swap_amm(
+		amm_one,
+		&ctx.accounts.token_authority,
+		&ctx.accounts.token_owner_account_one_synthetic,
+		&ctx.accounts.token_owner_account_one_quote,
+		&ctx.accounts.token_vault_one_synthetic,
+		&ctx.accounts.token_vault_one_quote,
+		&ctx.accounts.token_program,
+		swap_update_one,
+		synthetic_to_quote_one,
+		timestamp,
+		inside_range_one
+	)?;
+
+	update_and_swap_amm(
+		amm_two,
+		&ctx.accounts.token_authority,
+		&ctx.accounts.token_owner_account_two_synthetic,
+		&ctx.accounts.token_owner_account_two_quote,
+		&ctx.accounts.token_vault_two_synthetic,
+		&ctx.accounts.token_vault_two_quote,
+		&ctx.accounts.token_program,
+		swap_update_two,
+		synthetic_to_quote_two,
+		timestamp,
+		inside_range_two
+	)?;
+
+	ctx.accounts.amm_observations_one.load_mut()?.write(amm_one.get_observation(timestamp));
+	ctx.accounts.amm_observations_two.load_mut()?.write(amm_two.get_observation(timestamp));
+
+	Ok(())
+}
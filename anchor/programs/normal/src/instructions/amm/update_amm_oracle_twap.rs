@@ -1,14 +1,14 @@
 use anchor_lang::prelude::*;
 
-use crate::instructions::constraints::{ market_valid, valid_oracle_for_amm };
-use crate::{ state::{ self, synth_market::SynthMarket }, State };
+use crate::constants::main::ONE_HOUR;
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::math::stats::calculate_rolling_sum;
+use crate::state::oracle::get_oracle_price;
 
 use super::reset_amm_oracle_twap::RepegCurve;
 
-#[access_control(
-    market_valid(&ctx.accounts.market)
-    valid_oracle_for_amm(&ctx.accounts.oracle, &ctx.accounts.market)
-)]
 pub fn handle_update_amm_oracle_twap(ctx: Context<RepegCurve>) -> Result<()> {
 	// allow update to amm's oracle twap iff price gap is reduced and thus more tame funding
 	// otherwise if oracle error or funding flip: set oracle twap to mark twap (0 gap)
@@ -16,11 +16,41 @@ pub fn handle_update_amm_oracle_twap(ctx: Context<RepegCurve>) -> Result<()> {
 	let clock = Clock::get()?;
 	let now = clock.unix_timestamp;
 
-	let market = &mut load_mut!(ctx.accounts.market)?;
-	msg!("updating amm oracle twap for market {}", market.market_index);
+	let amm = &mut ctx.accounts.amm;
+	msg!("updating amm oracle twap for market {}", amm.market_index);
 	let price_oracle = &ctx.accounts.oracle;
 	let oracle_twap = amm.get_oracle_twap(price_oracle, clock.slot)?;
 
+	// the oracle's std and confidence drive the dynamic swap fee, both decay from the last
+	// update so they are read before the twap timestamp moves
+	let oracle_price_data = get_oracle_price(&amm.oracle_source, price_oracle, clock.slot)?;
+	let since_last = now
+		.safe_sub(amm.historical_oracle_data.last_oracle_price_twap_ts)?
+		.clamp(0, ONE_HOUR);
+
+	let oracle_std = calculate_rolling_sum(
+		amm.oracle_std,
+		oracle_price_data.price
+			.safe_sub(amm.historical_oracle_data.last_oracle_price)?
+			.unsigned_abs(),
+		since_last,
+		ONE_HOUR
+	)?;
+	let last_oracle_conf_pct = amm.get_new_oracle_conf_pct(
+		oracle_price_data.confidence,
+		oracle_price_data.price.unsigned_abs(),
+		now
+	)?;
+
+	msg!("amm.oracle_std {} -> {}", amm.oracle_std, oracle_std);
+	msg!("amm.last_oracle_conf_pct {} -> {}", amm.last_oracle_conf_pct, last_oracle_conf_pct);
+
+	amm.oracle_std = oracle_std;
+	amm.last_oracle_conf_pct = last_oracle_conf_pct;
+	amm.historical_oracle_data.last_oracle_price = oracle_price_data.price;
+	amm.historical_oracle_data.last_oracle_conf = oracle_price_data.confidence;
+	amm.historical_oracle_data.last_oracle_delay = oracle_price_data.delay;
+
 	if let Some(oracle_twap) = oracle_twap {
 		let oracle_mark_gap_before = amm.last_mark_price_twap
 			.cast::<i64>()?
@@ -46,6 +76,8 @@ pub fn handle_update_amm_oracle_twap(ctx: Context<RepegCurve>) -> Result<()> {
 			);
 			amm.historical_oracle_data.last_oracle_price_twap =
 				amm.last_mark_price_twap.cast::<i64>()?;
+			amm.historical_oracle_data.last_oracle_price_twap_5min =
+				amm.last_mark_price_twap.cast::<i64>()?;
 			amm.historical_oracle_data.last_oracle_price_twap_ts = now;
 		} else if
 			oracle_mark_gap_after.unsigned_abs() <=
@@ -62,6 +94,7 @@ pub fn handle_update_amm_oracle_twap(ctx: Context<RepegCurve>) -> Result<()> {
 				now
 			);
 			amm.historical_oracle_data.last_oracle_price_twap = oracle_twap;
+			amm.historical_oracle_data.last_oracle_price_twap_5min = oracle_twap;
 			amm.historical_oracle_data.last_oracle_price_twap_ts = now;
 		} else {
 			return Err(ErrorCode::PriceBandsBreached.into());
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::state::auction::Auction;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct BidCollateralAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(mut)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_collateral
+	)]
+	pub token_vault_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = token_vault_synthetic.mint,
+		token::authority = authority
+	)]
+	pub bidder_token_account_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = token_vault_collateral.mint
+	)]
+	pub bidder_token_account_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_bid_collateral_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, BidCollateralAuction<'info>>,
+	collateral_amount: u64,
+	limit_price: Option<u64>
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+	validate!(
+		auction.initializer != Pubkey::default(),
+		ErrorCode::AuctionNotActive,
+		"auction not initialized"
+	)?;
+
+	let (lot_amount, bid_amount) = controller::auction::bid_collateral_auction(
+		auction,
+		&ctx.accounts.authority.key(),
+		collateral_amount,
+		limit_price,
+		now
+	)?;
+
+	if bid_amount > 0 {
+		controller::token::receive(
+			&ctx.accounts.token_program,
+			&ctx.accounts.bidder_token_account_synthetic,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.authority.to_account_info(),
+			bid_amount,
+			&None
+		)?;
+	}
+
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.token_vault_collateral,
+		&ctx.accounts.bidder_token_account_collateral,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		lot_amount,
+		&None
+	)?;
+
+	Ok(())
+}
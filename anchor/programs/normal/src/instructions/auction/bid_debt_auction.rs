@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::instructions::constraints::*;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::auction::Auction;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+pub struct BidDebtAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(mut)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = token_vault_synthetic.mint,
+		token::authority = authority
+	)]
+	pub bidder_token_account_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	/// Refunded the outbid amount, required once the auction has a bid
+	#[account(
+		mut,
+		token::mint = token_vault_synthetic.mint,
+		token::authority = auction.load()?.highest_bidder
+	)]
+	pub outbid_token_account_synthetic: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_bid_debt_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, BidDebtAuction<'info>>,
+	lot_amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let (outbid, bid_amount) = {
+		let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+		let outbid = controller::auction::bid_debt_auction(
+			auction,
+			&ctx.accounts.authority.key(),
+			lot_amount,
+			now
+		)?;
+
+		(outbid, auction.debt_raised)
+	};
+
+	controller::token::receive(
+		&ctx.accounts.token_program,
+		&ctx.accounts.bidder_token_account_synthetic,
+		&ctx.accounts.token_vault_synthetic,
+		&ctx.accounts.authority.to_account_info(),
+		bid_amount,
+		&None
+	)?;
+
+	// the outbid token account is checked against the previous bidder before the bid lands
+	if outbid.is_some() {
+		let outbid_token_account = ctx.accounts.outbid_token_account_synthetic
+			.as_ref()
+			.safe_unwrap()?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			outbid_token_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			bid_amount,
+			&None
+		)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::instructions::constraints::*;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::auction::Auction;
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+pub struct BidSurplusAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(mut)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		mut,
+		seeds = [
+			b"surplus_auction_bid_vault".as_ref(),
+			auction.load()?.market_index.to_le_bytes().as_ref(),
+		],
+		bump
+	)]
+	pub surplus_auction_bid_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = surplus_auction_bid_vault.mint,
+		token::authority = authority
+	)]
+	pub bidder_gov_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	/// Refunded the outbid amount, required once the auction has a bid
+	#[account(
+		mut,
+		token::mint = surplus_auction_bid_vault.mint,
+		token::authority = auction.load()?.highest_bidder
+	)]
+	pub outbid_gov_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_bid_surplus_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, BidSurplusAuction<'info>>,
+	bid_amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let outbid = {
+		let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+		controller::auction::bid_surplus_auction(
+			auction,
+			&ctx.accounts.authority.key(),
+			bid_amount,
+			now
+		)?
+	};
+
+	controller::token::receive(
+		&ctx.accounts.token_program,
+		&ctx.accounts.bidder_gov_token_account,
+		&ctx.accounts.surplus_auction_bid_vault,
+		&ctx.accounts.authority.to_account_info(),
+		bid_amount,
+		&None
+	)?;
+
+	// the outbid token account is checked against the previous bidder before the bid lands
+	if let Some((_, outbid_amount)) = outbid {
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.surplus_auction_bid_vault,
+			ctx.accounts.outbid_gov_token_account.as_ref().safe_unwrap()?,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			outbid_amount,
+			&None
+		)?;
+	}
+
+	Ok(())
+}
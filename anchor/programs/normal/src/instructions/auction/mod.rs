@@ -0,0 +1,21 @@
+pub mod bid_collateral_auction;
+pub mod bid_debt_auction;
+pub mod bid_surplus_auction;
+pub mod reset_collateral_auction;
+pub mod route_collateral_auction;
+pub mod settle_collateral_auction;
+pub mod settle_debt_auction;
+pub mod settle_surplus_auction;
+pub mod start_debt_auction;
+pub mod start_surplus_auction;
+
+pub use bid_collateral_auction::*;
+pub use bid_debt_auction::*;
+pub use bid_surplus_auction::*;
+pub use reset_collateral_auction::*;
+pub use route_collateral_auction::*;
+pub use settle_collateral_auction::*;
+pub use settle_debt_auction::*;
+pub use settle_surplus_auction::*;
+pub use start_debt_auction::*;
+pub use start_surplus_auction::*;
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::state::auction::Auction;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct ResetCollateralAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(mut)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	pub oracle: AccountInfo<'info>,
+	/// CHECK: checked against the market's collateral oracle
+	pub collateral_oracle: AccountInfo<'info>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_reset_collateral_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, ResetCollateralAuction<'info>>
+) -> Result<()> {
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let market = load!(ctx.accounts.synth_market)?;
+
+	validate!(
+		ctx.accounts.oracle.key() == market.oracle &&
+			ctx.accounts.collateral_oracle.key() == market.collateral_oracle,
+		ErrorCode::InvalidOracle,
+		"oracles do not match market {}",
+		market.market_index
+	)?;
+
+	// the reset start price comes from both oracles, so both must be valid
+	let oracle_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&market.oracle)?,
+		market.market_index,
+		Some(market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+	let collateral_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.collateral_oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&market.collateral_oracle)?,
+		market.market_index,
+		None,
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+	controller::auction::reset_collateral_auction(
+		auction,
+		&market,
+		&ctx.accounts.keeper.key(),
+		collateral_price,
+		oracle_price,
+		now
+	)?;
+
+	Ok(())
+}
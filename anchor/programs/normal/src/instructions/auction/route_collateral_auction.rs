@@ -0,0 +1,300 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::{ AMM_MAX_TWAP_DIVERGENCE, AMM_TWAP_PERIOD };
+use crate::controller::swap_adapter::{ AmmSwapAdapter, CpiSwapAdapter };
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::amm::AMM;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::auction::Auction;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::SynthMarket;
+use crate::util::{ to_timestamp_u64, SparseSwapTickSequenceBuilder };
+use crate::{ controller, load, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct RouteCollateralAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub keeper: Signer<'info>,
+	#[account(mut)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	pub oracle: AccountInfo<'info>,
+	/// CHECK: checked against the market's collateral oracle
+	pub collateral_oracle: AccountInfo<'info>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_collateral
+	)]
+	pub token_vault_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+
+	// Market AMM venue, used when no external swap program is configured
+	#[account(
+		mut,
+		address = synth_market.load()?.amm
+	)]
+	pub amm: Option<Box<Account<'info, AMM>>>,
+	#[account(mut)]
+	pub amm_observations: Option<AccountLoader<'info, AMMObservations>>,
+	#[account(mut)]
+	pub amm_token_vault_synthetic: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(mut)]
+	pub amm_token_vault_quote: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_0: Option<UncheckedAccount<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_1: Option<UncheckedAccount<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_2: Option<UncheckedAccount<'info>>,
+
+	// External venue, its accounts are passed as remaining accounts
+	#[account(
+		executable,
+		address = synth_market.load()?.collateral_action_config.external_swap_program
+	)]
+	/// CHECK: checked against the market's auction config
+	pub external_swap_program: Option<AccountInfo<'info>>,
+	#[account(
+		seeds = [b"auction_signer".as_ref(), auction.key().as_ref()],
+		bump
+	)]
+	/// CHECK: pda that owns only the auction vaults, the one authority handed to the venue
+	pub auction_signer: Option<AccountInfo<'info>>,
+	#[account(
+		init,
+		seeds = [b"auction_vault_collateral".as_ref(), auction.key().as_ref()],
+		bump,
+		payer = keeper,
+		token::mint = collateral_mint,
+		token::authority = auction_signer
+	)]
+	pub auction_vault_collateral: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(
+		init,
+		seeds = [b"auction_vault_synthetic".as_ref(), auction.key().as_ref()],
+		bump,
+		payer = keeper,
+		token::mint = synthetic_mint,
+		token::authority = auction_signer
+	)]
+	pub auction_vault_synthetic: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(address = token_vault_collateral.mint)]
+	pub collateral_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	#[account(address = token_vault_synthetic.mint)]
+	pub synthetic_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	pub system_program: Option<Program<'info, System>>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_route_collateral_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RouteCollateralAuction<'info>>,
+	swap_data: Vec<u8>
+) -> Result<()> {
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let market = load!(ctx.accounts.synth_market)?;
+
+	validate!(
+		ctx.accounts.oracle.key() == market.oracle &&
+			ctx.accounts.collateral_oracle.key() == market.collateral_oracle,
+		ErrorCode::InvalidOracle,
+		"oracles do not match market {}",
+		market.market_index
+	)?;
+
+	// the min price the venue must fill at comes from both oracles, so both must be valid
+	let oracle_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&market.oracle)?,
+		market.market_index,
+		Some(market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+	let collateral_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.collateral_oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&market.collateral_oracle)?,
+		market.market_index,
+		None,
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	let auction_key = ctx.accounts.auction.key();
+	let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+	if market.collateral_action_config.external_swap_program != Pubkey::default() {
+		let swap_program = ctx.accounts.external_swap_program.as_ref().safe_unwrap()?;
+		let venue_key = swap_program.key();
+
+		let auction_signer = ctx.accounts.auction_signer.as_ref().safe_unwrap()?;
+		let auction_signer_bump = [ctx.bumps.auction_signer.safe_unwrap()?];
+		let auction_signer_seeds: [&[u8]; 3] = [
+			b"auction_signer".as_ref(),
+			auction_key.as_ref(),
+			&auction_signer_bump,
+		];
+
+		let auction_vault_collateral = ctx.accounts.auction_vault_collateral
+			.as_mut()
+			.safe_unwrap()?;
+		let auction_vault_synthetic = ctx.accounts.auction_vault_synthetic
+			.as_mut()
+			.safe_unwrap()?;
+
+		{
+			let mut adapter = CpiSwapAdapter {
+				swap_program,
+				accounts: ctx.remaining_accounts,
+				data: swap_data,
+				token_program: &ctx.accounts.token_program,
+				token_vault_collateral: &ctx.accounts.token_vault_collateral,
+				token_vault_synthetic: &ctx.accounts.token_vault_synthetic,
+				normal_signer: &ctx.accounts.normal_signer,
+				signer_nonce: state.signer_nonce,
+				auction_vault_collateral: &mut **auction_vault_collateral,
+				auction_vault_synthetic: &mut **auction_vault_synthetic,
+				auction_signer,
+				auction_signer_seeds: &auction_signer_seeds,
+			};
+
+			controller::liquidation::route_collateral_auction_externally(
+				&mut adapter,
+				auction,
+				&market,
+				&venue_key,
+				collateral_price,
+				oracle_price,
+				now
+			)?;
+		}
+
+		// the auction vaults only live for the route, the keeper gets the rent back
+		let keeper = ctx.accounts.keeper.to_account_info();
+		for vault in [&**auction_vault_collateral, &**auction_vault_synthetic] {
+			controller::token::close_pda_vault(
+				&ctx.accounts.token_program,
+				vault,
+				&keeper,
+				auction_signer,
+				&auction_signer_seeds
+			)?;
+		}
+
+		return Ok(());
+	}
+
+	let amm = ctx.accounts.amm.as_mut().safe_unwrap()?;
+	let amm_key = amm.key();
+	let amm_token_vault_synthetic = ctx.accounts.amm_token_vault_synthetic
+		.as_ref()
+		.safe_unwrap()?;
+	let amm_token_vault_quote = ctx.accounts.amm_token_vault_quote.as_ref().safe_unwrap()?;
+	let amm_observations = ctx.accounts.amm_observations.as_ref().safe_unwrap()?;
+
+	let (amm_observations_key, _) = Pubkey::find_program_address(
+		&[b"amm_observations", amm_key.as_ref()],
+		&crate::ID
+	);
+
+	validate!(
+		amm_token_vault_synthetic.key() == amm.token_vault_synthetic &&
+			amm_token_vault_quote.key() == amm.token_vault_quote &&
+			amm_observations.key() == amm_observations_key &&
+			amm.token_mint_quote == market.token_mint_collateral,
+		ErrorCode::InvalidAuctionType,
+		"amm vaults dont match market {}",
+		market.market_index
+	)?;
+
+	let tick_arrays = [
+		&ctx.accounts.tick_array_0,
+		&ctx.accounts.tick_array_1,
+		&ctx.accounts.tick_array_2,
+	]
+		.iter()
+		.filter_map(|tick_array| tick_array.as_ref().map(|a| a.to_account_info()))
+		.collect();
+
+	// the pool's own twap keeps a keeper from pushing the pool off its price just before routing
+	load!(amm_observations)?.validate_price_against_twap(
+		amm,
+		to_timestamp_u64(now)?,
+		AMM_TWAP_PERIOD,
+		AMM_MAX_TWAP_DIVERGENCE
+	)?;
+
+	let builder = SparseSwapTickSequenceBuilder::try_from(amm, false, tick_arrays, None)?;
+
+	let fill = {
+		let mut adapter = AmmSwapAdapter {
+			amm,
+			swap_tick_sequence: builder.build()?,
+			timestamp: to_timestamp_u64(now)?,
+		};
+
+		controller::liquidation::route_collateral_auction_externally(
+			&mut adapter,
+			auction,
+			&market,
+			&amm_key,
+			collateral_price,
+			oracle_price,
+			now
+		)?
+	};
+
+	if let Some(fill) = fill {
+		load_mut!(amm_observations)?.write(amm.get_observation(to_timestamp_u64(now)?));
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_collateral,
+			amm_token_vault_quote,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			fill.collateral_sold,
+			&None
+		)?;
+
+		// the amm's vaults are owned by the amm, not the normal signer
+		controller::token::send_from_pda_vault(
+			&ctx.accounts.token_program,
+			amm_token_vault_synthetic,
+			&ctx.accounts.token_vault_synthetic,
+			&amm.to_account_info(),
+			&amm.seeds(),
+			fill.synthetic_received
+		)?;
+	}
+
+	Ok(())
+}
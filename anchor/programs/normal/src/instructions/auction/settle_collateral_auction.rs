@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::instructions::constraints::*;
+use crate::state::auction::Auction;
+use crate::state::synth_market::SynthMarket;
+use crate::state::user::User;
+use crate::{ controller, load_mut, state::*, State };
+
+#[derive(Accounts)]
+pub struct SettleCollateralAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(
+		mut,
+		close = initializer
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	/// The liquidator, refunded the auction's rent
+	#[account(
+		mut,
+		address = auction.load()?.initializer
+	)]
+	pub initializer: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		address = auction.load()?.user
+	)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.mint
+	)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.vault
+	)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_collateral
+	)]
+	pub token_vault_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	/// Receives the liquidator fee
+	#[account(
+		mut,
+		token::mint = token_vault_collateral.mint,
+		token::authority = initializer.load()?.authority
+	)]
+	pub liquidator_token_account_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		seeds = [
+			b"insurance_fund_vault".as_ref(),
+			auction.load()?.market_index.to_le_bytes().as_ref(),
+		],
+		bump
+	)]
+	pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_settle_collateral_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, SettleCollateralAuction<'info>>
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let (synthetic_to_burn, liquidator_fee, if_fee, collateral_to_return) = {
+		let auction = &mut load_mut!(ctx.accounts.auction)?;
+		let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+		let (synthetic_to_burn, liquidator_fee, if_fee, collateral_to_return) =
+			controller::auction::settle_collateral_auction(
+				auction,
+				market,
+				&ctx.accounts.keeper.key(),
+				now
+			)?;
+
+		if collateral_to_return > 0 {
+			let user = &mut load_mut!(ctx.accounts.user)?;
+			let position_index = user.force_get_position_index(market.market_index)?;
+
+			controller::synth_position::update_synth_balances_and_cumulative_deposits(
+				collateral_to_return as u128,
+				&SpotBalanceType::Deposit,
+				market,
+				&mut user.positions[position_index],
+				false,
+				None
+			)?;
+		}
+
+		(synthetic_to_burn, liquidator_fee, if_fee, collateral_to_return)
+	};
+
+	// synthetic raised by the auction repays the vault's debt, so it leaves circulation
+	if synthetic_to_burn > 0 {
+		controller::token::burn_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_to_burn
+		)?;
+	}
+
+	// the liquidation fees come out of the lot left once the debt is covered
+	for (recipient, amount) in [
+		(&ctx.accounts.liquidator_token_account_collateral, liquidator_fee),
+		(&ctx.accounts.insurance_fund_vault, if_fee),
+	] {
+		if amount > 0 {
+			controller::token::send_from_program_vault(
+				&ctx.accounts.token_program,
+				&ctx.accounts.token_vault_collateral,
+				recipient,
+				&ctx.accounts.normal_signer,
+				state.signer_nonce,
+				amount,
+				&None
+			)?;
+		}
+	}
+
+	if collateral_to_return > 0 {
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_collateral,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			collateral_to_return,
+			&None
+		)?;
+	}
+
+	Ok(())
+}
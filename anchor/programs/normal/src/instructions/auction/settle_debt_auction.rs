@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::auction::Auction;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct SettleDebtAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(
+		mut,
+		close = initializer
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	/// CHECK: refunded the auction's rent
+	#[account(
+		mut,
+		address = auction.load()?.initializer
+	)]
+	pub initializer: AccountInfo<'info>,
+	#[account(
+		mut,
+		constraint = synth_market.load()?.market_index == auction.load()?.market_index
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.mint
+	)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = state.gov_token_mint
+	)]
+	pub gov_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	/// Receives the lot, required if the auction had a bid
+	#[account(
+		mut,
+		token::mint = gov_token_mint,
+		token::authority = auction.load()?.highest_bidder
+	)]
+	pub winner_gov_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_settle_debt_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, SettleDebtAuction<'info>>
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let (gov_tokens_to_mint, synthetic_to_burn) = {
+		let auction = &mut load_mut!(ctx.accounts.auction)?;
+		let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+		controller::auction::settle_debt_auction(
+			auction,
+			market,
+			&ctx.accounts.keeper.key(),
+			now
+		)?
+	};
+
+	if gov_tokens_to_mint == 0 {
+		return Ok(());
+	}
+
+	validate!(
+		ctx.accounts.winner_gov_token_account.is_some(),
+		ErrorCode::AuctionNotComplete,
+		"winner gov token account required to settle"
+	)?;
+
+	controller::token::burn_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.token_vault_synthetic,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		synthetic_to_burn
+	)?;
+
+	controller::token::mint_from_program(
+		&ctx.accounts.token_program,
+		&ctx.accounts.gov_token_mint,
+		ctx.accounts.winner_gov_token_account.as_ref().safe_unwrap()?,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		gov_tokens_to_mint
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::instructions::constraints::*;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::auction::Auction;
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+pub struct SettleSurplusAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(
+		mut,
+		close = initializer
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	/// CHECK: refunded the auction's rent
+	#[account(
+		mut,
+		address = auction.load()?.initializer
+	)]
+	pub initializer: AccountInfo<'info>,
+	#[account(
+		mut,
+		seeds = [
+			b"insurance_fund_vault".as_ref(),
+			auction.load()?.market_index.to_le_bytes().as_ref(),
+		],
+		bump
+	)]
+	pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		seeds = [
+			b"surplus_auction_vault".as_ref(),
+			auction.load()?.market_index.to_le_bytes().as_ref(),
+		],
+		bump
+	)]
+	pub surplus_auction_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		seeds = [
+			b"surplus_auction_bid_vault".as_ref(),
+			auction.load()?.market_index.to_le_bytes().as_ref(),
+		],
+		bump
+	)]
+	pub surplus_auction_bid_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = state.gov_token_mint
+	)]
+	pub gov_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	/// Receives the lot, required if the auction had a bid
+	#[account(
+		mut,
+		token::mint = surplus_auction_vault.mint,
+		token::authority = auction.load()?.highest_bidder
+	)]
+	pub winner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_settle_surplus_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, SettleSurplusAuction<'info>>
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let (lot_amount, gov_tokens_to_burn) = {
+		let auction = &mut load_mut!(ctx.accounts.auction)?;
+
+		controller::auction::settle_surplus_auction(
+			auction,
+			&ctx.accounts.keeper.key(),
+			now
+		)?
+	};
+
+	if gov_tokens_to_burn > 0 {
+		controller::token::burn_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.surplus_auction_bid_vault,
+			&ctx.accounts.gov_token_mint,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			gov_tokens_to_burn
+		)?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.surplus_auction_vault,
+			ctx.accounts.winner_token_account.as_ref().safe_unwrap()?,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			lot_amount,
+			&None
+		)?;
+	} else if lot_amount > 0 {
+		// no bids, the surplus goes back to the insurance fund
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.surplus_auction_vault,
+			&ctx.accounts.insurance_fund_vault,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			lot_amount,
+			&None
+		)?;
+	}
+
+	controller::token::close_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.surplus_auction_vault,
+		&ctx.accounts.initializer,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
+	)?;
+
+	controller::token::close_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.surplus_auction_bid_vault,
+		&ctx.accounts.initializer,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::constraints::*;
+use crate::state::auction::Auction;
+use crate::state::synth_market::SynthMarket;
+use crate::state::traits::Size;
+use crate::{ controller, load, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct StartDebtAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub keeper: Signer<'info>,
+	#[account(
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		init,
+		seeds = [b"debt_auction", market_index.to_le_bytes().as_ref()],
+		space = Auction::SIZE,
+		bump,
+		payer = keeper
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	pub system_program: Program<'info, System>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_start_debt_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, StartDebtAuction<'info>>,
+	_market_index: u16
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	let market = load!(ctx.accounts.synth_market)?;
+	let auction_key = ctx.accounts.auction.key();
+	let auction = &mut ctx.accounts.auction.load_init()?;
+
+	controller::auction::start_debt_auction(
+		auction,
+		&auction_key,
+		&market,
+		&state.debt_auction_config,
+		&ctx.accounts.keeper.key(),
+		now
+	)?;
+
+	Ok(())
+}
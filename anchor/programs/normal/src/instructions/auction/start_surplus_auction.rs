@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::instructions::constraints::*;
+use crate::math::auction::calculate_insurance_fund_surplus;
+use crate::state::auction::Auction;
+use crate::state::insurance::InsuranceFund;
+use crate::state::traits::Size;
+use crate::{ controller, load, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct StartSurplusAuction<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub keeper: Signer<'info>,
+	#[account(address = state.insurance_fund)]
+	pub insurance_fund: AccountLoader<'info, InsuranceFund>,
+	#[account(
+		mut,
+		seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		init,
+		seeds = [b"surplus_auction", market_index.to_le_bytes().as_ref()],
+		space = Auction::SIZE,
+		bump,
+		payer = keeper
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(
+		init,
+		seeds = [b"surplus_auction_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+		bump,
+		payer = keeper,
+		token::mint = insurance_fund_vault_mint,
+		token::authority = normal_signer
+	)]
+	pub surplus_auction_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		init,
+		seeds = [b"surplus_auction_bid_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+		bump,
+		payer = keeper,
+		token::mint = gov_token_mint,
+		token::authority = normal_signer
+	)]
+	pub surplus_auction_bid_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(address = insurance_fund_vault.mint)]
+	pub insurance_fund_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(address = state.gov_token_mint)]
+	pub gov_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub system_program: Program<'info, System>,
+}
+
+#[access_control(liq_not_paused(&ctx.accounts.state))]
+pub fn handle_start_surplus_auction<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, StartSurplusAuction<'info>>,
+	market_index: u16
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+
+	// the insurance fund's own cap, in the vault's token. The market's quote_max_insurance caps
+	// what the market may draw from the fund, not what the fund holds
+	let surplus = calculate_insurance_fund_surplus(
+		ctx.accounts.insurance_fund_vault.amount,
+		load!(ctx.accounts.insurance_fund)?.max_insurance
+	);
+
+	let auction_key = ctx.accounts.auction.key();
+	let lot_amount = {
+		let auction = &mut ctx.accounts.auction.load_init()?;
+
+		controller::auction::start_surplus_auction(
+			auction,
+			&auction_key,
+			market_index,
+			&state.surplus_auction_config,
+			&ctx.accounts.keeper.key(),
+			surplus,
+			now
+		)?;
+
+		auction.lot_amount
+	};
+
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.insurance_fund_vault,
+		&ctx.accounts.surplus_auction_vault,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		lot_amount,
+		&None
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::instructions::optional_accounts::get_index_basket_accounts;
+use crate::math::index::calculate_redemption_amount;
+use crate::math::safe_math::SafeMath;
+use crate::state::index_market::{ FeeAccrualMethod, IndexMarket };
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct ClaimIndexManagerFees<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(mut, address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = authority
+	)]
+	pub authority_index_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays the signer's owed manager and/or referral fees. Dilution markets mint the owed index
+/// tokens; NavDeduction markets pay the owed shares out of the basket, so remaining accounts
+/// are a (recipient token account, basket vault) pair per asset in asset order.
+pub fn handle_claim_index_manager_fees<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, ClaimIndexManagerFees<'info>>,
+	_market_index: u16
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let now = Clock::get()?.unix_timestamp;
+
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
+
+	// value the basket before the claimed shares leave total shares
+	let total_shares = index_market.get_total_shares(index_token_supply)?;
+
+	let (manager_fee, referral_fee) = controller::index::claim_index_fees(
+		index_market,
+		&ctx.accounts.authority.key(),
+		now
+	)?;
+
+	let claim_amount = manager_fee.safe_add(referral_fee)?;
+
+	if claim_amount == 0 {
+		return Ok(());
+	}
+
+	match index_market.fee_accrual_method {
+		FeeAccrualMethod::Dilution => {
+			controller::token::mint_from_program(
+				&ctx.accounts.token_program,
+				&ctx.accounts.index_token_mint,
+				&ctx.accounts.authority_index_token_account,
+				&ctx.accounts.normal_signer,
+				state.signer_nonce,
+				claim_amount
+			)?;
+		}
+		FeeAccrualMethod::NavDeduction => {
+			let basket_accounts = get_index_basket_accounts(
+				&mut ctx.remaining_accounts.iter().peekable(),
+				&index_market.assets
+			)?;
+
+			for (recipient_token_account, vault) in basket_accounts.iter() {
+				let amount = calculate_redemption_amount(
+					vault.amount,
+					claim_amount,
+					total_shares
+				)?;
+
+				if amount == 0 {
+					continue;
+				}
+
+				controller::token::send_from_program_vault(
+					&ctx.accounts.token_program,
+					vault,
+					recipient_token_account,
+					&ctx.accounts.normal_signer,
+					state.signer_nonce,
+					amount,
+					&None
+				)?;
+			}
+		}
+	}
+
+	Ok(())
+}
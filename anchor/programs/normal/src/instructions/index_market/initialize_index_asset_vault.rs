@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::state::index_market::IndexMarket;
+use crate::state::synth_market::SynthMarket;
+use crate::{ load_mut, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16, asset_market_index: u16)]
+pub struct InitializeIndexAssetVault<'info> {
+	#[account(mut)]
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(
+		seeds = [b"market", asset_market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(address = synth_market.load()?.mint)]
+	pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		init,
+		seeds = [
+			b"index_asset_vault".as_ref(),
+			market_index.to_le_bytes().as_ref(),
+			asset_market_index.to_le_bytes().as_ref(),
+		],
+		bump,
+		payer = admin,
+		token::mint = asset_mint,
+		token::authority = normal_signer
+	)]
+	pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub rent: Sysvar<'info, Rent>,
+	pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_index_asset_vault(
+	ctx: Context<InitializeIndexAssetVault>,
+	_market_index: u16,
+	asset_market_index: u16
+) -> Result<()> {
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+	let market_index = index_market.market_index;
+
+	let asset = index_market.assets
+		.iter_mut()
+		.find(|asset| asset.market_index == asset_market_index)
+		.ok_or(ErrorCode::IndexAssetNotFound)?;
+
+	msg!(
+		"index market {} asset {} vault: {}",
+		market_index,
+		asset_market_index,
+		ctx.accounts.asset_vault.key()
+	);
+
+	asset.vault = ctx.accounts.asset_vault.key();
+
+	Ok(())
+}
@@ -1,12 +1,22 @@
 use amm::AMM;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{ self, Mint, Token, TokenAccount };
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHook;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+	BaseStateWithExtensions,
+	StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintInner;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface;
 
-use index_market::{ IndexAsset, IndexMarket, IndexVisibility };
+use index_market::{ IndexAsset, IndexMarket, IndexVisibility, WeightingMethod };
 use synth_market::{ AuctionConfig, AuctionPreference, Market };
 use oracle_map::OracleMap;
 
-use crate::{ state::*, validation::margin::validate_margin, State };
+use crate::constants::main::DEFAULT_INDEX_REBALANCE_INTERVAL;
+use crate::error::ErrorCode;
+use crate::{ state::*, validate, validation::margin::validate_margin, State };
 
 #[derive(Accounts)]
 pub struct InitializeIndexMarket<'info> {
@@ -49,6 +59,12 @@ pub struct InitializeIndexMarket<'info> {
 
 	/// CHECK: checked in `initialize_market`
 	pub oracle: AccountInfo<'info>,
+	#[account(mint::token_program = index_token_program)]
+	pub index_token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub index_token_program: Program<'info, Token2022>,
 	#[account(address = token::ID)]
 	pub token_program: Program<'info, Token>,
 	pub rent: Sysvar<'info, Rent>,
@@ -68,6 +84,7 @@ pub fn handle_initialize_index_market(
 	// Index
 	visibility: IndexVisibility,
 	assets: Vec<IndexAsset>,
+	weighting_method: WeightingMethod,
 	manager_fee: u16,
 
 	// Insurance
@@ -83,6 +100,11 @@ pub fn handle_initialize_index_market(
 
 	OracleMap::validate_oracle_account_info(&ctx.accounts.oracle)?;
 
+	validate_index_token_mint(
+		&ctx.accounts.index_token_mint.to_account_info(),
+		&ctx.accounts.normal_signer.key()
+	)?;
+
 	// Verify oracle is readable
 	let (oracle_price, oracle_delay, last_oracle_price_twap) = match
 		oracle_source
@@ -201,6 +223,15 @@ pub fn handle_initialize_index_market(
 				..
 			} = get_sb_on_demand_price(&ctx.accounts.oracle, clock_slot)?;
 
+			(oracle_price, oracle_delay, oracle_price)
+		}
+		OracleSource::IndexNav => {
+			let OraclePriceData {
+				price: oracle_price,
+				delay: oracle_delay,
+				..
+			} = get_index_nav_price(&ctx.accounts.oracle, clock_slot)?;
+
 			(oracle_price, oracle_delay, oracle_price)
 		}
 	};
@@ -214,6 +245,16 @@ pub fn handle_initialize_index_market(
 		state.number_of_markets
 	)?;
 
+	// basket vaults are created per asset by initialize_index_asset_vault
+	let assets = assets
+		.into_iter()
+		.map(|asset| IndexAsset {
+			vault: Pubkey::default(),
+			last_updated_ts: now,
+			..asset
+		})
+		.collect();
+
 	**index_market = IndexMarket {
 		pubkey: *index_market_pubkey,
 		market_index,
@@ -232,11 +273,15 @@ pub fn handle_initialize_index_market(
 
 		// Accounts
 		vault: ctx.accounts.vault.key(),
-		token_mint: ctx.accounts.token_mint.key(),
+		token_mint: ctx.accounts.index_token_mint.key(),
 
 		// Index
 		visibility,
 		assets,
+		weighting_method,
+		rebalance_interval: DEFAULT_INDEX_REBALANCE_INTERVAL,
+		min_rebalance_ts: now,
+		fees_accrued_ts: now,
 		manager_fee,
 
 		// Metrics
@@ -245,10 +290,41 @@ pub fn handle_initialize_index_market(
 		expiry_price: 0,
 		expiry_ts: 0,
 
-		padding: [0; 43],
+		padding: [0; 9],
 	};
 
 	safe_increment!(state.number_of_index_markets, 1);
 
 	Ok(())
 }
+
+/// Index tokens only move through token-2022 transfers that call back into this program to
+/// enforce the market's whitelist and blacklist, and only the program can mint them
+fn validate_index_token_mint(index_token_mint: &AccountInfo, normal_signer: &Pubkey) -> Result<()> {
+	let mint_data = index_token_mint.try_borrow_data()?;
+	let mint = StateWithExtensions::<MintInner>::unpack(&mint_data)?;
+
+	let transfer_hook_program_id = mint
+		.get_extension::<TransferHook>()
+		.ok()
+		.and_then(|transfer_hook| Option::<Pubkey>::from(transfer_hook.program_id));
+
+	validate!(
+		transfer_hook_program_id == Some(crate::ID),
+		ErrorCode::InvalidIndexTokenMint,
+		"index token mint {} transfer hook is {:?}, expected {}",
+		index_token_mint.key(),
+		transfer_hook_program_id,
+		crate::ID
+	)?;
+
+	validate!(
+		Option::<Pubkey>::from(mint.base.mint_authority) == Some(*normal_signer) &&
+			mint.base.supply == 0,
+		ErrorCode::InvalidIndexTokenMint,
+		"index token mint {} must be unminted with the program as mint authority",
+		index_token_mint.key()
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::state::index_market::IndexMarket;
+use crate::state::index_oracle::IndexOracle;
+use crate::state::oracle::OracleSource;
+use crate::state::traits::Size;
+use crate::{ load_mut, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeIndexOracle<'info> {
+	#[account(mut)]
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(
+		init,
+		seeds = [b"index_oracle", market_index.to_le_bytes().as_ref()],
+		space = IndexOracle::SIZE,
+		bump,
+		payer = admin
+	)]
+	pub index_oracle: AccountLoader<'info, IndexOracle>,
+	pub rent: Sysvar<'info, Rent>,
+	pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_index_oracle(
+	ctx: Context<InitializeIndexOracle>,
+	market_index: u16
+) -> Result<()> {
+	let index_oracle_key = ctx.accounts.index_oracle.key();
+	let index_oracle = &mut ctx.accounts.index_oracle.load_init()?;
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	**index_oracle = IndexOracle {
+		pubkey: index_oracle_key,
+		index_market: ctx.accounts.index_market.key(),
+		market_index,
+		..IndexOracle::default()
+	};
+
+	msg!(
+		"index market {} oracle: {} -> {}",
+		market_index,
+		index_market.oracle,
+		index_oracle_key
+	);
+
+	index_market.oracle = index_oracle_key;
+	index_market.oracle_source = OracleSource::IndexNav;
+
+	Ok(())
+}
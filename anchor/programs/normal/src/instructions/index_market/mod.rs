@@ -1,14 +1,22 @@
 use crate::{ state::index_market::IndexMarket, State };
 
 pub mod initialize_index_market;
+pub mod initialize_index_asset_vault;
+pub mod initialize_index_oracle;
 pub mod rebalance_index_market;
 pub mod update_index_market_visibility;
 pub mod update_index_market_whitelist;
+pub mod update_index_oracle;
 pub mod collect_index_market_protocol_fees;
+pub mod claim_index_manager_fees;
+pub mod settle_index_market_fees_to_treasury;
 pub mod update_index_market_expiry;
 pub mod update_index_market_expense_ratio;
 pub mod update_index_market_revenue_share;
+pub mod update_index_market_fee_accrual_method;
+pub mod update_index_market_referrer;
 pub mod update_index_market_paused_operations;
+pub mod update_index_market_rebalance_interval;
 pub mod delete_initialized_index_market;
 pub mod token;
 
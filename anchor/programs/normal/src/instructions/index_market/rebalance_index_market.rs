@@ -1,40 +1,46 @@
 use anchor_lang::prelude::*;
 
-use crate::{ load_mut, state::index_market::{ IndexMarket, UpdateWeight } };
+use crate::instructions::optional_accounts::{ load_maps, AccountMaps };
+use crate::state::index_market::IndexMarket;
+use crate::state::synth_market_map::MarketSet;
+use crate::{ controller, load_mut, State };
 
-use super::UpdateIndexMarket;
+#[derive(Accounts)]
+pub struct RebalanceIndexMarket<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		constraint = index_market.load()?.rebalance_authority.eq(&authority.key())
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+}
 
-pub fn handle_rebalance_index_market(
-	ctx: Context<UpdateIndexMarket>,
-	updates: Vec<UpdateWeight>
+pub fn handle_rebalance_index_market<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RebalanceIndexMarket<'info>>,
+	custom_weights: Vec<u16>
 ) -> Result<()> {
+	let state = &ctx.accounts.state;
 	let clock = Clock::get()?;
 	let now = clock.unix_timestamp;
 
 	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
 
-	for operation in operations.iter() {
-		if let Some(new_weight) = operation.new_weight {
-			// Update the weight
-			if
-				let Some(asset) = index_fund.assets
-					.iter_mut()
-					.find(|a| a.market_index == operation.market_index)
-			{
-				asset.weight = new_weight;
-				asset.last_updated_ts = Clock::get()?.unix_timestamp;
-			} else {
-				return Err(error!(CustomError::AssetNotFound));
-			}
-		} else {
-			// Remove the asset
-			index_fund.assets.retain(
-				|asset| asset.market_index != operation.market_index
-			);
-		}
-	}
+	let AccountMaps { synth_market_map, mut oracle_map, .. } = load_maps(
+		&mut ctx.remaining_accounts.iter().peekable(),
+		&MarketSet::new(),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
 
-	index_fund.last_updated_ts = now;
+	controller::index::rebalance(
+		index_market,
+		&synth_market_map,
+		&mut oracle_map,
+		&custom_weights,
+		now
+	)?;
 
 	Ok(())
 }
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::optional_accounts::get_index_basket_accounts;
+use crate::math::index::calculate_redemption_amount;
+use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::index_market::{ FeeAccrualMethod, IndexMarket };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct SettleIndexMarketFeesToTreasury<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(mut, address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = state.admin
+	)]
+	pub treasury_index_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub index_token_program: Interface<'info, TokenInterface>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays the protocol's owed index fees to the treasury, the admin's token accounts. Dilution
+/// markets mint the owed index tokens into `treasury_index_token_account`; NavDeduction markets
+/// pay the owed shares out of the basket, so remaining accounts are a (treasury token account,
+/// basket vault) pair per asset in asset order.
+pub fn handle_settle_index_market_fees_to_treasury<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, SettleIndexMarketFeesToTreasury<'info>>,
+	_market_index: u16
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let now = Clock::get()?.unix_timestamp;
+
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
+
+	// value the basket before the settled shares leave total shares
+	let total_shares = index_market.get_total_shares(index_token_supply)?;
+
+	let protocol_fee = controller::index::settle_index_protocol_fees(
+		index_market,
+		&state.admin,
+		now
+	)?;
+
+	if protocol_fee == 0 {
+		return Ok(());
+	}
+
+	match index_market.fee_accrual_method {
+		FeeAccrualMethod::Dilution => {
+			let treasury_index_token_account = ctx.accounts.treasury_index_token_account
+				.as_ref()
+				.safe_unwrap()?;
+
+			controller::token::mint_from_program(
+				&ctx.accounts.index_token_program,
+				&ctx.accounts.index_token_mint,
+				treasury_index_token_account,
+				&ctx.accounts.normal_signer,
+				state.signer_nonce,
+				protocol_fee
+			)?;
+		}
+		FeeAccrualMethod::NavDeduction => {
+			let basket_accounts = get_index_basket_accounts(
+				&mut ctx.remaining_accounts.iter().peekable(),
+				&index_market.assets
+			)?;
+
+			for (treasury_token_account, vault) in basket_accounts.iter() {
+				validate!(
+					treasury_token_account.owner == state.admin,
+					ErrorCode::InvalidIndexFeeRecipient,
+					"token account {} isn't the treasury's",
+					treasury_token_account.key()
+				)?;
+
+				let amount = calculate_redemption_amount(vault.amount, protocol_fee, total_shares)?;
+
+				if amount == 0 {
+					continue;
+				}
+
+				controller::token::send_from_program_vault(
+					&ctx.accounts.token_program,
+					vault,
+					treasury_token_account,
+					&ctx.accounts.normal_signer,
+					state.signer_nonce,
+					amount,
+					&None
+				)?;
+			}
+		}
+	}
+
+	Ok(())
+}
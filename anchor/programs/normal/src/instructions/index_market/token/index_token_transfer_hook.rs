@@ -1,4 +1,15 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHookAccount;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+	BaseStateWithExtensions,
+	StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Account as TokenAccountInner;
+use anchor_spl::token_interface::{ Mint, TokenAccount };
+
+use crate::error::ErrorCode;
+use crate::state::index_market::IndexMarket;
+use crate::{ load, validate };
 
 // Order of accounts matters for this struct.
 // The first 4 accounts are the accounts required for token transfer (source, mint, destination, owner)
@@ -16,49 +27,54 @@ pub struct IndexTokenTransferHook<'info> {
 	/// CHECK: ExtraAccountMetaList Account,
 	#[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
 	pub extra_account_meta_list: UncheckedAccount<'info>,
-	pub wsol_mint: InterfaceAccount<'info, Mint>,
-	pub token_program: Interface<'info, TokenInterface>,
-	pub associated_token_program: Program<'info, AssociatedToken>,
-	#[account(
-        mut,
-        seeds = [b"delegate"], 
-        bump
-    )]
-	pub delegate: SystemAccount<'info>,
-	#[account(
-        mut,
-        token::mint = wsol_mint, 
-        token::authority = delegate,
-    )]
-	pub delegate_wsol_token_account: InterfaceAccount<'info, TokenAccount>,
-	#[account(
-        mut,
-        token::mint = wsol_mint, 
-        token::authority = owner,
-    )]
-	pub sender_wsol_token_account: InterfaceAccount<'info, TokenAccount>,
+	#[account(constraint = index_market.load()?.token_mint == mint.key())]
+	pub index_market: AccountLoader<'info, IndexMarket>,
 }
 
 pub fn handle_index_token_transfer_hook(
 	ctx: Context<IndexTokenTransferHook>,
 	amount: u64
 ) -> Result<()> {
-	let signer_seeds: &[&[&[u8]]] = &[&[b"delegate", &[ctx.bumps.delegate]]];
-	msg!("Transfer WSOL using delegate PDA");
+	assert_is_transferring(&ctx.accounts.source_token.to_account_info())?;
+
+	let index_market = load!(ctx.accounts.index_market)?;
 
-	// transfer WSOL from sender to delegate token account using delegate PDA
-	transfer_checked(
-		CpiContext::new(
-			ctx.accounts.token_program.to_account_info(),
-			TransferChecked {
-				from: ctx.accounts.sender_wsol_token_account.to_account_info(),
-				mint: ctx.accounts.wsol_mint.to_account_info(),
-				to: ctx.accounts.delegate_wsol_token_account.to_account_info(),
-				authority: ctx.accounts.delegate.to_account_info(),
-			}
-		).with_signer(signer_seeds),
-		amount,
-		ctx.accounts.wsol_mint.decimals
+	let source_owner = ctx.accounts.source_token.owner;
+	let destination_owner = ctx.accounts.destination_token.owner;
+
+	validate!(
+		!index_market.is_blacklisted(&source_owner),
+		ErrorCode::IndexHolderNotPermitted,
+		"index market {} blacklists sender {}",
+		index_market.market_index,
+		source_owner
 	)?;
+
+	validate!(
+		index_market.can_hold(&destination_owner),
+		ErrorCode::IndexHolderNotPermitted,
+		"index market {} does not permit {} to receive {}",
+		index_market.market_index,
+		destination_owner,
+		amount
+	)?;
+
+	Ok(())
+}
+
+/// The hook is only meant to run inside a token-2022 transfer, which sets the flag on the source
+fn assert_is_transferring(source_token: &AccountInfo) -> Result<()> {
+	let source_token_data = source_token.try_borrow_data()?;
+	let source_token_account = StateWithExtensions::<TokenAccountInner>::unpack(
+		&source_token_data
+	)?;
+	let transfer_hook_account = source_token_account.get_extension::<TransferHookAccount>()?;
+
+	validate!(
+		bool::from(transfer_hook_account.transferring),
+		ErrorCode::IndexTransferHookNotTransferring,
+		"transfer hook called outside of a transfer"
+	)?;
+
 	Ok(())
 }
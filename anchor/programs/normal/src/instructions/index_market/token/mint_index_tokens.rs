@@ -1,85 +1,145 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{ self, Token, TokenAccount };
-
-use crate::{
-	controller,
-	errors::ErrorCode,
-	load_mut,
-	state::synth_market::SynthMarket,
-	util::transfer_from_owner_to_vault,
+use anchor_spl::token;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::optional_accounts::{
+	get_index_basket_accounts,
+	load_maps,
+	AccountMaps,
 };
+use crate::math::safe_math::SafeMath;
+use crate::state::events::IndexMintRecord;
+use crate::state::index_market::IndexMarket;
+use crate::state::synth_market_map::MarketSet;
+use crate::{ controller, load_mut, validate, State };
 
 #[derive(Accounts)]
-#[instruction(market_index: u16,)]
+#[instruction(market_index: u16)]
 pub struct MintIndexTokens<'info> {
-	#[account(mut)]
-	pub user: AccountLoader<'info, User>,
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
 	#[account(
-        mut,
-        constraint = is_stats_for_user(&user, &user_stats)?
-    )]
-	pub user_stats: AccountLoader<'info, UserStats>,
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
 	#[account(
-        mut,
-        seeds = [b"index_market_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-	pub index_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+		mut,
+		address = index_market.load()?.token_mint,
+		mint::token_program = index_token_program
+	)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = authority
+	)]
+	pub user_index_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	/// Moves the constituent synthetics, which are SPL Token mints
+	#[account(address = token::ID)]
 	pub token_program: Interface<'info, TokenInterface>,
+	pub index_token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn handle_index_mint(
-	ctx: Context<MintIndexTokens>,
+/// Creates `amount` index tokens in kind. Remaining accounts are the constituent synth markets
+/// and oracles, then a (user token account, basket vault) pair per asset in asset order.
+pub fn handle_mint_index_tokens<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, MintIndexTokens<'info>>,
+	_market_index: u16,
 	amount: u64
 ) -> Result<()> {
+	let state = &ctx.accounts.state;
 	let clock = Clock::get()?;
 	let now = clock.unix_timestamp;
 
+	validate!(amount > 0, ErrorCode::InsufficientDeposit, "amount must be positive")?;
+
 	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
 
-	let user_key = ctx.accounts.user.key();
+	// the transfer hook doesn't run on mint, so gate holders here too
+	validate!(
+		index_market.can_hold(&ctx.accounts.authority.key()),
+		ErrorCode::IndexHolderNotPermitted,
+		"index market {} does not permit {} to hold it",
+		index_market.market_index,
+		ctx.accounts.authority.key()
+	)?;
+
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let AccountMaps { synth_market_map, mut oracle_map, .. } = load_maps(
+		remaining_accounts_iter,
+		&MarketSet::new(),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let basket_accounts = get_index_basket_accounts(
+		remaining_accounts_iter,
+		&index_market.assets
+	)?;
 
-	let user = &mut load_mut!(ctx.accounts.user)?;
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+	let index_decimals = ctx.accounts.index_token_mint.decimals as u32;
 
-	validate!(!user.is_being_liquidated(), ErrorCode::LiquidationsOngoing)?;
-	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt, "user bankrupt")?;
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
 
-	let previouse_index_token_balance = ctx.accounts.token_owner_account.amount;
+	let vault_amounts = basket_accounts
+		.iter()
+		.map(|(_, vault)| vault.amount)
+		.collect::<Vec<u64>>();
 
-	// Transfer <amount> of SOL into market
-	transfer_from_owner_to_vault(
-		&ctx.accounts.authority,
-		&ctx.accounts.token_owner_account,
-		&ctx.accounts.index_market_vault,
-		&ctx.accounts.token_program,
+	let (deposit_amounts, nav) = controller::index::calculate_creation_basket(
+		index_market,
+		&synth_market_map,
+		&mut oracle_map,
+		&vault_amounts,
+		index_token_supply,
+		index_decimals,
 		amount
 	)?;
 
-	// Calculate swap amounts
-	let swap_amounts = index_market.compute_swap_amounts(amount);
-
-	// Loop through <swap_amounts> and execute each swap
-	{
-		// ...
+	for ((user_token_account, vault), deposit_amount) in basket_accounts
+		.iter()
+		.zip(deposit_amounts.iter()) {
+		controller::token::receive(
+			&ctx.accounts.token_program,
+			user_token_account,
+			vault,
+			&ctx.accounts.authority.to_account_info(),
+			*deposit_amount,
+			&None
+		)?;
 	}
 
-	// Update user stats
-	let mut user_stats = load_mut!(ctx.accounts.user_stats)?;
+	let previous_index_token_balance = ctx.accounts.user_index_token_account.amount;
 
-	// Update the index market
-	// ...
-	index_market.total_minted = index_market.total_minted.safe_add(amount);
+	controller::token::mint_from_program(
+		&ctx.accounts.index_token_program,
+		&ctx.accounts.index_token_mint,
+		&ctx.accounts.user_index_token_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		amount
+	)?;
 
-	if previouse_index_token_balance == 0 {
-		index_market.number_of_users = index_market.number_of_users.safe_add(1);
+	index_market.total_minted = index_market.total_minted.safe_add(amount)?;
+	if previous_index_token_balance == 0 {
+		index_market.number_of_users = index_market.number_of_users.safe_add(1)?;
 	}
 
 	emit!(IndexMintRecord {
-		market_index: index_market.market_index,
-		user: user_key,
-		oracle_price: 0, // TODO:
-		quote_asset_amount: amount,
 		ts: now,
+		market_index: index_market.market_index,
+		user: ctx.accounts.authority.key(),
+		nav,
+		index_amount: amount,
+		basket_amounts: deposit_amounts,
+		quote_amount: 0,
 	});
 
 	Ok(())
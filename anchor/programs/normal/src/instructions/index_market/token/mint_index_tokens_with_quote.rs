@@ -0,0 +1,208 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ self, Token };
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::optional_accounts::{ get_index_swap_legs, load_maps, AccountMaps };
+use crate::math::index::{ calculate_index_amount_for_value, calculate_market_cap, split_by_weights };
+use crate::math::safe_math::SafeMath;
+use crate::state::events::IndexMintRecord;
+use crate::state::index_market::IndexMarket;
+use crate::state::synth_market_map::MarketSet;
+use crate::util::{ swap_amm_exact_input, to_timestamp_u64, transfer_from_owner_to_vault };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct MintIndexTokensWithQuote<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(mut, address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = authority
+	)]
+	pub user_index_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, token::authority = authority)]
+	pub user_token_account_quote: Box<Account<'info, token::TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	#[account(address = token::ID)]
+	pub token_program: Program<'info, Token>,
+	pub index_token_program: Interface<'info, TokenInterface>,
+}
+
+/// Swaps `quote_amount` across each asset's AMM by weight, deposits the synthetic into the
+/// basket and mints index tokens at the pre-deposit NAV. Remaining accounts are the
+/// constituent synth markets and oracles, then an index swap leg per asset in asset order.
+pub fn handle_mint_index_tokens_with_quote<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, MintIndexTokensWithQuote<'info>>,
+	_market_index: u16,
+	quote_amount: u64,
+	min_index_amount: u64,
+	sqrt_price_limits: Vec<u128>
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+	let timestamp = to_timestamp_u64(now)?;
+
+	validate!(quote_amount > 0, ErrorCode::InsufficientDeposit, "quote_amount must be positive")?;
+
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	// the transfer hook doesn't run on mint, so gate holders here too
+	validate!(
+		index_market.can_hold(&ctx.accounts.authority.key()),
+		ErrorCode::IndexHolderNotPermitted,
+		"index market {} does not permit {} to hold it",
+		index_market.market_index,
+		ctx.accounts.authority.key()
+	)?;
+	index_market.validate_weights()?;
+
+	validate!(
+		sqrt_price_limits.len() == index_market.assets.len(),
+		ErrorCode::InvalidIndexPriceLimits,
+		"expected {} sqrt price limits, got {}",
+		index_market.assets.len(),
+		sqrt_price_limits.len()
+	)?;
+
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let AccountMaps { synth_market_map, mut oracle_map, .. } = load_maps(
+		remaining_accounts_iter,
+		&MarketSet::new(),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let mut legs = get_index_swap_legs(
+		remaining_accounts_iter,
+		&index_market.assets,
+		&synth_market_map
+	)?;
+
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+	let index_decimals = ctx.accounts.index_token_mint.decimals as u32;
+
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
+
+	let vault_amounts = legs
+		.iter()
+		.map(|leg| leg.basket_vault.amount)
+		.collect::<Vec<u64>>();
+
+	let nav = controller::index::calculate_nav(
+		index_market,
+		&synth_market_map,
+		&mut oracle_map,
+		&vault_amounts,
+		index_token_supply,
+		index_decimals
+	)?;
+
+	let weights = index_market.assets
+		.iter()
+		.map(|asset| asset.weight)
+		.collect::<Vec<u16>>();
+	let quote_legs = split_by_weights(quote_amount, &weights)?;
+
+	let mut quote_spent = 0_u64;
+	let mut deposit_value = 0_u128;
+	let mut deposit_amounts = Vec::with_capacity(legs.len());
+
+	for (i, leg) in legs.iter_mut().enumerate() {
+		if quote_legs[i] == 0 {
+			deposit_amounts.push(0);
+			continue;
+		}
+
+		// a leg stopped by its price limit only spends part of its quote
+		let (quote_in, synthetic_out) = swap_amm_exact_input(
+			&mut leg.amm,
+			&ctx.accounts.authority,
+			&leg.user_token_account_synthetic,
+			&ctx.accounts.user_token_account_quote,
+			&leg.token_vault_synthetic,
+			&leg.token_vault_quote,
+			&ctx.accounts.token_program,
+			leg.tick_arrays.clone(),
+			quote_legs[i],
+			sqrt_price_limits[i],
+			false,
+			timestamp
+		)?;
+
+		leg.amm_observations.load_mut()?.write(leg.amm.get_observation(timestamp));
+		// remaining accounts aren't written back by anchor
+		leg.amm.exit(&crate::ID)?;
+
+		transfer_from_owner_to_vault(
+			&ctx.accounts.authority,
+			&leg.user_token_account_synthetic,
+			&leg.basket_vault,
+			&ctx.accounts.token_program,
+			synthetic_out
+		)?;
+
+		let market = synth_market_map.get_ref(&index_market.assets[i].market_index)?;
+		let oracle_price = controller::index::get_valid_constituent_price_data(
+			&market,
+			&mut oracle_map
+		)?.price;
+
+		deposit_value = deposit_value.safe_add(
+			calculate_market_cap(synthetic_out, market.decimals, oracle_price)?
+		)?;
+		quote_spent = quote_spent.safe_add(quote_in)?;
+		deposit_amounts.push(synthetic_out);
+	}
+
+	let index_amount = calculate_index_amount_for_value(deposit_value, nav, index_decimals)?;
+
+	validate!(
+		index_amount > 0 && index_amount >= min_index_amount,
+		ErrorCode::AmountOutBelowMinimum,
+		"index_amount={} below min_index_amount={}",
+		index_amount,
+		min_index_amount
+	)?;
+
+	let previous_index_token_balance = ctx.accounts.user_index_token_account.amount;
+
+	controller::token::mint_from_program(
+		&ctx.accounts.index_token_program,
+		&ctx.accounts.index_token_mint,
+		&ctx.accounts.user_index_token_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		index_amount
+	)?;
+
+	index_market.total_minted = index_market.total_minted.safe_add(index_amount)?;
+	if previous_index_token_balance == 0 {
+		index_market.number_of_users = index_market.number_of_users.safe_add(1)?;
+	}
+
+	emit!(IndexMintRecord {
+		ts: now,
+		market_index: index_market.market_index,
+		user: ctx.accounts.authority.key(),
+		nav,
+		index_amount,
+		basket_amounts: deposit_amounts,
+		quote_amount: quote_spent,
+	});
+
+	Ok(())
+}
@@ -1,4 +1,6 @@
 pub mod index_token_transfer_hook;
 pub mod initialize_extra_account_meta_list;
 pub mod mint_index_tokens;
-pub mod redeem_index_tokens;
\ No newline at end of file
+pub mod mint_index_tokens_with_quote;
+pub mod redeem_index_tokens;
+pub mod redeem_index_tokens_to_quote;
@@ -1,92 +1,120 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{ self, Token, TokenAccount };
+use anchor_spl::token;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
 
-use crate::{
-	controller,
-	errors::ErrorCode,
-	load_mut,
-	state::synth_market::SynthMarket,
-	util::{ transfer_from_owner_to_vault, transfer_from_vault_to_owner },
-};
+use crate::error::ErrorCode;
+use crate::instructions::optional_accounts::get_index_basket_accounts;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::IndexRedeemRecord;
+use crate::state::index_market::IndexMarket;
+use crate::{ controller, load_mut, validate, State };
 
 #[derive(Accounts)]
-#[instruction(market_index: u16,)]
+#[instruction(market_index: u16)]
 pub struct RedeemIndexTokens<'info> {
-	#[account(mut)]
-	pub user: AccountLoader<'info, User>,
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
 	#[account(
-        mut,
-        constraint = is_stats_for_user(&user, &user_stats)?
-    )]
-	pub user_stats: AccountLoader<'info, UserStats>,
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
 	#[account(
-        mut,
-        seeds = [b"index_market_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-	pub index_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+		mut,
+		address = index_market.load()?.token_mint,
+		mint::token_program = index_token_program
+	)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = authority
+	)]
+	pub user_index_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	/// Moves the constituent synthetics, which are SPL Token mints
+	#[account(address = token::ID)]
 	pub token_program: Interface<'info, TokenInterface>,
+	pub index_token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn handle_index_redeem(
-	ctx: Context<RedeemIndexTokens>,
+/// Redeems `amount` index tokens for a pro rata share of the basket. Remaining accounts are a
+/// (user token account, basket vault) pair per asset in asset order.
+pub fn handle_redeem_index_tokens<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RedeemIndexTokens<'info>>,
+	_market_index: u16,
 	amount: u64
 ) -> Result<()> {
-	let clock = Clock::get()?;
-	let now = clock.unix_timestamp;
-
-	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
-
-	let user_key = ctx.accounts.user.key();
+	let state = &ctx.accounts.state;
+	let now = Clock::get()?.unix_timestamp;
 
-	let user = &mut load_mut!(ctx.accounts.user)?;
+	validate!(amount > 0, ErrorCode::InsufficientCollateral, "amount must be positive")?;
 
-	/**
-	 * Flow:
-
-	 - 
-	 */
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
 
-	// Calculate swap amounts
+	let basket_accounts = get_index_basket_accounts(
+		&mut ctx.remaining_accounts.iter().peekable(),
+		&index_market.assets
+	)?;
 
-	let swap_amounts = index_market.compute_swap_amounts(amount);
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
 
-	// Loop through <swap_amounts> and execute each swap
-	{
-		// ...
-	}
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
 
-	// Burn index tokens
-	// ...
+	let vault_amounts = basket_accounts
+		.iter()
+		.map(|(_, vault)| vault.amount)
+		.collect::<Vec<u64>>();
 
-	// Transfer SOL back to user
-	transfer_from_vault_to_owner(
-		&ctx.accounts.authority,
-		&ctx.accounts.index_market_vault,
-		&ctx.accounts.token_owner_account,
-		&ctx.accounts.token_program,
+	let redemption_amounts = controller::index::calculate_redemption_basket(
+		index_market,
+		&vault_amounts,
+		index_token_supply,
 		amount
 	)?;
 
-	// Update user stats
-	let mut user_stats = load_mut!(ctx.accounts.user_stats)?;
-
-	// Update the index market
-	// ...
-	index_market.total_redeemded = index_market.total_redeemded.safe_add(amount);
+	controller::token::burn(
+		&ctx.accounts.index_token_program,
+		&ctx.accounts.user_index_token_account,
+		&ctx.accounts.index_token_mint,
+		&ctx.accounts.authority.to_account_info(),
+		amount
+	)?;
 
-	let updated_index_token_balance = ctx.accounts.token_owner_account.amount;
+	for ((user_token_account, vault), redemption_amount) in basket_accounts
+		.iter()
+		.zip(redemption_amounts.iter()) {
+		if *redemption_amount == 0 {
+			continue;
+		}
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			vault,
+			user_token_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			*redemption_amount,
+			&None
+		)?;
+	}
 
-	if updated_index_token_balance == 0 {
-		index_market.number_of_users = index_market.number_of_users.safe_sub(1);
+	index_market.total_redeemed = index_market.total_redeemed.safe_add(amount.cast()?)?;
+	if ctx.accounts.user_index_token_account.amount == amount {
+		index_market.number_of_users = index_market.number_of_users.saturating_sub(1);
 	}
 
 	emit!(IndexRedeemRecord {
-		market_index: index_market.market_index,
-		user: user_key,
-		oracle_price: 0, // TODO:
-		base_asset_amount: amount,
 		ts: now,
+		market_index: index_market.market_index,
+		user: ctx.accounts.authority.key(),
+		index_amount: amount,
+		basket_amounts: redemption_amounts,
+		quote_amount: 0,
 	});
 
 	Ok(())
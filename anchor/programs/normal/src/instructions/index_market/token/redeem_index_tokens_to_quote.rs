@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ self, Token };
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::optional_accounts::{ get_index_swap_legs, load_maps, AccountMaps };
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::IndexRedeemRecord;
+use crate::state::index_market::IndexMarket;
+use crate::state::synth_market_map::MarketSet;
+use crate::util::{ swap_amm_exact_input, to_timestamp_u64, transfer_from_program_vault_to_owner };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct RedeemIndexTokensToQuote<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(mut, address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = index_token_mint,
+		token::authority = authority
+	)]
+	pub user_index_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, token::authority = authority)]
+	pub user_token_account_quote: Box<Account<'info, token::TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	#[account(address = token::ID)]
+	pub token_program: Program<'info, Token>,
+	pub index_token_program: Interface<'info, TokenInterface>,
+}
+
+/// Redeems `amount` index tokens for a pro rata share of the basket and swaps each asset to
+/// quote through its AMM. Synthetic left by a leg stopped at its price limit stays with the
+/// user. Remaining accounts are the constituent synth markets and oracles, then an index
+/// swap leg per asset in asset order.
+pub fn handle_redeem_index_tokens_to_quote<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RedeemIndexTokensToQuote<'info>>,
+	_market_index: u16,
+	amount: u64,
+	min_quote_amount: u64,
+	sqrt_price_limits: Vec<u128>
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+	let timestamp = to_timestamp_u64(now)?;
+
+	validate!(amount > 0, ErrorCode::InsufficientCollateral, "amount must be positive")?;
+
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	validate!(
+		sqrt_price_limits.len() == index_market.assets.len(),
+		ErrorCode::InvalidIndexPriceLimits,
+		"expected {} sqrt price limits, got {}",
+		index_market.assets.len(),
+		sqrt_price_limits.len()
+	)?;
+
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let AccountMaps { synth_market_map, .. } = load_maps(
+		remaining_accounts_iter,
+		&MarketSet::new(),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let mut legs = get_index_swap_legs(
+		remaining_accounts_iter,
+		&index_market.assets,
+		&synth_market_map
+	)?;
+
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+
+	controller::index::accrue_expenses(index_market, index_token_supply, now)?;
+
+	let vault_amounts = legs
+		.iter()
+		.map(|leg| leg.basket_vault.amount)
+		.collect::<Vec<u64>>();
+
+	let redemption_amounts = controller::index::calculate_redemption_basket(
+		index_market,
+		&vault_amounts,
+		index_token_supply,
+		amount
+	)?;
+
+	let previous_index_token_balance = ctx.accounts.user_index_token_account.amount;
+
+	controller::token::burn(
+		&ctx.accounts.index_token_program,
+		&ctx.accounts.user_index_token_account,
+		&ctx.accounts.index_token_mint,
+		&ctx.accounts.authority.to_account_info(),
+		amount
+	)?;
+
+	let mut quote_received = 0_u64;
+
+	for (i, leg) in legs.iter_mut().enumerate() {
+		if redemption_amounts[i] == 0 {
+			continue;
+		}
+
+		transfer_from_program_vault_to_owner(
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			&leg.basket_vault,
+			&leg.user_token_account_synthetic,
+			&ctx.accounts.token_program,
+			redemption_amounts[i]
+		)?;
+
+		let (_, quote_out) = swap_amm_exact_input(
+			&mut leg.amm,
+			&ctx.accounts.authority,
+			&leg.user_token_account_synthetic,
+			&ctx.accounts.user_token_account_quote,
+			&leg.token_vault_synthetic,
+			&leg.token_vault_quote,
+			&ctx.accounts.token_program,
+			leg.tick_arrays.clone(),
+			redemption_amounts[i],
+			sqrt_price_limits[i],
+			true,
+			timestamp
+		)?;
+
+		leg.amm_observations.load_mut()?.write(leg.amm.get_observation(timestamp));
+		// remaining accounts aren't written back by anchor
+		leg.amm.exit(&crate::ID)?;
+
+		quote_received = quote_received.safe_add(quote_out)?;
+	}
+
+	validate!(
+		quote_received >= min_quote_amount,
+		ErrorCode::AmountOutBelowMinimum,
+		"quote_received={} below min_quote_amount={}",
+		quote_received,
+		min_quote_amount
+	)?;
+
+	index_market.total_redeemed = index_market.total_redeemed.safe_add(amount.cast()?)?;
+	if previous_index_token_balance == amount {
+		index_market.number_of_users = index_market.number_of_users.saturating_sub(1);
+	}
+
+	emit!(IndexRedeemRecord {
+		ts: now,
+		market_index: index_market.market_index,
+		user: ctx.accounts.authority.key(),
+		index_amount: amount,
+		basket_amounts: redemption_amounts,
+		quote_amount: quote_received,
+	});
+
+	Ok(())
+}
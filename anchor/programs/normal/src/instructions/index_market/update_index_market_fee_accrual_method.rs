@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::index_market::{ FeeAccrualMethod, IndexMarket };
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+pub struct UpdateIndexMarketFeeAccrualMethod<'info> {
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+pub fn handle_update_index_market_fee_accrual_method(
+	ctx: Context<UpdateIndexMarketFeeAccrualMethod>,
+	fee_accrual_method: FeeAccrualMethod
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	msg!("updating index market {} fee_accrual_method", index_market.market_index);
+
+	// fees up to now accrue under the old method
+	controller::index::accrue_expenses(index_market, ctx.accounts.index_token_mint.supply, now)?;
+
+	msg!(
+		"index_market.fee_accrual_method: {:?} -> {:?}",
+		index_market.fee_accrual_method,
+		fee_accrual_method
+	);
+
+	index_market.fee_accrual_method = fee_accrual_method;
+
+	Ok(())
+}
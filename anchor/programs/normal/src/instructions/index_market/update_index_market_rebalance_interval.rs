@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::math::safe_math::SafeMath;
+use crate::{ load_mut, validate };
+
+use super::UpdateIndexMarket;
+
+/// Sets the min seconds between rebalances. The next rebalance is rescheduled from the last one
+#[access_control(index_market_valid(&ctx.accounts.index_market))]
+pub fn handle_update_index_market_rebalance_interval(
+	ctx: Context<UpdateIndexMarket>,
+	rebalance_interval: i64
+) -> Result<()> {
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+	msg!("updating index market {} rebalance_interval", index_market.market_index);
+
+	validate!(
+		rebalance_interval >= 0,
+		ErrorCode::InvalidIndexRebalanceInterval,
+		"rebalance_interval={} must not be negative",
+		rebalance_interval
+	)?;
+
+	let min_rebalance_ts = index_market.rebalanced_ts.safe_add(rebalance_interval)?;
+
+	msg!(
+		"index_market.rebalance_interval: {} -> {}",
+		index_market.rebalance_interval,
+		rebalance_interval
+	);
+	msg!(
+		"index_market.min_rebalance_ts: {} -> {}",
+		index_market.min_rebalance_ts,
+		min_rebalance_ts
+	);
+
+	index_market.rebalance_interval = rebalance_interval;
+	index_market.min_rebalance_ts = min_rebalance_ts;
+
+	Ok(())
+}
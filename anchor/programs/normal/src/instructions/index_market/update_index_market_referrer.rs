@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::ONE_BPS_DENOMINATOR;
+use crate::error::ErrorCode;
+use crate::math::safe_math::SafeMath;
+use crate::{ load_mut, validate };
+
+use super::UpdateIndexMarket;
+
+pub fn handle_update_index_market_referrer(
+	ctx: Context<UpdateIndexMarket>,
+	referrer: Pubkey,
+	referral_share: u64
+) -> Result<()> {
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+
+	msg!("updating index market {} referrer", index_market.market_index);
+
+	msg!("index_market.referrer: {:?} -> {:?}", index_market.referrer, referrer);
+
+	msg!(
+		"index_market.referral_share: {:?} -> {:?}",
+		index_market.referral_share,
+		referral_share
+	);
+
+	validate!(
+		referral_share.safe_add(index_market.revenue_share)? <= ONE_BPS_DENOMINATOR.into(),
+		ErrorCode::FeeRateMaxExceeded,
+		"referral_share + revenue_share must be <= {}",
+		ONE_BPS_DENOMINATOR
+	)?;
+
+	index_market.referrer = referrer;
+	index_market.referral_share = referral_share;
+
+	Ok(())
+}
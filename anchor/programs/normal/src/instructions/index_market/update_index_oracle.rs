@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::instructions::optional_accounts::{ get_index_basket_vaults, load_maps, AccountMaps };
+use crate::state::index_market::IndexMarket;
+use crate::state::index_oracle::IndexOracle;
+use crate::state::synth_market_map::MarketSet;
+use crate::{ controller, load_mut, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct UpdateIndexOracle<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"index_market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_market: AccountLoader<'info, IndexMarket>,
+	#[account(
+		mut,
+		seeds = [b"index_oracle", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub index_oracle: AccountLoader<'info, IndexOracle>,
+	#[account(address = index_market.load()?.token_mint)]
+	pub index_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+/// Recomputes the index NAV. Remaining accounts are the constituent oracles and synth markets,
+/// then the basket vault of each asset in asset order.
+pub fn handle_update_index_oracle<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, UpdateIndexOracle<'info>>,
+	_market_index: u16
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+
+	let index_market = &mut load_mut!(ctx.accounts.index_market)?;
+	let index_oracle = &mut load_mut!(ctx.accounts.index_oracle)?;
+
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let AccountMaps { synth_market_map, mut oracle_map, .. } = load_maps(
+		remaining_accounts_iter,
+		&MarketSet::new(),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let vault_amounts = get_index_basket_vaults(remaining_accounts_iter, &index_market.assets)?
+		.iter()
+		.map(|vault| vault.amount)
+		.collect::<Vec<u64>>();
+
+	let index_token_supply = ctx.accounts.index_token_mint.supply;
+
+	// owed fees dilute the nav, so bring them up to date first
+	controller::index::accrue_expenses(index_market, index_token_supply, clock.unix_timestamp)?;
+
+	controller::index::update_index_oracle(
+		index_oracle,
+		index_market,
+		&synth_market_map,
+		&mut oracle_map,
+		&vault_amounts,
+		index_token_supply,
+		ctx.accounts.index_token_mint.decimals as u32,
+		clock.slot,
+		clock.unix_timestamp
+	)?;
+
+	Ok(())
+}
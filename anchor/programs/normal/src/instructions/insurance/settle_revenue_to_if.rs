@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
 use solana_program::instruction::Instruction;
 use solana_program::sysvar::instructions::{
 	load_current_index_checked,
@@ -7,6 +7,8 @@ use solana_program::sysvar::instructions::{
 	ID as IX_ID,
 };
 
+use crate::state::events::InsuranceFundSurplusRecord;
+use crate::state::insurance::InsuranceFund;
 use crate::state::synth_market::SynthMarket;
 use crate::State;
 
@@ -19,22 +21,35 @@ pub struct SettleRevenueToInsuranceFund<'info> {
         seeds = [b"market", market_index.to_le_bytes().as_ref()],
         bump
     )]
-	pub market: AccountLoader<'info, Market>,
+	pub market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
 	#[account(
         mut,
-        seeds = [b"market_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
+        address = market.load()?.token_vault_synthetic
     )]
-	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
 	#[account(constraint = state.signer.eq(&normal_signer.key()))]
 	/// CHECK: forced normal_signer
 	pub normal_signer: AccountInfo<'info>,
+	/// Receives the insurance fund's share of the revenue pool, which is synthetic
 	#[account(
         mut,
+        seeds = [b"insurance_fund_synthetic_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        token::mint = synthetic_mint,
+    )]
+	pub insurance_fund_synthetic_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
         seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
         bump,
     )]
 	pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+        mut,
+        address = state.insurance_fund
+    )]
+	pub insurance_fund: AccountLoader<'info, InsuranceFund>,
 	pub token_program: Interface<'info, TokenInterface>,
 }
 
@@ -45,9 +60,7 @@ pub fn handle_settle_revenue_to_if<'c: 'info, 'info>(
 ) -> Result<()> {
 	let state = &ctx.accounts.state;
 	let market = &mut load_mut!(ctx.accounts.market)?;
-
-	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
-	let mint = get_token_mint(remaining_accounts_iter)?;
+	let insurance_fund = &mut load_mut!(ctx.accounts.insurance_fund)?;
 
 	validate!(
 		market_index == market.market_index,
@@ -61,9 +74,6 @@ pub fn handle_settle_revenue_to_if<'c: 'info, 'info>(
 		"invalid revenue_settle_period settings on market"
 	)?;
 
-	let market_vault_amount = ctx.accounts.market_vault.amount;
-	let insurance_vault_amount = ctx.accounts.insurance_fund_vault.amount;
-
 	let clock = Clock::get()?;
 	let now = clock.unix_timestamp;
 
@@ -80,10 +90,37 @@ pub fn handle_settle_revenue_to_if<'c: 'info, 'info>(
 		time_until_next_update
 	)?;
 
+	// back the accrued stability fees with synthetic before any of the pool is paid out
+	let stability_fees = controller::insurance::mint_stability_fees_to_revenue_pool(market, now)?;
+	if stability_fees > 0 {
+		controller::token::mint_from_program(
+			&ctx.accounts.token_program,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			stability_fees
+		)?;
+	}
+
+	let synthetic_to_burn = controller::insurance::cover_protocol_debt_with_revenue(market)?;
+	if synthetic_to_burn > 0 {
+		controller::token::burn_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_to_burn
+		)?;
+	}
+
+	ctx.accounts.token_vault_synthetic.reload()?;
+
 	// uses proportion of revenue pool allocated to insurance fund
 	let token_amount = controller::insurance::settle_revenue_to_insurance_fund(
-		spot_vault_amount,
-		insurance_vault_amount,
+		ctx.accounts.token_vault_synthetic.amount,
+		ctx.accounts.insurance_fund_synthetic_vault.amount,
 		market,
 		now,
 		true
@@ -93,20 +130,31 @@ pub fn handle_settle_revenue_to_if<'c: 'info, 'info>(
 
 	controller::token::send_from_program_vault(
 		&ctx.accounts.token_program,
-		&ctx.accounts.spot_market_vault,
-		&ctx.accounts.insurance_fund_vault,
+		&ctx.accounts.token_vault_synthetic,
+		&ctx.accounts.insurance_fund_synthetic_vault,
 		&ctx.accounts.normal_signer,
 		state.signer_nonce,
 		token_amount,
-		&mint
+		&None
 	)?;
 
-	// reload the spot market vault balance so it's up-to-date
-	ctx.accounts.market_vault.reload()?;
-	math::spot_withdraw::validate_spot_market_vault_amount(
-		spot_market,
-		ctx.accounts.spot_market_vault.amount
-	)?;
+	// anything over the insurance fund's cap is left for a surplus auction to sell for
+	// governance tokens
+	let max_insurance = insurance_fund.max_insurance;
+	let surplus = math::auction::calculate_insurance_fund_surplus(
+		ctx.accounts.insurance_fund_vault.amount,
+		max_insurance
+	);
+
+	if surplus > 0 {
+		emit!(InsuranceFundSurplusRecord {
+			ts: now,
+			market_index,
+			insurance_vault_amount: ctx.accounts.insurance_fund_vault.amount,
+			max_insurance,
+			surplus,
+		});
+	}
 
 	Ok(())
 }
@@ -1,4 +1,5 @@
 pub use amm::*;
+pub use auction::*;
 pub use schedule::*;
 pub use constraints::*;
 pub use oracle::*;
@@ -10,6 +11,7 @@ pub use position::*;
 pub use user;
 
 mod amm;
+mod auction;
 mod schedule;
 mod constraints;
 pub mod optional_accounts;
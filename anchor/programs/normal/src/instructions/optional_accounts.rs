@@ -1,4 +1,7 @@
 use crate::error::{ NormalResult, ErrorCode };
+use crate::state::amm::AMM;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::index_market::IndexAsset;
 use crate::state::index_market_map::IndexMarketMap;
 use crate::state::synth_market_map::SynthMarketMap;
 use std::convert::TryFrom;
@@ -10,9 +13,10 @@ use crate::state::traits::Size;
 use crate::validate;
 use anchor_lang::accounts::account::Account;
 use anchor_lang::prelude::{ AccountInfo, Interface };
-use anchor_lang::prelude::{ AccountLoader, InterfaceAccount };
+use anchor_lang::prelude::{ AccountLoader, InterfaceAccount, Pubkey };
 use anchor_lang::Discriminator;
-use anchor_spl::token::TokenAccount;
+use anchor_spl::token;
+use anchor_spl::token_interface::TokenAccount;
 use anchor_spl::token_interface::{ Mint, TokenInterface };
 use arrayref::array_ref;
 use solana_program::account_info::next_account_info;
@@ -69,3 +73,189 @@ pub fn get_token_mint<'a>(
 		Err(_) => Ok(None),
 	}
 }
+
+/// Loads a (user token account, basket vault) pair for each index asset, in asset order
+pub fn get_index_basket_accounts<'a>(
+	account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+	assets: &[IndexAsset]
+) -> NormalResult<Vec<(InterfaceAccount<'a, TokenAccount>, InterfaceAccount<'a, TokenAccount>)>> {
+	let mut basket_accounts = Vec::with_capacity(assets.len());
+
+	for asset in assets.iter() {
+		let user_token_account_info = next_account_info(account_info_iter).or(
+			Err(ErrorCode::InvalidIndexBasketAccount)
+		)?;
+		let vault_account_info = next_account_info(account_info_iter).or(
+			Err(ErrorCode::InvalidIndexBasketAccount)
+		)?;
+
+		validate!(
+			asset.vault != Pubkey::default() && vault_account_info.key == &asset.vault,
+			ErrorCode::InvalidIndexBasketAccount,
+			"expected basket vault {} for asset {}",
+			asset.vault,
+			asset.market_index
+		)?;
+
+		let user_token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(
+			user_token_account_info
+		).or(Err(ErrorCode::InvalidIndexBasketAccount))?;
+		let vault: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(
+			vault_account_info
+		).or(Err(ErrorCode::InvalidIndexBasketAccount))?;
+
+		validate!(
+			user_token_account.mint == vault.mint,
+			ErrorCode::InvalidIndexBasketAccount,
+			"token account mint {} doesn't match asset {} mint {}",
+			user_token_account.mint,
+			asset.market_index,
+			vault.mint
+		)?;
+
+		basket_accounts.push((user_token_account, vault));
+	}
+
+	Ok(basket_accounts)
+}
+
+/// Loads the basket vault of each index asset, in asset order
+pub fn get_index_basket_vaults<'a>(
+	account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+	assets: &[IndexAsset]
+) -> NormalResult<Vec<InterfaceAccount<'a, TokenAccount>>> {
+	let mut vaults = Vec::with_capacity(assets.len());
+
+	for asset in assets.iter() {
+		let vault_account_info = next_account_info(account_info_iter).or(
+			Err(ErrorCode::InvalidIndexBasketAccount)
+		)?;
+
+		validate!(
+			asset.vault != Pubkey::default() && vault_account_info.key == &asset.vault,
+			ErrorCode::InvalidIndexBasketAccount,
+			"expected basket vault {} for asset {}",
+			asset.vault,
+			asset.market_index
+		)?;
+
+		vaults.push(
+			InterfaceAccount::try_from(vault_account_info).or(
+				Err(ErrorCode::InvalidIndexBasketAccount)
+			)?
+		);
+	}
+
+	Ok(vaults)
+}
+
+/// The accounts to swap one index asset through its market's AMM
+pub struct IndexSwapLeg<'a> {
+	pub amm: Box<Account<'a, AMM>>,
+	pub amm_observations: AccountLoader<'a, AMMObservations>,
+	pub basket_vault: Box<Account<'a, token::TokenAccount>>,
+	pub user_token_account_synthetic: Box<Account<'a, token::TokenAccount>>,
+	pub token_vault_synthetic: Box<Account<'a, token::TokenAccount>>,
+	pub token_vault_quote: Box<Account<'a, token::TokenAccount>>,
+	pub tick_arrays: Vec<AccountInfo<'a>>,
+}
+
+/// Loads an IndexSwapLeg for each index asset, in asset order. Each leg is the amm, its
+/// observations, basket vault, user synthetic token account, amm synthetic vault, amm quote vault
+/// and 3 tick arrays.
+pub fn get_index_swap_legs<'a>(
+	account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+	assets: &[IndexAsset],
+	synth_market_map: &SynthMarketMap
+) -> NormalResult<Vec<IndexSwapLeg<'a>>> {
+	let mut legs = Vec::with_capacity(assets.len());
+
+	for asset in assets.iter() {
+		let mut next_account = || {
+			next_account_info(account_info_iter).or(Err(ErrorCode::InvalidIndexBasketAccount))
+		};
+
+		let amm_account_info = next_account()?;
+		let amm_observations_account_info = next_account()?;
+		let basket_vault_account_info = next_account()?;
+		let user_token_account_info = next_account()?;
+		let token_vault_synthetic_account_info = next_account()?;
+		let token_vault_quote_account_info = next_account()?;
+		let tick_arrays = vec![
+			next_account()?.clone(),
+			next_account()?.clone(),
+			next_account()?.clone()
+		];
+
+		let market = synth_market_map.get_ref(&asset.market_index)?;
+
+		validate!(
+			amm_account_info.key == &market.amm,
+			ErrorCode::InvalidIndexBasketAccount,
+			"expected amm {} for asset {}",
+			market.amm,
+			asset.market_index
+		)?;
+
+		validate!(
+			asset.vault != Pubkey::default() && basket_vault_account_info.key == &asset.vault,
+			ErrorCode::InvalidIndexBasketAccount,
+			"expected basket vault {} for asset {}",
+			asset.vault,
+			asset.market_index
+		)?;
+
+		let amm: Box<Account<AMM>> = Box::new(
+			Account::try_from(amm_account_info).or(Err(ErrorCode::InvalidIndexBasketAccount))?
+		);
+
+		let amm_observations: AccountLoader<AMMObservations> = AccountLoader::try_from(
+			amm_observations_account_info
+		).or(Err(ErrorCode::InvalidIndexBasketAccount))?;
+
+		validate!(
+			amm_observations_account_info.is_writable &&
+				amm_observations.load().or(Err(ErrorCode::InvalidIndexBasketAccount))?.amm ==
+					*amm_account_info.key,
+			ErrorCode::InvalidIndexBasketAccount,
+			"amm observations don't match asset {} amm",
+			asset.market_index
+		)?;
+
+		validate!(
+			token_vault_synthetic_account_info.key == &amm.token_vault_synthetic &&
+				token_vault_quote_account_info.key == &amm.token_vault_quote,
+			ErrorCode::InvalidIndexBasketAccount,
+			"amm vaults don't match asset {} amm",
+			asset.market_index
+		)?;
+
+		let load_token_account = |account_info: &'a AccountInfo<'a>| {
+			Account::<token::TokenAccount>
+				::try_from(account_info)
+				.map(Box::new)
+				.or(Err(ErrorCode::InvalidIndexBasketAccount))
+		};
+
+		let user_token_account_synthetic = load_token_account(user_token_account_info)?;
+
+		validate!(
+			user_token_account_synthetic.mint == amm.token_mint_synthetic,
+			ErrorCode::InvalidIndexBasketAccount,
+			"user token account isn't for asset {} synthetic",
+			asset.market_index
+		)?;
+
+		legs.push(IndexSwapLeg {
+			basket_vault: load_token_account(basket_vault_account_info)?,
+			token_vault_synthetic: load_token_account(token_vault_synthetic_account_info)?,
+			token_vault_quote: load_token_account(token_vault_quote_account_info)?,
+			user_token_account_synthetic,
+			amm,
+			amm_observations,
+			tick_arrays,
+		});
+	}
+
+	Ok(legs)
+}
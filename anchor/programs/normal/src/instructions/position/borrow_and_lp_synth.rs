@@ -18,6 +18,8 @@ use crate::manager::liquidity_manager::{
 	sync_modify_liquidity_values,
 };
 use crate::math::liquidation::is_user_being_liquidated;
+use crate::math::margin::validate_isolated_tier_requirements;
+use crate::math::mint_limit::calculate_mint_value;
 use crate::math::safe_math::SafeMath;
 use crate::math::{ self, convert_to_liquidity_delta };
 use crate::{ controller, load_mut, state::*, validate };
@@ -31,6 +33,7 @@ use crate::util::{
 #[derive(Accounts)]
 #[instruction(vault_index: u16,)]
 pub struct DepositCollateral<'info> {
+	#[account(mut)]
 	pub state: Box<Account<'info, State>>,
 	#[account(
         mut,
@@ -83,7 +86,7 @@ pub fn handle_deposit_into_synth_market_vault<'c: 'info, 'info>(
 	let user_key = ctx.accounts.user.key();
 	let user = &mut load_mut!(ctx.accounts.user)?;
 
-	let state = &ctx.accounts.state;
+	let state = &mut ctx.accounts.state;
 	let clock = Clock::get()?;
 	let now = clock.unix_timestamp;
 	let slot = clock.slot;
@@ -106,6 +109,8 @@ pub fn handle_deposit_into_synth_market_vault<'c: 'info, 'info>(
 
 	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt)?;
 
+	validate_isolated_tier_requirements(user, &synth_market_map, market_index)?;
+
 	let mut synth_market = synth_market_map.get_ref_mut(&market_index)?;
 	let oracle_price_data = &oracle_map
 		.get_price_data(&synth_market.oracle)?
@@ -154,14 +159,27 @@ pub fn handle_deposit_into_synth_market_vault<'c: 'info, 'info>(
 		.safe_div(precision)?
 		.cast::<u64>()?;
 
-	// Validate against debt ceiling / floor
-	let updated_outstanding_debt = market.outstanding_debt.safe_plus(
-		synthetic_tokens_to_mint
+	// Validate against the debt ceiling and the market / protocol mint limits
+	market.record_mint(synthetic_tokens_to_mint, now)?;
+	state.mint_limiter.record_mint(
+		calculate_mint_value(
+			synthetic_tokens_to_mint,
+			market.decimals,
+			synthetic_oracle_price_data.price
+		)?,
+		now
 	)?;
-	validate!(
-		updated_outstanding_debt >= market.debt_ceiling,
-		ErrorCode::MarketBeingInitialized,
-		"Market has reached its debt ceiling"
+
+	// the stability fee accrues up to now before the new debt is scaled in
+	controller::synth_balance::update_synth_market_cumulative_interest(
+		&mut market,
+		Some(synthetic_oracle_price_data),
+		now
+	)?;
+	controller::synth_balance::increase_position_debt(
+		synthetic_tokens_to_mint,
+		&mut market,
+		user.force_get_position_mut(market_index)?
 	)?;
 
 	// Mint synthetic tokens to LP
@@ -195,8 +213,7 @@ pub fn handle_deposit_into_synth_market_vault<'c: 'info, 'info>(
 	);
 	ctx.accounts.market_vault.reload()?;
 
-	// Update variables
-	market.outstanding_debt = 0;
+	// Update variables (outstanding_debt was recorded with the mint)
 	vault.synthetic_tokens_minted = vault.synthetic_tokens_minted.safe_add(
 		synthetic_tokens_to_mint
 	)?;
@@ -154,6 +154,7 @@ pub fn handle_deposit_collateral<'c: 'info, 'info>(
 		false,
 		None
 	)?;
+	controller::synth_balance::update_vault_redemption_bucket(&mut synth_market, synth_position)?;
 
 	let token_amount = synth_position.get_token_amount(&synth_market)?;
 	if token_amount == 0 {
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::instructions::optional_accounts::{ load_maps, AccountMaps };
+use crate::math::casting::Cast;
+use crate::math::margin::validate_isolated_tier_requirements;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::CollateralAssetRecord;
+use crate::state::synth_market::MarketStatus;
+use crate::state::synth_market_map::{ get_writable_synth_market_set, MarketSet };
+use crate::state::user::User;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct DepositCollateralAsset<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		constraint = can_sign_for_user(&user, &authority)?
+	)]
+	pub user: AccountLoader<'info, User>,
+	pub authority: Signer<'info>,
+	#[account(mut)]
+	pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		constraint = &collateral_vault.mint.eq(&user_token_account.mint),
+		token::authority = authority
+	)]
+	pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Deposits one of the market's registry collateral assets into the user's vault.
+/// Remaining accounts are the oracles then the synth market
+#[access_control(deposit_not_paused(&ctx.accounts.state))]
+pub fn handle_deposit_collateral_asset<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, DepositCollateralAsset<'info>>,
+	market_index: u16,
+	asset_index: u8,
+	amount: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+
+	validate!(amount > 0, ErrorCode::InsufficientDeposit, "amount must be positive")?;
+
+	let user_key = ctx.accounts.user.key();
+	let user = &mut load_mut!(ctx.accounts.user)?;
+
+	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt)?;
+
+	let AccountMaps { synth_market_map, .. } = load_maps(
+		&mut ctx.remaining_accounts.iter().peekable(),
+		&get_writable_synth_market_set(market_index),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+
+	validate_isolated_tier_requirements(user, &synth_market_map, market_index)?;
+
+	let synth_market = &mut synth_market_map.get_ref_mut(&market_index)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Active,
+		ErrorCode::MarketActionPaused,
+		"market {} not active",
+		market_index
+	)?;
+
+	let collateral = synth_market.get_collateral_asset_mut(asset_index)?;
+
+	validate!(
+		ctx.accounts.collateral_vault.key() == collateral.vault,
+		ErrorCode::CollateralAssetNotFound,
+		"collateral vault {} is not registry slot {}",
+		ctx.accounts.collateral_vault.key(),
+		asset_index
+	)?;
+
+	collateral.validate_max_token_deposits(amount)?;
+	collateral.deposit_amount = collateral.deposit_amount.safe_add(amount)?;
+
+	let position = user.force_get_position_mut(market_index)?;
+	let balance = &mut position.collateral_balances[asset_index as usize];
+	*balance = balance.safe_add(amount)?;
+	let balance_after = *balance;
+
+	controller::token::receive(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_token_account,
+		&ctx.accounts.collateral_vault,
+		&ctx.accounts.authority,
+		amount,
+		&None
+	)?;
+
+	emit!(CollateralAssetRecord {
+		ts: now,
+		user: user_key,
+		market_index,
+		asset_index,
+		mint: ctx.accounts.collateral_vault.mint,
+		amount: amount.cast()?,
+		balance_after,
+	});
+
+	Ok(())
+}
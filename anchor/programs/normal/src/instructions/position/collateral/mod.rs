@@ -1,5 +1,7 @@
 pub mod deposit;
+pub mod deposit_collateral_asset;
 pub mod lend_collateral;
 pub mod unlend_collateral;
 pub mod transfer_collateral;
 pub mod withdraw_collateral;
+pub mod withdraw_collateral_asset;
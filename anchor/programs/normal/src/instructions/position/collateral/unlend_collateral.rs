@@ -1,69 +1,89 @@
-use amm::AMM;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{ self, Token, TokenAccount };
-use anchor_spl::token_interface::TokenAccount as TokenAccountInterface;
-use vault::Vault;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
 
-use crate::errors::ErrorCode;
-use crate::manager::liquidity_manager::{
-	calculate_liquidity_token_deltas,
-	calculate_modify_liquidity,
-	sync_modify_liquidity_values,
-};
-use crate::math::{ self, convert_to_liquidity_delta };
-use crate::{ controller, state::* };
-use crate::util::{
-	mint_synthetic_to_vault,
-	to_timestamp_u64,
-	transfer_from_owner_to_vault,
-	verify_position_authority_interface,
-};
+use crate::controller::lending_adapter::CpiLendingAdapter;
+use crate::instructions::constraints::*;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, State };
 
 #[derive(Accounts)]
 pub struct UnlendCollateral<'info> {
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
 	#[account(mut)]
-	pub vault: Account<'info, Vault>,
-
-	#[account(mut)]
-	pub amm: Account<'info, AMM>,
-
-	#[account(address = token::ID)]
-	pub token_program: Program<'info, Token>,
-
-	pub position_authority: Signer<'info>,
-
-	#[account(mut, has_one = amm)]
-	pub position: Account<'info, Position>,
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.vault
+	)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(address = synth_market.load()?.token_mint_collateral)]
+	pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	#[account(
+		seeds = [b"lending_signer", synth_market.load()?.market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	/// CHECK: per-market lending signer, owns the market's lending obligation
+	pub lending_signer: AccountInfo<'info>,
+	#[account(
+		executable,
+		address = synth_market.load()?.lending_program
+	)]
+	/// CHECK: checked against the market's lending config
+	pub lending_program: AccountInfo<'info>,
+	#[account(
+		mut,
+		address = synth_market.load()?.lending_reserve
+	)]
+	/// CHECK: checked against the market's lending config
+	pub lending_reserve: AccountInfo<'info>,
 	#[account(
-		constraint = position_token_account.mint == position.position_mint,
-		constraint = position_token_account.amount == 1
+		mut,
+		address = synth_market.load()?.lending_obligation
 	)]
-	pub position_token_account: Box<
-		InterfaceAccount<'info, TokenAccountInterface>
-	>,
+	/// CHECK: checked against the market's lending config
+	pub lending_obligation: AccountInfo<'info>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
 
-	#[account(mut, constraint = token_owner_account_quote.mint == amm.token_mint_quote)]
-	pub token_owner_account_quote: Box<Account<'info, TokenAccount>>,
+/// Brings lent collateral back to the market vault, with the yield earned on it
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_unlend_collateral(ctx: Context<UnlendCollateral>, amount: u64) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let signer_nonce = ctx.accounts.state.signer_nonce;
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
 
-	#[account(mut, constraint = token_vault_synthetic.key() == amm.token_vault_synthetic)]
-	pub token_vault_synthetic: Box<Account<'info, TokenAccount>>,
-	#[account(mut, constraint = token_vault_quote.key() == amm.token_vault_quote)]
-	pub token_vault_quote: Box<Account<'info, TokenAccount>>,
+	let collateral_mint = ctx.accounts.collateral_mint.to_account_info();
+	let market_index_bytes = market.market_index.to_le_bytes();
+	let lending_signer_bump = [ctx.bumps.lending_signer];
+	let lending_signer_seeds: &[&[u8]] = &[
+		b"lending_signer",
+		&market_index_bytes,
+		&lending_signer_bump,
+	];
 
-	#[account(mut, has_one = amm)]
-	pub tick_array_lower: AccountLoader<'info, TickArray>,
-	#[account(mut, has_one = amm)]
-	pub tick_array_upper: AccountLoader<'info, TickArray>,
+	let mut adapter = CpiLendingAdapter {
+		lending_program: &ctx.accounts.lending_program,
+		reserve: &ctx.accounts.lending_reserve,
+		obligation: &ctx.accounts.lending_obligation,
+		liquidity_mint: &collateral_mint,
+		liquidity_vault: &ctx.accounts.lending_vault,
+		collateral_vault: &mut ctx.accounts.market_vault,
+		lending_signer: &ctx.accounts.lending_signer,
+		lending_signer_seeds,
+		normal_signer: &ctx.accounts.normal_signer,
+		token_program: &ctx.accounts.token_program,
+		signer_nonce,
+	};
 
-	/// CHECK: Kamino's program account
-	#[account(mut, owner = kamino_program::id())]
-	pub kamino_program: AccountInfo<'info>,
-}
+	controller::lending::unlend_collateral(&mut adapter, market, amount, now)?;
 
-pub fn handle_unlend_collateral(
-	ctx: Context<UnlendCollateral>,
-	amount: u128
-) -> Result<()> {
-	
 	Ok(())
 }
@@ -1,9 +1,10 @@
 use amm::AMM;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{ self, Token, TokenAccount };
-use anchor_spl::token_interface::TokenAccount as TokenAccountInterface;
+use anchor_spl::token_interface::{ Mint, TokenAccount as TokenAccountInterface };
 use vault_map::get_writable_vault_set;
 
+use crate::controller::lending_adapter::OptionalLendingAccounts;
 use crate::errors::ErrorCode;
 use crate::manager::liquidity_manager::{
 	calculate_liquidity_token_deltas,
@@ -49,6 +50,24 @@ pub struct WithdrawCollateral<'info> {
     )]
 	pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 	pub token_program: Interface<'info, TokenInterface>,
+
+	// Lending venue, used to unlend collateral when the market vault can't cover the withdrawal.
+	// Checked against the market's lending config in the handler
+	pub collateral_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	#[account(executable)]
+	/// CHECK: checked in the handler
+	pub lending_program: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_reserve: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_obligation: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: Option<AccountInfo<'info>>,
+	/// CHECK: checked in the handler
+	pub lending_signer: Option<AccountInfo<'info>>,
 }
 
 #[access_control(withdraw_not_paused(&ctx.accounts.state))]
@@ -149,6 +168,10 @@ pub fn handle_withdraw_collateral<'c: 'info, 'info>(
 			spot_market,
 			user
 		)?;
+		controller::synth_balance::update_vault_redemption_bucket(
+			market,
+			user.get_position_mut(market_index)?
+		)?;
 
 		amount
 	};
@@ -207,6 +230,26 @@ pub fn handle_withdraw_collateral<'c: 'info, 'info>(
 	};
 	emit!(deposit_record);
 
+	// collateral lent out is pulled back so withdrawals never wait on the lending venue
+	let lending_accounts = OptionalLendingAccounts {
+		lending_program: ctx.accounts.lending_program.as_ref(),
+		lending_reserve: ctx.accounts.lending_reserve.as_ref(),
+		lending_obligation: ctx.accounts.lending_obligation.as_ref(),
+		lending_vault: ctx.accounts.lending_vault.as_ref(),
+		collateral_mint: ctx.accounts.collateral_mint.as_ref().map(|mint| mint.to_account_info()),
+		lending_signer: ctx.accounts.lending_signer.as_ref(),
+	};
+	controller::lending_adapter::unlend_market_vault_shortfall(
+		&lending_accounts,
+		&mut ctx.accounts.spot_market_vault,
+		&mut market,
+		amount,
+		&ctx.accounts.normal_signer,
+		&ctx.accounts.token_program,
+		state.signer_nonce,
+		now
+	)?;
+
 	controller::token::send_from_program_vault(
 		&ctx.accounts.token_program,
 		&ctx.accounts.spot_market_vault,
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::instructions::optional_accounts::{ load_maps, AccountMaps };
+use crate::math::casting::Cast;
+use crate::math::margin::meets_initial_margin_requirement;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::CollateralAssetRecord;
+use crate::state::synth_market_map::{ get_writable_synth_market_set, MarketSet };
+use crate::state::user::User;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct WithdrawCollateralAsset<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		has_one = authority
+	)]
+	pub user: AccountLoader<'info, User>,
+	pub authority: Signer<'info>,
+	#[account(mut)]
+	pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		constraint = &collateral_vault.mint.eq(&user_token_account.mint)
+	)]
+	pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Withdraws registry collateral from the user's vault as long as the user still meets
+/// initial margin. Remaining accounts are the oracles then the synth markets the user is in
+#[access_control(withdraw_not_paused(&ctx.accounts.state))]
+pub fn handle_withdraw_collateral_asset<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, WithdrawCollateralAsset<'info>>,
+	market_index: u16,
+	asset_index: u8,
+	amount: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+
+	validate!(amount > 0, ErrorCode::InsufficientCollateral, "amount must be positive")?;
+
+	let user_key = ctx.accounts.user.key();
+	let user = &mut load_mut!(ctx.accounts.user)?;
+
+	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt)?;
+
+	let AccountMaps { synth_market_map, mut oracle_map, .. } = load_maps(
+		&mut ctx.remaining_accounts.iter().peekable(),
+		&get_writable_synth_market_set(market_index),
+		&MarketSet::new(),
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+
+	let balance_after = {
+		let synth_market = &mut synth_market_map.get_ref_mut(&market_index)?;
+		let collateral = synth_market.get_collateral_asset_mut(asset_index)?;
+
+		validate!(
+			ctx.accounts.collateral_vault.key() == collateral.vault,
+			ErrorCode::CollateralAssetNotFound,
+			"collateral vault {} is not registry slot {}",
+			ctx.accounts.collateral_vault.key(),
+			asset_index
+		)?;
+
+		collateral.deposit_amount = collateral.deposit_amount.safe_sub(amount)?;
+
+		let position = user.get_position_mut(market_index)?;
+		let balance = &mut position.collateral_balances[asset_index as usize];
+		*balance = balance.safe_sub(amount)?;
+		*balance
+	};
+
+	validate!(
+		meets_initial_margin_requirement(user, &synth_market_map, &mut oracle_map)?,
+		ErrorCode::InsufficientCollateral,
+		"withdrawing {} of collateral {} leaves user below initial margin",
+		amount,
+		asset_index
+	)?;
+
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.collateral_vault,
+		&ctx.accounts.user_token_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		amount,
+		&None
+	)?;
+
+	emit!(CollateralAssetRecord {
+		ts: now,
+		user: user_key,
+		market_index,
+		asset_index,
+		mint: ctx.accounts.collateral_vault.mint,
+		amount: -amount.cast::<i64>()?,
+		balance_after,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::constraints::*;
+use crate::state::deleverage::DeleverageOrder;
+use crate::state::user::User;
+
+#[derive(Accounts)]
+pub struct DeleteVaultDeleverageOrder<'info> {
+	#[account(mut)]
+	pub authority: Signer<'info>,
+	#[account(constraint = can_sign_for_user(&user, &authority)?)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		constraint = deleverage_order.load()?.user == user.key(),
+		close = authority
+	)]
+	pub deleverage_order: AccountLoader<'info, DeleverageOrder>,
+}
+
+pub fn handle_delete_vault_deleverage_order(
+	_ctx: Context<DeleteVaultDeleverageOrder>
+) -> Result<()> {
+	Ok(())
+}
@@ -0,0 +1,456 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::PERCENTAGE_PRECISION;
+use crate::controller::swap_adapter::{ AmmSwapAdapter, CollateralSwapAdapter, ExternalFill };
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::deleverage::{
+	calculate_deleverage_collateral_to_sell,
+	calculate_deleverage_keeper_tip,
+	calculate_deleverage_min_synthetic_out,
+	calculate_deleverage_tip_rate,
+};
+use crate::math::redemption::{
+	calculate_collateral_ratio,
+	calculate_redemption_collateral,
+	calculate_scaled_balance_delta,
+};
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::safe_math::SafeMath;
+use crate::state::amm::AMM;
+use crate::state::amm_observation::AMMObservations;
+use crate::state::deleverage::DeleverageOrder;
+use crate::state::events::VaultDeleverageRecord;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::state::user::User;
+use crate::util::{ to_timestamp_u64, SparseSwapTickSequenceBuilder };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct ExecuteVaultDeleverage<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(mut)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		seeds = [b"deleverage_order", user.key().as_ref(), market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub deleverage_order: AccountLoader<'info, DeleverageOrder>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	#[account(address = synth_market.load()?.oracle)]
+	pub oracle: AccountInfo<'info>,
+	/// CHECK: checked against the market's collateral oracle
+	#[account(address = synth_market.load()?.collateral_oracle)]
+	pub collateral_oracle: AccountInfo<'info>,
+	#[account(mut, address = synth_market.load()?.vault)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = market_vault.mint
+	)]
+	pub keeper_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = user.load()?.authority
+	)]
+	pub owner_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.amm,
+		constraint = amm.token_mint_quote == synth_market.load()?.token_mint_collateral
+	)]
+	pub amm: Box<Account<'info, AMM>>,
+	#[account(mut, seeds = [b"amm_observations", amm.key().as_ref()], bump)]
+	pub amm_observations: AccountLoader<'info, AMMObservations>,
+	#[account(mut, address = amm.token_vault_synthetic)]
+	pub amm_token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = amm.token_vault_quote)]
+	pub amm_token_vault_quote: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_0: UncheckedAccount<'info>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_1: UncheckedAccount<'info>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub tick_array_2: UncheckedAccount<'info>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Executes a vault's deleverage order once its collateral ratio is below the order's
+/// trigger: sells collateral through the market's AMM, burns the synthetic bought against
+/// the vault's debt and tips the keeper. Debt the primary collateral can't cover is settled
+/// with registry collateral to the protocol. Permissionless so any keeper can protect the vault
+/// before it can be liquidated. Remaining accounts are the registry oracles, then a registry
+/// vault and admin token account per seized asset
+pub fn handle_execute_vault_deleverage<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, ExecuteVaultDeleverage<'info>>,
+	market_index: u16
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+
+	let user_key = ctx.accounts.user.key();
+	let user = &mut load_mut!(ctx.accounts.user)?;
+	let deleverage_order = &mut load_mut!(ctx.accounts.deleverage_order)?;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt)?;
+
+	validate!(
+		matches!(synth_market.status, MarketStatus::Active | MarketStatus::ReduceOnly),
+		ErrorCode::MarketActionPaused,
+		"deleveraging paused for market {}",
+		market_index
+	)?;
+
+	controller::synth_balance::update_synth_market_cumulative_interest(synth_market, None, now)?;
+
+	// collateral is sold and debt repaid at these prices, so both oracles must be fully valid
+	let synthetic_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.oracle)?,
+		market_index,
+		Some(synth_market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+	let collateral_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.collateral_oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.collateral_oracle)?,
+		market_index,
+		None,
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	// registry oracles lead the remaining accounts
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let mut oracle_map = OracleMap::load(
+		remaining_accounts_iter,
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+
+	let position = user.get_position_mut(market_index)?;
+
+	let collateral_amount = position.get_collateral_amount(synth_market)?;
+	let debt_amount = position.get_debt_amount(synth_market)?;
+
+	// registry collateral counts towards the vault's health at its primary collateral value
+	let collateral_asset_prices = controller::collateral::get_collateral_asset_prices(
+		position,
+		synth_market,
+		&mut oracle_map
+	)?;
+	let collateral_assets_amount = controller::collateral::get_collateral_assets_in_collateral(
+		position,
+		synth_market,
+		&collateral_asset_prices,
+		collateral_price
+	)?;
+	let total_collateral_amount = collateral_amount.safe_add(collateral_assets_amount)?;
+
+	let collateral_ratio_before = calculate_collateral_ratio(
+		total_collateral_amount,
+		synth_market.collateral_decimals,
+		collateral_price,
+		debt_amount,
+		synth_market.decimals,
+		synthetic_price
+	)?;
+
+	validate!(
+		collateral_ratio_before < deleverage_order.trigger_collateral_ratio.cast()?,
+		ErrorCode::DeleverageTriggerNotReached,
+		"vault collateral ratio {} >= trigger {}",
+		collateral_ratio_before,
+		deleverage_order.trigger_collateral_ratio
+	)?;
+
+	let tip_rate = calculate_deleverage_tip_rate(
+		deleverage_order.keeper_tip,
+		synth_market.liquidation_penalty
+	)?;
+
+	let debt_in_collateral = calculate_redemption_collateral(
+		debt_amount,
+		synth_market.decimals,
+		synthetic_price,
+		synth_market.collateral_decimals,
+		collateral_price
+	)?;
+
+	let collateral_to_deleverage = calculate_deleverage_collateral_to_sell(
+		total_collateral_amount,
+		debt_in_collateral,
+		deleverage_order.target_collateral_ratio,
+		tip_rate
+	)?;
+
+	// the amm only buys the primary collateral, so the sale and its tip have to fit in it
+	let collateral_to_sell: u64 = collateral_to_deleverage
+		.min(
+			collateral_amount
+				.safe_mul(PERCENTAGE_PRECISION)?
+				.safe_div(PERCENTAGE_PRECISION.safe_add(tip_rate.cast()?)?)?
+		)
+		.cast()?;
+
+	let fill = if collateral_to_sell > 0 {
+		let min_synthetic_out = calculate_deleverage_min_synthetic_out(
+			collateral_to_sell,
+			synth_market.collateral_decimals,
+			collateral_price,
+			synth_market.decimals,
+			synthetic_price,
+			deleverage_order.max_slippage
+		)?;
+
+		let tick_arrays = vec![
+			ctx.accounts.tick_array_0.to_account_info(),
+			ctx.accounts.tick_array_1.to_account_info(),
+			ctx.accounts.tick_array_2.to_account_info()
+		];
+		let builder = SparseSwapTickSequenceBuilder::try_from(
+			&ctx.accounts.amm,
+			false,
+			tick_arrays,
+			None
+		)?;
+
+		let fill = {
+			let mut adapter = AmmSwapAdapter {
+				amm: &mut ctx.accounts.amm,
+				swap_tick_sequence: builder.build()?,
+				timestamp: to_timestamp_u64(now)?,
+			};
+
+			adapter.sell_collateral(collateral_to_sell, min_synthetic_out)?
+		};
+
+		match fill {
+			Some(fill) => {
+				load_mut!(ctx.accounts.amm_observations)?.write(
+					ctx.accounts.amm.get_observation(to_timestamp_u64(now)?)
+				);
+				fill
+			}
+			None => {
+				msg!(
+					"amm can't buy {} synthetic for {} collateral",
+					min_synthetic_out,
+					collateral_to_sell
+				);
+				return Err(ErrorCode::DeleverageNoFill.into());
+			}
+		}
+	} else {
+		ExternalFill::default()
+	};
+
+	let keeper_tip = calculate_deleverage_keeper_tip(fill.collateral_sold, tip_rate)?;
+	let collateral_removed = fill.collateral_sold.safe_add(keeper_tip)?;
+
+	// synthetic bought beyond the debt goes back to the owner
+	let synthetic_repaid = fill.synthetic_received.min(debt_amount.cast()?);
+	let synthetic_excess = fill.synthetic_received.safe_sub(synthetic_repaid)?;
+
+	// what the primary collateral couldn't cover is settled with registry collateral at oracle
+	// price. It goes to the insurance fund, which backs the debt it covers
+	let debt_unfunded = calculate_redemption_collateral(
+		collateral_to_deleverage.saturating_sub(collateral_to_sell.cast()?),
+		synth_market.collateral_decimals,
+		collateral_price,
+		synth_market.decimals,
+		synthetic_price
+	)?.min(debt_amount.safe_sub(synthetic_repaid.cast()?)?);
+	let (collateral_assets_seized, synthetic_covered) =
+		controller::collateral::seize_collateral_assets(
+			position,
+			synth_market,
+			debt_unfunded,
+			synthetic_price,
+			&collateral_asset_prices
+		)?;
+
+	let debt_removed = synthetic_repaid.cast::<u128>()?.safe_add(synthetic_covered)?;
+
+	validate!(
+		debt_removed > 0,
+		ErrorCode::DeleverageNoFill,
+		"vault in market {} has nothing to deleverage with",
+		market_index
+	)?;
+
+	let scaled_debt_delta = calculate_scaled_balance_delta(
+		position.scaled_debt,
+		debt_amount,
+		debt_removed
+	)?;
+	let scaled_collateral_delta = calculate_scaled_balance_delta(
+		position.scaled_balance,
+		collateral_amount,
+		collateral_removed.cast()?
+	)?;
+
+	let scaled_debt_before = position.scaled_debt;
+	position.scaled_debt = position.scaled_debt.safe_sub(scaled_debt_delta)?;
+	position.scaled_balance = position.scaled_balance.safe_sub(scaled_collateral_delta)?;
+
+	synth_market.debt_balance = synth_market.debt_balance.safe_sub(scaled_debt_delta.cast()?)?;
+	synth_market.collateral_balance = synth_market.collateral_balance.safe_sub(
+		scaled_collateral_delta.cast()?
+	)?;
+	controller::synth_balance::update_number_of_vaults_with_debt(
+		synth_market,
+		scaled_debt_before,
+		position.scaled_debt
+	)?;
+	controller::synth_balance::update_vault_redemption_bucket(synth_market, position)?;
+	synth_market.outstanding_debt = synth_market.outstanding_debt.safe_sub(
+		synthetic_repaid.cast()?
+	)?;
+
+	let collateral_assets_amount_after =
+		controller::collateral::get_collateral_assets_in_collateral(
+			position,
+			synth_market,
+			&collateral_asset_prices,
+			collateral_price
+		)?;
+	let collateral_ratio_after = calculate_collateral_ratio(
+		collateral_amount
+			.safe_sub(collateral_removed.cast()?)?
+			.safe_add(collateral_assets_amount_after)?,
+		synth_market.collateral_decimals,
+		collateral_price,
+		debt_amount.safe_sub(debt_removed)?,
+		synth_market.decimals,
+		synthetic_price
+	)?;
+
+	validate!(
+		collateral_ratio_after > collateral_ratio_before,
+		ErrorCode::DeleverageHealthNotImproved,
+		"vault collateral ratio {} -> {}",
+		collateral_ratio_before,
+		collateral_ratio_after
+	)?;
+
+	deleverage_order.last_execution_ts = now;
+
+	if fill.collateral_sold > 0 {
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.amm_token_vault_quote,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			fill.collateral_sold,
+			&None
+		)?;
+	}
+
+	if keeper_tip > 0 {
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.keeper_collateral_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			keeper_tip,
+			&None
+		)?;
+	}
+
+	// the amm's vaults are owned by the amm, the synthetic bought is settled into the market's
+	// synthetic vault and burnt or returned from there
+	if fill.synthetic_received > 0 {
+		controller::token::send_from_pda_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.amm_token_vault_synthetic,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.amm.to_account_info(),
+			&ctx.accounts.amm.seeds(),
+			fill.synthetic_received
+		)?;
+	}
+
+	if synthetic_repaid > 0 {
+		controller::token::burn_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.synthetic_mint,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_repaid
+		)?;
+	}
+
+	if synthetic_excess > 0 {
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.owner_synthetic_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			synthetic_excess,
+			&None
+		)?;
+	}
+
+	// registry vaults and the insurance fund's vaults follow the registry oracles
+	controller::collateral::send_collateral_assets_to_insurance_fund(
+		remaining_accounts_iter,
+		synth_market,
+		&collateral_assets_seized,
+		&ctx.accounts.token_program,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
+	)?;
+
+	emit!(VaultDeleverageRecord {
+		ts: now,
+		market_index,
+		user: user_key,
+		keeper: ctx.accounts.keeper.key(),
+		collateral_sold: fill.collateral_sold,
+		synthetic_repaid,
+		keeper_tip,
+		collateral_ratio_before,
+		collateral_ratio_after,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::constraints::*;
+use crate::state::deleverage::DeleverageOrder;
+use crate::state::traits::Size;
+use crate::state::user::User;
+use crate::validation::deleverage::validate_deleverage_order;
+use crate::State;
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeVaultDeleverageOrder<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub authority: Signer<'info>,
+	#[account(constraint = can_sign_for_user(&user, &authority)?)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		init,
+		seeds = [b"deleverage_order", user.key().as_ref(), market_index.to_le_bytes().as_ref()],
+		space = DeleverageOrder::SIZE,
+		bump,
+		payer = authority
+	)]
+	pub deleverage_order: AccountLoader<'info, DeleverageOrder>,
+	pub rent: Sysvar<'info, Rent>,
+	pub system_program: Program<'info, System>,
+}
+
+/// Lets keepers sell collateral from the user's vault in `market_index` once its collateral
+/// ratio drops below `trigger_collateral_ratio`, restoring `target_collateral_ratio`
+pub fn handle_initialize_vault_deleverage_order(
+	ctx: Context<InitializeVaultDeleverageOrder>,
+	market_index: u16,
+	trigger_collateral_ratio: u64,
+	target_collateral_ratio: u64,
+	max_slippage: u32,
+	keeper_tip: u32
+) -> Result<()> {
+	// the vault has to exist to be protected
+	ctx.accounts.user.load()?.get_position(market_index)?;
+
+	validate_deleverage_order(
+		trigger_collateral_ratio,
+		target_collateral_ratio,
+		max_slippage,
+		keeper_tip
+	)?;
+
+	let deleverage_order = &mut ctx.accounts.deleverage_order.load_init()?;
+
+	**deleverage_order = DeleverageOrder {
+		user: ctx.accounts.user.key(),
+		trigger_collateral_ratio,
+		target_collateral_ratio,
+		last_execution_ts: 0,
+		max_slippage,
+		keeper_tip,
+		market_index,
+		padding: [0; 14],
+	};
+
+	Ok(())
+}
@@ -1,16 +1,23 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
 use synth_market_map::{ get_writable_market_set, MarketSet };
 use vault::Vault;
 use synth_market::VaultsConfig;
 use vault_map::get_writable_vault_set;
 use crate::instructions::constraints::*;
+use crate::controller::lending_adapter::OptionalLendingAccounts;
+use crate::error::ErrorCode;
+use crate::state::auction::Auction;
+use crate::state::traits::Size;
 
 use crate::instructions::optional_accounts::load_maps;
-use crate::{ controller, load_mut, state::*, validate };
+use crate::{ controller, load, load_mut, state::*, validate };
 
 #[derive(Accounts)]
+#[instruction(vault_index: u16)]
 pub struct LiquidateVault<'info> {
 	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
 	pub authority: Signer<'info>,
 	#[account(
         mut,
@@ -29,6 +36,41 @@ pub struct LiquidateVault<'info> {
         constraint = is_stats_for_user(&user, &user_stats)?
     )]
 	pub user_stats: AccountLoader<'info, UserStats>,
+	#[account(
+		init,
+		seeds = [b"auction", user.key().as_ref(), vault_index.to_le_bytes().as_ref()],
+		space = Auction::SIZE,
+		bump,
+		payer = authority
+	)]
+	pub auction: AccountLoader<'info, Auction>,
+	#[account(mut)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut)]
+	pub token_vault_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub system_program: Program<'info, System>,
+
+	// Lending venue, used to unlend collateral when the market vault can't cover the lot.
+	// Checked against the market's lending config in the handler
+	pub collateral_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	#[account(executable)]
+	/// CHECK: checked in the handler
+	pub lending_program: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_reserve: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_obligation: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: Option<AccountInfo<'info>>,
+	/// CHECK: checked in the handler
+	pub lending_signer: Option<AccountInfo<'info>>,
 }
 
 #[access_control(liq_not_paused(&ctx.accounts.state))]
@@ -49,12 +91,13 @@ pub fn handle_liquidate_vault<'c: 'info, 'info>(
 	validate!(user_key != liquidator_key, ErrorCode::UserCantLiquidateThemself)?;
 
 	let user = &mut load_mut!(ctx.accounts.user)?;
-	let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
-	let liquidator = &mut load_mut!(ctx.accounts.liquidator)?;
-	let liquidator_stats = &mut load_mut!(ctx.accounts.liquidator_stats)?;
+	let liquidator = load!(ctx.accounts.liquidator)?;
+	let auction_key = ctx.accounts.auction.key();
+	let auction = &mut ctx.accounts.auction.load_init()?;
 
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
 	let AccountMaps { market_map, vault_map, mut oracle_map } = load_maps(
-		&mut ctx.remaining_accounts.iter().peekable(),
+		remaining_accounts_iter,
 		&get_writable_vault_set(vault_index),
 		&MarketSet::new(),
 		&MarketSet::new(),
@@ -62,22 +105,79 @@ pub fn handle_liquidate_vault<'c: 'info, 'info>(
 		Some(state.oracle_guard_rails)
 	)?;
 
-	controller::liquidation::liquidate_vault(
+	// liquidate_vault fails rather than open an empty auction, so there is always a lot to seize
+	let collateral_assets_seized = controller::liquidation::liquidate_vault(
 		vault_index,
 		liquidator_max_base_asset_amount,
 		limit_price,
 		user,
 		&user_key,
-		user_stats,
-		liquidator,
+		&liquidator,
 		&liquidator_key,
-		liquidator_stats,
 		&market_map,
 		&vault_map,
 		&mut oracle_map,
 		slot,
 		now,
-		state
+		state,
+		auction,
+		&auction_key
+	)?;
+
+	let market = market_map.get_ref(&auction.market_index)?;
+
+	validate!(
+		ctx.accounts.market_vault.key() == market.vault &&
+			ctx.accounts.token_vault_collateral.key() ==
+				market.token_vault_collateral,
+		ErrorCode::InvalidLiquidation,
+		"market vaults dont match market {}",
+		market.market_index
+	)?;
+
+	drop(market);
+
+	// collateral lent out is pulled back so the lot can always be seized
+	let lending_accounts = OptionalLendingAccounts {
+		lending_program: ctx.accounts.lending_program.as_ref(),
+		lending_reserve: ctx.accounts.lending_reserve.as_ref(),
+		lending_obligation: ctx.accounts.lending_obligation.as_ref(),
+		lending_vault: ctx.accounts.lending_vault.as_ref(),
+		collateral_mint: ctx.accounts.collateral_mint.as_ref().map(|mint| mint.to_account_info()),
+		lending_signer: ctx.accounts.lending_signer.as_ref(),
+	};
+	controller::lending_adapter::unlend_market_vault_shortfall(
+		&lending_accounts,
+		&mut ctx.accounts.market_vault,
+		&mut market_map.get_ref_mut(&auction.market_index)?,
+		auction.lot_amount,
+		&ctx.accounts.normal_signer,
+		&ctx.accounts.token_program,
+		state.signer_nonce,
+		now
+	)?;
+
+	// seized collateral is held for the auction until it is bid on or settled
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.market_vault,
+		&ctx.accounts.token_vault_collateral,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		auction.lot_amount,
+		&None
+	)?;
+
+	// registry collateral seized for debt the primary collateral couldn't cover goes to the
+	// insurance fund. Its vaults and the insurance fund's vaults follow the maps in the remaining
+	// accounts
+	controller::collateral::send_collateral_assets_to_insurance_fund(
+		remaining_accounts_iter,
+		&market_map.get_ref(&vault_index)?,
+		&collateral_assets_seized,
+		&ctx.accounts.token_program,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
 	)?;
 
 	Ok(())
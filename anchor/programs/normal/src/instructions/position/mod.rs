@@ -1,3 +1,9 @@
 pub mod collateral;
 pub mod liquidate_position;
 pub mod resolve_position_bankruptcy;
+pub mod settle_expired_vault;
+pub mod initialize_vault_deleverage_order;
+pub mod update_vault_deleverage_order;
+pub mod delete_vault_deleverage_order;
+pub mod execute_vault_deleverage;
+pub mod repay_synthetic_debt;
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::state::synth_market::SynthMarket;
+use crate::state::user::User;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct RepaySyntheticDebt<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		has_one = authority
+	)]
+	pub user: AccountLoader<'info, User>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = authority
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Burns up to `amount` synthetic against the user's vault debt in `market_index`, accrued
+/// stability fee included. Only the debt outstanding is burned
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_repay_synthetic_debt<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RepaySyntheticDebt<'info>>,
+	market_index: u16,
+	amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+
+	validate!(amount > 0, ErrorCode::InvalidRedemptionAmount, "amount must be positive")?;
+
+	let user = &mut load_mut!(ctx.accounts.user)?;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	// the stability fee accrues up to now before the debt is repaid
+	controller::synth_balance::update_synth_market_cumulative_interest(synth_market, None, now)?;
+
+	let repaid = controller::synth_balance::decrease_position_debt(
+		amount,
+		synth_market,
+		user.get_position_mut(market_index)?
+	)?;
+
+	validate!(repaid > 0, ErrorCode::InvalidRedemptionAmount, "no debt to repay in market {}", market_index)?;
+
+	// the accrued fee was never counted as minted
+	synth_market.outstanding_debt = synth_market.outstanding_debt.saturating_sub(repaid.cast()?);
+
+	controller::token::burn(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.authority.to_account_info(),
+		repaid
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::controller::lending_adapter::OptionalLendingAccounts;
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::redemption::calculate_redemption_collateral;
+use crate::math::safe_math::SafeMath;
+use crate::math::settlement::{
+	calculate_settlement_debt_in_collateral,
+	calculate_vault_settlement,
+};
+use crate::state::events::VaultSettlementRecord;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::state::user::User;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct SettleExpiredVault<'info> {
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.vault)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(address = synth_market.load()?.token_mint_collateral)]
+	pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = collateral_mint,
+		token::authority = user.load()?.authority
+	)]
+	pub owner_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+
+	// Lending venue, used to unlend collateral when the market vault can't cover the excess.
+	// Checked against the market's lending config in the handler
+	#[account(executable)]
+	/// CHECK: checked in the handler
+	pub lending_program: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_reserve: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_obligation: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: Option<AccountInfo<'info>>,
+	/// CHECK: checked in the handler
+	pub lending_signer: Option<AccountInfo<'info>>,
+}
+
+/// Settles a vault of a market in Settlement: collateral covering its debt at the frozen
+/// prices moves to the holders' settlement pool and the excess goes back to the owner.
+/// Registry collateral seized for a shortfall is held for the holders too. Permissionless so
+/// keepers can clear every vault before holders redeem. Remaining accounts are the registry
+/// oracles
+pub fn handle_settle_expired_vault<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, SettleExpiredVault<'info>>,
+	market_index: u16
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+
+	let user_key = ctx.accounts.user.key();
+	let user = &mut load_mut!(ctx.accounts.user)?;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Settlement,
+		ErrorCode::SynthMarketNotInSettlement,
+		"market {} is not in settlement",
+		market_index
+	)?;
+
+	let position = user.get_position_mut(market_index)?;
+
+	// a settled position is emptied, settling it again would count it out twice
+	validate!(
+		!position.is_available(),
+		ErrorCode::UserHasNoPositionInMarket,
+		"user {} has no open vault in market {}",
+		user_key,
+		market_index
+	)?;
+
+	let collateral_amount = position.get_collateral_amount(synth_market)?;
+	let debt_amount = position.get_debt_amount(synth_market)?;
+
+	let debt_in_collateral = calculate_settlement_debt_in_collateral(
+		debt_amount,
+		synth_market.decimals,
+		synth_market.expiry_price,
+		ctx.accounts.collateral_mint.decimals.cast()?,
+		synth_market.expiry_collateral_price
+	)?;
+
+	let settlement = calculate_vault_settlement(collateral_amount, debt_in_collateral)?;
+
+	// the vault's registry collateral covers what its primary collateral was short, and is held
+	// in the registry vaults for synthetic holders to redeem. The rest stays in the vault for the
+	// owner to withdraw. Registry oracles are the remaining accounts
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let mut oracle_map = OracleMap::load(
+		remaining_accounts_iter,
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let uncovered_debt = calculate_redemption_collateral(
+		settlement.deficit,
+		synth_market.collateral_decimals,
+		synth_market.expiry_collateral_price,
+		synth_market.decimals,
+		synth_market.expiry_price
+	)?;
+	let collateral_asset_prices = controller::collateral::get_collateral_asset_prices(
+		position,
+		synth_market,
+		&mut oracle_map
+	)?;
+	let (collateral_assets_seized, debt_covered) = controller::collateral::seize_collateral_assets(
+		position,
+		synth_market,
+		uncovered_debt,
+		synth_market.expiry_price,
+		&collateral_asset_prices
+	)?;
+	for (i, amount) in collateral_assets_seized.iter().enumerate() {
+		let collateral = &mut synth_market.collateral_assets[i];
+		collateral.settlement_pool = collateral.settlement_pool.safe_add(*amount)?;
+	}
+
+	// only what the registry collateral couldn't cover is left short
+	let deficit = settlement.deficit.saturating_sub(
+		calculate_settlement_debt_in_collateral(
+			debt_covered,
+			synth_market.decimals,
+			synth_market.expiry_price,
+			ctx.accounts.collateral_mint.decimals.cast()?,
+			synth_market.expiry_collateral_price
+		)?
+	);
+
+	synth_market.collateral_balance = synth_market.collateral_balance.safe_sub(
+		position.scaled_balance.cast()?
+	)?;
+	synth_market.debt_balance = synth_market.debt_balance.safe_sub(
+		position.scaled_debt.cast()?
+	)?;
+	let scaled_debt_before = position.scaled_debt;
+	position.scaled_balance = 0;
+	position.scaled_debt = 0;
+	controller::synth_balance::update_number_of_vaults_with_debt(
+		synth_market,
+		scaled_debt_before,
+		0
+	)?;
+	controller::synth_balance::update_vault_redemption_bucket(synth_market, position)?;
+
+	synth_market.settlement_pool = synth_market.settlement_pool.safe_add(
+		settlement.collateral_to_pool.cast()?
+	)?;
+	synth_market.settlement_deficit = synth_market.settlement_deficit.safe_add(
+		deficit.cast()?
+	)?;
+	synth_market.number_of_users = synth_market.number_of_users.saturating_sub(1);
+
+	if settlement.excess_collateral > 0 {
+		// collateral lent out is pulled back so the owner's excess can always be paid
+		let lending_accounts = OptionalLendingAccounts {
+			lending_program: ctx.accounts.lending_program.as_ref(),
+			lending_reserve: ctx.accounts.lending_reserve.as_ref(),
+			lending_obligation: ctx.accounts.lending_obligation.as_ref(),
+			lending_vault: ctx.accounts.lending_vault.as_ref(),
+			collateral_mint: Some(ctx.accounts.collateral_mint.to_account_info()),
+			lending_signer: ctx.accounts.lending_signer.as_ref(),
+		};
+		controller::lending_adapter::unlend_market_vault_shortfall(
+			&lending_accounts,
+			&mut ctx.accounts.market_vault,
+			synth_market,
+			settlement.excess_collateral.cast()?,
+			&ctx.accounts.normal_signer,
+			&ctx.accounts.token_program,
+			state.signer_nonce,
+			now
+		)?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.owner_collateral_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			settlement.excess_collateral.cast()?,
+			&None
+		)?;
+	}
+
+	emit!(VaultSettlementRecord {
+		ts: now,
+		market_index,
+		user: user_key,
+		debt_amount,
+		collateral_amount,
+		collateral_to_pool: settlement.collateral_to_pool,
+		excess_collateral: settlement.excess_collateral,
+		deficit,
+	});
+
+	Ok(())
+}
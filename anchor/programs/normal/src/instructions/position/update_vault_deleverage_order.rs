@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::constraints::*;
+use crate::state::deleverage::DeleverageOrder;
+use crate::state::user::User;
+use crate::validation::deleverage::validate_deleverage_order;
+use crate::load_mut;
+
+#[derive(Accounts)]
+pub struct UpdateVaultDeleverageOrder<'info> {
+	pub authority: Signer<'info>,
+	#[account(constraint = can_sign_for_user(&user, &authority)?)]
+	pub user: AccountLoader<'info, User>,
+	#[account(
+		mut,
+		constraint = deleverage_order.load()?.user == user.key()
+	)]
+	pub deleverage_order: AccountLoader<'info, DeleverageOrder>,
+}
+
+pub fn handle_update_vault_deleverage_order(
+	ctx: Context<UpdateVaultDeleverageOrder>,
+	trigger_collateral_ratio: u64,
+	target_collateral_ratio: u64,
+	max_slippage: u32,
+	keeper_tip: u32
+) -> Result<()> {
+	validate_deleverage_order(
+		trigger_collateral_ratio,
+		target_collateral_ratio,
+		max_slippage,
+		keeper_tip
+	)?;
+
+	let deleverage_order = &mut load_mut!(ctx.accounts.deleverage_order)?;
+
+	deleverage_order.trigger_collateral_ratio = trigger_collateral_ratio;
+	deleverage_order.target_collateral_ratio = target_collateral_ratio;
+	deleverage_order.max_slippage = max_slippage;
+	deleverage_order.keeper_tip = keeper_tip;
+
+	Ok(())
+}
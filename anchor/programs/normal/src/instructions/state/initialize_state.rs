@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::TokenInterface;
+use anchor_spl::token_interface::{ Mint, TokenInterface };
 
-use crate::{ state::synth_market::AuctionConfig, State };
+use crate::constants::main::DEFAULT_MINT_LIMIT_WINDOW;
+use crate::{ state::synth_market::{ AuctionConfig, MintLimiter }, State };
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -24,6 +25,8 @@ pub struct Initialize<'info> {
 		token::authority = drift_signer
 	)]
 	pub index_fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mint::authority = normal_signer)]
+	pub gov_token_mint: Box<InterfaceAccount<'info, Mint>>,
 	/// CHECK: checked in `initialize`
 	pub normal_signer: AccountInfo<'info>,
 	pub oracle: AccountInfo<'info>,
@@ -59,7 +62,10 @@ pub fn handle_initialize_state(
 		max_number_of_sub_accounts: 0,
 		max_initialize_user_fee: 0,
 		total_debt_ceiling,
+		mint_limiter: MintLimiter::new(DEFAULT_MINT_LIMIT_WINDOW),
 		debt_auction_config: AuctionConfig::default(),
+		surplus_auction_config: AuctionConfig::default(),
+		gov_token_mint: ctx.accounts.gov_token_mint.key(),
 		insurance_fund: *ctx.accounts.insurance_fund.key,
 		emergency_oracles: [], // TODO: fix
 		default_index_oracle: *ctx.accounts.oracle.key,
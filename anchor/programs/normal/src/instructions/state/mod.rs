@@ -8,6 +8,9 @@ pub mod update_state_max_initialize_user_fee;
 pub mod update_state_max_number_of_sub_accounts;
 pub mod update_state_exchange_status;
 pub mod update_state_protocol_index_fee;
+pub mod update_state_mint_limit;
+pub mod update_state_debt_auction_config;
+pub mod update_state_surplus_auction_config;
 
 #[derive(Accounts)]
 pub struct AdminUpdateState<'info> {
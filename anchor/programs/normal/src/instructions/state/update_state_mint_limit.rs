@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::validate;
+
+use super::AdminUpdateState;
+
+pub fn handle_update_state_mint_limit(
+	ctx: Context<AdminUpdateState>,
+	max_mint_per_window: u64,
+	mint_window: i64
+) -> Result<()> {
+	validate!(mint_window > 0, ErrorCode::InvalidMintLimit, "mint_window must be positive")?;
+
+	let mint_limiter = &mut ctx.accounts.state.mint_limiter;
+
+	msg!(
+		"mint_limiter.max_mint_per_window: {} -> {}",
+		mint_limiter.max_mint_per_window,
+		max_mint_per_window
+	);
+
+	msg!("mint_limiter.window: {} -> {}", mint_limiter.window, mint_window);
+
+	mint_limiter.max_mint_per_window = max_mint_per_window;
+	mint_limiter.window = mint_window;
+	Ok(())
+}
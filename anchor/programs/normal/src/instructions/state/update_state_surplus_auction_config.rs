@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::AUCTION_BID_DECREASE_RATE_PRECISION;
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::validate;
+
+use super::AdminUpdateState;
+
+pub fn handle_update_state_surplus_auction_config(
+	ctx: Context<AdminUpdateState>,
+	auction_duration: u16,
+	auction_bid_decrease_rate: u16,
+	max_auction_lot_size: u64
+) -> Result<()> {
+	validate!(
+		auction_duration > 0 &&
+			auction_bid_decrease_rate > 0 &&
+			auction_bid_decrease_rate.cast::<u128>()? <= AUCTION_BID_DECREASE_RATE_PRECISION,
+		ErrorCode::InvalidAuctionConfig,
+		"auction_duration={} and auction_bid_decrease_rate={} must be positive, rate <= {}",
+		auction_duration,
+		auction_bid_decrease_rate,
+		AUCTION_BID_DECREASE_RATE_PRECISION
+	)?;
+
+	// a max_auction_lot_size of 0 auctions the whole surplus at once
+
+	let config = &mut ctx.accounts.state.surplus_auction_config;
+
+	msg!("surplus_auction_config.auction_duration: {} -> {}", config.auction_duration, auction_duration);
+	msg!(
+		"surplus_auction_config.auction_bid_decrease_rate: {} -> {}",
+		config.auction_bid_decrease_rate,
+		auction_bid_decrease_rate
+	);
+	msg!(
+		"surplus_auction_config.max_auction_lot_size: {} -> {}",
+		config.max_auction_lot_size,
+		max_auction_lot_size
+	);
+
+	config.auction_duration = auction_duration;
+	config.auction_bid_decrease_rate = auction_bid_decrease_rate;
+	config.max_auction_lot_size = max_auction_lot_size;
+	Ok(())
+}
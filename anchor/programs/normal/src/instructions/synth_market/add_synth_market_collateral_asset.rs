@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::state::collateral::{ validate_collateral_weights, Collateral };
+use crate::state::oracle::{ get_oracle_price, OracleSource };
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::SynthMarket;
+use crate::{ load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16, asset_index: u8)]
+pub struct AddSynthMarketCollateralAsset<'info> {
+	#[account(mut)]
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		init,
+		seeds = [
+			b"collateral_vault".as_ref(),
+			market_index.to_le_bytes().as_ref(),
+			asset_index.to_le_bytes().as_ref(),
+		],
+		bump,
+		payer = admin,
+		token::mint = collateral_mint,
+		token::authority = normal_signer
+	)]
+	pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	/// CHECK: checked in `handle_add_synth_market_collateral_asset`
+	pub oracle: AccountInfo<'info>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub rent: Sysvar<'info, Rent>,
+	pub system_program: Program<'info, System>,
+}
+
+/// Registers an additional collateral asset in the market's collateral registry
+pub fn handle_add_synth_market_collateral_asset(
+	ctx: Context<AddSynthMarketCollateralAsset>,
+	_market_index: u16,
+	asset_index: u8,
+	oracle_source: OracleSource,
+	initial_asset_weight: u32,
+	maintenance_asset_weight: u32,
+	max_token_deposits: u64
+) -> Result<()> {
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let collateral_mint = ctx.accounts.collateral_mint.key();
+
+	msg!("market {} adding collateral {}", synth_market.market_index, collateral_mint);
+
+	validate_collateral_weights(initial_asset_weight, maintenance_asset_weight)?;
+
+	validate!(
+		collateral_mint != synth_market.token_mint_collateral &&
+			!synth_market.collateral_assets
+				.iter()
+				.any(|collateral| collateral.mint == collateral_mint),
+		ErrorCode::DuplicateCollateralAsset,
+		"collateral {} already backs market {}",
+		collateral_mint,
+		synth_market.market_index
+	)?;
+
+	let slot = synth_market.collateral_assets
+		.get_mut(asset_index as usize)
+		.ok_or(ErrorCode::CollateralRegistryFull)?;
+
+	validate!(
+		!slot.is_active(),
+		ErrorCode::CollateralRegistryFull,
+		"collateral registry slot {} is taken",
+		asset_index
+	)?;
+
+	OracleMap::validate_oracle_account_info(&ctx.accounts.oracle)?;
+	get_oracle_price(&oracle_source, &ctx.accounts.oracle, Clock::get()?.slot)?;
+
+	*slot = Collateral {
+		mint: collateral_mint,
+		vault: ctx.accounts.collateral_vault.key(),
+		oracle: ctx.accounts.oracle.key(),
+		deposit_amount: 0,
+		max_token_deposits,
+		settlement_pool: 0,
+		initial_asset_weight,
+		maintenance_asset_weight,
+		decimals: ctx.accounts.collateral_mint.decimals.cast()?,
+		oracle_source,
+		padding: [0; 3],
+	};
+
+	Ok(())
+}
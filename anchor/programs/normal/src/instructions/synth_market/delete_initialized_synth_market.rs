@@ -28,17 +28,21 @@ pub fn handle_delete_initialized_market(
 
 	// to preserve all protocol invariants, can only remove the last market if it hasn't been "activated"
 
+	// delisted markets have settled every vault and holder, see delist_synth_market.
+	// their index stays reserved so it is never reused
+	let is_delisted = market.status == MarketStatus::Delisted;
+
 	validate!(
-		state.number_of_markets - 1 == market_index,
+		is_delisted || state.number_of_markets - 1 == market_index,
 		ErrorCode::InvalidMarketAccountforDeletion,
 		"state.number_of_markets={} != market_index={}",
 		state.number_of_markets,
 		market_index
 	)?;
 	validate!(
-		market.status == MarketStatus::Initialized,
+		is_delisted || market.status == MarketStatus::Initialized,
 		ErrorCode::InvalidMarketAccountforDeletion,
-		"market.status != Initialized"
+		"market.status != Initialized or Delisted"
 	)?;
 	validate!(
 		market.number_of_users == 0,
@@ -54,7 +58,9 @@ pub fn handle_delete_initialized_market(
 		market.market_index
 	)?;
 
-	safe_decrement!(state.number_of_markets, 1);
+	if !is_delisted {
+		safe_decrement!(state.number_of_markets, 1);
+	}
 
 	Ok(())
 }
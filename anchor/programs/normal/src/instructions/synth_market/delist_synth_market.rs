@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::error::ErrorCode;
+use crate::state::events::SynthMarketSettlementRecord;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::{ load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct DelistSynthMarket<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub keeper: Signer<'info>,
+	#[account(mut)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+/// Moves a settled market to Delisted once every vault is settled and every synthetic
+/// token redeemed, after which the market account can be deleted
+pub fn handle_delist_synth_market(ctx: Context<DelistSynthMarket>) -> Result<()> {
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let now = Clock::get()?.unix_timestamp;
+
+	validate!(
+		synth_market.status == MarketStatus::Settlement,
+		ErrorCode::SynthMarketNotInSettlement,
+		"market {} is not in settlement",
+		synth_market.market_index
+	)?;
+
+	validate!(
+		synth_market.collateral_balance == 0 &&
+			synth_market.debt_balance == 0 &&
+			ctx.accounts.synthetic_mint.supply == 0,
+		ErrorCode::SynthMarketHasOutstandingBalances,
+		"market {} has collateral_balance={} debt_balance={} synthetic supply={}",
+		synth_market.market_index,
+		synth_market.collateral_balance,
+		synth_market.debt_balance,
+		ctx.accounts.synthetic_mint.supply
+	)?;
+
+	msg!("market.status {:?} -> {:?}", synth_market.status, MarketStatus::Delisted);
+
+	synth_market.status = MarketStatus::Delisted;
+
+	emit!(SynthMarketSettlementRecord {
+		ts: now,
+		market_index: synth_market.market_index,
+		status: synth_market.status,
+		expiry_price: synth_market.expiry_price,
+		expiry_collateral_price: synth_market.expiry_collateral_price,
+		settlement_pool: synth_market.settlement_pool,
+		settlement_deficit: synth_market.settlement_deficit,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use solana_program::sysvar::instructions::ID as IX_ID;
+
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::paused_operations::SynthOperation;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::validation::flash_mint::validate_flash_repay_follows;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct FlashMint<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	/// CHECK: checked by address
+	#[account(address = IX_ID)]
+	pub instructions: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Mints `amount` synthetic without collateral. The transaction must burn it again with a
+/// flash_repay later on, or the mint fails
+pub fn handle_flash_mint<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, FlashMint<'info>>,
+	market_index: u16,
+	amount: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+
+	validate!(amount > 0, ErrorCode::InvalidFlashMintAmount, "amount must be positive")?;
+
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Active &&
+			!synth_market.is_operation_paused(SynthOperation::FlashMint),
+		ErrorCode::MarketActionPaused,
+		"flash mint paused for market {}",
+		market_index
+	)?;
+
+	validate!(
+		synth_market.flash_mint_amount == 0,
+		ErrorCode::FlashMintAlreadyOpen,
+		"market {} already has {} flash minted",
+		market_index,
+		synth_market.flash_mint_amount
+	)?;
+
+	// flash mints can't outlive the transaction, so they count against the ceiling in effect
+	// but not the rolling mint limits
+	let now = Clock::get()?.unix_timestamp;
+	let outstanding_debt_after = synth_market.outstanding_debt.safe_add(amount.cast()?)?;
+	let debt_ceiling = synth_market.get_debt_ceiling(now)?;
+
+	validate!(
+		outstanding_debt_after <= debt_ceiling,
+		ErrorCode::DebtCeilingExceeded,
+		"flash minting {} takes market {} debt to {} over the {} ceiling",
+		amount,
+		market_index,
+		outstanding_debt_after,
+		debt_ceiling
+	)?;
+
+	validate_flash_repay_follows(&ctx.accounts.instructions, market_index, amount)?;
+
+	synth_market.flash_mint_amount = amount;
+	synth_market.outstanding_debt = outstanding_debt_after;
+
+	controller::token::mint_from_program(
+		&ctx.accounts.token_program,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		amount
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::math::synth_balance::calculate_flash_mint_fee;
+use crate::state::events::FlashMintRecord;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, validate };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct FlashRepay<'info> {
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = authority
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.token_vault_synthetic
+	)]
+	pub token_vault_synthetic: Box<InterfaceAccount<'info, TokenAccount>>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Burns the open flash mint and pays the flash fee into the market's revenue pool
+pub fn handle_flash_repay<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, FlashRepay<'info>>,
+	market_index: u16,
+	amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.flash_mint_amount > 0 && synth_market.flash_mint_amount == amount,
+		ErrorCode::NoOpenFlashMint,
+		"market {} has {} flash minted, repaying {}",
+		market_index,
+		synth_market.flash_mint_amount,
+		amount
+	)?;
+
+	let fee = calculate_flash_mint_fee(amount, synth_market.flash_mint_fee)?;
+
+	controller::token::burn(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.authority,
+		amount
+	)?;
+
+	if fee > 0 {
+		controller::token::receive(
+			&ctx.accounts.token_program,
+			&ctx.accounts.user_synthetic_account,
+			&ctx.accounts.token_vault_synthetic,
+			&ctx.accounts.authority,
+			fee,
+			&None
+		)?;
+	}
+
+	synth_market.flash_mint_amount = 0;
+	synth_market.outstanding_debt = synth_market.outstanding_debt.safe_sub(amount.cast()?)?;
+	synth_market.revenue_pool = synth_market.revenue_pool.safe_add(fee.cast()?)?;
+
+	emit!(FlashMintRecord {
+		ts: now,
+		market_index,
+		authority: ctx.accounts.authority.key(),
+		amount,
+		fee,
+	});
+
+	Ok(())
+}
@@ -1,71 +1,103 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ErrorCode;
 use crate::instructions::constraints::market_valid;
-use synth_market::{ Market, MarketStatus };
-
-use crate::{ state::*, State };
+use crate::state::events::SynthMarketSettlementRecord;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::{ controller, load_mut, validate, State };
 
 #[derive(Accounts)]
-pub struct FreezeMarketOracle<'info> {
-	#[account(mut)]
+pub struct FreezeSynthMarketOracle<'info> {
 	pub admin: Signer<'info>,
-	#[account(
-        mut,
-        has_one = admin
-    )]
+	#[account(has_one = admin)]
 	pub state: Box<Account<'info, State>>,
-	#[account(
-		init,
-		seeds = [b"market", state.number_of_markets.to_le_bytes().as_ref()],
-		space = Market::SIZE,
-		bump,
-		payer = admin
-	)]
-	pub market: AccountLoader<'info, Market>,
-	/// CHECK: checked in `initialize_perp_market`
+	#[account(mut)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	#[account(address = synth_market.load()?.oracle)]
 	pub oracle: AccountInfo<'info>,
-	pub rent: Sysvar<'info, Rent>,
-	pub system_program: Program<'info, System>,
+	/// CHECK: checked against the market's collateral oracle
+	#[account(address = synth_market.load()?.collateral_oracle)]
+	pub collateral_oracle: AccountInfo<'info>,
 }
 
-#[access_control(market_valid(&ctx.accounts.market))]
-pub fn handle_freeze_market_oracle(
-	ctx: Context<FreezeMarketOracle>,
-	oracle: Pubkey,
-	oracle_source: OracleSource
-) -> Result<()> {
-	let market = &mut load_mut!(ctx.accounts.market)?;
-	msg!("market {}", market.market_index);
+/// Freezes the synthetic and collateral prices once a shut down market reaches its expiry_ts,
+/// moving it into Settlement. Admin only since the frozen prices decide every vault's settlement
+#[access_control(market_valid(&ctx.accounts.synth_market))]
+pub fn handle_freeze_synth_market_oracle(ctx: Context<FreezeSynthMarketOracle>) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+	msg!("market {}", synth_market.market_index);
 
 	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
 
-	OracleMap::validate_oracle_account_info(&ctx.accounts.oracle)?;
+	validate!(
+		synth_market.status == MarketStatus::ReduceOnly && synth_market.expiry_ts != 0,
+		ErrorCode::SynthMarketNotShutdown,
+		"market {} must be shut down before it can settle",
+		synth_market.market_index
+	)?;
 
 	validate!(
-		ctx.accounts.oracle.key == &oracle,
-		ErrorCode::DefaultError,
-		"oracle account info ({:?}) and ix data ({:?}) must match",
-		ctx.accounts.oracle.key,
-		oracle
+		now >= synth_market.expiry_ts,
+		ErrorCode::SynthMarketExpiryNotReached,
+		"market {} expires at {} (now {})",
+		synth_market.market_index,
+		synth_market.expiry_ts,
+		now
 	)?;
 
-	// Verify oracle is readable
-	let OraclePriceData {
-		price: _oracle_price,
-		delay: _oracle_delay,
-		..
-	} = get_oracle_price(&oracle_source, &ctx.accounts.oracle, clock.slot)?;
+	// the last stability fee accrual, debt is fixed from here on
+	controller::synth_balance::update_synth_market_cumulative_interest(synth_market, None, now)?;
 
-	msg!("amm.oracle: {:?} -> {:?}", amm.oracle, oracle);
+	// settlement prices are final, so both oracles must be fully valid
+	let expiry_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.oracle)?,
+		synth_market.market_index,
+		Some(synth_market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+	let expiry_collateral_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.collateral_oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.collateral_oracle)?,
+		synth_market.market_index,
+		None,
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
 
+	msg!("market.status {:?} -> {:?}", synth_market.status, MarketStatus::Settlement);
+	msg!("market.expiry_price {} -> {}", synth_market.expiry_price, expiry_price);
 	msg!(
-		"amm.oracle_source: {:?} -> {:?}",
-		amm.oracle_source,
-		oracle_source
+		"market.expiry_collateral_price {} -> {}",
+		synth_market.expiry_collateral_price,
+		expiry_collateral_price
 	);
 
-	// amm.oracle = oracle;
-	// amm.oracle_source = oracle_source;
+	synth_market.expiry_price = expiry_price;
+	synth_market.expiry_collateral_price = expiry_collateral_price;
+	synth_market.status = MarketStatus::Settlement;
+
+	emit!(SynthMarketSettlementRecord {
+		ts: now,
+		market_index: synth_market.market_index,
+		status: synth_market.status,
+		expiry_price,
+		expiry_collateral_price,
+		settlement_pool: synth_market.settlement_pool,
+		settlement_deficit: synth_market.settlement_deficit,
+	});
 
 	Ok(())
 }
@@ -1,9 +1,27 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{ self, Mint, Token, TokenAccount };
 
-use synth_market::{ AuctionConfig, AuctionPreference, Market, SynthMarket };
+use synth_market::{
+	AmmRebalancer,
+	AuctionConfig,
+	AuctionPreference,
+	Market,
+	MintLimiter,
+	PegStabilityModule,
+	SynthMarket,
+};
 use oracle_map::OracleMap;
 
+use crate::constants::main::{
+	DEFAULT_AUCTION_DURATION,
+	DEFAULT_DEBT_CEILING_RAMP_DURATION,
+	DEFAULT_MINT_LIMIT_WINDOW,
+	MAX_COLLATERAL_ASSETS,
+	REDEMPTION_BUCKETS,
+	SPOT_CUMULATIVE_INTEREST_PRECISION,
+};
+use crate::state::collateral::Collateral;
+use crate::math::casting::Cast;
 use crate::{ state::*, validation::margin::validate_margin, State };
 
 #[derive(Accounts)]
@@ -45,6 +63,8 @@ pub struct InitializeSynthMarket<'info> {
 
 	/// CHECK: checked in `initialize_market`
 	pub oracle: AccountInfo<'info>,
+	/// CHECK: checked in `initialize_market`
+	pub collateral_oracle: AccountInfo<'info>,
 	#[account(address = token::ID)]
 	pub token_program: Program<'info, Token>,
 	pub rent: Sysvar<'info, Rent>,
@@ -60,6 +80,7 @@ pub fn handle_initialize_synth_market(
 
 	// Oracle
 	oracle_source: OracleSource,
+	collateral_oracle_source: OracleSource,
 
 	// Margin
 	margin_ratio_initial: u32,
@@ -207,6 +228,15 @@ pub fn handle_initialize_synth_market(
 				..
 			} = get_sb_on_demand_price(&ctx.accounts.oracle, clock_slot)?;
 
+			(oracle_price, oracle_delay, oracle_price)
+		}
+		OracleSource::IndexNav => {
+			let OraclePriceData {
+				price: oracle_price,
+				delay: oracle_delay,
+				..
+			} = get_index_nav_price(&ctx.accounts.oracle, clock_slot)?;
+
 			(oracle_price, oracle_delay, oracle_price)
 		}
 	};
@@ -217,6 +247,19 @@ pub fn handle_initialize_synth_market(
 		liquidator_fee
 	)?;
 
+	validate!(
+		quote_max_insurance <= synthetic_tier.get_max_quote_insurance(),
+		ErrorCode::InvalidQuoteMaxInsurance,
+		"quote_max_insurance={} above the {:?} tier cap {}",
+		quote_max_insurance,
+		synthetic_tier,
+		synthetic_tier.get_max_quote_insurance()
+	)?;
+
+	// collateral is priced at global settlement
+	OracleMap::validate_oracle_account_info(&ctx.accounts.collateral_oracle)?;
+	get_oracle_price(&collateral_oracle_source, &ctx.accounts.collateral_oracle, clock_slot)?;
+
 	let state = &mut ctx.accounts.state;
 	validate!(
 		market_index == state.number_of_markets,
@@ -242,6 +285,10 @@ pub fn handle_initialize_synth_market(
 		// Oracle
 		oracle: ctx.accounts.oracle.key(),
 		oracle_source,
+		collateral_oracle: ctx.accounts.collateral_oracle.key(),
+		collateral_oracle_source,
+		collateral_decimals: ctx.accounts.token_mint_collateral.decimals.cast()?,
+		collateral_assets: [Collateral::default(); MAX_COLLATERAL_ASSETS],
 
 		// Collateral
 		token_mint_collateral: ctx.accounts.token_mint_collateral.key(),
@@ -256,12 +303,34 @@ pub fn handle_initialize_synth_market(
 		margin_ratio_maintenance,
 		imf_factor,
 		debt_ceiling,
+		target_debt_ceiling: debt_ceiling,
+		debt_ceiling_ramp_start_ts: now,
+		debt_ceiling_ramp_duration: DEFAULT_DEBT_CEILING_RAMP_DURATION,
+		mint_limiter: MintLimiter::new(DEFAULT_MINT_LIMIT_WINDOW),
 		debt_floor,
+
+		// Collateral lending, off until configured
+		lending_program: Pubkey::default(),
+		lending_reserve: Pubkey::default(),
+		lending_obligation: Pubkey::default(),
 		collateral_lending_utilization: 0,
+		max_collateral_lending_utilization: 0,
+		collateral_loan_balance: 0,
+		collateral_loan_shares: 0,
+		cumulative_lp_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+
+		// Stability fee, off until configured
+		cumulative_debt_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+		last_interest_ts: now.cast()?,
+		optimal_utilization: 0,
+		base_stability_fee: 0,
+		optimal_stability_fee: 0,
+		max_stability_fee: 0,
 
 		// Auction
 		collateral_action_config: AuctionConfig {
 			auction_location: collateral_auction_location,
+			auction_duration: DEFAULT_AUCTION_DURATION.cast()?,
 			..AuctionConfig::default()
 		},
 
@@ -278,14 +347,35 @@ pub fn handle_initialize_synth_market(
 		// Metrics
 		outstanding_debt: 0,
 		protocol_debt: 0,
+		revenue_pool: 0,
+		unminted_stability_fees: 0,
+
+		// Flash mint
+		flash_mint_fee: 0,
+		flash_mint_amount: 0,
+
+		// Peg stability module, set up by initialize_synth_market_psm
+		psm: PegStabilityModule::default(),
+
+		// Redemptions
+		redemption_base_rate: 0,
+		last_redemption_ts: 0,
+		// AMM rebalancing, enabled by update_synth_market_amm_rebalancer
+		amm_rebalancer: AmmRebalancer::default(),
 
 		// Market settlement
 		expiry_price: 0,
 		expiry_ts: 0,
+		expiry_collateral_price: 0,
+		settlement_pool: 0,
+		settlement_deficit: 0,
 
 		total_gov_token_inflation: 0,
 
-		padding: [0; 43],
+		number_of_vaults_with_debt: 0,
+		redemption_buckets: [0; REDEMPTION_BUCKETS],
+
+		padding: [0; 11],
 	};
 
 	safe_increment!(state.number_of_markets, 1);
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::{ PERCENTAGE_PRECISION, PRICE_PRECISION_I64 };
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::safe_math::SafeMath;
+use crate::state::oracle::OracleSource;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ PegStabilityModule, SynthMarket };
+use crate::{ load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct InitializeSynthMarketPsm<'info> {
+	#[account(mut)]
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	#[account(address = synth_market.load()?.oracle)]
+	pub oracle: AccountInfo<'info>,
+	pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		init,
+		seeds = [b"psm_reserve", synth_market.load()?.market_index.to_le_bytes().as_ref()],
+		bump,
+		payer = admin,
+		token::mint = quote_mint,
+		token::authority = normal_signer
+	)]
+	pub reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+	pub rent: Sysvar<'info, Rent>,
+	pub system_program: Program<'info, System>,
+}
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_initialize_synth_market_psm(
+	ctx: Context<InitializeSynthMarketPsm>,
+	fee_in: u32,
+	fee_out: u32,
+	max_reserve: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let clock = Clock::get()?;
+
+	msg!("initializing market {} peg stability module", market.market_index);
+
+	validate!(
+		!market.psm.is_initialized(),
+		ErrorCode::PsmAlreadyInitialized,
+		"market {} psm reserve vault already set to {}",
+		market.market_index,
+		market.psm.reserve_vault
+	)?;
+
+	// swaps are at par with the quote mint, so the synthetic must track USD: priced by a
+	// stable coin oracle and currently within 1% of $1
+	let oracle_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&market.oracle)?,
+		market.market_index,
+		Some(market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	validate!(
+		matches!(
+			market.oracle_source,
+			OracleSource::PythStableCoin | OracleSource::PythStableCoinPull
+		) &&
+			oracle_price.safe_sub(PRICE_PRECISION_I64)?.abs() <= PRICE_PRECISION_I64 / 100,
+		ErrorCode::PsmMarketNotUsdPegged,
+		"market {} oracle source {:?} price {} is not USD-pegged",
+		market.market_index,
+		market.oracle_source,
+		oracle_price
+	)?;
+
+	validate!(
+		fee_in.cast::<u128>()? < PERCENTAGE_PRECISION &&
+			fee_out.cast::<u128>()? < PERCENTAGE_PRECISION,
+		ErrorCode::InvalidPsmFee,
+		"psm fees must be < {}",
+		PERCENTAGE_PRECISION
+	)?;
+
+	market.psm = PegStabilityModule {
+		quote_mint: ctx.accounts.quote_mint.key(),
+		reserve_vault: ctx.accounts.reserve_vault.key(),
+		quote_decimals: ctx.accounts.quote_mint.decimals.cast()?,
+		fee_in,
+		fee_out,
+		max_reserve,
+		reserve_balance: 0,
+		fees_accrued: 0,
+	};
+
+	Ok(())
+}
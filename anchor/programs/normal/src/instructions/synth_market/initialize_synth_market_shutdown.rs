@@ -22,9 +22,10 @@ pub fn handle_initialize_market_shutdown(
 
 	VaultOperation::log_all_operations_paused(market.paused_operations);
 
-	// TODO: freeze collateral prices
-
-	// vault owners can withraw any excess collateral if their debt obligations are met
+	// at expiry_ts freeze_synth_market_oracle fixes the settlement prices, then
+	// settle_expired_vault returns each vault's excess collateral to its owner,
+	// redeem_settled_synthetic pays holders out of the settlement pool and
+	// delist_synth_market retires the market once nothing is outstanding
 
 	validate!(
 		clock.unix_timestamp < expiry_ts,
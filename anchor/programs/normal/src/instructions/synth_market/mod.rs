@@ -1,10 +1,26 @@
 use crate::{ state::synth_market::{ SynthMarket }, State };
 
 pub mod initialize_synth_market;
+pub mod add_synth_market_collateral_asset;
+pub mod update_synth_market_collateral_asset;
 pub mod update_synth_market_amm;
+pub mod update_synth_market_amm_rebalancer;
 pub mod update_synth_market_liquidation_penalty;
 pub mod update_synth_market_debt_ceiling;
+pub mod update_synth_market_mint_limit;
 pub mod update_synth_market_debt_floor;
+pub mod update_synth_market_stability_fee;
+pub mod update_synth_market_lending_config;
+pub mod update_synth_market_flash_mint_fee;
+pub mod update_synth_market_collateral_auction_config;
+pub mod update_synth_market_external_swap_program;
+pub mod flash_mint;
+pub mod flash_repay;
+pub mod initialize_synth_market_psm;
+pub mod update_synth_market_psm;
+pub mod withdraw_synth_market_psm_fees;
+pub mod psm_swap_in;
+pub mod psm_swap_out;
 pub mod update_synth_market_margin_ratio;
 pub mod update_synth_market_synthetic_tier;
 pub mod update_synth_market_paused_operations;
@@ -16,6 +32,9 @@ pub mod delete_initialized_synth_market;
 pub mod update_synth_market_imf_factor;
 pub mod update_synth_market_oracle;
 pub mod freeze_synth_market_oracle;
+pub mod redeem_settled_synthetic;
+pub mod redeem_synthetic;
+pub mod delist_synth_market;
 pub mod update_synth_market_number_of_users;
 pub mod update_synth_market_expiry;
 
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::PRICE_PRECISION_I64;
+use crate::error::ErrorCode;
+use crate::math::mint_limit::calculate_mint_value;
+use crate::math::psm::calculate_psm_swap_in;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::{ PsmSwapDirection, PsmSwapRecord };
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct PsmSwapIn<'info> {
+	#[account(mut)]
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.psm.reserve_vault
+	)]
+	pub reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synth_market.load()?.psm.quote_mint,
+		token::authority = authority
+	)]
+	pub user_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Swaps `quote_amount` quote for synthetic at par less the market's psm fee_in
+pub fn handle_psm_swap_in(
+	ctx: Context<PsmSwapIn>,
+	market_index: u16,
+	quote_amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &mut ctx.accounts.state;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.psm.is_initialized(),
+		ErrorCode::PsmNotInitialized,
+		"market {} has no psm",
+		market_index
+	)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Active,
+		ErrorCode::MarketActionPaused,
+		"psm swaps paused for market {}",
+		market_index
+	)?;
+
+	let swap = calculate_psm_swap_in(
+		quote_amount,
+		synth_market.psm.quote_decimals,
+		synth_market.decimals,
+		synth_market.psm.fee_in
+	)?;
+
+	validate!(
+		swap.synthetic_amount > 0,
+		ErrorCode::PsmSwapTooSmall,
+		"{} quote mints no synthetic",
+		quote_amount
+	)?;
+
+	// only the quote backing minted synthetic counts against the cap, fees sit on top
+	let reserve_balance_after = synth_market.psm.reserve_balance.safe_add(
+		quote_amount.safe_sub(swap.fee)?
+	)?;
+
+	validate!(
+		reserve_balance_after <= synth_market.psm.max_reserve,
+		ErrorCode::PsmReserveCapExceeded,
+		"psm reserve {} would exceed market {} cap {}",
+		reserve_balance_after,
+		market_index,
+		synth_market.psm.max_reserve
+	)?;
+
+	// synthetic minted against the reserve is debt like any other
+	synth_market.record_mint(swap.synthetic_amount, now)?;
+	state.mint_limiter.record_mint(
+		calculate_mint_value(swap.synthetic_amount, synth_market.decimals, PRICE_PRECISION_I64)?,
+		now
+	)?;
+
+	synth_market.psm.reserve_balance = reserve_balance_after;
+	synth_market.psm.fees_accrued = synth_market.psm.fees_accrued.safe_add(swap.fee)?;
+
+	controller::token::receive(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_quote_account,
+		&ctx.accounts.reserve_vault,
+		&ctx.accounts.authority,
+		quote_amount,
+		&None
+	)?;
+
+	controller::token::mint_from_program(
+		&ctx.accounts.token_program,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		swap.synthetic_amount
+	)?;
+
+	emit!(PsmSwapRecord {
+		ts: now,
+		market_index,
+		user: ctx.accounts.authority.key(),
+		direction: PsmSwapDirection::QuoteToSynthetic,
+		quote_amount,
+		synthetic_amount: swap.synthetic_amount,
+		fee: swap.fee,
+		reserve_balance_after,
+	});
+
+	Ok(())
+}
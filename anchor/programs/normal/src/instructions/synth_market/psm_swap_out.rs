@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::psm::calculate_psm_swap_out;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::{ PsmSwapDirection, PsmSwapRecord };
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct PsmSwapOut<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		address = synth_market.load()?.psm.reserve_vault
+	)]
+	pub reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synth_market.load()?.psm.quote_mint
+	)]
+	pub user_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = authority
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Swaps `synthetic_amount` synthetic for quote from the reserve at par less the market's
+/// psm fee_out
+pub fn handle_psm_swap_out(
+	ctx: Context<PsmSwapOut>,
+	market_index: u16,
+	synthetic_amount: u64
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let state = &ctx.accounts.state;
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.psm.is_initialized(),
+		ErrorCode::PsmNotInitialized,
+		"market {} has no psm",
+		market_index
+	)?;
+
+	// swaps out stay open while reduce only and in settlement so holders can exit through the
+	// reserve, which backs the psm minted supply the settlement pool leaves out
+	validate!(
+		matches!(
+			synth_market.status,
+			MarketStatus::Active | MarketStatus::ReduceOnly | MarketStatus::Settlement
+		),
+		ErrorCode::MarketActionPaused,
+		"psm swaps paused for market {}",
+		market_index
+	)?;
+
+	let swap = calculate_psm_swap_out(
+		synthetic_amount,
+		synth_market.psm.quote_decimals,
+		synth_market.decimals,
+		synth_market.psm.fee_out
+	)?;
+
+	validate!(
+		swap.quote_amount > 0,
+		ErrorCode::PsmSwapTooSmall,
+		"{} synthetic redeems no quote",
+		synthetic_amount
+	)?;
+
+	// the fee stays in the vault, so the full par value leaves the reserve backing
+	let quote_released = swap.quote_amount.safe_add(swap.fee)?;
+
+	validate!(
+		quote_released <= synth_market.psm.reserve_balance,
+		ErrorCode::PsmInsufficientReserve,
+		"market {} psm reserve {} can't cover {}",
+		market_index,
+		synth_market.psm.reserve_balance,
+		quote_released
+	)?;
+
+	synth_market.psm.reserve_balance = synth_market.psm.reserve_balance.safe_sub(quote_released)?;
+	synth_market.psm.fees_accrued = synth_market.psm.fees_accrued.safe_add(swap.fee)?;
+	synth_market.outstanding_debt = synth_market.outstanding_debt.saturating_sub(
+		synthetic_amount.cast()?
+	);
+
+	controller::token::burn(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.authority,
+		synthetic_amount
+	)?;
+
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.reserve_vault,
+		&ctx.accounts.user_quote_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		swap.quote_amount,
+		&None
+	)?;
+
+	emit!(PsmSwapRecord {
+		ts: now,
+		market_index,
+		user: ctx.accounts.authority.key(),
+		direction: PsmSwapDirection::SyntheticToQuote,
+		quote_amount: swap.quote_amount,
+		synthetic_amount,
+		fee: swap.fee,
+		reserve_balance_after: synth_market.psm.reserve_balance,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::MAX_COLLATERAL_ASSETS;
+use crate::controller::lending_adapter::OptionalLendingAccounts;
+use crate::error::ErrorCode;
+use crate::math::psm::convert_at_par;
+use crate::math::safe_math::SafeMath;
+use crate::math::settlement::calculate_settlement_redemption;
+use crate::state::events::SettlementRedeemRecord;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct RedeemSettledSynthetic<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = authority
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = synth_market.load()?.vault)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = market_vault.mint
+	)]
+	pub user_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+
+	// Lending venue, used to unlend collateral when the market vault can't cover the redemption.
+	// Checked against the market's lending config in the handler
+	pub collateral_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	#[account(executable)]
+	/// CHECK: checked in the handler
+	pub lending_program: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_reserve: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_obligation: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: Option<AccountInfo<'info>>,
+	/// CHECK: checked in the handler
+	pub lending_signer: Option<AccountInfo<'info>>,
+}
+
+/// Burns `amount` synthetic tokens of a settled market for their pro rata share of the
+/// settlement pool and of the registry collateral seized from settled vaults. Synthetic backed
+/// by the psm reserve is left out of the split, as it can still be swapped out at par. Only
+/// opens once every vault has been settled. For every registry slot paying out, the remaining
+/// accounts are the slot's vault then a token account owned by the authority
+pub fn handle_redeem_settled_synthetic<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RedeemSettledSynthetic<'info>>,
+	_market_index: u16,
+	amount: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let now = Clock::get()?.unix_timestamp;
+
+	validate!(amount > 0, ErrorCode::InsufficientCollateral, "amount must be positive")?;
+
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Settlement,
+		ErrorCode::SynthMarketNotInSettlement,
+		"market {} is not in settlement",
+		synth_market.market_index
+	)?;
+
+	// the pool is only final once every vault has paid in
+	validate!(
+		synth_market.number_of_vaults_with_debt == 0,
+		ErrorCode::SynthMarketHasUnsettledVaults,
+		"market {} has {} unsettled vaults",
+		synth_market.market_index,
+		synth_market.number_of_vaults_with_debt
+	)?;
+
+	// synthetic minted through the psm is backed by its reserve, which stays open to swap out of
+	// in settlement, so it doesn't share the pool
+	let psm_backed_synthetic = convert_at_par(
+		synth_market.psm.reserve_balance,
+		synth_market.psm.quote_decimals,
+		synth_market.decimals
+	)?;
+	let settlement_supply = ctx.accounts.synthetic_mint.supply.saturating_sub(psm_backed_synthetic);
+
+	let collateral_amount = calculate_settlement_redemption(
+		amount,
+		synth_market.settlement_pool,
+		settlement_supply
+	)?;
+
+	let mut collateral_assets_amounts = [0_u64; MAX_COLLATERAL_ASSETS];
+	for (i, collateral) in synth_market.collateral_assets.iter_mut().enumerate() {
+		collateral_assets_amounts[i] = calculate_settlement_redemption(
+			amount,
+			collateral.settlement_pool,
+			settlement_supply
+		)?;
+		collateral.settlement_pool = collateral.settlement_pool.safe_sub(
+			collateral_assets_amounts[i]
+		)?;
+	}
+
+	controller::token::burn(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.authority.to_account_info(),
+		amount
+	)?;
+
+	synth_market.settlement_pool = synth_market.settlement_pool.safe_sub(collateral_amount)?;
+
+	if collateral_amount > 0 {
+		// collateral lent out is pulled back so the settlement pool can always be paid
+		let lending_accounts = OptionalLendingAccounts {
+			lending_program: ctx.accounts.lending_program.as_ref(),
+			lending_reserve: ctx.accounts.lending_reserve.as_ref(),
+			lending_obligation: ctx.accounts.lending_obligation.as_ref(),
+			lending_vault: ctx.accounts.lending_vault.as_ref(),
+			collateral_mint: ctx.accounts.collateral_mint.as_ref().map(|mint| mint.to_account_info()),
+			lending_signer: ctx.accounts.lending_signer.as_ref(),
+		};
+		controller::lending_adapter::unlend_market_vault_shortfall(
+			&lending_accounts,
+			&mut ctx.accounts.market_vault,
+			synth_market,
+			collateral_amount,
+			&ctx.accounts.normal_signer,
+			&ctx.accounts.token_program,
+			state.signer_nonce,
+			now
+		)?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.user_collateral_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			collateral_amount,
+			&None
+		)?;
+	}
+
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	controller::collateral::send_collateral_assets(
+		remaining_accounts_iter,
+		synth_market,
+		&collateral_assets_amounts,
+		&ctx.accounts.authority.key(),
+		&ctx.accounts.token_program,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
+	)?;
+
+	emit!(SettlementRedeemRecord {
+		ts: now,
+		market_index: synth_market.market_index,
+		user: ctx.accounts.authority.key(),
+		synthetic_amount: amount,
+		collateral_amount,
+		settlement_pool: synth_market.settlement_pool,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,417 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+
+use crate::constants::main::{ MAX_COLLATERAL_ASSETS, PERCENTAGE_PRECISION, REDEMPTION_BUCKETS };
+use crate::controller::lending_adapter::OptionalLendingAccounts;
+use crate::error::ErrorCode;
+use crate::math::casting::Cast;
+use crate::math::redemption::{
+	calculate_collateral_ratio,
+	calculate_decayed_redemption_base_rate,
+	calculate_redemption_base_rate,
+	calculate_redemption_collateral,
+	calculate_redemption_fee,
+	calculate_redemption_fee_rate,
+	calculate_redeemable_buckets_end,
+	calculate_scaled_balance_delta,
+};
+use crate::math::oracle::get_valid_oracle_price;
+use crate::math::safe_math::SafeMath;
+use crate::state::events::RedemptionRecord;
+use crate::state::oracle_map::OracleMap;
+use crate::state::synth_market::{ MarketStatus, SynthMarket };
+use crate::state::user_map::load_user_map;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct RedeemSynthetic<'info> {
+	pub state: Box<Account<'info, State>>,
+	pub authority: Signer<'info>,
+	#[account(
+		mut,
+		seeds = [b"market", market_index.to_le_bytes().as_ref()],
+		bump
+	)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	/// CHECK: checked against the market's oracle
+	#[account(address = synth_market.load()?.oracle)]
+	pub oracle: AccountInfo<'info>,
+	/// CHECK: checked against the market's collateral oracle
+	#[account(address = synth_market.load()?.collateral_oracle)]
+	pub collateral_oracle: AccountInfo<'info>,
+	#[account(mut, address = synth_market.load()?.mint)]
+	pub synthetic_mint: Box<InterfaceAccount<'info, Mint>>,
+	#[account(
+		mut,
+		token::mint = synthetic_mint,
+		token::authority = authority
+	)]
+	pub user_synthetic_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(mut, address = synth_market.load()?.vault)]
+	pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = market_vault.mint
+	)]
+	pub user_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+
+	// Lending venue, used to unlend collateral when the market vault can't cover the redemption.
+	// Checked against the market's lending config in the handler
+	pub collateral_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+	#[account(executable)]
+	/// CHECK: checked in the handler
+	pub lending_program: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_reserve: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked in the handler
+	pub lending_obligation: Option<AccountInfo<'info>>,
+	#[account(mut)]
+	/// CHECK: checked against the lending reserve by the adapter
+	pub lending_vault: Option<AccountInfo<'info>>,
+	/// CHECK: checked in the handler
+	pub lending_signer: Option<AccountInfo<'info>>,
+}
+
+/// Burns up to `amount` synthetic for collateral at oracle price, repaying the debt of the
+/// market's lowest collateralized vaults first. Vaults are grouped in redemption buckets by
+/// collateral ratio, and every vault of the buckets redeemed from must be passed in the remaining
+/// accounts so none can be skipped. Vaults under 100% collateralized are left for liquidation.
+/// The fee stays in the redeemed vaults as collateral, and fails the redemption if above
+/// `max_fee_rate`. Lending accounts are only needed if the market vault is short
+pub fn handle_redeem_synthetic<'c: 'info, 'info>(
+	ctx: Context<'_, '_, 'c, 'info, RedeemSynthetic<'info>>,
+	market_index: u16,
+	amount: u64,
+	max_fee_rate: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let clock = Clock::get()?;
+	let now = clock.unix_timestamp;
+
+	validate!(amount > 0, ErrorCode::InvalidRedemptionAmount, "amount must be positive")?;
+
+	let redeemer_key = ctx.accounts.authority.key();
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	validate!(
+		synth_market.status == MarketStatus::Active,
+		ErrorCode::MarketActionPaused,
+		"redemptions paused for market {}",
+		market_index
+	)?;
+
+	controller::synth_balance::update_synth_market_cumulative_interest(synth_market, None, now)?;
+
+	// collateral leaves at these prices, so both oracles must be fully valid
+	let synthetic_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.oracle)?,
+		market_index,
+		Some(synth_market.historical_oracle_data.last_oracle_price_twap),
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+	let collateral_price = get_valid_oracle_price(
+		OracleMap::load_one(
+			&ctx.accounts.collateral_oracle,
+			clock.slot,
+			Some(state.oracle_guard_rails)
+		)?.get_price_data(&synth_market.collateral_oracle)?,
+		market_index,
+		None,
+		&state.oracle_guard_rails.validity,
+		None
+	)?;
+
+	// registry oracles, then the vaults, then the registry vaults and redeemer token accounts
+	let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+	let mut oracle_map = OracleMap::load(
+		remaining_accounts_iter,
+		clock.slot,
+		Some(state.oracle_guard_rails)
+	)?;
+	let user_map = load_user_map(remaining_accounts_iter, true)?;
+
+	// (user, bucket, collateral ratio, debt) of every vault that can be redeemed against
+	let mut vaults = Vec::with_capacity(user_map.0.len());
+	let mut passed_buckets = [0_u32; REDEMPTION_BUCKETS];
+	for user_key in user_map.0.keys() {
+		let user = user_map.get_ref(user_key)?;
+		let position = match user.get_position(market_index) {
+			Ok(position) if position.has_debt() => position,
+			_ => {
+				continue;
+			}
+		};
+
+		validate!(
+			position.redemption_bucket > 0,
+			ErrorCode::RedemptionVaultsIncomplete,
+			"vault {} in market {} has debt but no redemption bucket",
+			user_key,
+			market_index
+		)?;
+
+		let bucket = (position.redemption_bucket - 1) as usize;
+		passed_buckets[bucket] = passed_buckets[bucket].safe_add(1)?;
+
+		// registry collateral backs the debt too, so it counts toward the ratio
+		let collateral_asset_prices = controller::collateral::get_collateral_asset_prices(
+			position,
+			synth_market,
+			&mut oracle_map
+		)?;
+		let collateral_amount = position
+			.get_collateral_amount(synth_market)?
+			.safe_add(
+				controller::collateral::get_collateral_assets_in_collateral(
+					position,
+					synth_market,
+					&collateral_asset_prices,
+					collateral_price
+				)?
+			)?;
+
+		let debt_amount = position.get_debt_amount(synth_market)?;
+		let collateral_ratio = calculate_collateral_ratio(
+			collateral_amount,
+			synth_market.collateral_decimals,
+			collateral_price,
+			debt_amount,
+			synth_market.decimals,
+			synthetic_price
+		)?;
+
+		if collateral_ratio < PERCENTAGE_PRECISION {
+			continue;
+		}
+
+		vaults.push((*user_key, bucket, collateral_ratio, debt_amount));
+	}
+
+	// only the lowest buckets whose every vault was passed can be redeemed from, so the caller
+	// only has to pass as many vaults as it redeems through
+	let redeemable_buckets_end = calculate_redeemable_buckets_end(
+		&synth_market.redemption_buckets,
+		&passed_buckets
+	);
+	let lowest_bucket = synth_market.redemption_buckets
+		.iter()
+		.position(|number_of_vaults| *number_of_vaults > 0)
+		.unwrap_or(REDEMPTION_BUCKETS);
+
+	validate!(
+		redeemable_buckets_end > lowest_bucket || redeemable_buckets_end == REDEMPTION_BUCKETS,
+		ErrorCode::RedemptionVaultsIncomplete,
+		"every vault of market {}'s lowest redemption bucket {} must be passed",
+		market_index,
+		lowest_bucket
+	)?;
+
+	vaults.retain(|(_, bucket, _, _)| *bucket < redeemable_buckets_end);
+
+	vaults.sort_by_key(|(_, bucket, collateral_ratio, _)| (*bucket, *collateral_ratio));
+
+	// plan the fill first so the fee rate reflects the total redeemed
+	let mut remaining = amount.cast::<u128>()?;
+	let mut fills = Vec::with_capacity(vaults.len());
+	for (user_key, _, collateral_ratio, debt_amount) in vaults {
+		if remaining == 0 {
+			break;
+		}
+
+		let synthetic_amount = debt_amount.min(remaining);
+		remaining = remaining.safe_sub(synthetic_amount)?;
+		fills.push((user_key, collateral_ratio, synthetic_amount));
+	}
+
+	let redeemed_amount = amount.cast::<u128>()?.safe_sub(remaining)?;
+
+	validate!(
+		redeemed_amount > 0,
+		ErrorCode::NoRedeemableVaults,
+		"no vault in market {} could be redeemed against",
+		market_index
+	)?;
+
+	let decayed_base_rate = calculate_decayed_redemption_base_rate(
+		synth_market.redemption_base_rate,
+		now.safe_sub(synth_market.last_redemption_ts)?
+	)?;
+	let base_rate = calculate_redemption_base_rate(
+		decayed_base_rate,
+		redeemed_amount.cast()?,
+		ctx.accounts.synthetic_mint.supply
+	)?;
+	let fee_rate = calculate_redemption_fee_rate(base_rate)?;
+
+	validate!(
+		fee_rate <= max_fee_rate,
+		ErrorCode::RedemptionFeeExceedsMax,
+		"redemption fee rate {} > max_fee_rate {}",
+		fee_rate,
+		max_fee_rate
+	)?;
+
+	let mut collateral_to_redeemer = 0_u128;
+	let mut collateral_assets_to_redeemer = [0_u64; MAX_COLLATERAL_ASSETS];
+	for (user_key, collateral_ratio_before, synthetic_amount) in fills {
+		let mut user = user_map.get_ref_mut(&user_key)?;
+		let position = user.get_position_mut(market_index)?;
+
+		let collateral_amount = position.get_collateral_amount(synth_market)?;
+		let debt_amount = position.get_debt_amount(synth_market)?;
+
+		let collateral_for_debt = calculate_redemption_collateral(
+			synthetic_amount,
+			synth_market.decimals,
+			synthetic_price,
+			synth_market.collateral_decimals,
+			collateral_price
+		)?;
+		let gross_collateral = collateral_for_debt.min(collateral_amount);
+		let fee = calculate_redemption_fee(gross_collateral, fee_rate)?;
+		let net_collateral = gross_collateral.safe_sub(fee)?;
+
+		// debt the primary collateral can't cover is redeemed from the vault's registry
+		// collateral, net of the same fee
+		if collateral_for_debt > collateral_amount {
+			let uncovered_collateral = collateral_for_debt.safe_sub(collateral_amount)?;
+			let uncovered_collateral_net = uncovered_collateral.safe_sub(
+				calculate_redemption_fee(uncovered_collateral, fee_rate)?
+			)?;
+			let collateral_asset_prices = controller::collateral::get_collateral_asset_prices(
+				position,
+				synth_market,
+				&mut oracle_map
+			)?;
+			let (collateral_assets_seized, _) = controller::collateral::seize_collateral_assets(
+				position,
+				synth_market,
+				calculate_redemption_collateral(
+					uncovered_collateral_net,
+					synth_market.collateral_decimals,
+					collateral_price,
+					synth_market.decimals,
+					synthetic_price
+				)?,
+				synthetic_price,
+				&collateral_asset_prices
+			)?;
+
+			for (total, seized) in collateral_assets_to_redeemer
+				.iter_mut()
+				.zip(collateral_assets_seized.iter()) {
+				*total = total.safe_add(*seized)?;
+			}
+		}
+
+		let scaled_debt_delta = calculate_scaled_balance_delta(
+			position.scaled_debt,
+			debt_amount,
+			synthetic_amount
+		)?;
+		let scaled_collateral_delta = calculate_scaled_balance_delta(
+			position.scaled_balance,
+			collateral_amount,
+			net_collateral
+		)?;
+
+		let scaled_debt_before = position.scaled_debt;
+		position.scaled_debt = position.scaled_debt.safe_sub(scaled_debt_delta)?;
+		position.scaled_balance = position.scaled_balance.safe_sub(scaled_collateral_delta)?;
+
+		synth_market.debt_balance = synth_market.debt_balance.safe_sub(
+			scaled_debt_delta.cast()?
+		)?;
+		synth_market.collateral_balance = synth_market.collateral_balance.safe_sub(
+			scaled_collateral_delta.cast()?
+		)?;
+		controller::synth_balance::update_number_of_vaults_with_debt(
+			synth_market,
+			scaled_debt_before,
+			position.scaled_debt
+		)?;
+		controller::synth_balance::update_vault_redemption_bucket(synth_market, position)?;
+
+		collateral_to_redeemer = collateral_to_redeemer.safe_add(net_collateral)?;
+
+		emit!(RedemptionRecord {
+			ts: now,
+			market_index,
+			redeemer: redeemer_key,
+			user: user_key,
+			synthetic_amount,
+			collateral_amount: net_collateral,
+			fee,
+			collateral_ratio_before,
+			fee_rate,
+		});
+	}
+
+	synth_market.outstanding_debt = synth_market.outstanding_debt.safe_sub(redeemed_amount)?;
+	synth_market.redemption_base_rate = base_rate;
+	synth_market.last_redemption_ts = now;
+
+	controller::token::burn(
+		&ctx.accounts.token_program,
+		&ctx.accounts.user_synthetic_account,
+		&ctx.accounts.synthetic_mint,
+		&ctx.accounts.authority.to_account_info(),
+		redeemed_amount.cast()?
+	)?;
+
+	if collateral_to_redeemer > 0 {
+		// collateral lent out is pulled back so redemptions never wait on the lending venue
+		let lending_accounts = OptionalLendingAccounts {
+			lending_program: ctx.accounts.lending_program.as_ref(),
+			lending_reserve: ctx.accounts.lending_reserve.as_ref(),
+			lending_obligation: ctx.accounts.lending_obligation.as_ref(),
+			lending_vault: ctx.accounts.lending_vault.as_ref(),
+			collateral_mint: ctx.accounts.collateral_mint.as_ref().map(|mint| mint.to_account_info()),
+			lending_signer: ctx.accounts.lending_signer.as_ref(),
+		};
+		controller::lending_adapter::unlend_market_vault_shortfall(
+			&lending_accounts,
+			&mut ctx.accounts.market_vault,
+			synth_market,
+			collateral_to_redeemer.cast()?,
+			&ctx.accounts.normal_signer,
+			&ctx.accounts.token_program,
+			state.signer_nonce,
+			now
+		)?;
+
+		controller::token::send_from_program_vault(
+			&ctx.accounts.token_program,
+			&ctx.accounts.market_vault,
+			&ctx.accounts.user_collateral_account,
+			&ctx.accounts.normal_signer,
+			state.signer_nonce,
+			collateral_to_redeemer.cast()?,
+			&None
+		)?;
+	}
+
+	controller::collateral::send_collateral_assets(
+		remaining_accounts_iter,
+		synth_market,
+		&collateral_assets_to_redeemer,
+		&redeemer_key,
+		&ctx.accounts.token_program,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::state::amm::AMM;
+use crate::state::synth_market::SynthMarket;
+use crate::{ load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct AdminUpdateSynthMarketAmmRebalancer<'info> {
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(mut, address = synth_market.load()?.amm)]
+	pub amm: Box<Account<'info, AMM>>,
+}
+
+/// Sets the caps on protocol owned synthetic liquidity used to rebalance the market's AMM.
+/// Both caps at 0 disables rebalancing; what was already minted can't be bought back until
+/// it is enabled again
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_amm_rebalancer(
+	ctx: Context<AdminUpdateSynthMarketAmmRebalancer>,
+	max_rebalance_per_call: u64,
+	max_rebalance_per_day: u64
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let amm = &mut ctx.accounts.amm;
+
+	msg!("updating market {} amm rebalancer", market.market_index);
+
+	let enabled = max_rebalance_per_call != 0;
+
+	validate!(
+		enabled == (max_rebalance_per_day != 0) &&
+			max_rebalance_per_call <= max_rebalance_per_day,
+		ErrorCode::InvalidAmmRebalanceConfig,
+		"max_rebalance_per_call={} must be <= max_rebalance_per_day={} and both set or unset",
+		max_rebalance_per_call,
+		max_rebalance_per_day
+	)?;
+
+	msg!(
+		"market.amm_rebalancer.max_rebalance_per_call: {:?} -> {:?}",
+		market.amm_rebalancer.max_rebalance_per_call,
+		max_rebalance_per_call
+	);
+	msg!(
+		"market.amm_rebalancer.limiter.max_mint_per_window: {:?} -> {:?}",
+		market.amm_rebalancer.limiter.max_mint_per_window,
+		max_rebalance_per_day
+	);
+
+	market.amm_rebalancer.max_rebalance_per_call = max_rebalance_per_call;
+	market.amm_rebalancer.limiter.max_mint_per_window = max_rebalance_per_day;
+
+	// the program signer moves tokens in and out of the pool's vaults while rebalancing
+	let vault_balance_authority = if enabled {
+		ctx.accounts.state.signer
+	} else {
+		Pubkey::default()
+	};
+
+	msg!(
+		"amm.vault_balance_authority: {:?} -> {:?}",
+		amm.vault_balance_authority,
+		vault_balance_authority
+	);
+
+	amm.vault_balance_authority = vault_balance_authority;
+
+	Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::load_mut;
+use crate::state::collateral::validate_collateral_weights;
+
+use super::AdminUpdateSynthMarket;
+
+pub fn handle_update_synth_market_collateral_asset(
+	ctx: Context<AdminUpdateSynthMarket>,
+	asset_index: u8,
+	initial_asset_weight: u32,
+	maintenance_asset_weight: u32,
+	max_token_deposits: u64
+) -> Result<()> {
+	let synth_market = &mut load_mut!(ctx.accounts.synth_market)?;
+	msg!("updating market {} collateral {}", synth_market.market_index, asset_index);
+
+	validate_collateral_weights(initial_asset_weight, maintenance_asset_weight)?;
+
+	let collateral = synth_market.get_collateral_asset_mut(asset_index)?;
+
+	msg!(
+		"collateral.initial_asset_weight: {:?} -> {:?}",
+		collateral.initial_asset_weight,
+		initial_asset_weight
+	);
+
+	msg!(
+		"collateral.maintenance_asset_weight: {:?} -> {:?}",
+		collateral.maintenance_asset_weight,
+		maintenance_asset_weight
+	);
+
+	msg!(
+		"collateral.max_token_deposits: {:?} -> {:?}",
+		collateral.max_token_deposits,
+		max_token_deposits
+	);
+
+	collateral.initial_asset_weight = initial_asset_weight;
+	collateral.maintenance_asset_weight = maintenance_asset_weight;
+	collateral.max_token_deposits = max_token_deposits;
+
+	Ok(())
+}
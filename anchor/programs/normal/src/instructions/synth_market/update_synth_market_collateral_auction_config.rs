@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::AUCTION_BID_DECREASE_RATE_PRECISION;
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::state::synth_market::AuctionPreference;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_collateral_auction_config(
+	ctx: Context<AdminUpdateSynthMarket>,
+	auction_location: AuctionPreference,
+	auction_duration: u16,
+	auction_bid_decrease_rate: u16,
+	max_auction_lot_size: u64
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("updating market {} collateral auction config", market.market_index);
+
+	validate!(
+		auction_duration > 0 &&
+			auction_bid_decrease_rate > 0 &&
+			auction_bid_decrease_rate.cast::<u128>()? <= AUCTION_BID_DECREASE_RATE_PRECISION,
+		ErrorCode::InvalidAuctionConfig,
+		"auction_duration={} and auction_bid_decrease_rate={} must be positive, rate <= {}",
+		auction_duration,
+		auction_bid_decrease_rate,
+		AUCTION_BID_DECREASE_RATE_PRECISION
+	)?;
+
+	let config = &mut market.collateral_action_config;
+
+	msg!("collateral_action_config.auction_location: {:?} -> {:?}", config.auction_location, auction_location);
+	msg!("collateral_action_config.auction_duration: {:?} -> {:?}", config.auction_duration, auction_duration);
+	msg!(
+		"collateral_action_config.auction_bid_decrease_rate: {:?} -> {:?}",
+		config.auction_bid_decrease_rate,
+		auction_bid_decrease_rate
+	);
+	msg!(
+		"collateral_action_config.max_auction_lot_size: {:?} -> {:?}",
+		config.max_auction_lot_size,
+		max_auction_lot_size
+	);
+
+	config.auction_location = auction_location;
+	config.auction_duration = auction_duration;
+	config.auction_bid_decrease_rate = auction_bid_decrease_rate;
+	config.max_auction_lot_size = max_auction_lot_size;
+
+	Ok(())
+}
@@ -1,13 +1,29 @@
 use anchor_lang::prelude::*;
 
+use crate::instructions::constraints::*;
+use crate::load_mut;
+
 use super::AdminUpdateSynthMarket;
 
-#[access_control(market_valid(&ctx.accounts.market))]
+/// Cuts apply immediately, raises ramp in over the market's debt_ceiling_ramp_duration
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
 pub fn handle_update_synth_market_debt_ceiling(
 	ctx: Context<AdminUpdateSynthMarket>,
-	debt_ceiling: u64
+	debt_ceiling: u128
 ) -> Result<()> {
-	ctx.accounts.market.update_debt_ceiling(debt_ceiling);
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let now = Clock::get()?.unix_timestamp;
+
+	msg!("updating market {} debt ceiling", market.market_index);
+
+	msg!(
+		"market.debt_ceiling: {:?} -> {:?} (ramping over {}s)",
+		market.get_debt_ceiling(now)?,
+		debt_ceiling,
+		market.debt_ceiling_ramp_duration
+	);
+
+	market.update_debt_ceiling(debt_ceiling, now)?;
 
 	Ok(())
 }
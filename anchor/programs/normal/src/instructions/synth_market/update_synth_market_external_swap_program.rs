@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{ token, token_2022 };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_external_swap_program(
+	ctx: Context<AdminUpdateSynthMarket>,
+	external_swap_program: Pubkey
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("updating market {} external swap program", market.market_index);
+
+	// the venue is invoked with keeper supplied data, it can't be a program that moves
+	// protocol funds on its own
+	validate!(
+		external_swap_program != crate::ID &&
+			external_swap_program != token::ID &&
+			external_swap_program != token_2022::ID &&
+			external_swap_program != System::id(),
+		ErrorCode::InvalidAuctionConfig,
+		"{} can't be an external swap program",
+		external_swap_program
+	)?;
+
+	msg!(
+		"collateral_action_config.external_swap_program: {:?} -> {:?}",
+		market.collateral_action_config.external_swap_program,
+		external_swap_program
+	);
+
+	market.collateral_action_config.external_swap_program = external_swap_program;
+
+	Ok(())
+}
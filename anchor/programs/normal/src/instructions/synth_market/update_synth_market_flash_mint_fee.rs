@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::PERCENTAGE_PRECISION;
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_flash_mint_fee(
+	ctx: Context<AdminUpdateSynthMarket>,
+	flash_mint_fee: u32
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("updating market {} flash mint fee", market.market_index);
+
+	validate!(
+		flash_mint_fee.cast::<u128>()? <= PERCENTAGE_PRECISION,
+		ErrorCode::InvalidFlashMintFee,
+		"flash_mint_fee must be <= {}",
+		PERCENTAGE_PRECISION
+	)?;
+
+	msg!("market.flash_mint_fee: {:?} -> {:?}", market.flash_mint_fee, flash_mint_fee);
+
+	market.flash_mint_fee = flash_mint_fee;
+
+	Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::SPOT_UTILIZATION_PRECISION;
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_lending_config(
+	ctx: Context<AdminUpdateSynthMarket>,
+	lending_program: Pubkey,
+	lending_reserve: Pubkey,
+	lending_obligation: Pubkey,
+	max_collateral_lending_utilization: u64
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("updating market {} lending config", market.market_index);
+
+	validate!(
+		max_collateral_lending_utilization.cast::<u128>()? <= SPOT_UTILIZATION_PRECISION,
+		ErrorCode::InvalidCollateralLendingConfig,
+		"max_collateral_lending_utilization must be <= {}",
+		SPOT_UTILIZATION_PRECISION
+	)?;
+
+	// shares are only redeemable against the venue they were minted by
+	if
+		lending_program != market.lending_program ||
+		lending_reserve != market.lending_reserve ||
+		lending_obligation != market.lending_obligation
+	{
+		validate!(
+			market.collateral_loan_shares == 0,
+			ErrorCode::CollateralStillLent,
+			"unlend the {} shares held before changing lending program",
+			market.collateral_loan_shares
+		)?;
+	}
+
+	msg!("market.lending_program: {:?} -> {:?}", market.lending_program, lending_program);
+
+	msg!("market.lending_reserve: {:?} -> {:?}", market.lending_reserve, lending_reserve);
+
+	msg!(
+		"market.lending_obligation: {:?} -> {:?}",
+		market.lending_obligation,
+		lending_obligation
+	);
+
+	msg!(
+		"market.max_collateral_lending_utilization: {:?} -> {:?}",
+		market.max_collateral_lending_utilization,
+		max_collateral_lending_utilization
+	);
+
+	market.lending_program = lending_program;
+	market.lending_reserve = lending_reserve;
+	market.lending_obligation = lending_obligation;
+	market.max_collateral_lending_utilization = max_collateral_lending_utilization;
+
+	Ok(())
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_mint_limit(
+	ctx: Context<AdminUpdateSynthMarket>,
+	max_mint_per_window: u64,
+	mint_window: i64,
+	debt_ceiling_ramp_duration: i64
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let now = Clock::get()?.unix_timestamp;
+
+	msg!("updating market {} mint limit", market.market_index);
+
+	validate!(
+		mint_window > 0 && debt_ceiling_ramp_duration >= 0,
+		ErrorCode::InvalidMintLimit,
+		"mint_window must be positive and debt_ceiling_ramp_duration non-negative"
+	)?;
+
+	msg!(
+		"market.mint_limiter.max_mint_per_window: {:?} -> {:?}",
+		market.mint_limiter.max_mint_per_window,
+		max_mint_per_window
+	);
+
+	msg!("market.mint_limiter.window: {:?} -> {:?}", market.mint_limiter.window, mint_window);
+
+	msg!(
+		"market.debt_ceiling_ramp_duration: {:?} -> {:?}",
+		market.debt_ceiling_ramp_duration,
+		debt_ceiling_ramp_duration
+	);
+
+	// restart any raise in progress from where it is now under the new duration
+	let target_debt_ceiling = market.target_debt_ceiling;
+	market.update_debt_ceiling(target_debt_ceiling, now)?;
+
+	market.mint_limiter.max_mint_per_window = max_mint_per_window;
+	market.mint_limiter.window = mint_window;
+	market.debt_ceiling_ramp_duration = debt_ceiling_ramp_duration;
+
+	Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::PERCENTAGE_PRECISION;
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::casting::Cast;
+use crate::{ load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_psm(
+	ctx: Context<AdminUpdateSynthMarket>,
+	fee_in: u32,
+	fee_out: u32,
+	max_reserve: u64
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("updating market {} peg stability module", market.market_index);
+
+	validate!(
+		market.psm.is_initialized(),
+		ErrorCode::PsmNotInitialized,
+		"market {} has no psm",
+		market.market_index
+	)?;
+
+	validate!(
+		fee_in.cast::<u128>()? < PERCENTAGE_PRECISION &&
+			fee_out.cast::<u128>()? < PERCENTAGE_PRECISION,
+		ErrorCode::InvalidPsmFee,
+		"psm fees must be < {}",
+		PERCENTAGE_PRECISION
+	)?;
+
+	// lowering the cap below the reserve only stops swaps in, swaps out keep working
+	msg!("market.psm.fee_in: {:?} -> {:?}", market.psm.fee_in, fee_in);
+	msg!("market.psm.fee_out: {:?} -> {:?}", market.psm.fee_out, fee_out);
+	msg!("market.psm.max_reserve: {:?} -> {:?}", market.psm.max_reserve, max_reserve);
+
+	market.psm.fee_in = fee_in;
+	market.psm.fee_out = fee_out;
+	market.psm.max_reserve = max_reserve;
+
+	Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::SPOT_UTILIZATION_PRECISION_U32;
+use crate::error::ErrorCode;
+use crate::{ controller, load_mut, validate };
+
+use super::AdminUpdateSynthMarket;
+
+#[access_control(market_valid(&ctx.accounts.synth_market))]
+pub fn handle_update_synth_market_stability_fee(
+	ctx: Context<AdminUpdateSynthMarket>,
+	optimal_utilization: u32,
+	base_stability_fee: u32,
+	optimal_stability_fee: u32,
+	max_stability_fee: u32
+) -> Result<()> {
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+	let now = Clock::get()?.unix_timestamp;
+
+	msg!("updating market {} stability fee", market.market_index);
+
+	validate!(
+		optimal_utilization > 0 && optimal_utilization <= SPOT_UTILIZATION_PRECISION_U32,
+		ErrorCode::InvalidStabilityFeeCurve,
+		"optimal_utilization must be in (0, {}]",
+		SPOT_UTILIZATION_PRECISION_U32
+	)?;
+
+	validate!(
+		base_stability_fee <= optimal_stability_fee && optimal_stability_fee <= max_stability_fee,
+		ErrorCode::InvalidStabilityFeeCurve,
+		"stability fees must be base <= optimal <= max"
+	)?;
+
+	// debt accrued so far is charged at the old curve
+	controller::synth_balance::update_synth_market_cumulative_interest(market, None, now)?;
+
+	msg!(
+		"market.optimal_utilization: {:?} -> {:?}",
+		market.optimal_utilization,
+		optimal_utilization
+	);
+
+	msg!(
+		"market.base_stability_fee: {:?} -> {:?}",
+		market.base_stability_fee,
+		base_stability_fee
+	);
+
+	msg!(
+		"market.optimal_stability_fee: {:?} -> {:?}",
+		market.optimal_stability_fee,
+		optimal_stability_fee
+	);
+
+	msg!(
+		"market.max_stability_fee: {:?} -> {:?}",
+		market.max_stability_fee,
+		max_stability_fee
+	);
+
+	market.optimal_utilization = optimal_utilization;
+	market.base_stability_fee = base_stability_fee;
+	market.optimal_stability_fee = optimal_stability_fee;
+	market.max_stability_fee = max_stability_fee;
+
+	Ok(())
+}
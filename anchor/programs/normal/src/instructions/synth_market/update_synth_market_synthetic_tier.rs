@@ -1,19 +1,12 @@
 use anchor_lang::prelude::*;
 
-use crate::{
-	controller,
-	error::ErrorCode,
-	instructions::optional_accounts::{ load_maps, AccountMaps },
-	load_mut,
-	state::{
-		synth_market::SyntheticTier,
-		synth_market_map::get_writable_market_set,
-	},
-};
-use crate::instructions::constraints::market_valid;
+use crate::instructions::constraints::*;
+use crate::load_mut;
+use crate::state::synth_market::SyntheticTier;
+
 use super::AdminUpdateSynthMarket;
 
-#[access_control(market_valid(&ctx.accounts.market))]
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
 pub fn handle_update_synth_market_synthetic_tier(
 	ctx: Context<AdminUpdateSynthMarket>,
 	synthetic_tier: SyntheticTier
@@ -22,11 +15,12 @@ pub fn handle_update_synth_market_synthetic_tier(
 	msg!("synth market {}", synth_market.market_index);
 
 	msg!(
-		"synth_market.contract_tier: {:?} -> {:?}",
-		synth_market.contract_tier,
-		contract_tier
+		"synth_market.synthetic_tier: {:?} -> {:?}",
+		synth_market.synthetic_tier,
+		synthetic_tier
 	);
 
-	synth_market.contract_tier = contract_tier;
+	synth_market.synthetic_tier = synthetic_tier;
+
 	Ok(())
 }
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ TokenAccount, TokenInterface };
+
+use crate::error::ErrorCode;
+use crate::instructions::constraints::*;
+use crate::math::safe_math::SafeMath;
+use crate::state::synth_market::SynthMarket;
+use crate::{ controller, load_mut, validate, State };
+
+#[derive(Accounts)]
+pub struct WithdrawSynthMarketPsmFees<'info> {
+	pub admin: Signer<'info>,
+	#[account(has_one = admin)]
+	pub state: Box<Account<'info, State>>,
+	#[account(mut)]
+	pub synth_market: AccountLoader<'info, SynthMarket>,
+	#[account(
+		mut,
+		address = synth_market.load()?.psm.reserve_vault
+	)]
+	pub reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(
+		mut,
+		token::mint = synth_market.load()?.psm.quote_mint
+	)]
+	pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+	#[account(constraint = state.signer.eq(&normal_signer.key()))]
+	/// CHECK: forced normal_signer
+	pub normal_signer: AccountInfo<'info>,
+	pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Withdraws up to `amount` of the fees the market's psm has kept in its reserve vault.
+/// The quote backing minted synthetic (reserve_balance) can't be withdrawn
+#[access_control(synth_market_valid(&ctx.accounts.synth_market))]
+pub fn handle_withdraw_synth_market_psm_fees(
+	ctx: Context<WithdrawSynthMarketPsmFees>,
+	amount: u64
+) -> Result<()> {
+	let state = &ctx.accounts.state;
+	let market = &mut load_mut!(ctx.accounts.synth_market)?;
+
+	msg!("withdrawing market {} psm fees", market.market_index);
+
+	validate!(
+		market.psm.is_initialized(),
+		ErrorCode::PsmNotInitialized,
+		"market {} has no psm",
+		market.market_index
+	)?;
+
+	validate!(
+		amount <= market.psm.fees_accrued,
+		ErrorCode::PsmInsufficientFees,
+		"market {} psm has {} fees accrued, cant withdraw {}",
+		market.market_index,
+		market.psm.fees_accrued,
+		amount
+	)?;
+
+	msg!(
+		"market.psm.fees_accrued: {:?} -> {:?}",
+		market.psm.fees_accrued,
+		market.psm.fees_accrued.safe_sub(amount)?
+	);
+
+	market.psm.fees_accrued = market.psm.fees_accrued.safe_sub(amount)?;
+
+	controller::token::send_from_program_vault(
+		&ctx.accounts.token_program,
+		&ctx.accounts.reserve_vault,
+		&ctx.accounts.recipient_token_account,
+		&ctx.accounts.normal_signer,
+		state.signer_nonce,
+		amount,
+		&None
+	)?;
+
+	Ok(())
+}
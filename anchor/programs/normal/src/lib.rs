@@ -9,7 +9,7 @@ use math::amm;
 use math::{ bn, constants::* };
 use state::oracle::OracleSource;
 
-use crate::state::synth_market::{ SyntheticTier, MarketStatus };
+use crate::state::synth_market::{ AuctionPreference, SyntheticTier, MarketStatus };
 use crate::state::state::FeeStructure;
 use crate::state::state::*;
 
@@ -40,7 +40,7 @@ pub mod normal {
 		UpdateIndexMarket,
 	};
 	use state::{
-		index_market::{ IndexAsset, IndexVisibility },
+		index_market::{ FeeAccrualMethod, IndexAsset, IndexVisibility, WeightingMethod },
 		schedule::OrderDirection,
 	};
 
@@ -62,6 +62,42 @@ pub mod normal {
 		handle_update_state_admin(ctx, admin)
 	}
 
+	pub fn update_state_mint_limit(
+		ctx: Context<AdminUpdateState>,
+		max_mint_per_window: u64,
+		mint_window: i64
+	) -> Result<()> {
+		handle_update_state_mint_limit(ctx, max_mint_per_window, mint_window)
+	}
+
+	pub fn update_state_debt_auction_config(
+		ctx: Context<AdminUpdateState>,
+		auction_duration: u16,
+		auction_bid_decrease_rate: u16,
+		max_auction_lot_size: u64
+	) -> Result<()> {
+		handle_update_state_debt_auction_config(
+			ctx,
+			auction_duration,
+			auction_bid_decrease_rate,
+			max_auction_lot_size
+		)
+	}
+
+	pub fn update_state_surplus_auction_config(
+		ctx: Context<AdminUpdateState>,
+		auction_duration: u16,
+		auction_bid_decrease_rate: u16,
+		max_auction_lot_size: u64
+	) -> Result<()> {
+		handle_update_state_surplus_auction_config(
+			ctx,
+			auction_duration,
+			auction_bid_decrease_rate,
+			max_auction_lot_size
+		)
+	}
+
 	pub fn update_state_initial_pct_to_liquidate(
 		ctx: Context<AdminUpdateState>,
 		initial_pct_to_liquidate: u16
@@ -112,11 +148,30 @@ pub mod normal {
 	}
 
 	pub fn freeze_synth_market_oracle(
-		ctx: Context<FreezeMarketOracle>
+		ctx: Context<FreezeSynthMarketOracle>
 	) -> Result<()> {
 		handle_freeze_synth_market_oracle(ctx)
 	}
 
+	pub fn settle_expired_vault<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, SettleExpiredVault<'info>>,
+		market_index: u16
+	) -> Result<()> {
+		handle_settle_expired_vault(ctx, market_index)
+	}
+
+	pub fn redeem_settled_synthetic<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RedeemSettledSynthetic<'info>>,
+		market_index: u16,
+		amount: u64
+	) -> Result<()> {
+		handle_redeem_settled_synthetic(ctx, market_index, amount)
+	}
+
+	pub fn delist_synth_market(ctx: Context<DelistSynthMarket>) -> Result<()> {
+		handle_delist_synth_market(ctx)
+	}
+
 	pub fn initialize_synth_market_shutdown(
 		ctx: Context<AdminUpdateSynthMarket>
 	) -> Result<()> {
@@ -137,11 +192,37 @@ pub mod normal {
 		handle_update_synth_market_amm(ctx, amm)
 	}
 
+	pub fn update_synth_market_amm_rebalancer(
+		ctx: Context<AdminUpdateSynthMarketAmmRebalancer>,
+		max_rebalance_per_call: u64,
+		max_rebalance_per_day: u64
+	) -> Result<()> {
+		handle_update_synth_market_amm_rebalancer(
+			ctx,
+			max_rebalance_per_call,
+			max_rebalance_per_day
+		)
+	}
+
 	pub fn update_synth_market_debt_ceiling(
 		ctx: Context<AdminUpdateSynthMarket>,
 		debt_ceiling: u128
 	) -> Result<()> {
-		handle_update_synth_market_debt_celing(ctx, debt_ceiling)
+		handle_update_synth_market_debt_ceiling(ctx, debt_ceiling)
+	}
+
+	pub fn update_synth_market_mint_limit(
+		ctx: Context<AdminUpdateSynthMarket>,
+		max_mint_per_window: u64,
+		mint_window: i64,
+		debt_ceiling_ramp_duration: i64
+	) -> Result<()> {
+		handle_update_synth_market_mint_limit(
+			ctx,
+			max_mint_per_window,
+			mint_window,
+			debt_ceiling_ramp_duration
+		)
 	}
 
 	pub fn update_synth_market_debt_floor(
@@ -151,6 +232,170 @@ pub mod normal {
 		handle_update_synth_market_debt_celing(ctx, debt_floor)
 	}
 
+	pub fn add_synth_market_collateral_asset(
+		ctx: Context<AddSynthMarketCollateralAsset>,
+		market_index: u16,
+		asset_index: u8,
+		oracle_source: OracleSource,
+		initial_asset_weight: u32,
+		maintenance_asset_weight: u32,
+		max_token_deposits: u64
+	) -> Result<()> {
+		handle_add_synth_market_collateral_asset(
+			ctx,
+			market_index,
+			asset_index,
+			oracle_source,
+			initial_asset_weight,
+			maintenance_asset_weight,
+			max_token_deposits
+		)
+	}
+
+	pub fn update_synth_market_collateral_asset(
+		ctx: Context<AdminUpdateSynthMarket>,
+		asset_index: u8,
+		initial_asset_weight: u32,
+		maintenance_asset_weight: u32,
+		max_token_deposits: u64
+	) -> Result<()> {
+		handle_update_synth_market_collateral_asset(
+			ctx,
+			asset_index,
+			initial_asset_weight,
+			maintenance_asset_weight,
+			max_token_deposits
+		)
+	}
+
+	pub fn update_synth_market_flash_mint_fee(
+		ctx: Context<AdminUpdateSynthMarket>,
+		flash_mint_fee: u32
+	) -> Result<()> {
+		handle_update_synth_market_flash_mint_fee(ctx, flash_mint_fee)
+	}
+
+	pub fn update_synth_market_collateral_auction_config(
+		ctx: Context<AdminUpdateSynthMarket>,
+		auction_location: AuctionPreference,
+		auction_duration: u16,
+		auction_bid_decrease_rate: u16,
+		max_auction_lot_size: u64
+	) -> Result<()> {
+		handle_update_synth_market_collateral_auction_config(
+			ctx,
+			auction_location,
+			auction_duration,
+			auction_bid_decrease_rate,
+			max_auction_lot_size
+		)
+	}
+
+	pub fn update_synth_market_external_swap_program(
+		ctx: Context<AdminUpdateSynthMarket>,
+		external_swap_program: Pubkey
+	) -> Result<()> {
+		handle_update_synth_market_external_swap_program(ctx, external_swap_program)
+	}
+
+	pub fn flash_mint<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, FlashMint<'info>>,
+		market_index: u16,
+		amount: u64
+	) -> Result<()> {
+		handle_flash_mint(ctx, market_index, amount)
+	}
+
+	pub fn flash_repay<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, FlashRepay<'info>>,
+		market_index: u16,
+		amount: u64
+	) -> Result<()> {
+		handle_flash_repay(ctx, market_index, amount)
+	}
+
+	pub fn initialize_synth_market_psm(
+		ctx: Context<InitializeSynthMarketPsm>,
+		fee_in: u32,
+		fee_out: u32,
+		max_reserve: u64
+	) -> Result<()> {
+		handle_initialize_synth_market_psm(ctx, fee_in, fee_out, max_reserve)
+	}
+
+	pub fn update_synth_market_psm(
+		ctx: Context<AdminUpdateSynthMarket>,
+		fee_in: u32,
+		fee_out: u32,
+		max_reserve: u64
+	) -> Result<()> {
+		handle_update_synth_market_psm(ctx, fee_in, fee_out, max_reserve)
+	}
+
+	pub fn withdraw_synth_market_psm_fees(
+		ctx: Context<WithdrawSynthMarketPsmFees>,
+		amount: u64
+	) -> Result<()> {
+		handle_withdraw_synth_market_psm_fees(ctx, amount)
+	}
+
+	pub fn psm_swap_in(
+		ctx: Context<PsmSwapIn>,
+		market_index: u16,
+		quote_amount: u64
+	) -> Result<()> {
+		handle_psm_swap_in(ctx, market_index, quote_amount)
+	}
+
+	pub fn psm_swap_out(
+		ctx: Context<PsmSwapOut>,
+		market_index: u16,
+		synthetic_amount: u64
+	) -> Result<()> {
+		handle_psm_swap_out(ctx, market_index, synthetic_amount)
+	}
+
+	pub fn redeem_synthetic<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RedeemSynthetic<'info>>,
+		market_index: u16,
+		amount: u64,
+		max_fee_rate: u64
+	) -> Result<()> {
+		handle_redeem_synthetic(ctx, market_index, amount, max_fee_rate)
+	}
+
+	pub fn update_synth_market_lending_config(
+		ctx: Context<AdminUpdateSynthMarket>,
+		lending_program: Pubkey,
+		lending_reserve: Pubkey,
+		lending_obligation: Pubkey,
+		max_collateral_lending_utilization: u64
+	) -> Result<()> {
+		handle_update_synth_market_lending_config(
+			ctx,
+			lending_program,
+			lending_reserve,
+			lending_obligation,
+			max_collateral_lending_utilization
+		)
+	}
+
+	pub fn update_synth_market_stability_fee(
+		ctx: Context<AdminUpdateSynthMarket>,
+		optimal_utilization: u32,
+		base_stability_fee: u32,
+		optimal_stability_fee: u32,
+		max_stability_fee: u32
+	) -> Result<()> {
+		handle_update_synth_market_stability_fee(
+			ctx,
+			optimal_utilization,
+			base_stability_fee,
+			optimal_stability_fee,
+			max_stability_fee
+		)
+	}
+
 	pub fn update_synth_market_expiry(
 		ctx: Context<AdminUpdateSynthMarket>,
 		expiry_ts: i64
@@ -322,12 +567,12 @@ pub mod normal {
 		handle_deposit_collateral(ctx, vault_index, amount, reduce_only)
 	}
 
-	pub fn lend_collateral(ctx: Context<LendCollateral>) -> Result<()> {
-		handle_lend_collateral(ctx)
+	pub fn lend_collateral(ctx: Context<LendCollateral>, amount: u64) -> Result<()> {
+		handle_lend_collateral(ctx, amount)
 	}
 
-	pub fn unlend_collateral(ctx: Context<UnlendCollateral>) -> Result<()> {
-		handle_unlend_collateral(ctx)
+	pub fn unlend_collateral(ctx: Context<UnlendCollateral>, amount: u64) -> Result<()> {
+		handle_unlend_collateral(ctx, amount)
 	}
 
 	pub fn withdraw_collateral(ctx: Context<WithdrawCollateral>) -> Result<()> {
@@ -343,6 +588,32 @@ pub mod normal {
 		handle_withdraw_collateral(ctx, vault_index, amount, reduce_only)
 	}
 
+	pub fn deposit_collateral_asset<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, DepositCollateralAsset<'info>>,
+		market_index: u16,
+		asset_index: u8,
+		amount: u64
+	) -> Result<()> {
+		handle_deposit_collateral_asset(ctx, market_index, asset_index, amount)
+	}
+
+	pub fn withdraw_collateral_asset<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, WithdrawCollateralAsset<'info>>,
+		market_index: u16,
+		asset_index: u8,
+		amount: u64
+	) -> Result<()> {
+		handle_withdraw_collateral_asset(ctx, market_index, asset_index, amount)
+	}
+
+	pub fn repay_synthetic_debt<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RepaySyntheticDebt<'info>>,
+		market_index: u16,
+		amount: u64
+	) -> Result<()> {
+		handle_repay_synthetic_debt(ctx, market_index, amount)
+	}
+
 	pub fn transfer_collateral<'c: 'info, 'info>(
 		ctx: Context<'_, '_, 'c, 'info, TransferCollateral<'info>>,
 		vault_index: u16,
@@ -376,6 +647,51 @@ pub mod normal {
 		)
 	}
 
+	pub fn initialize_vault_deleverage_order(
+		ctx: Context<InitializeVaultDeleverageOrder>,
+		market_index: u16,
+		trigger_collateral_ratio: u64,
+		target_collateral_ratio: u64,
+		max_slippage: u32,
+		keeper_tip: u32
+	) -> Result<()> {
+		handle_initialize_vault_deleverage_order(
+			ctx,
+			market_index,
+			trigger_collateral_ratio,
+			target_collateral_ratio,
+			max_slippage,
+			keeper_tip
+		)
+	}
+
+	pub fn update_vault_deleverage_order(
+		ctx: Context<UpdateVaultDeleverageOrder>,
+		trigger_collateral_ratio: u64,
+		target_collateral_ratio: u64,
+		max_slippage: u32,
+		keeper_tip: u32
+	) -> Result<()> {
+		handle_update_vault_deleverage_order(
+			ctx,
+			trigger_collateral_ratio,
+			target_collateral_ratio,
+			max_slippage,
+			keeper_tip
+		)
+	}
+
+	pub fn delete_vault_deleverage_order(ctx: Context<DeleteVaultDeleverageOrder>) -> Result<()> {
+		handle_delete_vault_deleverage_order(ctx)
+	}
+
+	pub fn execute_vault_deleverage<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, ExecuteVaultDeleverage<'info>>,
+		market_index: u16
+	) -> Result<()> {
+		handle_execute_vault_deleverage(ctx, market_index)
+	}
+
 	pub fn set_vault_status_to_being_liquidated<'c: 'info, 'info>(
 		ctx: Context<'_, '_, 'c, 'info, SetUserStatusToBeingLiquidated<'info>>
 	) -> Result<()> {
@@ -394,6 +710,75 @@ pub mod normal {
 		handle_delete_vault(ctx)
 	}
 
+	// Auction instructions
+
+	pub fn bid_collateral_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, BidCollateralAuction<'info>>,
+		collateral_amount: u64,
+		limit_price: Option<u64>
+	) -> Result<()> {
+		handle_bid_collateral_auction(ctx, collateral_amount, limit_price)
+	}
+
+	pub fn reset_collateral_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, ResetCollateralAuction<'info>>
+	) -> Result<()> {
+		handle_reset_collateral_auction(ctx)
+	}
+
+	pub fn route_collateral_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RouteCollateralAuction<'info>>,
+		swap_data: Vec<u8>
+	) -> Result<()> {
+		handle_route_collateral_auction(ctx, swap_data)
+	}
+
+	pub fn settle_collateral_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, SettleCollateralAuction<'info>>
+	) -> Result<()> {
+		handle_settle_collateral_auction(ctx)
+	}
+
+	pub fn start_debt_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, StartDebtAuction<'info>>,
+		market_index: u16
+	) -> Result<()> {
+		handle_start_debt_auction(ctx, market_index)
+	}
+
+	pub fn bid_debt_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, BidDebtAuction<'info>>,
+		lot_amount: u64
+	) -> Result<()> {
+		handle_bid_debt_auction(ctx, lot_amount)
+	}
+
+	pub fn settle_debt_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, SettleDebtAuction<'info>>
+	) -> Result<()> {
+		handle_settle_debt_auction(ctx)
+	}
+
+	pub fn start_surplus_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, StartSurplusAuction<'info>>,
+		market_index: u16
+	) -> Result<()> {
+		handle_start_surplus_auction(ctx, market_index)
+	}
+
+	pub fn bid_surplus_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, BidSurplusAuction<'info>>,
+		bid_amount: u64
+	) -> Result<()> {
+		handle_bid_surplus_auction(ctx, bid_amount)
+	}
+
+	pub fn settle_surplus_auction<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, SettleSurplusAuction<'info>>
+	) -> Result<()> {
+		handle_settle_surplus_auction(ctx)
+	}
+
 	// AMM instructions
 
 	/// Initializes a market's AMM account.
@@ -440,6 +825,27 @@ pub mod normal {
 		handle_initialize_amm_tick_array(ctx, start_tick_index)
 	}
 
+	/// Initializes the observation ring buffer of an AMM, seeded with its current accumulators.
+	/// Swaps through the AMM write to it so the pool's own twap can be read back with observe.
+	pub fn initialize_amm_observations(ctx: Context<InitializeAMMObservations>) -> Result<()> {
+		handle_initialize_amm_observations(ctx)
+	}
+
+	/// Grows the observation ring buffer of an AMM so it covers a longer twap window. The new
+	/// slots are used once the buffer next wraps.
+	///
+	/// ### Parameters
+	/// - `cardinality_next` - Number of observations the buffer grows to.
+	///
+	/// #### Special Errors
+	/// - `InvalidObservationCardinality` - If cardinality_next exceeds MAX_AMM_OBSERVATION_CARDINALITY.
+	pub fn increase_amm_observation_cardinality(
+		ctx: Context<IncreaseAMMObservationCardinality>,
+		cardinality_next: u16
+	) -> Result<()> {
+		handle_increase_amm_observation_cardinality(ctx, cardinality_next)
+	}
+
 	/// Initialize reward for an AMM. An AMM can only support up to a set number of rewards.
 	///
 	/// ### Authority
@@ -504,6 +910,36 @@ pub mod normal {
 		handle_set_amm_fee_rate(ctx, fee_rate)
 	}
 
+	/// Sets the dynamic fee config for an AMM. While max_dynamic_fee_rate is set each swap
+	/// pays fee_rate plus a share of the oracle's std/confidence and the pool's recent tick
+	/// movement, bounded by min_dynamic_fee_rate and max_dynamic_fee_rate.
+	/// Only the current fee authority has permission to invoke this instruction.
+	///
+	/// ### Authority
+	/// - "fee_authority" - Set authority that can modify pool fees in the AMMConfig
+	///
+	/// ### Parameters
+	/// - `min_dynamic_fee_rate` - Lowest fee rate, in hundredths of a basis point.
+	/// - `max_dynamic_fee_rate` - Highest fee rate, in hundredths of a basis point. 0 disables dynamic fees.
+	/// - `volatility_fee_multiplier` - Basis points of the measured volatility added to fee_rate.
+	///
+	/// #### Special Errors
+	/// - `FeeRateMaxExceeded` - If max_dynamic_fee_rate exceeds MAX_FEE_RATE.
+	/// - `InvalidDynamicFeeConfig` - If min_dynamic_fee_rate exceeds max_dynamic_fee_rate.
+	pub fn set_amm_dynamic_fee(
+		ctx: Context<SetAMMDynamicFee>,
+		min_dynamic_fee_rate: u16,
+		max_dynamic_fee_rate: u16,
+		volatility_fee_multiplier: u16
+	) -> Result<()> {
+		handle_set_amm_dynamic_fee(
+			ctx,
+			min_dynamic_fee_rate,
+			max_dynamic_fee_rate,
+			volatility_fee_multiplier
+		)
+	}
+
 	/// Sets the protocol fee rate for an AMM.
 	/// Protocol fee rate is represented as a basis point.
 	/// Only the current fee authority has permission to invoke this instruction.
@@ -569,6 +1005,93 @@ pub mod normal {
 		)
 	}
 
+	/// Perform a two-hop swap in this AMM pair, typically one synthetic for another through
+	/// the shared quote token. Fees, rewards and observations are updated on both AMMs.
+	///
+	/// ### Authority
+	/// - "token_authority" - The authority to withdraw tokens from the input token account.
+	///
+	/// ### Parameters
+	/// - `amount` - The amount of input or output token to swap from (depending on amount_specified_is_input).
+	/// - `other_amount_threshold` - The maximum/minimum of input/output token to swap into (depending on amount_specified_is_input).
+	/// - `amount_specified_is_input` - Specifies the token the parameter `amount`represents. If true, the amount represents the input token of the swap.
+	/// - `synthetic_to_quote_one` - The direction of the swap on the first AMM.
+	/// - `synthetic_to_quote_two` - The direction of the swap on the second AMM.
+	/// - `sqrt_price_limit_one` - The maximum/minimum price the first swap will swap to.
+	/// - `sqrt_price_limit_two` - The maximum/minimum price the second swap will swap to.
+	///
+	/// #### Special Errors
+	/// - `ZeroTradableAmount` - User provided parameter `amount` is 0.
+	/// - `InvalidSqrtPriceLimitDirection` - User provided parameter `sqrt_price_limit` does not match the direction of the trade.
+	/// - `SqrtPriceOutOfBounds` - User provided parameter `sqrt_price_limit` is over AMM's max/min bounds for sqrt-price.
+	/// - `InvalidTickArraySequence` - User provided tick-arrays are not in sequential order required to proceed in this trade direction.
+	/// - `TickArraySequenceInvalidIndex` - The swap loop attempted to access an invalid array index during the query of the next initialized tick.
+	/// - `TickArrayIndexOutofBounds` - The swap loop attempted to access an invalid array index during tick crossing.
+	/// - `LiquidityOverflow` - Liquidity value overflowed 128bits during tick crossing.
+	/// - `InvalidTickSpacing` - The swap pool was initialized with tick-spacing of 0.
+	/// - `InvalidIntermediaryMint` - Error if the intermediary mint between hop one and two do not equal.
+	/// - `DuplicateTwoHopPool` - Error if AMM one & two are the same pool.
+	/// - `IntermediateTokenAmountMismatch` - Error if the first hop's output is not exactly the second hop's input.
+	#[allow(clippy::too_many_arguments)]
+	pub fn two_hop_swap(
+		ctx: Context<TwoHopSwap>,
+		amount: u64,
+		other_amount_threshold: u64,
+		amount_specified_is_input: bool,
+		synthetic_to_quote_one: bool,
+		synthetic_to_quote_two: bool,
+		sqrt_price_limit_one: u128,
+		sqrt_price_limit_two: u128
+	) -> Result<()> {
+		handle_two_hop_swap(
+			ctx,
+			amount,
+			other_amount_threshold,
+			amount_specified_is_input,
+			synthetic_to_quote_one,
+			synthetic_to_quote_two,
+			sqrt_price_limit_one,
+			sqrt_price_limit_two
+		)
+	}
+
+	/// Swap an exact input through a route of two or more AMMs. Each hop's accounts are passed
+	/// as remaining accounts, MULTI_HOP_ACCOUNTS_PER_HOP per hop in route order.
+	///
+	/// ### Authority
+	/// - "token_authority" - The authority to withdraw tokens from the input token account.
+	///
+	/// ### Parameters
+	/// - `amount` - The amount of input token to swap.
+	/// - `other_amount_threshold` - The minimum of output token the route must pay out.
+	/// - `synthetic_to_quote` - The direction of the swap on each AMM of the route.
+	///
+	/// #### Special Errors
+	/// - `RemainingAccountsInvalidSlice` - The remaining accounts don't match the route.
+	/// - `InvalidIntermediaryMint` - Error if a hop's input mint is not the previous hop's output mint.
+	/// - `DuplicateTwoHopPool` - Error if an AMM appears more than once in the route.
+	/// - `IntermediateTokenAmountMismatch` - Error if a hop doesn't take in the previous hop's whole output.
+	pub fn multi_hop_swap<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, MultiHopSwap<'info>>,
+		amount: u64,
+		other_amount_threshold: u64,
+		synthetic_to_quote: Vec<bool>
+	) -> Result<()> {
+		handle_multi_hop_swap(ctx, amount, other_amount_threshold, synthetic_to_quote)
+	}
+
+	/// Permissionless crank that pulls an AMM back to its oracle twap once the pool price
+	/// has left the max_price_variance band, by selling or buying back protocol owned
+	/// synthetic against the market's collateral.
+	///
+	/// #### Special Errors
+	/// - `AmmPriceWithinBand` - The pool price is inside the oracle band.
+	/// - `AmmRebalanceLimitReached` - The per-call or daily cap leaves nothing to rebalance.
+	/// - `AmmRebalanceInsufficientCollateral` - Buying back costs more than the protocol holds.
+	pub fn rebalance_amm(ctx: Context<RebalanceAMM>, market_index: u16) -> Result<()> {
+		handle_rebalance_amm(ctx, market_index)
+	}
+
 	/// Open a position in an AMM. A unique token will be minted to represent the position
 	/// in the users wallet. The position will start off with 0 liquidity.
 	///
@@ -771,9 +1294,32 @@ pub mod normal {
 		ctx: Context<InitializeIndexMarket>,
 		name: [u8; 32],
 		visibility: IndexVisibility,
-		weights: Vec<IndexAsset>
+		weights: Vec<IndexAsset>,
+		weighting_method: WeightingMethod
 	) -> Result<()> {
-		handle_initialize_index_market(ctx, name, visibility, weights)
+		handle_initialize_index_market(ctx, name, visibility, weights, weighting_method)
+	}
+
+	pub fn initialize_index_asset_vault(
+		ctx: Context<InitializeIndexAssetVault>,
+		market_index: u16,
+		asset_market_index: u16
+	) -> Result<()> {
+		handle_initialize_index_asset_vault(ctx, market_index, asset_market_index)
+	}
+
+	pub fn initialize_index_oracle(
+		ctx: Context<InitializeIndexOracle>,
+		market_index: u16
+	) -> Result<()> {
+		handle_initialize_index_oracle(ctx, market_index)
+	}
+
+	pub fn update_index_oracle<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, UpdateIndexOracle<'info>>,
+		market_index: u16
+	) -> Result<()> {
+		handle_update_index_oracle(ctx, market_index)
 	}
 
 	pub fn update_index_market_expense_ratio(
@@ -797,6 +1343,13 @@ pub mod normal {
 		handle_update_index_market_paused_operations(ctx, paused_operations)
 	}
 
+	pub fn update_index_market_rebalance_interval(
+		ctx: Context<UpdateIndexMarket>,
+		rebalance_interval: i64
+	) -> Result<()> {
+		handle_update_index_market_rebalance_interval(ctx, rebalance_interval)
+	}
+
 	pub fn update_index_market_revenue_share(
 		ctx: Context<UpdateIndexMarket>,
 		revenue_share: u64
@@ -804,6 +1357,21 @@ pub mod normal {
 		handle_update_index_market_revenue_share(ctx, revenue_share)
 	}
 
+	pub fn update_index_market_fee_accrual_method(
+		ctx: Context<UpdateIndexMarketFeeAccrualMethod>,
+		fee_accrual_method: FeeAccrualMethod
+	) -> Result<()> {
+		handle_update_index_market_fee_accrual_method(ctx, fee_accrual_method)
+	}
+
+	pub fn update_index_market_referrer(
+		ctx: Context<UpdateIndexMarket>,
+		referrer: Pubkey,
+		referral_share: u64
+	) -> Result<()> {
+		handle_update_index_market_referrer(ctx, referrer, referral_share)
+	}
+
 	pub fn update_index_market_visibility(
 		ctx: Context<UpdateIndexMarket>,
 		visibility: IndexVisibility
@@ -819,31 +1387,87 @@ pub mod normal {
 	}
 
 	pub fn settle_index_market_fees_to_treasury<'c: 'info, 'info>(
-		ctx: Context<'_, '_, 'c, 'info, SettleRevenueToInsuranceFund<'info>>,
+		ctx: Context<'_, '_, 'c, 'info, SettleIndexMarketFeesToTreasury<'info>>,
 		market_index: u16
 	) -> Result<()> {
 		handle_settle_index_market_fees_to_treasury(ctx, market_index)
 	}
 
-	pub fn rebalance_index_market(
-		ctx: Context<RebalanceIndexMarket<'info>>,
+	pub fn claim_index_manager_fees<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, ClaimIndexManagerFees<'info>>,
 		market_index: u16
 	) -> Result<()> {
-		handle_rebalance_index_market(ctx, market_index)
+		handle_claim_index_manager_fees(ctx, market_index)
+	}
+
+	pub fn rebalance_index_market<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RebalanceIndexMarket<'info>>,
+		custom_weights: Vec<u16>
+	) -> Result<()> {
+		handle_rebalance_index_market(ctx, custom_weights)
+	}
+
+	pub fn mint_index_tokens<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, MintIndexTokens<'info>>,
+		market_index: u16,
+		amount: u64
+	) -> Result<()> {
+		handle_mint_index_tokens(ctx, market_index, amount)
 	}
 
-	pub fn mint_index_tokens(
-		ctx: Context<MintIndexTokens>,
+	pub fn redeem_index_tokens<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RedeemIndexTokens<'info>>,
+		market_index: u16,
 		amount: u64
 	) -> Result<()> {
-		handle_mint_index_tokens(ctx, amount)
+		handle_redeem_index_tokens(ctx, market_index, amount)
+	}
+
+	pub fn mint_index_tokens_with_quote<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, MintIndexTokensWithQuote<'info>>,
+		market_index: u16,
+		quote_amount: u64,
+		min_index_amount: u64,
+		sqrt_price_limits: Vec<u128>
+	) -> Result<()> {
+		handle_mint_index_tokens_with_quote(
+			ctx,
+			market_index,
+			quote_amount,
+			min_index_amount,
+			sqrt_price_limits
+		)
+	}
+
+	pub fn redeem_index_tokens_to_quote<'c: 'info, 'info>(
+		ctx: Context<'_, '_, 'c, 'info, RedeemIndexTokensToQuote<'info>>,
+		market_index: u16,
+		amount: u64,
+		min_quote_amount: u64,
+		sqrt_price_limits: Vec<u128>
+	) -> Result<()> {
+		handle_redeem_index_tokens_to_quote(
+			ctx,
+			market_index,
+			amount,
+			min_quote_amount,
+			sqrt_price_limits
+		)
+	}
+
+	pub fn initialize_extra_account_meta_list(
+		ctx: Context<InitializeExtraAccountMetaList>,
+		market_index: u16
+	) -> Result<()> {
+		handle_initialize_extra_account_meta_list(ctx, market_index)
 	}
 
-	pub fn redeem_index_tokens(
-		ctx: Context<RedeemIndexTokens>,
+	#[interface(spl_transfer_hook_interface::execute)]
+	pub fn index_token_transfer_hook(
+		ctx: Context<IndexTokenTransferHook>,
 		amount: u64
 	) -> Result<()> {
-		handle_redeem_index_tokens(ctx, amount)
+		handle_index_token_transfer_hook(ctx, amount)
 	}
 
 	pub fn delete_initialized_index_market(
@@ -0,0 +1,337 @@
+use crate::constants::main::{
+	AUCTION_BID_DECREASE_RATE_PRECISION,
+	COLLATERAL_AUCTION_PRICE_FLOOR,
+	COLLATERAL_AUCTION_START_PREMIUM,
+	LIQUIDATION_FEE_PRECISION_U128,
+	PERCENTAGE_PRECISION,
+	PRICE_PRECISION,
+};
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::amm::{ MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64 };
+use crate::math::bn::U256;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::validate;
+use solana_program::msg;
+
+/// Oracle value of one raw unit of collateral in raw units of synthetic
+/// precision: PRICE_PRECISION
+pub fn calculate_collateral_to_synthetic_price(
+	collateral_price: i64,
+	collateral_decimals: u32,
+	synthetic_price: i64,
+	synthetic_decimals: u32
+) -> NormalResult<u128> {
+	validate!(
+		collateral_price > 0 && synthetic_price > 0,
+		ErrorCode::OracleNonPositive,
+		"collateral_price={} synthetic_price={} must be positive to price collateral",
+		collateral_price,
+		synthetic_price
+	)?;
+
+	collateral_price
+		.cast::<u128>()?
+		.safe_mul(PRICE_PRECISION)?
+		.safe_mul((10_u128).pow(synthetic_decimals))?
+		.safe_div(synthetic_price.cast::<u128>()?.safe_mul((10_u128).pow(collateral_decimals))?)
+}
+
+/// Collateral auctions are priced in synthetic per unit of collateral, in raw token amounts, and
+/// start above the oracle price so the curve can walk down to a clearing price
+/// precision: PRICE_PRECISION
+pub fn calculate_collateral_auction_start_price(
+	collateral_price: i64,
+	collateral_decimals: u32,
+	synthetic_price: i64,
+	synthetic_decimals: u32
+) -> NormalResult<u64> {
+	calculate_collateral_to_synthetic_price(
+		collateral_price,
+		collateral_decimals,
+		synthetic_price,
+		synthetic_decimals
+	)?
+		.safe_mul(PERCENTAGE_PRECISION.safe_add(COLLATERAL_AUCTION_START_PREMIUM)?)?
+		.safe_div(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+/// The lowest price a collateral auction decays to before it has to be reset
+pub fn calculate_collateral_auction_floor_price(start_price: u64) -> NormalResult<u64> {
+	start_price
+		.cast::<u128>()?
+		.safe_mul(COLLATERAL_AUCTION_PRICE_FLOOR)?
+		.safe_div(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+/// Linear decay from the start price, floored at `floor_price`
+pub fn calculate_auction_price(
+	start_price: u64,
+	bid_decrease_rate: u16,
+	start_ts: i64,
+	now: i64,
+	floor_price: u64
+) -> NormalResult<u64> {
+	let elapsed = now.safe_sub(start_ts)?.max(0).cast::<u128>()?;
+
+	let decrease_pct = elapsed
+		.safe_mul(bid_decrease_rate.cast()?)?
+		.min(AUCTION_BID_DECREASE_RATE_PRECISION);
+
+	start_price
+		.cast::<u128>()?
+		.safe_mul(AUCTION_BID_DECREASE_RATE_PRECISION.safe_sub(decrease_pct)?)?
+		.safe_div(AUCTION_BID_DECREASE_RATE_PRECISION)?
+		.cast::<u64>()
+		.map(|price| price.max(floor_price))
+}
+
+/// Returns the (lot_amount, bid_amount) filled by a bid for `lot_amount_requested` at `price`.
+/// The fill is capped by the remaining lot, the max lot size per bid and the debt left to raise.
+pub fn calculate_auction_fill(
+	lot_amount_requested: u64,
+	lot_amount_remaining: u64,
+	max_lot_size: u64,
+	debt_remaining: u64,
+	price: u64
+) -> NormalResult<(u64, u64)> {
+	let mut lot_amount = lot_amount_requested.min(lot_amount_remaining);
+	if max_lot_size > 0 {
+		lot_amount = lot_amount.min(max_lot_size);
+	}
+
+	if price == 0 {
+		// price fully decayed, remaining lot clears for nothing
+		return Ok((lot_amount, 0));
+	}
+
+	let bid_amount = lot_amount
+		.cast::<u128>()?
+		.safe_mul(price.cast()?)?
+		.safe_div_ceil(PRICE_PRECISION)?
+		.cast::<u64>()?;
+
+	if bid_amount <= debt_remaining {
+		return Ok((lot_amount, bid_amount));
+	}
+
+	// only sell enough of the lot to cover what is owed
+	let lot_amount = debt_remaining
+		.cast::<u128>()?
+		.safe_mul(PRICE_PRECISION)?
+		.safe_div(price.cast()?)?
+		.cast::<u64>()?;
+
+	Ok((lot_amount, debt_remaining))
+}
+
+/// Debt auctions run in reverse: each bid pays the same amount of synthetic and must ask for
+/// at least `min_decrease_rate` bps fewer governance tokens than the current lot
+pub fn calculate_debt_auction_max_lot(
+	lot_amount: u64,
+	min_decrease_rate: u16
+) -> NormalResult<u64> {
+	lot_amount
+		.cast::<u128>()?
+		.safe_mul(
+			AUCTION_BID_DECREASE_RATE_PRECISION.safe_sub(
+				min_decrease_rate.cast::<u128>()?.min(AUCTION_BID_DECREASE_RATE_PRECISION)
+			)?
+		)?
+		.safe_div(AUCTION_BID_DECREASE_RATE_PRECISION)?
+		.cast()
+}
+
+/// Surplus auctions run ascending: each bid must offer at least `min_increase_rate` bps more
+/// governance tokens than the current bid
+pub fn calculate_surplus_auction_min_bid(
+	bid_amount: u64,
+	min_increase_rate: u16
+) -> NormalResult<u64> {
+	bid_amount
+		.cast::<u128>()?
+		.safe_mul(AUCTION_BID_DECREASE_RATE_PRECISION.safe_add(min_increase_rate.cast()?)?)?
+		.safe_div_ceil(AUCTION_BID_DECREASE_RATE_PRECISION)?
+		.cast()
+}
+
+/// The amount held by the insurance fund above its cap, 0 if the cap is unset
+pub fn calculate_insurance_fund_surplus(
+	insurance_vault_amount: u64,
+	max_insurance: u64
+) -> u64 {
+	if max_insurance == 0 {
+		return 0;
+	}
+
+	insurance_vault_amount.saturating_sub(max_insurance)
+}
+
+/// The lowest price an external venue may fill a collateral auction at: the oracle price
+/// less the liquidation penalty the vault already owes
+pub fn calculate_external_auction_min_price(
+	collateral_price: i64,
+	collateral_decimals: u32,
+	synthetic_price: i64,
+	synthetic_decimals: u32,
+	liquidation_penalty: u32
+) -> NormalResult<u64> {
+	let discount = liquidation_penalty.cast::<u128>()?.min(LIQUIDATION_FEE_PRECISION_U128);
+
+	calculate_collateral_to_synthetic_price(
+		collateral_price,
+		collateral_decimals,
+		synthetic_price,
+		synthetic_decimals
+	)?
+		.safe_mul(LIQUIDATION_FEE_PRECISION_U128.safe_sub(discount)?)?
+		.safe_div(LIQUIDATION_FEE_PRECISION_U128)?
+		.cast()
+}
+
+/// Sqrt price limit (Q64.64) for selling quote into an AMM for synthetic without filling
+/// below `min_amount_out / amount_in`. The AMM price is quote per synthetic, so the limit
+/// is the highest price the sale may push it to.
+pub fn calculate_sqrt_price_limit_for_min_out(
+	amount_in: u64,
+	min_amount_out: u64
+) -> NormalResult<u128> {
+	if min_amount_out == 0 {
+		return Ok(MAX_SQRT_PRICE_X64);
+	}
+
+	let sqrt_price_limit = (U256::from(amount_in) << 128)
+		.checked_div(U256::from(min_amount_out))
+		.ok_or(ErrorCode::MathError)?
+		.integer_sqrt()
+		.try_to_u128()?;
+
+	Ok(sqrt_price_limit.clamp(MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64))
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::PRICE_PRECISION_U64;
+	use crate::math::auction::{
+		calculate_auction_fill,
+		calculate_auction_price,
+		calculate_collateral_auction_floor_price,
+		calculate_collateral_auction_start_price,
+		calculate_debt_auction_max_lot,
+		calculate_external_auction_min_price,
+		calculate_insurance_fund_surplus,
+		calculate_sqrt_price_limit_for_min_out,
+		calculate_surplus_auction_min_bid,
+	};
+
+	#[test]
+	fn start_price_above_oracle() {
+		let usd = PRICE_PRECISION_U64 as i64;
+
+		// $100 collateral for a $1 synthetic, same decimals
+		let start_price = calculate_collateral_auction_start_price(100 * usd, 6, usd, 6).unwrap();
+		assert_eq!(start_price, 110 * PRICE_PRECISION_U64);
+
+		// $150 SOL (9 decimals) for a $1 synthetic (6 decimals): 0.165 raw synthetic per lamport
+		let start_price = calculate_collateral_auction_start_price(150 * usd, 9, usd, 6).unwrap();
+		assert_eq!(start_price, 165_000);
+
+		// $100 collateral for a $50 synthetic
+		let start_price = calculate_collateral_auction_start_price(100 * usd, 6, 50 * usd, 6).unwrap();
+		assert_eq!(start_price, 2_200_000);
+
+		assert!(calculate_collateral_auction_start_price(0, 6, usd, 6).is_err());
+		assert!(calculate_collateral_auction_start_price(usd, 6, 0, 6).is_err());
+	}
+
+	#[test]
+	fn price_decays_linearly() {
+		let start_price = 110 * PRICE_PRECISION_U64;
+
+		// 10 bps per second
+		assert_eq!(calculate_auction_price(start_price, 10, 0, 0, 0).unwrap(), start_price);
+		assert_eq!(
+			calculate_auction_price(start_price, 10, 0, 100, 0).unwrap(),
+			99 * PRICE_PRECISION_U64
+		);
+		// no floor decays to zero
+		assert_eq!(calculate_auction_price(start_price, 10, 0, 2_000, 0).unwrap(), 0);
+		// clock before start does not inflate the price
+		assert_eq!(calculate_auction_price(start_price, 10, 50, 0, 0).unwrap(), start_price);
+
+		// stops at the floor
+		let floor_price = calculate_collateral_auction_floor_price(start_price).unwrap();
+		assert_eq!(floor_price, 88 * PRICE_PRECISION_U64);
+		assert_eq!(calculate_auction_price(start_price, 10, 0, 100, floor_price).unwrap(), 99 * PRICE_PRECISION_U64);
+		assert_eq!(calculate_auction_price(start_price, 10, 0, 2_000, floor_price).unwrap(), floor_price);
+	}
+
+	#[test]
+	fn fill_capped_by_lot_size_and_debt() {
+		let price = 2 * PRICE_PRECISION_U64;
+
+		// capped by max lot size
+		assert_eq!(calculate_auction_fill(100, 1_000, 40, 1_000, price).unwrap(), (40, 80));
+
+		// capped by remaining lot
+		assert_eq!(calculate_auction_fill(100, 30, 0, 1_000, price).unwrap(), (30, 60));
+
+		// only sells enough to cover the remaining debt
+		assert_eq!(calculate_auction_fill(100, 1_000, 0, 50, price).unwrap(), (25, 50));
+
+		// fully decayed price clears the lot for nothing
+		assert_eq!(calculate_auction_fill(100, 1_000, 0, 50, 0).unwrap(), (100, 0));
+	}
+
+	#[test]
+	fn debt_auction_lot_must_decrease() {
+		// 5% min decrease
+		assert_eq!(calculate_debt_auction_max_lot(1_000_000, 500).unwrap(), 950_000);
+		assert_eq!(calculate_debt_auction_max_lot(1_000_000, 0).unwrap(), 1_000_000);
+		assert_eq!(calculate_debt_auction_max_lot(1_000_000, 20_000).unwrap(), 0);
+	}
+
+	#[test]
+	fn surplus_auction_bid_must_increase() {
+		// 5% min increase
+		assert_eq!(calculate_surplus_auction_min_bid(1_000_000, 500).unwrap(), 1_050_000);
+		// rounds up so tiny bids still have to move
+		assert_eq!(calculate_surplus_auction_min_bid(10, 500).unwrap(), 11);
+	}
+
+	#[test]
+	fn insurance_fund_surplus() {
+		assert_eq!(calculate_insurance_fund_surplus(1_500, 1_000), 500);
+		assert_eq!(calculate_insurance_fund_surplus(900, 1_000), 0);
+		// no cap set
+		assert_eq!(calculate_insurance_fund_surplus(1_500, 0), 0);
+	}
+
+	#[test]
+	fn external_auction_min_price() {
+		let usd = PRICE_PRECISION_U64 as i64;
+
+		// $2 collateral sells for at least 1.9 of a $1 synthetic with a 5% penalty
+		assert_eq!(calculate_external_auction_min_price(2 * usd, 6, usd, 6, 50_000).unwrap(), 1_900_000);
+		assert_eq!(calculate_external_auction_min_price(2 * usd, 6, usd, 6, 0).unwrap(), 2 * PRICE_PRECISION_U64);
+
+		// $100 collateral against a $50 synthetic is 2 synthetic per collateral, not 5000
+		assert_eq!(
+			calculate_external_auction_min_price(100 * usd, 6, 50 * usd, 6, 0).unwrap(),
+			2 * PRICE_PRECISION_U64
+		);
+
+		assert!(calculate_external_auction_min_price(0, 6, usd, 6, 0).is_err());
+		assert!(calculate_external_auction_min_price(usd, 6, 0, 6, 0).is_err());
+	}
+
+	#[test]
+	fn sqrt_price_limit_for_min_out() {
+		// 4 quote in for at least 1 synthetic out -> price 4, sqrt price 2
+		assert_eq!(calculate_sqrt_price_limit_for_min_out(4, 1).unwrap(), 2 << 64);
+		// 1 quote in for at least 1 synthetic out -> price 1
+		assert_eq!(calculate_sqrt_price_limit_for_min_out(1_000, 1_000).unwrap(), 1 << 64);
+	}
+}
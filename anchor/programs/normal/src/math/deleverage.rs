@@ -0,0 +1,136 @@
+use crate::constants::main::{ DELEVERAGE_KEEPER_TIP_PENALTY_DIVISOR, PERCENTAGE_PRECISION };
+use crate::error::{ ErrorCode, NormalResult };
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::validate;
+use solana_program::msg;
+
+/// The order's keeper tip, capped at a fraction of the liquidation penalty so deleveraging
+/// always costs the owner less than being liquidated
+pub fn calculate_deleverage_tip_rate(keeper_tip: u32, liquidation_penalty: u32) -> NormalResult<u32> {
+	Ok(keeper_tip.min(liquidation_penalty.safe_div(DELEVERAGE_KEEPER_TIP_PENALTY_DIVISOR)?))
+}
+
+/// Collateral to sell at oracle price to bring a vault to `target_collateral_ratio`. Selling
+/// `x` repays `x` of debt but also pays `x * tip_rate` to the keeper, so
+/// x = (target * debt - collateral) / (target - 1 - tip_rate)
+/// Rounds up, so the vault lands at or just above the target
+pub fn calculate_deleverage_collateral_to_sell(
+	collateral_amount: u128,
+	debt_in_collateral: u128,
+	target_collateral_ratio: u64,
+	tip_rate: u32
+) -> NormalResult<u128> {
+	let target_debt = debt_in_collateral.safe_mul(target_collateral_ratio.cast()?)?;
+	let scaled_collateral = collateral_amount.safe_mul(PERCENTAGE_PRECISION)?;
+
+	if target_debt <= scaled_collateral {
+		return Ok(0);
+	}
+
+	let denominator = target_collateral_ratio
+		.cast::<u128>()?
+		.saturating_sub(PERCENTAGE_PRECISION.safe_add(tip_rate.cast()?)?);
+
+	validate!(
+		denominator > 0,
+		ErrorCode::InvalidDeleverageOrder,
+		"target collateral ratio {} can't be reached with tip {}",
+		target_collateral_ratio,
+		tip_rate
+	)?;
+
+	// the sale and its tip together can't exceed the vault's collateral
+	let max_collateral_to_sell = collateral_amount
+		.safe_mul(PERCENTAGE_PRECISION)?
+		.safe_div(PERCENTAGE_PRECISION.safe_add(tip_rate.cast()?)?)?;
+
+	Ok(target_debt.safe_sub(scaled_collateral)?.safe_div_ceil(denominator)?.min(max_collateral_to_sell))
+}
+
+/// Least synthetic the AMM must return for `collateral_amount`, `max_slippage` below oracle
+pub fn calculate_deleverage_min_synthetic_out(
+	collateral_amount: u64,
+	collateral_decimals: u32,
+	collateral_price: i64,
+	synthetic_decimals: u32,
+	synthetic_price: i64,
+	max_slippage: u32
+) -> NormalResult<u64> {
+	collateral_amount
+		.cast::<u128>()?
+		.safe_mul(collateral_price.max(0).cast()?)?
+		.safe_mul((10_u128).pow(synthetic_decimals))?
+		.safe_div(synthetic_price.max(1).cast::<u128>()?.safe_mul((10_u128).pow(collateral_decimals))?)?
+		.safe_mul(PERCENTAGE_PRECISION.saturating_sub(max_slippage.cast()?))?
+		.safe_div(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+pub fn calculate_deleverage_keeper_tip(collateral_sold: u64, tip_rate: u32) -> NormalResult<u64> {
+	collateral_sold
+		.cast::<u128>()?
+		.safe_mul(tip_rate.cast()?)?
+		.safe_div(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::math::deleverage::*;
+
+	#[test]
+	fn tip_capped_by_liquidation_penalty() {
+		// 5% penalty caps the tip at 1.25%
+		assert_eq!(calculate_deleverage_tip_rate(50_000, 50_000).unwrap(), 12_500);
+		assert_eq!(calculate_deleverage_tip_rate(5_000, 50_000).unwrap(), 5_000);
+		assert_eq!(calculate_deleverage_tip_rate(5_000, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn collateral_to_reach_target() {
+		// 140 collateral against 100 debt (140%), target 200% with no tip:
+		// (200 - 140) / (2 - 1) = 60 -> 80 / 40
+		assert_eq!(calculate_deleverage_collateral_to_sell(140, 100, 2_000_000, 0).unwrap(), 60);
+
+		// 1% tip: (200 - 140) / (2 - 1.01) = 60.6 -> 61
+		assert_eq!(
+			calculate_deleverage_collateral_to_sell(140, 100, 2_000_000, 10_000).unwrap(),
+			61
+		);
+
+		// already above target
+		assert_eq!(calculate_deleverage_collateral_to_sell(250, 100, 2_000_000, 0).unwrap(), 0);
+
+		// target at or below 100% + tip is unreachable
+		assert!(calculate_deleverage_collateral_to_sell(90, 100, 1_000_000, 0).is_err());
+
+		// never sells more than the vault holds
+		assert_eq!(
+			calculate_deleverage_collateral_to_sell(100, 110, 1_500_000, 0).unwrap(),
+			100
+		);
+	}
+
+	#[test]
+	fn min_synthetic_out() {
+		// 1 SOL (9 decimals) at $150 into synthetic USD (6 decimals) at $1, 1% slippage
+		assert_eq!(
+			calculate_deleverage_min_synthetic_out(
+				1_000_000_000,
+				9,
+				150_000_000,
+				6,
+				1_000_000,
+				10_000
+			).unwrap(),
+			148_500_000
+		);
+	}
+
+	#[test]
+	fn keeper_tip() {
+		assert_eq!(calculate_deleverage_keeper_tip(1_000_000, 12_500).unwrap(), 12_500);
+		assert_eq!(calculate_deleverage_keeper_tip(79, 12_500).unwrap(), 0);
+	}
+}
@@ -0,0 +1,113 @@
+use crate::constants::main::{
+	DYNAMIC_FEE_TICK_VOLATILITY,
+	ONE_BPS_DENOMINATOR,
+	PERCENTAGE_PRECISION,
+};
+use crate::error::NormalResult;
+use crate::math::amm::MAX_FEE_RATE;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+
+/// Volatility as a fraction of price: the larger of the oracle's standard deviation and
+/// confidence, plus the pool's tick movement since the reference tick
+/// precision: PERCENTAGE_PRECISION
+pub fn calculate_swap_volatility(
+	oracle_std: u64,
+	oracle_twap: i64,
+	oracle_conf_pct: u64,
+	tick_movement: u32
+) -> NormalResult<u64> {
+	let oracle_std_pct = if oracle_twap > 0 {
+		oracle_std
+			.cast::<u128>()?
+			.safe_mul(PERCENTAGE_PRECISION)?
+			.safe_div(oracle_twap.cast()?)?
+			.cast::<u64>()?
+	} else {
+		0
+	};
+
+	let tick_volatility = tick_movement.cast::<u64>()?.safe_mul(DYNAMIC_FEE_TICK_VOLATILITY)?;
+
+	oracle_std_pct.max(oracle_conf_pct).safe_add(tick_volatility)
+}
+
+/// `fee_rate` plus `volatility_fee_multiplier` (in bps) of the volatility, bounded by the
+/// min and max dynamic fee rates. All rates in hundredths of a basis point
+pub fn calculate_dynamic_fee_rate(
+	fee_rate: u16,
+	volatility: u64,
+	volatility_fee_multiplier: u16,
+	min_fee_rate: u16,
+	max_fee_rate: u16
+) -> NormalResult<u16> {
+	let volatility_fee = volatility
+		.cast::<u128>()?
+		.safe_mul(volatility_fee_multiplier.cast()?)?
+		.safe_div(ONE_BPS_DENOMINATOR.cast()?)?;
+
+	let max_fee_rate = max_fee_rate.min(MAX_FEE_RATE);
+
+	fee_rate
+		.cast::<u128>()?
+		.safe_add(volatility_fee)?
+		.clamp(min_fee_rate.min(max_fee_rate).cast()?, max_fee_rate.cast()?)
+		.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{ PERCENTAGE_PRECISION_U64, PRICE_PRECISION_U64 };
+	use crate::math::amm::MAX_FEE_RATE;
+	use crate::math::dynamic_fee::{ calculate_dynamic_fee_rate, calculate_swap_volatility };
+
+	#[test]
+	fn volatility_from_oracle_and_ticks() {
+		let twap = 100 * (PRICE_PRECISION_U64 as i64);
+
+		// $1 std on a $100 twap is 1%
+		assert_eq!(
+			calculate_swap_volatility(PRICE_PRECISION_U64, twap, 0, 0).unwrap(),
+			PERCENTAGE_PRECISION_U64 / 100
+		);
+
+		// the wider of std and confidence counts
+		assert_eq!(
+			calculate_swap_volatility(PRICE_PRECISION_U64, twap, PERCENTAGE_PRECISION_U64 / 50, 0).unwrap(),
+			PERCENTAGE_PRECISION_U64 / 50
+		);
+
+		// 50 ticks is ~50 bps
+		assert_eq!(
+			calculate_swap_volatility(PRICE_PRECISION_U64, twap, 0, 50).unwrap(),
+			PERCENTAGE_PRECISION_U64 / 100 + PERCENTAGE_PRECISION_U64 / 200
+		);
+
+		// no twap, no std component
+		assert_eq!(calculate_swap_volatility(PRICE_PRECISION_U64, 0, 0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn dynamic_fee_bounded() {
+		// 1% volatility charged at 1/10 adds 10 bps to a 30 bps fee
+		assert_eq!(
+			calculate_dynamic_fee_rate(3000, PERCENTAGE_PRECISION_U64 / 100, 1000, 0, 10_000).unwrap(),
+			4000
+		);
+
+		// calm markets sit at the min
+		assert_eq!(calculate_dynamic_fee_rate(100, 0, 1000, 500, 10_000).unwrap(), 500);
+
+		// volatile markets stop at the max
+		assert_eq!(
+			calculate_dynamic_fee_rate(3000, PERCENTAGE_PRECISION_U64, 10_000, 0, 10_000).unwrap(),
+			10_000
+		);
+
+		// which never passes MAX_FEE_RATE
+		assert_eq!(
+			calculate_dynamic_fee_rate(3000, PERCENTAGE_PRECISION_U64, 10_000, 0, u16::MAX).unwrap(),
+			MAX_FEE_RATE
+		);
+	}
+}
@@ -1,49 +1,410 @@
-use crate::controller::position::OrderSide;
+use crate::constants::main::{
+	INDEX_WEIGHT_PRECISION,
+	ONE_BPS_DENOMINATOR,
+	ONE_YEAR,
+	PRICE_PRECISION_U64,
+};
 use crate::error::{ NormalResult, ErrorCode };
+use crate::math::bn::{ U192, U256 };
 use crate::math::casting::Cast;
+use crate::math::helpers::get_proportion_u128;
 use crate::math::safe_math::SafeMath;
-use crate::state::oracle::OraclePriceData;
-use crate::state::oracles::index_fund::{ IndexFundAssets, WeightingMethod };
+use crate::state::index_market::{ IndexAsset, WeightingMethod };
+use crate::validate;
 use solana_program::msg;
 
-use crate::state::fill_mode::FillMode;
-use crate::state::synth_market::SynthMarket;
-use std::cmp::min;
-use std::collections::BTreeMap;
+pub fn calculate_total_weight(assets: &[IndexAsset]) -> NormalResult<u16> {
+	assets.iter().try_fold(0_u16, |total, asset| total.safe_add(asset.weight))
+}
 
-pub fn calculate_total_weight(index_assets: IndexAssets) -> NormalResult<u64> {
-	// assumes that missing times are zeros (e.g. handle NaN as 0)
-	let prev_twap_99 = data1
-		.cast::<u128>()?
-		.safe_mul(max(0, weight1_denom.safe_sub(weight1_numer)?).cast::<u128>()?)?
-		.safe_div(weight1_denom.cast::<u128>()?)?;
+/// Splits accrued fees into (protocol, manager, referral). The protocol and referral shares
+/// are bps of the accrual and the manager gets the rest.
+pub fn calculate_fee_split(
+	accrued: u64,
+	revenue_share: u64,
+	referral_share: u64
+) -> NormalResult<(u64, u64, u64)> {
+	let denominator = ONE_BPS_DENOMINATOR.cast::<u128>()?;
+
+	let share_of = |share: u64| -> NormalResult<u64> {
+		accrued
+			.cast::<u128>()?
+			.safe_mul(share.cast::<u128>()?.min(denominator))?
+			.safe_div(denominator)?
+			.cast()
+	};
 
-	prev_twap_99.cast::<u64>()?.safe_add(data2)
+	let protocol_fee = share_of(revenue_share)?;
+	let referral_fee = share_of(referral_share)?.min(accrued.safe_sub(protocol_fee)?);
+	let manager_fee = accrued.safe_sub(protocol_fee)?.safe_sub(referral_fee)?;
+
+	Ok((protocol_fee, manager_fee, referral_fee))
 }
 
+/// Value of `supply` of an asset at its oracle price, e.g. its market cap
+/// precision: PRICE_PRECISION
+pub fn calculate_market_cap(
+	supply: u64,
+	decimals: u32,
+	oracle_price: i64
+) -> NormalResult<u128> {
+	if oracle_price <= 0 {
+		return Ok(0);
+	}
+
+	supply
+		.cast::<u128>()?
+		.safe_mul(oracle_price.cast()?)?
+		.safe_div((10_u128).pow(decimals))
+}
+
+/// Generates weights in bps summing to exactly INDEX_WEIGHT_PRECISION.
+/// `market_caps` and `custom_weights` are in the same order as the index's assets.
 pub fn generate_weights(
-	method: WeightingMethod
-) -> NormalResult<IndexFundAssets> {
+	method: WeightingMethod,
+	market_caps: &[u128],
+	custom_weights: &[u16]
+) -> NormalResult<Vec<u16>> {
+	let num_assets = market_caps.len().max(custom_weights.len());
+
+	validate!(num_assets > 0, ErrorCode::InvalidIndexWeights, "index has no assets")?;
+
 	match method {
-		WeightingMethod::Equal => 10000 / (num_assets as u16), // TODO: replace with TEN_THOUSAND constant
-		WeightingMethod::Custom => {}
-		WeightingMethod::MarketCap => {
-			let total_market_cap: f64 = market_caps.iter().sum();
-			market_caps
-				.iter()
-				.map(|cap| (cap / total_market_cap) * 100.0)
+		WeightingMethod::Equal => {
+			let num_assets = num_assets.cast::<u16>()?;
+			let weight = INDEX_WEIGHT_PRECISION.safe_div(num_assets)?;
+			let remainder = INDEX_WEIGHT_PRECISION.safe_sub(weight.safe_mul(num_assets)?)?;
+
+			// the leftover bps go one each to the first assets
+			(0..num_assets)
+				.map(|i| if i < remainder { weight.safe_add(1) } else { Ok(weight) })
 				.collect()
 		}
-		WeightingMethod::SquareRootMarketCap => {
-			let sqrt_market_caps: Vec<f64> = market_caps
+		WeightingMethod::Custom => {
+			let total = custom_weights
 				.iter()
-				.map(|cap| cap.sqrt())
-				.collect();
-			let total_sqrt_market_cap: f64 = sqrt_market_caps.iter().sum();
-			sqrt_market_caps
+				.try_fold(0_u16, |total, weight| total.safe_add(*weight))?;
+
+			validate!(
+				total == INDEX_WEIGHT_PRECISION,
+				ErrorCode::InvalidIndexWeights,
+				"custom weights sum to {}, must be {}",
+				total,
+				INDEX_WEIGHT_PRECISION
+			)?;
+
+			Ok(custom_weights.to_vec())
+		}
+		WeightingMethod::MarketCap => proportional_weights(market_caps),
+		WeightingMethod::SquareRootMarketCap => {
+			let sqrt_market_caps = market_caps
 				.iter()
-				.map(|sqrt_cap| (sqrt_cap / total_sqrt_market_cap) * 100.0)
-				.collect()
+				.map(|market_cap| U256::from(*market_cap).integer_sqrt().try_to_u128())
+				.collect::<NormalResult<Vec<u128>>>()?;
+
+			proportional_weights(&sqrt_market_caps)
+		}
+	}
+}
+
+/// Splits INDEX_WEIGHT_PRECISION in proportion to `values`. Rounding dust goes to the
+/// largest weight (first on ties) so the result is deterministic.
+fn proportional_weights(values: &[u128]) -> NormalResult<Vec<u16>> {
+	let total = values.iter().try_fold(0_u128, |total, value| total.safe_add(*value))?;
+
+	validate!(
+		total > 0,
+		ErrorCode::InvalidIndexWeights,
+		"index assets have no market cap"
+	)?;
+
+	let mut weights = values
+		.iter()
+		.map(|value| get_proportion_u128(INDEX_WEIGHT_PRECISION.cast()?, *value, total)?.cast())
+		.collect::<NormalResult<Vec<u16>>>()?;
+
+	let assigned = weights.iter().try_fold(0_u16, |total, weight| total.safe_add(*weight))?;
+	let dust = INDEX_WEIGHT_PRECISION.safe_sub(assigned)?;
+
+	if dust > 0 {
+		let mut largest = 0;
+		for (i, weight) in weights.iter().enumerate() {
+			if *weight > weights[largest] {
+				largest = i;
+			}
+		}
+		weights[largest] = weights[largest].safe_add(dust)?;
+	}
+
+	Ok(weights)
+}
+
+/// Index tokens owed to the manager and protocol for `elapsed` seconds at an annual
+/// `expense_ratio` (bps). Paid by diluting holders rather than selling the basket.
+pub fn calculate_expense_accrual(
+	total_shares: u64,
+	expense_ratio: u64,
+	elapsed: i64
+) -> NormalResult<u64> {
+	if elapsed <= 0 || expense_ratio == 0 {
+		return Ok(0);
+	}
+
+	total_shares
+		.cast::<u128>()?
+		.safe_mul(expense_ratio.cast()?)?
+		.safe_mul(elapsed.cast()?)?
+		.safe_div(ONE_BPS_DENOMINATOR.cast::<u128>()?.safe_mul(ONE_YEAR)?)?
+		.cast()
+}
+
+/// NAV per whole index token, $1 until the first creation
+/// precision: PRICE_PRECISION
+pub fn calculate_index_nav(
+	basket_value: u128,
+	total_shares: u64,
+	index_decimals: u32
+) -> NormalResult<u64> {
+	if total_shares == 0 {
+		return Ok(PRICE_PRECISION_U64);
+	}
+
+	basket_value
+		.safe_mul((10_u128).pow(index_decimals))?
+		.safe_div(total_shares.cast()?)?
+		.cast()
+}
+
+/// Amount of a constituent to deposit to create `index_amount` at `nav`,
+/// rounded up so creations never dilute existing holders
+pub fn calculate_creation_deposit(
+	index_amount: u64,
+	index_decimals: u32,
+	nav: u64,
+	weight: u16,
+	oracle_price: i64,
+	asset_decimals: u32
+) -> NormalResult<u64> {
+	validate!(
+		oracle_price > 0,
+		ErrorCode::OracleNonPositive,
+		"oracle_price={} must be positive to create index tokens",
+		oracle_price
+	)?;
+
+	let numerator = U192::from(index_amount)
+		.safe_mul(U192::from(nav))?
+		.safe_mul(U192::from(weight))?
+		.safe_mul(U192::from((10_u128).pow(asset_decimals)))?;
+
+	let denominator = U192::from((10_u128).pow(index_decimals))
+		.safe_mul(U192::from(INDEX_WEIGHT_PRECISION))?
+		.safe_mul(U192::from(oracle_price.cast::<u64>()?))?;
+
+	numerator
+		.safe_add(denominator.safe_sub(U192::from(1_u8))?)?
+		.safe_div(denominator)?
+		.try_to_u64()
+}
+
+/// Pro rata share of a basket vault returned for redeeming `index_amount`
+pub fn calculate_redemption_amount(
+	vault_amount: u64,
+	index_amount: u64,
+	total_shares: u64
+) -> NormalResult<u64> {
+	validate!(
+		index_amount <= total_shares,
+		ErrorCode::InsufficientCollateral,
+		"redeeming {} of {} index tokens",
+		index_amount,
+		total_shares
+	)?;
+
+	get_proportion_u128(vault_amount.cast()?, index_amount.cast()?, total_shares.cast()?)?.cast()
+}
+
+/// Splits `amount` across the assets by weight. Rounding dust goes to the largest weight
+/// (first on ties) so the legs always sum to `amount`.
+pub fn split_by_weights(amount: u64, weights: &[u16]) -> NormalResult<Vec<u64>> {
+	let mut legs = weights
+		.iter()
+		.map(|weight|
+			amount
+				.cast::<u128>()?
+				.safe_mul(weight.cast()?)?
+				.safe_div(INDEX_WEIGHT_PRECISION.cast()?)?
+				.cast::<u64>()
+		)
+		.collect::<NormalResult<Vec<u64>>>()?;
+
+	let assigned = legs.iter().try_fold(0_u64, |total, leg| total.safe_add(*leg))?;
+	let dust = amount.safe_sub(assigned)?;
+
+	if dust > 0 && !legs.is_empty() {
+		let mut largest = 0;
+		for (i, weight) in weights.iter().enumerate() {
+			if *weight > weights[largest] {
+				largest = i;
+			}
 		}
+		legs[largest] = legs[largest].safe_add(dust)?;
+	}
+
+	Ok(legs)
+}
+
+/// Index tokens worth `value` at `nav`
+pub fn calculate_index_amount_for_value(
+	value: u128,
+	nav: u64,
+	index_decimals: u32
+) -> NormalResult<u64> {
+	validate!(nav > 0, ErrorCode::MathError, "index nav is zero")?;
+
+	value
+		.safe_mul((10_u128).pow(index_decimals))?
+		.safe_div(nav.cast()?)?
+		.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{ INDEX_WEIGHT_PRECISION, PRICE_PRECISION_I64 };
+	use crate::math::index::{
+		calculate_creation_deposit,
+		calculate_expense_accrual,
+		calculate_fee_split,
+		calculate_index_amount_for_value,
+		calculate_index_nav,
+		calculate_market_cap,
+		calculate_redemption_amount,
+		generate_weights,
+		split_by_weights,
+	};
+	use crate::state::index_market::WeightingMethod;
+
+	fn sum(weights: &[u16]) -> u16 {
+		weights.iter().sum()
+	}
+
+	#[test]
+	fn equal_weights() {
+		let weights = generate_weights(WeightingMethod::Equal, &[0, 0, 0], &[]).unwrap();
+		assert_eq!(weights, vec![3334, 3333, 3333]);
+		assert_eq!(sum(&weights), INDEX_WEIGHT_PRECISION);
+	}
+
+	#[test]
+	fn custom_weights() {
+		let weights = generate_weights(WeightingMethod::Custom, &[], &[6000, 4000]).unwrap();
+		assert_eq!(weights, vec![6000, 4000]);
+
+		assert!(generate_weights(WeightingMethod::Custom, &[], &[6000, 3000]).is_err());
+	}
+
+	#[test]
+	fn market_cap_weights() {
+		let weights = generate_weights(WeightingMethod::MarketCap, &[300, 100, 100], &[]).unwrap();
+		assert_eq!(weights, vec![6000, 2000, 2000]);
+
+		// dust goes to the largest weight
+		let weights = generate_weights(WeightingMethod::MarketCap, &[1, 1, 1], &[]).unwrap();
+		assert_eq!(weights, vec![3334, 3333, 3333]);
+
+		assert!(generate_weights(WeightingMethod::MarketCap, &[0, 0], &[]).is_err());
+	}
+
+	#[test]
+	fn square_root_market_cap_weights() {
+		let weights = generate_weights(
+			WeightingMethod::SquareRootMarketCap,
+			&[900, 100],
+			&[]
+		).unwrap();
+		assert_eq!(weights, vec![7500, 2500]);
+		assert_eq!(sum(&weights), INDEX_WEIGHT_PRECISION);
+	}
+
+	#[test]
+	fn market_cap_from_supply() {
+		// 1000 tokens with 6 decimals at $2
+		assert_eq!(
+			calculate_market_cap(1_000_000_000, 6, 2 * PRICE_PRECISION_I64).unwrap(),
+			2_000 * (PRICE_PRECISION_I64 as u128)
+		);
+		assert_eq!(calculate_market_cap(1_000_000_000, 6, -1).unwrap(), 0);
+	}
+
+	#[test]
+	fn expense_accrual() {
+		// 1% a year on 1M shares for a year
+		assert_eq!(
+			calculate_expense_accrual(1_000_000_000_000, 100, 31_536_000).unwrap(),
+			10_000_000_000
+		);
+		assert_eq!(calculate_expense_accrual(1_000_000_000_000, 100, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn creation_deposit_at_nav() {
+		let nav = calculate_index_nav(0, 0, 6).unwrap();
+		assert_eq!(nav, PRICE_PRECISION_I64 as u64);
+
+		// 100 index tokens at $1, 60% in an asset at $2 with 9 decimals -> 30 tokens
+		assert_eq!(
+			calculate_creation_deposit(100_000_000, 6, nav, 6000, 2 * PRICE_PRECISION_I64, 9).unwrap(),
+			30_000_000_000
+		);
+
+		// rounds up
+		assert_eq!(
+			calculate_creation_deposit(1, 6, nav, 3333, 3 * PRICE_PRECISION_I64, 6).unwrap(),
+			1
+		);
+
+		// $300 basket over 100 shares
+		assert_eq!(
+			calculate_index_nav(300 * (PRICE_PRECISION_I64 as u128), 100_000_000, 6).unwrap(),
+			3 * (PRICE_PRECISION_I64 as u64)
+		);
+	}
+
+	#[test]
+	fn redemption_pro_rata() {
+		assert_eq!(calculate_redemption_amount(1_000, 25, 100).unwrap(), 250);
+		assert_eq!(calculate_redemption_amount(1_000, 1, 3).unwrap(), 333);
+		assert!(calculate_redemption_amount(1_000, 101, 100).is_err());
+	}
+
+	#[test]
+	fn split_quote_by_weights() {
+		assert_eq!(split_by_weights(1_000, &[6000, 4000]).unwrap(), vec![600, 400]);
+		// dust goes to the largest weight
+		assert_eq!(split_by_weights(100, &[3333, 3334, 3333]).unwrap(), vec![33, 34, 33]);
+		assert_eq!(split_by_weights(10, &[3334, 3333, 3333]).unwrap(), vec![4, 3, 3]);
+	}
+
+	#[test]
+	fn index_amount_for_value() {
+		// $300 at a $3 nav with 6 decimals
+		assert_eq!(
+			calculate_index_amount_for_value(
+				300 * (PRICE_PRECISION_I64 as u128),
+				3 * (PRICE_PRECISION_I64 as u64),
+				6
+			).unwrap(),
+			100_000_000
+		);
+		assert!(calculate_index_amount_for_value(1, 0, 6).is_err());
+	}
+
+	#[test]
+	fn fee_split() {
+		// 20% protocol, 10% referrer
+		assert_eq!(calculate_fee_split(1_000, 2_000, 1_000).unwrap(), (200, 700, 100));
+		assert_eq!(calculate_fee_split(1_000, 0, 0).unwrap(), (0, 1_000, 0));
+		// shares over 100% never pay out more than accrued
+		assert_eq!(calculate_fee_split(1_000, 8_000, 5_000).unwrap(), (800, 0, 200));
 	}
 }
@@ -0,0 +1,156 @@
+use crate::constants::main::{
+	LENDING_EXCHANGE_RATE_PRECISION,
+	SPOT_CUMULATIVE_INTEREST_PRECISION,
+	SPOT_UTILIZATION_PRECISION,
+};
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+
+/// Collateral that can still be lent before the market hits its lending cap
+pub fn calculate_lendable_amount(
+	deposit_token_amount: u128,
+	collateral_loan_balance: u64,
+	max_collateral_lending_utilization: u64
+) -> NormalResult<u64> {
+	let max_loan_balance = deposit_token_amount
+		.safe_mul(max_collateral_lending_utilization.cast()?)?
+		.safe_div(SPOT_UTILIZATION_PRECISION)?;
+
+	max_loan_balance.saturating_sub(collateral_loan_balance.cast()?).cast()
+}
+
+pub fn calculate_lending_utilization(
+	deposit_token_amount: u128,
+	collateral_loan_balance: u64
+) -> NormalResult<u64> {
+	if deposit_token_amount == 0 {
+		return Ok(0);
+	}
+
+	collateral_loan_balance
+		.cast::<u128>()?
+		.safe_mul(SPOT_UTILIZATION_PRECISION)?
+		.safe_div(deposit_token_amount)?
+		.min(SPOT_UTILIZATION_PRECISION)
+		.cast()
+}
+
+/// Collateral the lending shares redeem for at `exchange_rate`
+pub fn calculate_loan_value(loan_shares: u64, exchange_rate: u128) -> NormalResult<u64> {
+	loan_shares
+		.cast::<u128>()?
+		.safe_mul(exchange_rate)?
+		.safe_div(LENDING_EXCHANGE_RATE_PRECISION)?
+		.cast()
+}
+
+/// Shares to redeem to get back at least `amount` at `exchange_rate`, capped at the shares held
+pub fn calculate_shares_to_redeem(
+	amount: u64,
+	exchange_rate: u128,
+	loan_shares: u64
+) -> NormalResult<u64> {
+	if exchange_rate == 0 {
+		return Ok(loan_shares);
+	}
+
+	let shares = amount
+		.cast::<u128>()?
+		.safe_mul(LENDING_EXCHANGE_RATE_PRECISION)?
+		.safe_div_ceil(exchange_rate)?;
+
+	shares.min(loan_shares.cast()?).cast()
+}
+
+/// Growth of cumulative_lp_interest that credits `lending_yield` pro rata to depositors
+pub fn calculate_lp_interest_delta(
+	lending_yield: u64,
+	deposit_token_amount: u128,
+	cumulative_lp_interest: u128
+) -> NormalResult<u128> {
+	if lending_yield == 0 || deposit_token_amount == 0 {
+		return Ok(0);
+	}
+
+	cumulative_lp_interest.safe_mul(lending_yield.cast()?)?.safe_div(deposit_token_amount)
+}
+
+/// Deposit index including lending yield
+/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
+pub fn calculate_cumulative_deposit_interest_with_lending_yield(
+	cumulative_deposit_interest: u128,
+	cumulative_lp_interest: u128
+) -> NormalResult<u128> {
+	cumulative_deposit_interest
+		.safe_mul(cumulative_lp_interest)?
+		.safe_div(SPOT_CUMULATIVE_INTEREST_PRECISION)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{
+		LENDING_EXCHANGE_RATE_PRECISION,
+		SPOT_CUMULATIVE_INTEREST_PRECISION,
+	};
+	use crate::math::lending::{
+		calculate_cumulative_deposit_interest_with_lending_yield,
+		calculate_lendable_amount,
+		calculate_lending_utilization,
+		calculate_loan_value,
+		calculate_lp_interest_delta,
+		calculate_shares_to_redeem,
+	};
+
+	#[test]
+	fn lending_cap() {
+		// 50% cap on 1000 deposited
+		assert_eq!(calculate_lendable_amount(1_000_000_000, 0, 500_000).unwrap(), 500_000_000);
+		assert_eq!(
+			calculate_lendable_amount(1_000_000_000, 400_000_000, 500_000).unwrap(),
+			100_000_000
+		);
+		// over the cap after withdrawals, nothing left to lend
+		assert_eq!(calculate_lendable_amount(600_000_000, 400_000_000, 500_000).unwrap(), 0);
+		// lending disabled
+		assert_eq!(calculate_lendable_amount(1_000_000_000, 0, 0).unwrap(), 0);
+
+		assert_eq!(calculate_lending_utilization(1_000_000_000, 400_000_000).unwrap(), 400_000);
+		assert_eq!(calculate_lending_utilization(0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn loan_value_and_redemption() {
+		let rate = (LENDING_EXCHANGE_RATE_PRECISION * 105) / 100;
+
+		assert_eq!(calculate_loan_value(1_000_000, rate).unwrap(), 1_050_000);
+
+		// rounds up so the unlend covers the amount asked for
+		assert_eq!(calculate_shares_to_redeem(1_050_000, rate, 2_000_000).unwrap(), 1_000_000);
+		assert_eq!(calculate_shares_to_redeem(1, rate, 2_000_000).unwrap(), 1);
+		// capped at the shares held
+		assert_eq!(calculate_shares_to_redeem(5_000_000, rate, 2_000_000).unwrap(), 2_000_000);
+	}
+
+	#[test]
+	fn lending_yield_credited_to_depositors() {
+		let cumulative_lp_interest = SPOT_CUMULATIVE_INTEREST_PRECISION;
+
+		// 50 of yield on 1000 deposited -> 5%
+		let delta = calculate_lp_interest_delta(
+			50_000_000,
+			1_000_000_000,
+			cumulative_lp_interest
+		).unwrap();
+		assert_eq!(delta, SPOT_CUMULATIVE_INTEREST_PRECISION / 20);
+
+		let deposit_interest = calculate_cumulative_deposit_interest_with_lending_yield(
+			SPOT_CUMULATIVE_INTEREST_PRECISION,
+			cumulative_lp_interest + delta
+		).unwrap();
+		assert_eq!(deposit_interest, (SPOT_CUMULATIVE_INTEREST_PRECISION * 105) / 100);
+
+		assert_eq!(calculate_lp_interest_delta(0, 1_000_000_000, cumulative_lp_interest).unwrap(), 0);
+		assert_eq!(calculate_lp_interest_delta(50, 0, cumulative_lp_interest).unwrap(), 0);
+	}
+}
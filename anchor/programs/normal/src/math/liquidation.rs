@@ -19,9 +19,9 @@ use crate::math::safe_math::SafeMath;
 use crate::math::synth_balance::get_token_amount;
 
 use crate::math::spot_swap::calculate_swap_price;
-use crate::state::margin_calculation::MarginContext;
+use crate::state::margin_calculation::{ MarginCalculation, MarginContext };
 use crate::state::oracle_map::OracleMap;
-use crate::state::synth_market::SynthMarket;
+use crate::state::synth_market::{ SynthMarket, SyntheticTier };
 use crate::state::synth_market_map::SynthMarketMap;
 use crate::state::user::{ User };
 use crate::{
@@ -463,3 +463,178 @@ pub fn get_liquidation_fee(
 	);
 	Ok(liquidation_fee.min(max_liquidation_fee))
 }
+
+/// A vault's debt can only be liquidated once none of the user's other vaults owe debt of a
+/// less speculative tier, A first through Isolated last
+pub fn validate_liability_tier_liquidation_order(
+	liability_tier: SyntheticTier,
+	other_liability_tiers: impl IntoIterator<Item = SyntheticTier>
+) -> NormalResult {
+	for other_tier in other_liability_tiers {
+		validate!(
+			liability_tier <= other_tier,
+			ErrorCode::TierViolationLiquidatingSynthetic,
+			"{:?} tier liability must be liquidated before {:?} tier",
+			other_tier,
+			liability_tier
+		)?;
+	}
+
+	Ok(())
+}
+
+/// Every vault liquidation opens a collateral auction in a fresh account, so it fails when the
+/// vault has nothing to auction. An empty auction could never settle and would block the vault's
+/// next liquidation
+pub fn validate_vault_liquidatable(
+	is_being_liquidated: bool,
+	margin_calculation: &MarginCalculation
+) -> NormalResult {
+	if !is_being_liquidated {
+		validate!(
+			!margin_calculation.meets_margin_requirement(),
+			ErrorCode::SufficientCollateral,
+			"margin calculation: {:?}",
+			margin_calculation
+		)?;
+	} else {
+		validate!(
+			!margin_calculation.can_exit_liquidation()?,
+			ErrorCode::SufficientCollateral,
+			"vault can exit liquidation, margin calculation: {:?}",
+			margin_calculation
+		)?;
+	}
+
+	Ok(())
+}
+
+/// The debt a liquidator takes from a vault, capped by their max and the share of the debt
+/// liquidatable so far. Vaults owing less than $50 can be liquidated in full
+pub fn calculate_vault_debt_to_liquidate(
+	debt_amount: u128,
+	debt_value: u128,
+	max_pct_allowed: u128,
+	liquidator_max_base_asset_amount: u64
+) -> NormalResult<u128> {
+	let max_debt_allowed = if debt_value > 50 * QUOTE_PRECISION {
+		debt_amount.saturating_mul(max_pct_allowed).safe_div(LIQUIDATION_PCT_PRECISION)?
+	} else {
+		debt_amount
+	};
+
+	let debt_to_liquidate = debt_amount
+		.min(liquidator_max_base_asset_amount.cast()?)
+		.min(max_debt_allowed);
+
+	validate!(
+		debt_to_liquidate > 0,
+		ErrorCode::InvalidLiquidation,
+		"max debt allowed to be liquidated == 0"
+	)?;
+
+	Ok(debt_to_liquidate)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::error::ErrorCode;
+	use crate::constants::main::{ LIQUIDATION_PCT_PRECISION, QUOTE_PRECISION };
+	use crate::math::liquidation::{
+		calculate_vault_debt_to_liquidate,
+		validate_liability_tier_liquidation_order,
+		validate_vault_liquidatable,
+	};
+	use crate::state::margin_calculation::{ MarginCalculation, MarginContext };
+	use crate::state::synth_market::SyntheticTier;
+
+	#[test]
+	fn vault_that_can_exit_liquidation_cant_be_liquidated() {
+		let mut margin_calculation = MarginCalculation::new(MarginContext::liquidation(100));
+		margin_calculation.margin_requirement = 100 * QUOTE_PRECISION;
+		margin_calculation.margin_requirement_plus_buffer = 110 * QUOTE_PRECISION;
+
+		// above the buffer, an ongoing liquidation has to end instead of opening an auction
+		margin_calculation.total_collateral = (110 * QUOTE_PRECISION) as i128;
+		assert_eq!(
+			validate_vault_liquidatable(true, &margin_calculation),
+			Err(ErrorCode::SufficientCollateral)
+		);
+
+		// between the requirement and the buffer only an ongoing liquidation continues
+		margin_calculation.total_collateral = (105 * QUOTE_PRECISION) as i128;
+		assert!(validate_vault_liquidatable(true, &margin_calculation).is_ok());
+		assert_eq!(
+			validate_vault_liquidatable(false, &margin_calculation),
+			Err(ErrorCode::SufficientCollateral)
+		);
+
+		margin_calculation.total_collateral = (90 * QUOTE_PRECISION) as i128;
+		assert!(validate_vault_liquidatable(false, &margin_calculation).is_ok());
+	}
+
+	#[test]
+	fn liquidation_of_no_debt_fails() {
+		let debt_amount = 1_000 * QUOTE_PRECISION;
+		let debt_value = 1_000 * QUOTE_PRECISION;
+		let max_pct_allowed = LIQUIDATION_PCT_PRECISION / 4;
+
+		// a liquidator max of 0 would otherwise open an empty auction
+		assert_eq!(
+			calculate_vault_debt_to_liquidate(debt_amount, debt_value, max_pct_allowed, 0),
+			Err(ErrorCode::InvalidLiquidation)
+		);
+		assert_eq!(
+			calculate_vault_debt_to_liquidate(debt_amount, debt_value, 0, u64::MAX),
+			Err(ErrorCode::InvalidLiquidation)
+		);
+
+		assert_eq!(
+			calculate_vault_debt_to_liquidate(debt_amount, debt_value, max_pct_allowed, u64::MAX),
+			Ok(debt_amount / 4)
+		);
+		assert_eq!(
+			calculate_vault_debt_to_liquidate(debt_amount, debt_value, max_pct_allowed, 100),
+			Ok(100)
+		);
+
+		// small vaults are liquidated in full
+		assert_eq!(
+			calculate_vault_debt_to_liquidate(debt_amount, 10 * QUOTE_PRECISION, 0, u64::MAX),
+			Ok(debt_amount)
+		);
+	}
+
+	#[test]
+	fn safest_tier_liquidates_first() {
+		let tiers = [
+			SyntheticTier::A,
+			SyntheticTier::B,
+			SyntheticTier::C,
+			SyntheticTier::Speculative,
+			SyntheticTier::HighlySpeculative,
+			SyntheticTier::Isolated,
+		];
+
+		for (i, tier) in tiers.iter().enumerate() {
+			// a lone liability can always be liquidated
+			assert!(validate_liability_tier_liquidation_order(*tier, []).is_ok());
+
+			for (j, other_tier) in tiers.iter().enumerate() {
+				let result = validate_liability_tier_liquidation_order(*tier, [*other_tier]);
+				if i <= j {
+					assert!(result.is_ok(), "{:?} before {:?}", tier, other_tier);
+				} else {
+					assert!(result.is_err(), "{:?} before {:?}", tier, other_tier);
+				}
+			}
+		}
+
+		assert!(
+			validate_liability_tier_liquidation_order(SyntheticTier::C, [
+				SyntheticTier::Speculative,
+				SyntheticTier::B,
+			]).is_err()
+		);
+	}
+}
@@ -6,6 +6,7 @@ use crate::math::constants::{
 	PRICE_PRECISION,
 	SPOT_IMF_PRECISION_U128,
 	SPOT_WEIGHT_PRECISION,
+	SPOT_WEIGHT_PRECISION_I128,
 	SPOT_WEIGHT_PRECISION_U128,
 };
 
@@ -13,7 +14,7 @@ use crate::{ validate, PRICE_PRECISION_I128 };
 use crate::{ validation, PRICE_PRECISION_I64 };
 
 use crate::math::casting::Cast;
-use crate::math::oracle::{ is_oracle_valid_for_action, NormalAction };
+use crate::math::oracle::{ is_oracle_valid_for_action, oracle_validity, NormalAction };
 
 use crate::math::synth_balance::{ get_strict_token_value, get_token_value };
 
@@ -30,6 +31,7 @@ use crate::state::synth_market_map::SynthSynthMarketMap;
 use crate::state::user::{ MarketType, User };
 use num_integer::Roots;
 use solana_program::msg;
+use solana_program::pubkey::Pubkey;
 use std::cmp::{ max, min, Ordering };
 
 #[derive(Clone, Copy, PartialEq, Debug, Eq)]
@@ -89,12 +91,12 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
 		0_u32
 	};
 
-	for vault_position in user.vault_positions.iter() {
-		if vault_position.is_available() {
+	for position in user.positions.iter() {
+		if position.is_available() {
 			continue;
 		}
 
-		let market = market_map.get_ref(&vault_position.market_index)?;
+		let market = market_map.get_ref(&position.market_index)?;
 
 		let (oracle_price_data, oracle_validity) =
 			oracle_map.get_price_data_and_validity(
@@ -112,7 +114,81 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
 			)?
 		);
 
-		// TODO: ...
+		let synthetic_price = oracle_price_data.price;
+
+		// collateral: token_mint_collateral plus every registry asset backing the vault
+		if position.scaled_balance > 0 {
+			let (collateral_price, collateral_oracle_valid) = get_collateral_price_and_validity(
+				oracle_map,
+				market.market_index,
+				&market.collateral_oracle
+			)?;
+			calculation.update_all_oracles_valid(collateral_oracle_valid);
+
+			let asset_weight = match context.margin_type {
+				MarginRequirementType::Initial => market.initial_asset_weight,
+				MarginRequirementType::Maintenance => market.maintenance_asset_weight,
+			};
+
+			calculation.add_total_collateral(
+				calculate_weighted_collateral_value(
+					position.get_collateral_amount(&market)?,
+					market.collateral_decimals,
+					collateral_price,
+					asset_weight
+				)?
+			)?;
+		}
+
+		for (collateral, balance) in market.collateral_assets
+			.iter()
+			.zip(position.collateral_balances.iter()) {
+			if *balance == 0 || !collateral.is_active() {
+				continue;
+			}
+
+			let (collateral_price, collateral_oracle_valid) = get_collateral_price_and_validity(
+				oracle_map,
+				market.market_index,
+				&collateral.oracle
+			)?;
+			calculation.update_all_oracles_valid(collateral_oracle_valid);
+
+			calculation.add_total_collateral(
+				calculate_weighted_collateral_value(
+					(*balance).cast()?,
+					collateral.decimals,
+					collateral_price,
+					collateral.get_asset_weight(context.margin_type)
+				)?
+			)?;
+		}
+
+		// liability: the synthetic debt minted against the vault
+		if position.has_debt() {
+			let debt_amount = position.get_debt_amount(&market)?;
+			let liability_value = get_token_value(
+				debt_amount.cast()?,
+				market.decimals,
+				synthetic_price
+			)?.unsigned_abs();
+
+			let margin_ratio = market
+				.get_margin_ratio(debt_amount, context.margin_type)?
+				.max(user_custom_margin_ratio);
+
+			let margin_requirement = liability_value
+				.safe_mul(margin_ratio.cast()?)?
+				.safe_div(MARGIN_PRECISION_U128)?;
+
+			calculation.add_margin_requirement(
+				margin_requirement,
+				liability_value,
+				MarketIdentifier::synth(market.market_index)
+			)?;
+			calculation.add_vault_liability()?;
+			calculation.update_with_perp_isolated_liability(market.synthetic_tier.is_isolated());
+		}
 	}
 
 	calculation.validate_num_spot_liabilities()?;
@@ -120,6 +196,103 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
 	Ok(calculation)
 }
 
+/// Collateral oracles keep no twap, so they're checked for sign, confidence and staleness but
+/// not volatility
+fn get_collateral_price_and_validity(
+	oracle_map: &mut OracleMap,
+	market_index: u16,
+	oracle: &Pubkey
+) -> NormalResult<(i64, bool)> {
+	let oracle_price_data = *oracle_map.get_price_data(oracle)?;
+	let validity = oracle_validity(
+		MarketType::Synth,
+		market_index,
+		oracle_price_data.price,
+		&oracle_price_data,
+		&oracle_map.oracle_guard_rails.validity,
+		1,
+		false
+	)?;
+
+	Ok((
+		oracle_price_data.price,
+		is_oracle_valid_for_action(validity, Some(NormalAction::MarginCalc))?,
+	))
+}
+
+/// Value of `token_amount` collateral discounted by its asset weight
+/// precision: QUOTE_PRECISION
+pub fn calculate_weighted_collateral_value(
+	token_amount: u128,
+	decimals: u32,
+	oracle_price: i64,
+	asset_weight: u32
+) -> NormalResult<i128> {
+	if oracle_price <= 0 {
+		return Ok(0);
+	}
+
+	get_token_value(token_amount.cast()?, decimals, oracle_price)?
+		.safe_mul(asset_weight.cast()?)?
+		.safe_div(SPOT_WEIGHT_PRECISION_I128)
+}
+
+/// An Isolated tier vault must be the user's only vault
+pub fn validate_synthetic_tier_mix(
+	synthetic_tier: SyntheticTier,
+	other_tiers: impl IntoIterator<Item = SyntheticTier>
+) -> NormalResult {
+	for other_tier in other_tiers {
+		validate!(
+			!synthetic_tier.is_isolated() && !other_tier.is_isolated(),
+			ErrorCode::IsolatedSyntheticTierViolation,
+			"can't hold a {:?} tier vault alongside a {:?} tier vault",
+			synthetic_tier,
+			other_tier
+		)?;
+	}
+
+	Ok(())
+}
+
+/// Checks opening or adding to the user's vault in `market_index` keeps Isolated tier vaults
+/// on their own
+pub fn validate_isolated_tier_requirements(
+	user: &User,
+	market_map: &SynthMarketMap,
+	market_index: u16
+) -> NormalResult {
+	let synthetic_tier = market_map.get_ref(&market_index)?.synthetic_tier;
+
+	let mut other_tiers = Vec::with_capacity(user.positions.len());
+	for position in user.positions.iter() {
+		if position.is_available() || position.market_index == market_index {
+			continue;
+		}
+
+		other_tiers.push(market_map.get_ref(&position.market_index)?.synthetic_tier);
+	}
+
+	validate_synthetic_tier_mix(synthetic_tier, other_tiers)
+}
+
+pub fn validate_any_isolated_tier_requirements(
+	user: &User,
+	calculation: MarginCalculation
+) -> NormalResult {
+	if calculation.with_perp_isolated_liability {
+		validate!(
+			calculation.num_vault_liabilities <= 1,
+			ErrorCode::IsolatedSyntheticTierViolation,
+			"user {} has {} vault liabilities including an Isolated tier one",
+			user.authority,
+			calculation.num_vault_liabilities
+		)?;
+	}
+
+	Ok(())
+}
+
 pub fn meets_initial_margin_requirement(
 	user: &User,
 	market_map: &SynthMarketMap,
@@ -130,7 +303,12 @@ pub fn meets_initial_margin_requirement(
 		market_map,
 		oracle_map,
 		MarginContext::standard(MarginRequirementType::Initial)
-	).map(|calc| calc.meets_margin_requirement())
+	).map(
+		|calc|
+			calc.meets_margin_requirement() &&
+			// debt can't be taken on against prices that can't be trusted
+			(calc.all_oracles_valid || calc.margin_requirement == 0)
+	)
 }
 
 pub fn meets_maintenance_margin_requirement(
@@ -267,3 +445,69 @@ pub fn calculate_user_equity(
 
 	Ok((net_usd_value, all_oracles_valid))
 }
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{ PRICE_PRECISION_I64, SPOT_WEIGHT_PRECISION };
+	use crate::math::margin::{ calculate_weighted_collateral_value, validate_synthetic_tier_mix };
+	use crate::state::synth_market::SyntheticTier;
+
+	#[test]
+	fn weighted_collateral_value() {
+		// 2 SOL (9 decimals) at $100 weighted 80%
+		assert_eq!(
+			calculate_weighted_collateral_value(
+				2_000_000_000,
+				9,
+				100 * PRICE_PRECISION_I64,
+				(SPOT_WEIGHT_PRECISION * 8) / 10
+			).unwrap(),
+			160_000_000
+		);
+		// a stablecoin at full weight
+		assert_eq!(
+			calculate_weighted_collateral_value(
+				50_000_000,
+				6,
+				PRICE_PRECISION_I64,
+				SPOT_WEIGHT_PRECISION
+			).unwrap(),
+			50_000_000
+		);
+		// a bad price never counts as collateral
+		assert_eq!(calculate_weighted_collateral_value(1_000, 6, 0, SPOT_WEIGHT_PRECISION).unwrap(), 0);
+	}
+
+	#[test]
+	fn isolated_tier_stands_alone() {
+		let tiers = [
+			SyntheticTier::A,
+			SyntheticTier::B,
+			SyntheticTier::C,
+			SyntheticTier::Speculative,
+			SyntheticTier::HighlySpeculative,
+			SyntheticTier::Isolated,
+		];
+
+		for tier in tiers {
+			// the first vault is always allowed
+			assert!(validate_synthetic_tier_mix(tier, []).is_ok());
+
+			for other_tier in tiers {
+				let result = validate_synthetic_tier_mix(tier, [other_tier]);
+				if tier.is_isolated() || other_tier.is_isolated() {
+					assert!(result.is_err(), "{:?} with {:?}", tier, other_tier);
+				} else {
+					assert!(result.is_ok(), "{:?} with {:?}", tier, other_tier);
+				}
+			}
+		}
+
+		assert!(
+			validate_synthetic_tier_mix(SyntheticTier::A, [
+				SyntheticTier::B,
+				SyntheticTier::Isolated,
+			]).is_err()
+		);
+	}
+}
@@ -0,0 +1,99 @@
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::math::stats::calculate_rolling_sum;
+
+/// Rolling sum of synthetic minted over the last `window` seconds, with `amount` minted now.
+/// Earlier mints decay linearly out of the window
+pub fn calculate_minted_in_window(
+	minted_in_window: u64,
+	amount: u64,
+	last_mint_ts: i64,
+	window: i64,
+	now: i64
+) -> NormalResult<u64> {
+	if window <= 0 {
+		return Ok(amount);
+	}
+
+	let since_last = now.safe_sub(last_mint_ts)?.clamp(0, window);
+
+	calculate_rolling_sum(minted_in_window, amount, since_last, window)
+}
+
+/// Quote value of `amount` synthetic minted at `oracle_price`, for protocol wide limits
+/// precision: QUOTE_PRECISION
+pub fn calculate_mint_value(amount: u64, decimals: u32, oracle_price: i64) -> NormalResult<u64> {
+	amount
+		.cast::<u128>()?
+		.safe_mul(oracle_price.max(0).cast()?)?
+		.safe_div((10_u128).pow(decimals))?
+		.cast()
+}
+
+/// Debt ceiling `ramp_duration` seconds into a raise from `ramp_start_ceiling` to
+/// `target_debt_ceiling`. Cuts are applied immediately so only raises ever ramp
+pub fn calculate_ramped_debt_ceiling(
+	ramp_start_ceiling: u128,
+	target_debt_ceiling: u128,
+	ramp_start_ts: i64,
+	ramp_duration: i64,
+	now: i64
+) -> NormalResult<u128> {
+	if target_debt_ceiling <= ramp_start_ceiling || ramp_duration <= 0 {
+		return Ok(target_debt_ceiling);
+	}
+
+	let elapsed = now.safe_sub(ramp_start_ts)?.clamp(0, ramp_duration);
+
+	ramp_start_ceiling.safe_add(
+		target_debt_ceiling
+			.safe_sub(ramp_start_ceiling)?
+			.safe_mul(elapsed.cast()?)?
+			.safe_div(ramp_duration.cast()?)?
+	)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::ONE_HOUR;
+	use crate::math::mint_limit::{
+		calculate_mint_value,
+		calculate_minted_in_window,
+		calculate_ramped_debt_ceiling,
+	};
+
+	#[test]
+	fn minted_in_window_decays() {
+		let window = ONE_HOUR;
+
+		assert_eq!(calculate_minted_in_window(0, 100, 0, window, 0).unwrap(), 100);
+		// half the window later half of the earlier mint has rolled off
+		assert_eq!(calculate_minted_in_window(100, 50, 0, window, ONE_HOUR / 2).unwrap(), 100);
+		// a full window later it's all gone
+		assert_eq!(calculate_minted_in_window(100, 50, 0, window, ONE_HOUR * 3).unwrap(), 50);
+		// same slot mints stack
+		assert_eq!(calculate_minted_in_window(100, 50, 10, window, 10).unwrap(), 150);
+	}
+
+	#[test]
+	fn debt_ceiling_ramp() {
+		let ramp = 6 * ONE_HOUR;
+
+		assert_eq!(calculate_ramped_debt_ceiling(1_000, 7_000, 0, ramp, 0).unwrap(), 1_000);
+		assert_eq!(calculate_ramped_debt_ceiling(1_000, 7_000, 0, ramp, ONE_HOUR).unwrap(), 2_000);
+		assert_eq!(calculate_ramped_debt_ceiling(1_000, 7_000, 0, ramp, ramp).unwrap(), 7_000);
+		assert_eq!(calculate_ramped_debt_ceiling(1_000, 7_000, 0, ramp, ramp * 2).unwrap(), 7_000);
+		// cuts are immediate
+		assert_eq!(calculate_ramped_debt_ceiling(7_000, 1_000, 0, ramp, 0).unwrap(), 1_000);
+		// no ramp configured
+		assert_eq!(calculate_ramped_debt_ceiling(1_000, 7_000, 0, 0, 0).unwrap(), 7_000);
+	}
+
+	#[test]
+	fn mint_value() {
+		// 2.5 synthetic (9 decimals) at $40
+		assert_eq!(calculate_mint_value(2_500_000_000, 9, 40_000_000).unwrap(), 100_000_000);
+		assert_eq!(calculate_mint_value(2_500_000_000, 9, -1).unwrap(), 0);
+	}
+}
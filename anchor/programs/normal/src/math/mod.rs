@@ -1,16 +1,26 @@
 pub mod amm;
+pub mod auction;
 pub mod bn;
 pub mod casting;
+pub mod deleverage;
+pub mod dynamic_fee;
 pub mod ceil_div;
 mod floor_div;
 pub mod insurance;
 pub mod liquidation;
 pub mod helpers;
 pub mod index;
+pub mod lending;
 pub mod margin;
+pub mod mint_limit;
+pub mod observation;
 pub mod oracle;
+pub mod price_band;
+pub mod psm;
+pub mod redemption;
 pub mod safe_math;
 pub mod safe_unwrap;
+pub mod settlement;
 pub mod stats;
 pub mod synth_balance;
 pub mod synth_withdraw;
@@ -0,0 +1,315 @@
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::bn::U256;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::amm_observation::Observation;
+use crate::validate;
+use solana_program::msg;
+
+/// Carries `last` forward to `timestamp` with `tick` and `liquidity` in effect since it was
+/// taken. Accumulators wrap like Uniswap's, only their differences are meaningful
+pub fn transform_observation(
+	last: &Observation,
+	timestamp: u64,
+	tick: i32,
+	liquidity: u128
+) -> Observation {
+	let delta = timestamp.saturating_sub(last.timestamp);
+
+	Observation {
+		seconds_per_liquidity_cumulative_x64: last.seconds_per_liquidity_cumulative_x64.wrapping_add(
+			((delta as u128) << 64) / liquidity.max(1)
+		),
+		tick_cumulative: last.tick_cumulative.wrapping_add((tick as i64).wrapping_mul(delta as i64)),
+		timestamp: timestamp.max(last.timestamp),
+		initialized: true,
+		..Observation::default()
+	}
+}
+
+/// The accumulators at `target`, linear between the observations either side of it
+pub fn interpolate_observation(
+	before: &Observation,
+	after: &Observation,
+	target: u64
+) -> NormalResult<Observation> {
+	if target <= before.timestamp {
+		return Ok(*before);
+	}
+
+	if target >= after.timestamp {
+		return Ok(*after);
+	}
+
+	let elapsed = target.safe_sub(before.timestamp)?;
+	let delta = after.timestamp.safe_sub(before.timestamp)?;
+
+	let tick_cumulative_delta = after.tick_cumulative
+		.wrapping_sub(before.tick_cumulative)
+		.cast::<i128>()?
+		.safe_mul(elapsed.cast()?)?
+		.safe_div(delta.cast()?)?
+		.cast::<i64>()?;
+
+	let seconds_per_liquidity_delta = (
+		U256::from(
+			after.seconds_per_liquidity_cumulative_x64.wrapping_sub(
+				before.seconds_per_liquidity_cumulative_x64
+			)
+		) * U256::from(elapsed)
+	)
+		.checked_div(U256::from(delta))
+		.ok_or(ErrorCode::MathError)?
+		.try_to_u128()?;
+
+	Ok(Observation {
+		seconds_per_liquidity_cumulative_x64: before.seconds_per_liquidity_cumulative_x64.wrapping_add(
+			seconds_per_liquidity_delta
+		),
+		tick_cumulative: before.tick_cumulative.wrapping_add(tick_cumulative_delta),
+		timestamp: target,
+		initialized: true,
+		..Observation::default()
+	})
+}
+
+/// The accumulators `seconds_ago` before `current`, the AMM's live observation. `observations`
+/// is the ring buffer in use with its newest entry at `index`
+pub fn observe(
+	observations: &[Observation],
+	index: u16,
+	current: Observation,
+	seconds_ago: u64
+) -> NormalResult<Observation> {
+	if seconds_ago == 0 {
+		return Ok(current);
+	}
+
+	validate!(
+		seconds_ago < current.timestamp && !observations.is_empty(),
+		ErrorCode::ObservationTooOld,
+		"seconds_ago={} before any observation",
+		seconds_ago
+	)?;
+
+	let target = current.timestamp.safe_sub(seconds_ago)?;
+	let cardinality = observations.len();
+	let index = index as usize;
+
+	let newest = observations[index];
+	if target >= newest.timestamp {
+		return interpolate_observation(&newest, &current, target);
+	}
+
+	// the slot after the newest is the oldest once the buffer has wrapped
+	let mut oldest = observations[(index + 1) % cardinality];
+	if !oldest.initialized {
+		oldest = observations[0];
+	}
+
+	validate!(
+		oldest.timestamp <= target,
+		ErrorCode::ObservationTooOld,
+		"target={} before oldest observation {}",
+		target,
+		oldest.timestamp
+	)?;
+
+	// binary search the buffer from oldest to newest
+	let mut left = index + 1;
+	let mut right = left + cardinality - 1;
+	while left <= right {
+		let i = (left + right) / 2;
+		let before = observations[i % cardinality];
+
+		// slots added by grow aren't written until the buffer wraps into them
+		if !before.initialized {
+			left = i + 1;
+			continue;
+		}
+
+		let after = observations[(i + 1) % cardinality];
+		if before.timestamp <= target && target <= after.timestamp {
+			return interpolate_observation(&before, &after, target);
+		}
+
+		if before.timestamp < target {
+			left = i + 1;
+		} else {
+			right = i - 1;
+		}
+	}
+
+	msg!("no observations surround target={}", target);
+	Err(ErrorCode::ObservationTooOld)
+}
+
+/// Time weighted average tick between two observations, rounded towards negative infinity
+pub fn calculate_twap_tick(start: &Observation, end: &Observation) -> NormalResult<i32> {
+	validate!(
+		end.timestamp > start.timestamp,
+		ErrorCode::InvalidTwapPeriod,
+		"observations at {} and {} span no time",
+		start.timestamp,
+		end.timestamp
+	)?;
+
+	let tick_cumulative_delta = end.tick_cumulative.wrapping_sub(start.tick_cumulative);
+	let seconds = end.timestamp.safe_sub(start.timestamp)?.cast::<i64>()?;
+
+	let mut tick = tick_cumulative_delta.safe_div(seconds)?;
+	if tick_cumulative_delta < 0 && tick_cumulative_delta % seconds != 0 {
+		tick = tick.safe_sub(1)?;
+	}
+
+	tick.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::math::observation::{
+		calculate_twap_tick,
+		interpolate_observation,
+		observe,
+		transform_observation,
+	};
+	use crate::math::amm::sqrt_price_from_tick_index;
+	use crate::state::amm::AMM;
+	use crate::state::amm_observation::{ AMMObservations, Observation };
+
+	fn observation(timestamp: u64, tick_cumulative: i64) -> Observation {
+		Observation {
+			tick_cumulative,
+			timestamp,
+			initialized: true,
+			..Observation::default()
+		}
+	}
+
+	#[test]
+	fn transform_accumulates_tick_and_liquidity() {
+		let last = observation(100, 1_000);
+
+		let next = transform_observation(&last, 110, -50, 1 << 64);
+		assert_eq!(next.timestamp, 110);
+		assert_eq!(next.tick_cumulative, 500);
+		// 10 seconds over 2^64 liquidity is 10 in Q64.64
+		assert_eq!(next.seconds_per_liquidity_cumulative_x64, 10);
+
+		// no liquidity counts as 1
+		let next = transform_observation(&last, 101, 0, 0);
+		assert_eq!(next.seconds_per_liquidity_cumulative_x64, 1 << 64);
+
+		// same timestamp changes nothing
+		assert_eq!(transform_observation(&last, 100, 7, 1).tick_cumulative, 1_000);
+	}
+
+	#[test]
+	fn interpolate_between_observations() {
+		let before = observation(100, 0);
+		let after = observation(110, 1_000);
+
+		assert_eq!(interpolate_observation(&before, &after, 105).unwrap().tick_cumulative, 500);
+		assert_eq!(interpolate_observation(&before, &after, 100).unwrap(), before);
+		assert_eq!(interpolate_observation(&before, &after, 110).unwrap(), after);
+	}
+
+	#[test]
+	fn observe_ring_buffer() {
+		let mut observations = AMMObservations::default();
+		observations.initialize(Default::default(), observation(100, 0));
+		observations.grow(3).unwrap();
+
+		// tick 10 from 100, then 20 from 110, then -10 from 120, then 0 from 130
+		observations.write(observation(110, 100));
+		observations.write(observation(120, 300));
+		observations.write(observation(130, 200));
+		assert_eq!(observations.cardinality, 3);
+		assert_eq!(observations.index, 0);
+
+		let buffer = &observations.observations[..observations.cardinality as usize];
+		let current = observation(140, 200);
+
+		assert_eq!(observe(buffer, observations.index, current, 0).unwrap(), current);
+		assert_eq!(observe(buffer, observations.index, current, 5).unwrap().tick_cumulative, 200);
+		assert_eq!(observe(buffer, observations.index, current, 15).unwrap().tick_cumulative, 250);
+		assert_eq!(observe(buffer, observations.index, current, 20).unwrap().tick_cumulative, 300);
+		assert_eq!(observe(buffer, observations.index, current, 25).unwrap().tick_cumulative, 200);
+		assert_eq!(observe(buffer, observations.index, current, 30).unwrap().tick_cumulative, 100);
+
+		// the observation at 100 was overwritten
+		assert!(observe(buffer, observations.index, current, 35).is_err());
+		assert!(observe(buffer, observations.index, current, 200).is_err());
+
+		let start = observe(buffer, observations.index, current, 30).unwrap();
+		assert_eq!(calculate_twap_tick(&start, &current).unwrap(), 3);
+	}
+
+	#[test]
+	fn observations_grow_when_wrapped() {
+		let mut observations = AMMObservations::default();
+		observations.initialize(Default::default(), observation(100, 0));
+
+		// a single slot keeps only the latest
+		observations.write(observation(110, 0));
+		assert_eq!((observations.index, observations.cardinality), (0, 1));
+
+		observations.grow(2).unwrap();
+		observations.write(observation(120, 0));
+		assert_eq!((observations.index, observations.cardinality), (1, 2));
+
+		// one write per timestamp
+		observations.write(observation(120, 5));
+		assert_eq!(observations.observations[1].tick_cumulative, 0);
+
+		// cardinality never shrinks
+		observations.grow(1).unwrap();
+		assert_eq!(observations.cardinality_next, 2);
+		assert!(observations.grow(u16::MAX).is_err());
+
+		// a partly grown buffer finds the oldest at slot 0
+		observations.grow(4).unwrap();
+		observations.write(observation(130, 0));
+		assert_eq!((observations.index, observations.cardinality), (2, 4));
+		let buffer = &observations.observations[..observations.cardinality as usize];
+		assert!(observe(buffer, observations.index, observation(140, 0), 25).is_ok());
+	}
+
+	#[test]
+	fn price_against_twap() {
+		// tick 0 since 100, a price of 1
+		let mut amm = AMM {
+			decimals_synthetic: 6,
+			decimals_quote: 6,
+			tick_current_index: 0,
+			liquidity: 1,
+			observation_timestamp: 100,
+			..AMM::default()
+		};
+		let mut observations = AMMObservations::default();
+		observations.initialize(Default::default(), amm.get_observation(100));
+
+		// ~4% off the twap is inside a 5% band
+		amm.sqrt_price = sqrt_price_from_tick_index(400);
+		assert!(observations.validate_price_against_twap(&amm, 1_000, 600, 500).is_ok());
+		amm.sqrt_price = sqrt_price_from_tick_index(-400);
+		assert!(observations.validate_price_against_twap(&amm, 1_000, 600, 500).is_ok());
+
+		// ~10% off isn't
+		amm.sqrt_price = sqrt_price_from_tick_index(1_000);
+		assert!(observations.validate_price_against_twap(&amm, 1_000, 600, 500).is_err());
+		amm.sqrt_price = sqrt_price_from_tick_index(-1_000);
+		assert!(observations.validate_price_against_twap(&amm, 1_000, 600, 500).is_err());
+
+		// no history over the period
+		assert!(observations.validate_price_against_twap(&amm, 500, 600, 500).is_err());
+	}
+
+	#[test]
+	fn twap_tick_rounds_down() {
+		assert_eq!(calculate_twap_tick(&observation(0, 0), &observation(10, 25)).unwrap(), 2);
+		assert_eq!(calculate_twap_tick(&observation(0, 0), &observation(10, -25)).unwrap(), -3);
+		assert_eq!(calculate_twap_tick(&observation(0, 0), &observation(10, -30)).unwrap(), -3);
+		assert!(calculate_twap_tick(&observation(10, 0), &observation(10, 0)).is_err());
+	}
+}
@@ -14,6 +14,7 @@ use crate::state::paused_operations::SynthOperation;
 use crate::state::synth_market::SynthMarket;
 use crate::state::state::{ OracleGuardRails, ValidityGuardRails };
 use crate::state::user::MarketType;
+use crate::validate;
 use std::fmt;
 
 // #[cfg(test)]
@@ -77,6 +78,7 @@ pub enum NormalAction {
 	UpdateTwap,
 	UpdateAMMCurve,
 	OracleOrderPrice,
+	MarginCalc,
 }
 
 pub fn is_oracle_valid_for_action(
@@ -113,6 +115,14 @@ pub fn is_oracle_valid_for_action(
 					!matches!(oracle_validity, OracleValidity::NonPositive),
 				NormalAction::UpdateAMMCurve =>
 					!matches!(oracle_validity, OracleValidity::NonPositive),
+				NormalAction::MarginCalc =>
+					!matches!(
+						oracle_validity,
+						OracleValidity::NonPositive |
+							OracleValidity::TooVolatile |
+							OracleValidity::TooUncertain |
+							OracleValidity::StaleForAMM
+					),
 			}
 		None => { matches!(oracle_validity, OracleValidity::Valid) }
 	};
@@ -281,6 +291,38 @@ pub fn oracle_validity(
 	Ok(oracle_validity)
 }
 
+/// Returns the price if the oracle is valid for `action`, None requiring it to be fully valid
+/// (fresh, confident and positive). Collateral oracles keep no twap, so they're checked without
+/// the volatility guard by passing None for `last_oracle_price_twap`
+pub fn get_valid_oracle_price(
+	oracle_price_data: &OraclePriceData,
+	market_index: u16,
+	last_oracle_price_twap: Option<i64>,
+	valid_oracle_guard_rails: &ValidityGuardRails,
+	action: Option<NormalAction>
+) -> NormalResult<i64> {
+	let validity = oracle_validity(
+		MarketType::Synth,
+		market_index,
+		last_oracle_price_twap.unwrap_or(oracle_price_data.price),
+		oracle_price_data,
+		valid_oracle_guard_rails,
+		1,
+		true
+	)?;
+
+	validate!(
+		is_oracle_valid_for_action(validity, action)?,
+		ErrorCode::InvalidOracle,
+		"invalid oracle ({}) for market {} and action {:?}",
+		validity,
+		market_index,
+		action
+	)?;
+
+	Ok(oracle_price_data.price)
+}
+
 pub fn get_timestamp_from_price_feed_account(
 	price_feed_account: &AccountInfo
 ) -> Result<i64> {
@@ -0,0 +1,246 @@
+use crate::constants::main::{ ONE_BPS_DENOMINATOR, PRICE_PRECISION };
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::amm::{ MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64 };
+use crate::math::bn::U256;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::validate;
+use solana_program::msg;
+
+/// Q64.64 sqrt price of a PRICE_PRECISION quote-per-synthetic price, in raw token amounts
+pub fn price_to_sqrt_price_x64(
+	price: u64,
+	decimals_synthetic: u8,
+	decimals_quote: u8
+) -> NormalResult<u128> {
+	let quote_scale = (10_u128).pow(decimals_quote.cast()?);
+	let synthetic_scale = PRICE_PRECISION.safe_mul(
+		(10_u128).pow(decimals_synthetic.cast()?)
+	)?;
+
+	let sqrt_price = (
+		U256::from(price)
+			.checked_mul(U256::from(quote_scale))
+			.ok_or(ErrorCode::MathError)? << 128
+	)
+		.checked_div(U256::from(synthetic_scale))
+		.ok_or(ErrorCode::MathError)?
+		.integer_sqrt()
+		.try_to_u128()?;
+
+	Ok(sqrt_price.clamp(MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64))
+}
+
+/// PRICE_PRECISION quote-per-synthetic price of a Q64.64 sqrt price in raw token amounts
+pub fn sqrt_price_x64_to_price(
+	sqrt_price: u128,
+	decimals_synthetic: u8,
+	decimals_quote: u8
+) -> NormalResult<u64> {
+	let synthetic_scale = PRICE_PRECISION.safe_mul(
+		(10_u128).pow(decimals_synthetic.cast()?)
+	)?;
+	let quote_scale = (10_u128).pow(decimals_quote.cast()?);
+
+	// shifting half before scaling keeps the product inside U256
+	let price = ((U256::from(sqrt_price) * U256::from(sqrt_price)) >> 64)
+		.checked_mul(U256::from(synthetic_scale))
+		.ok_or(ErrorCode::MathError)?
+		.checked_div(U256::from(quote_scale))
+		.ok_or(ErrorCode::MathError)? >> 64;
+
+	price.try_to_u64()
+}
+
+/// Lower and upper sqrt price a swap may move the pool to: the oracle twap less and plus
+/// `max_price_variance` (in bps)
+pub fn calculate_oracle_price_band(
+	oracle_twap: i64,
+	max_price_variance: u16,
+	decimals_synthetic: u8,
+	decimals_quote: u8
+) -> NormalResult<(u128, u128)> {
+	validate!(
+		oracle_twap > 0,
+		ErrorCode::OracleNonPositive,
+		"oracle_twap={} must be positive to bound swaps",
+		oracle_twap
+	)?;
+
+	let oracle_twap = oracle_twap.unsigned_abs();
+	let max_price_variance = max_price_variance.cast::<u64>()?;
+	let denominator = ONE_BPS_DENOMINATOR.cast::<u64>()?;
+
+	let lower_price = oracle_twap
+		.safe_mul(denominator.saturating_sub(max_price_variance))?
+		.safe_div(denominator)?;
+	let upper_price = oracle_twap
+		.safe_mul(denominator.safe_add(max_price_variance)?)?
+		.safe_div(denominator)?;
+
+	Ok((
+		price_to_sqrt_price_x64(lower_price, decimals_synthetic, decimals_quote)?,
+		price_to_sqrt_price_x64(upper_price, decimals_synthetic, decimals_quote)?,
+	))
+}
+
+/// Clips a swap's sqrt price limit to the band edge it is moving towards. A pool already at
+/// or past that edge can only be traded back towards the oracle.
+pub fn clip_sqrt_price_limit_to_band(
+	sqrt_price: u128,
+	sqrt_price_limit: u128,
+	(lower_sqrt_price, upper_sqrt_price): (u128, u128),
+	synthetic_to_quote: bool
+) -> NormalResult<u128> {
+	if synthetic_to_quote {
+		validate!(
+			sqrt_price > lower_sqrt_price,
+			ErrorCode::PriceBandsBreached,
+			"sqrt_price={} at or below oracle band lower={}",
+			sqrt_price,
+			lower_sqrt_price
+		)?;
+
+		Ok(sqrt_price_limit.max(lower_sqrt_price))
+	} else {
+		validate!(
+			sqrt_price < upper_sqrt_price,
+			ErrorCode::PriceBandsBreached,
+			"sqrt_price={} at or above oracle band upper={}",
+			sqrt_price,
+			upper_sqrt_price
+		)?;
+
+		Ok(sqrt_price_limit.min(upper_sqrt_price))
+	}
+}
+
+/// Some(true) if the pool is above the band, Some(false) if below, None while inside it
+pub fn is_sqrt_price_above_band(
+	sqrt_price: u128,
+	(lower_sqrt_price, upper_sqrt_price): (u128, u128)
+) -> Option<bool> {
+	if sqrt_price > upper_sqrt_price {
+		Some(true)
+	} else if sqrt_price < lower_sqrt_price {
+		Some(false)
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::PRICE_PRECISION_U64;
+	use crate::math::amm::{ MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64 };
+	use crate::math::price_band::{
+		calculate_oracle_price_band,
+		clip_sqrt_price_limit_to_band,
+		is_sqrt_price_above_band,
+		price_to_sqrt_price_x64,
+		sqrt_price_x64_to_price,
+	};
+
+	const ONE_X64: u128 = 1 << 64;
+
+	#[test]
+	fn sqrt_price_from_oracle_price() {
+		assert_eq!(price_to_sqrt_price_x64(PRICE_PRECISION_U64, 6, 6).unwrap(), ONE_X64);
+		assert_eq!(price_to_sqrt_price_x64(4 * PRICE_PRECISION_U64, 6, 6).unwrap(), 2 * ONE_X64);
+
+		// $100 synthetic with 8 decimals against a 6 decimal quote is 1:1 in raw amounts
+		assert_eq!(price_to_sqrt_price_x64(100 * PRICE_PRECISION_U64, 8, 6).unwrap(), ONE_X64);
+		assert_eq!(price_to_sqrt_price_x64(PRICE_PRECISION_U64, 6, 8).unwrap(), 10 * ONE_X64);
+
+		assert_eq!(price_to_sqrt_price_x64(0, 6, 6).unwrap(), MIN_SQRT_PRICE_X64);
+		assert_eq!(price_to_sqrt_price_x64(u64::MAX, 0, 18).unwrap(), MAX_SQRT_PRICE_X64);
+	}
+
+	#[test]
+	fn oracle_price_from_sqrt_price() {
+		assert_eq!(sqrt_price_x64_to_price(ONE_X64, 6, 6).unwrap(), PRICE_PRECISION_U64);
+		assert_eq!(sqrt_price_x64_to_price(2 * ONE_X64, 6, 6).unwrap(), 4 * PRICE_PRECISION_U64);
+		assert_eq!(sqrt_price_x64_to_price(ONE_X64, 8, 6).unwrap(), 100 * PRICE_PRECISION_U64);
+
+		let price = 1_234_567;
+		let sqrt_price = price_to_sqrt_price_x64(price, 9, 6).unwrap();
+		assert!(price.abs_diff(sqrt_price_x64_to_price(sqrt_price, 9, 6).unwrap()) <= 1);
+	}
+
+	#[test]
+	fn band_around_oracle_twap() {
+		let (lower, upper) = calculate_oracle_price_band(
+			4 * (PRICE_PRECISION_U64 as i64),
+			100,
+			6,
+			6
+		).unwrap();
+		assert_eq!(lower, price_to_sqrt_price_x64(3_960_000, 6, 6).unwrap());
+		assert_eq!(upper, price_to_sqrt_price_x64(4_040_000, 6, 6).unwrap());
+		assert!(lower < 2 * ONE_X64 && 2 * ONE_X64 < upper);
+
+		// variance at or past 100% leaves the band open below
+		let (lower, _) = calculate_oracle_price_band(
+			PRICE_PRECISION_U64 as i64,
+			20_000,
+			6,
+			6
+		).unwrap();
+		assert_eq!(lower, MIN_SQRT_PRICE_X64);
+
+		assert!(calculate_oracle_price_band(0, 100, 6, 6).is_err());
+		assert!(calculate_oracle_price_band(-1, 100, 6, 6).is_err());
+	}
+
+	#[test]
+	fn limit_clipped_to_band_edge() {
+		let band = (ONE_X64 - 1000, ONE_X64 + 1000);
+
+		// no limit in either direction stops at the edge
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(ONE_X64, MIN_SQRT_PRICE_X64, band, true).unwrap(),
+			band.0
+		);
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(ONE_X64, MAX_SQRT_PRICE_X64, band, false).unwrap(),
+			band.1
+		);
+
+		// a tighter limit is kept
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(ONE_X64, ONE_X64 - 10, band, true).unwrap(),
+			ONE_X64 - 10
+		);
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(ONE_X64, ONE_X64 + 10, band, false).unwrap(),
+			ONE_X64 + 10
+		);
+
+		// pool at the edge can't be pushed further out
+		assert!(clip_sqrt_price_limit_to_band(band.0, MIN_SQRT_PRICE_X64, band, true).is_err());
+		assert!(clip_sqrt_price_limit_to_band(band.1, MAX_SQRT_PRICE_X64, band, false).is_err());
+
+		// but can be traded back towards the oracle
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(band.0 - 5, MAX_SQRT_PRICE_X64, band, false).unwrap(),
+			band.1
+		);
+		assert_eq!(
+			clip_sqrt_price_limit_to_band(band.1 + 5, MIN_SQRT_PRICE_X64, band, true).unwrap(),
+			band.0
+		);
+	}
+
+	#[test]
+	fn band_breach_side() {
+		let band = (ONE_X64 - 1000, ONE_X64 + 1000);
+
+		assert_eq!(is_sqrt_price_above_band(ONE_X64 + 1001, band), Some(true));
+		assert_eq!(is_sqrt_price_above_band(ONE_X64 - 1001, band), Some(false));
+
+		// edges are inside the band
+		assert_eq!(is_sqrt_price_above_band(ONE_X64 + 1000, band), None);
+		assert_eq!(is_sqrt_price_above_band(ONE_X64 - 1000, band), None);
+		assert_eq!(is_sqrt_price_above_band(ONE_X64, band), None);
+	}
+}
@@ -0,0 +1,105 @@
+use crate::constants::main::PERCENTAGE_PRECISION;
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PsmSwap {
+	/// precision: quote mint precision
+	pub quote_amount: u64,
+	/// precision: synthetic mint precision
+	pub synthetic_amount: u64,
+	/// kept by the reserve
+	/// precision: quote mint precision
+	pub fee: u64,
+}
+
+/// Converts between mints of different decimals at 1:1, rounding down
+pub fn convert_at_par(amount: u64, from_decimals: u32, to_decimals: u32) -> NormalResult<u64> {
+	if from_decimals >= to_decimals {
+		amount.safe_div((10_u64).pow(from_decimals.safe_sub(to_decimals)?))
+	} else {
+		amount.safe_mul((10_u64).pow(to_decimals.safe_sub(from_decimals)?))
+	}
+}
+
+fn calculate_psm_fee(quote_amount: u64, fee: u32) -> NormalResult<u64> {
+	quote_amount
+		.cast::<u128>()?
+		.safe_mul(fee.cast()?)?
+		.safe_div_ceil(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+/// `quote_amount` in, synthetic out at par less `fee_in`
+pub fn calculate_psm_swap_in(
+	quote_amount: u64,
+	quote_decimals: u32,
+	synthetic_decimals: u32,
+	fee_in: u32
+) -> NormalResult<PsmSwap> {
+	let fee = calculate_psm_fee(quote_amount, fee_in)?;
+	let synthetic_amount = convert_at_par(
+		quote_amount.safe_sub(fee)?,
+		quote_decimals,
+		synthetic_decimals
+	)?;
+
+	Ok(PsmSwap {
+		quote_amount,
+		synthetic_amount,
+		fee,
+	})
+}
+
+/// `synthetic_amount` in, quote out at par less `fee_out`. `quote_amount` is what the user
+/// receives, the reserve backing released is `quote_amount + fee`
+pub fn calculate_psm_swap_out(
+	synthetic_amount: u64,
+	quote_decimals: u32,
+	synthetic_decimals: u32,
+	fee_out: u32
+) -> NormalResult<PsmSwap> {
+	let quote_at_par = convert_at_par(synthetic_amount, synthetic_decimals, quote_decimals)?;
+	let fee = calculate_psm_fee(quote_at_par, fee_out)?;
+
+	Ok(PsmSwap {
+		quote_amount: quote_at_par.safe_sub(fee)?,
+		synthetic_amount,
+		fee,
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use crate::math::psm::{ calculate_psm_swap_in, calculate_psm_swap_out, convert_at_par, PsmSwap };
+
+	#[test]
+	fn par_conversion() {
+		assert_eq!(convert_at_par(1_000_000, 6, 9).unwrap(), 1_000_000_000);
+		assert_eq!(convert_at_par(1_000_000_999, 9, 6).unwrap(), 1_000_000);
+		assert_eq!(convert_at_par(42, 6, 6).unwrap(), 42);
+	}
+
+	#[test]
+	fn swap_in_and_out() {
+		// 100 USDC (6 decimals) in at 10 bps -> 99.9 synthetic (9 decimals)
+		assert_eq!(calculate_psm_swap_in(100_000_000, 6, 9, 1_000).unwrap(), PsmSwap {
+			quote_amount: 100_000_000,
+			synthetic_amount: 99_900_000_000,
+			fee: 100_000,
+		});
+
+		// 100 synthetic out at 20 bps -> 99.8 USDC
+		assert_eq!(calculate_psm_swap_out(100_000_000_000, 6, 9, 2_000).unwrap(), PsmSwap {
+			quote_amount: 99_800_000,
+			synthetic_amount: 100_000_000_000,
+			fee: 200_000,
+		});
+
+		// no fee
+		assert_eq!(calculate_psm_swap_in(5, 6, 6, 0).unwrap().synthetic_amount, 5);
+		// fees round up against the user
+		assert_eq!(calculate_psm_swap_in(1, 6, 6, 1).unwrap().fee, 1);
+	}
+}
@@ -0,0 +1,389 @@
+use crate::constants::main::{
+	MAX_COLLATERAL_ASSETS,
+	PERCENTAGE_PRECISION,
+	REDEMPTION_BASE_RATE_BETA,
+	REDEMPTION_BASE_RATE_HALF_LIFE,
+	REDEMPTION_BUCKETS,
+	REDEMPTION_FEE_FLOOR,
+};
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+
+/// Collateral ratio of a vault at oracle prices, u128::MAX for a vault without debt
+/// precision: PERCENTAGE_PRECISION
+pub fn calculate_collateral_ratio(
+	collateral_amount: u128,
+	collateral_decimals: u32,
+	collateral_price: i64,
+	debt_amount: u128,
+	synthetic_decimals: u32,
+	synthetic_price: i64
+) -> NormalResult<u128> {
+	if debt_amount == 0 {
+		return Ok(u128::MAX);
+	}
+
+	let collateral_value = collateral_amount
+		.safe_mul(collateral_price.max(0).cast()?)?
+		.safe_mul((10_u128).pow(synthetic_decimals))?;
+	let debt_value = debt_amount
+		.safe_mul(synthetic_price.max(1).cast()?)?
+		.safe_mul((10_u128).pow(collateral_decimals))?;
+
+	collateral_value.safe_mul(PERCENTAGE_PRECISION)?.safe_div(debt_value)
+}
+
+/// Collateral worth `synthetic_amount` at oracle prices. Rounds down so redeemers never draw
+/// more than the debt they repay
+pub fn calculate_redemption_collateral(
+	synthetic_amount: u128,
+	synthetic_decimals: u32,
+	synthetic_price: i64,
+	collateral_decimals: u32,
+	collateral_price: i64
+) -> NormalResult<u128> {
+	synthetic_amount
+		.safe_mul(synthetic_price.max(0).cast()?)?
+		.safe_mul((10_u128).pow(collateral_decimals))?
+		.safe_div(
+			collateral_price
+				.max(1)
+				.cast::<u128>()?
+				.safe_mul((10_u128).pow(synthetic_decimals))?
+		)
+}
+
+/// Takes registry collateral worth `synthetic_amount` at oracle prices from `balances` in
+/// registry order, for debt a vault's primary collateral couldn't cover. Returns the amount taken
+/// from each balance and the synthetic those amounts cover
+pub fn calculate_collateral_asset_seizure(
+	synthetic_amount: u128,
+	synthetic_decimals: u32,
+	synthetic_price: i64,
+	balances: &[u64; MAX_COLLATERAL_ASSETS],
+	decimals: &[u32; MAX_COLLATERAL_ASSETS],
+	prices: &[i64; MAX_COLLATERAL_ASSETS]
+) -> NormalResult<([u64; MAX_COLLATERAL_ASSETS], u128)> {
+	let mut seized = [0_u64; MAX_COLLATERAL_ASSETS];
+	let mut remaining = synthetic_amount;
+
+	for i in 0..MAX_COLLATERAL_ASSETS {
+		if remaining == 0 {
+			break;
+		}
+
+		if balances[i] == 0 || prices[i] <= 0 {
+			continue;
+		}
+
+		let wanted = calculate_redemption_collateral(
+			remaining,
+			synthetic_decimals,
+			synthetic_price,
+			decimals[i],
+			prices[i]
+		)?;
+
+		if wanted < balances[i].cast()? {
+			seized[i] = wanted.cast()?;
+			remaining = 0;
+		} else {
+			seized[i] = balances[i];
+			let covered = calculate_redemption_collateral(
+				balances[i].cast()?,
+				decimals[i],
+				prices[i],
+				synthetic_decimals,
+				synthetic_price
+			)?;
+			remaining = remaining.saturating_sub(covered);
+		}
+	}
+
+	Ok((seized, synthetic_amount.safe_sub(remaining)?))
+}
+
+/// Halves `base_rate` every REDEMPTION_BASE_RATE_HALF_LIFE, interpolating linearly between
+/// halvings
+pub fn calculate_decayed_redemption_base_rate(
+	base_rate: u64,
+	time_since_last_redemption: i64
+) -> NormalResult<u64> {
+	let elapsed = time_since_last_redemption.max(0);
+	let halvings = elapsed.safe_div(REDEMPTION_BASE_RATE_HALF_LIFE)?;
+
+	if halvings >= 64 {
+		return Ok(0);
+	}
+
+	let halved_rate = base_rate >> halvings;
+	let remainder = elapsed.safe_sub(halvings.safe_mul(REDEMPTION_BASE_RATE_HALF_LIFE)?)?;
+
+	halved_rate
+		.cast::<u128>()?
+		.safe_sub(
+			halved_rate
+				.cast::<u128>()?
+				.safe_mul(remainder.cast()?)?
+				.safe_div(REDEMPTION_BASE_RATE_HALF_LIFE.safe_mul(2)?.cast()?)?
+		)?
+		.cast()
+}
+
+/// Base rate after redeeming `redeemed_amount` of `synthetic_supply`, the redeemed fraction
+/// over REDEMPTION_BASE_RATE_BETA is added to the decayed rate
+pub fn calculate_redemption_base_rate(
+	decayed_base_rate: u64,
+	redeemed_amount: u64,
+	synthetic_supply: u64
+) -> NormalResult<u64> {
+	if synthetic_supply == 0 {
+		return Ok(decayed_base_rate);
+	}
+
+	let increase = redeemed_amount
+		.cast::<u128>()?
+		.safe_mul(PERCENTAGE_PRECISION)?
+		.safe_div(synthetic_supply.cast::<u128>()?.safe_mul(REDEMPTION_BASE_RATE_BETA)?)?;
+
+	decayed_base_rate.cast::<u128>()?.safe_add(increase)?.min(PERCENTAGE_PRECISION).cast()
+}
+
+/// Fee rate charged on redeemed collateral
+/// precision: PERCENTAGE_PRECISION
+pub fn calculate_redemption_fee_rate(base_rate: u64) -> NormalResult<u64> {
+	base_rate.cast::<u128>()?.safe_add(REDEMPTION_FEE_FLOOR)?.min(PERCENTAGE_PRECISION).cast()
+}
+
+pub fn calculate_redemption_fee(collateral_amount: u128, fee_rate: u64) -> NormalResult<u128> {
+	collateral_amount.safe_mul(fee_rate.cast()?)?.safe_div_ceil(PERCENTAGE_PRECISION)
+}
+
+/// The redemption bucket of a vault with debt, by its primary collateral per unit of debt. The
+/// market's cumulative interest scales every vault's balances alike, so ordering vaults on scaled
+/// balances orders them by collateral ratio at any price. Vaults in the same bucket are within
+/// ~19% of each other
+pub fn calculate_redemption_bucket(scaled_balance: u64, scaled_debt: u64) -> NormalResult<u8> {
+	let ratio = scaled_balance
+		.cast::<u128>()?
+		.safe_mul(1 << 16)?
+		.safe_div(scaled_debt.max(1).cast()?)?;
+
+	if ratio == 0 {
+		return Ok(0);
+	}
+
+	// the doubling the ratio is in, then the quarter of that doubling
+	let doubling = 127 - ratio.leading_zeros();
+	let quarter = if doubling >= 2 {
+		(ratio >> (doubling - 2)) & 0b11
+	} else {
+		(ratio << (2 - doubling)) & 0b11
+	};
+
+	doubling
+		.cast::<u128>()?
+		.safe_mul(4)?
+		.safe_add(quarter)?
+		.min((REDEMPTION_BUCKETS - 1).cast()?)
+		.cast()
+}
+
+/// End of the buckets a redemption can draw from. It works up from the lowest bucket and stops
+/// at the first whose vaults weren't all passed, so no vault with a lower collateral ratio can
+/// be skipped
+pub fn calculate_redeemable_buckets_end(
+	market_buckets: &[u32; REDEMPTION_BUCKETS],
+	passed_buckets: &[u32; REDEMPTION_BUCKETS]
+) -> usize {
+	market_buckets
+		.iter()
+		.zip(passed_buckets.iter())
+		.position(|(market_count, passed_count)| passed_count != market_count)
+		.unwrap_or(REDEMPTION_BUCKETS)
+}
+
+/// Scaled balance matching `token_delta` of a balance worth `token_amount`
+pub fn calculate_scaled_balance_delta(
+	scaled_balance: u64,
+	token_amount: u128,
+	token_delta: u128
+) -> NormalResult<u64> {
+	if token_delta >= token_amount {
+		return Ok(scaled_balance);
+	}
+
+	scaled_balance.cast::<u128>()?.safe_mul(token_delta)?.safe_div(token_amount)?.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{
+		PERCENTAGE_PRECISION,
+		REDEMPTION_BASE_RATE_HALF_LIFE,
+		REDEMPTION_BUCKETS,
+		REDEMPTION_FEE_FLOOR,
+	};
+	use crate::math::redemption::*;
+
+	#[test]
+	fn collateral_ratio() {
+		// 10 SOL (9 decimals) at $150 against 1000 synthetic USD (6 decimals) at $1
+		let ratio = calculate_collateral_ratio(
+			10_000_000_000,
+			9,
+			150_000_000,
+			1_000_000_000,
+			6,
+			1_000_000
+		).unwrap();
+		assert_eq!(ratio, 1_500_000);
+
+		assert_eq!(calculate_collateral_ratio(1, 9, 150_000_000, 0, 6, 1_000_000).unwrap(), u128::MAX);
+	}
+
+	#[test]
+	fn redemption_collateral() {
+		// 300 synthetic USD at $1 redeems $300 of SOL at $150
+		let collateral = calculate_redemption_collateral(
+			300_000_000,
+			6,
+			1_000_000,
+			9,
+			150_000_000
+		).unwrap();
+		assert_eq!(collateral, 2_000_000_000);
+	}
+
+	#[test]
+	fn collateral_asset_seizure() {
+		// 300 synthetic USD at $1 against 1 SOL at $150 then 1000 USDC at $1
+		let balances = [1_000_000_000, 1_000_000_000, 0, 0];
+		let decimals = [9, 6, 0, 0];
+		let prices = [150_000_000, 1_000_000, 0, 0];
+
+		let (seized, covered) = calculate_collateral_asset_seizure(
+			300_000_000,
+			6,
+			1_000_000,
+			&balances,
+			&decimals,
+			&prices
+		).unwrap();
+		assert_eq!(seized, [1_000_000_000, 150_000_000, 0, 0]);
+		assert_eq!(covered, 300_000_000);
+
+		// a vault short of registry collateral covers what it can
+		let (seized, covered) = calculate_collateral_asset_seizure(
+			300_000_000,
+			6,
+			1_000_000,
+			&[1_000_000_000, 0, 0, 0],
+			&decimals,
+			&prices
+		).unwrap();
+		assert_eq!(seized, [1_000_000_000, 0, 0, 0]);
+		assert_eq!(covered, 150_000_000);
+	}
+
+	#[test]
+	fn base_rate_decay() {
+		let base_rate = 40_000;
+
+		assert_eq!(calculate_decayed_redemption_base_rate(base_rate, 0).unwrap(), base_rate);
+		assert_eq!(
+			calculate_decayed_redemption_base_rate(base_rate, REDEMPTION_BASE_RATE_HALF_LIFE).unwrap(),
+			20_000
+		);
+		assert_eq!(
+			calculate_decayed_redemption_base_rate(
+				base_rate,
+				REDEMPTION_BASE_RATE_HALF_LIFE * 2 + REDEMPTION_BASE_RATE_HALF_LIFE / 2
+			).unwrap(),
+			7_500
+		);
+		assert_eq!(calculate_decayed_redemption_base_rate(base_rate, i64::MAX).unwrap(), 0);
+	}
+
+	#[test]
+	fn base_rate_rises_with_volume() {
+		// redeeming 10% of supply adds 5%
+		assert_eq!(calculate_redemption_base_rate(0, 100, 1_000).unwrap(), 50_000);
+		assert_eq!(calculate_redemption_base_rate(10_000, 100, 1_000).unwrap(), 60_000);
+		// capped at 100%
+		assert_eq!(
+			calculate_redemption_base_rate(900_000, 1_000, 1_000).unwrap(),
+			PERCENTAGE_PRECISION as u64
+		);
+	}
+
+	#[test]
+	fn redemption_fee() {
+		assert_eq!(calculate_redemption_fee_rate(0).unwrap(), REDEMPTION_FEE_FLOOR as u64);
+
+		let fee_rate = calculate_redemption_fee_rate(45_000).unwrap();
+		assert_eq!(fee_rate, 50_000);
+		assert_eq!(calculate_redemption_fee(2_000_000_000, fee_rate).unwrap(), 100_000_000);
+		// rounds up
+		assert_eq!(calculate_redemption_fee(1, fee_rate).unwrap(), 1);
+	}
+
+	#[test]
+	fn redemption_bucket() {
+		// a vault with no collateral left is the first to be redeemed against
+		assert_eq!(calculate_redemption_bucket(0, 1_000).unwrap(), 0);
+		// equal scaled balances are ratio 2^16
+		assert_eq!(calculate_redemption_bucket(1_000, 1_000).unwrap(), 64);
+		assert_eq!(calculate_redemption_bucket(1_250, 1_000).unwrap(), 65);
+		assert_eq!(calculate_redemption_bucket(1_500, 1_000).unwrap(), 66);
+		assert_eq!(calculate_redemption_bucket(2_000, 1_000).unwrap(), 68);
+
+		// ordered by collateral ratio
+		let mut last_bucket = 0;
+		for scaled_balance in (0..10_000).step_by(7) {
+			let bucket = calculate_redemption_bucket(scaled_balance, 1_000).unwrap();
+			assert!(bucket >= last_bucket);
+			last_bucket = bucket;
+		}
+
+		// anything past 2^32 shares the last bucket
+		assert_eq!(
+			calculate_redemption_bucket(u64::MAX, 1).unwrap(),
+			(REDEMPTION_BUCKETS - 1) as u8
+		);
+	}
+
+	#[test]
+	fn redeemable_buckets() {
+		let mut market_buckets = [0_u32; REDEMPTION_BUCKETS];
+		market_buckets[60] = 2;
+		market_buckets[64] = 3;
+		market_buckets[70] = 1;
+
+		// every vault of the lowest two buckets
+		let mut passed_buckets = [0_u32; REDEMPTION_BUCKETS];
+		passed_buckets[60] = 2;
+		passed_buckets[64] = 3;
+		assert_eq!(calculate_redeemable_buckets_end(&market_buckets, &passed_buckets), 70);
+
+		// one vault of the lowest bucket left out
+		passed_buckets[60] = 1;
+		assert_eq!(calculate_redeemable_buckets_end(&market_buckets, &passed_buckets), 60);
+
+		// every vault
+		passed_buckets[60] = 2;
+		passed_buckets[70] = 1;
+		assert_eq!(
+			calculate_redeemable_buckets_end(&market_buckets, &passed_buckets),
+			REDEMPTION_BUCKETS
+		);
+	}
+
+	#[test]
+	fn scaled_balance_delta() {
+		assert_eq!(calculate_scaled_balance_delta(1_000, 2_000, 500).unwrap(), 250);
+		assert_eq!(calculate_scaled_balance_delta(1_000, 2_000, 2_000).unwrap(), 1_000);
+		assert_eq!(calculate_scaled_balance_delta(1_000, 2_000, 5_000).unwrap(), 1_000);
+	}
+}
@@ -0,0 +1,127 @@
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::validate;
+use solana_program::msg;
+
+/// Collateral owed for `debt_amount` synthetic tokens at the frozen settlement prices.
+/// Rounds up so settled vaults never under-pay the holders' pool
+pub fn calculate_settlement_debt_in_collateral(
+	debt_amount: u128,
+	synthetic_decimals: u32,
+	expiry_price: i64,
+	collateral_decimals: u32,
+	expiry_collateral_price: i64
+) -> NormalResult<u128> {
+	validate!(
+		expiry_price > 0 && expiry_collateral_price > 0,
+		ErrorCode::OracleNonPositive,
+		"settlement prices must be positive: expiry_price={} expiry_collateral_price={}",
+		expiry_price,
+		expiry_collateral_price
+	)?;
+
+	if debt_amount == 0 {
+		return Ok(0);
+	}
+
+	let numerator = debt_amount
+		.safe_mul(expiry_price.cast()?)?
+		.safe_mul((10_u128).pow(collateral_decimals))?;
+	let denominator = expiry_collateral_price
+		.cast::<u128>()?
+		.safe_mul((10_u128).pow(synthetic_decimals))?;
+
+	numerator.safe_div_ceil(denominator)
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct VaultSettlement {
+	/// collateral moved to the holders' settlement pool
+	pub collateral_to_pool: u128,
+	/// collateral returned to the vault owner
+	pub excess_collateral: u128,
+	/// collateral the vault was short of its debt, socialized across holders
+	pub deficit: u128,
+}
+
+/// Splits a vault's collateral between the settlement pool and its owner
+pub fn calculate_vault_settlement(
+	collateral_amount: u128,
+	debt_in_collateral: u128
+) -> NormalResult<VaultSettlement> {
+	let collateral_to_pool = collateral_amount.min(debt_in_collateral);
+
+	Ok(VaultSettlement {
+		collateral_to_pool,
+		excess_collateral: collateral_amount.safe_sub(collateral_to_pool)?,
+		deficit: debt_in_collateral.safe_sub(collateral_to_pool)?,
+	})
+}
+
+/// Pro rata share of the settlement pool for `synthetic_amount` of the remaining supply.
+/// Any deficit is absorbed evenly because the pool only holds what vaults could pay
+pub fn calculate_settlement_redemption(
+	synthetic_amount: u64,
+	settlement_pool: u64,
+	synthetic_supply: u64
+) -> NormalResult<u64> {
+	if synthetic_supply == 0 {
+		return Ok(0);
+	}
+
+	synthetic_amount
+		.cast::<u128>()?
+		.safe_mul(settlement_pool.cast()?)?
+		.safe_div(synthetic_supply.cast()?)?
+		.min(settlement_pool.cast()?)
+		.cast()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::math::settlement::{
+		calculate_settlement_debt_in_collateral,
+		calculate_settlement_redemption,
+		calculate_vault_settlement,
+		VaultSettlement,
+	};
+
+	#[test]
+	fn debt_in_collateral() {
+		// 10 synthetic (6 decimals) at $150 against $100 collateral (9 decimals) -> 15 collateral
+		assert_eq!(
+			calculate_settlement_debt_in_collateral(10_000_000, 6, 150_000_000, 9, 100_000_000).unwrap(),
+			15_000_000_000
+		);
+		// rounds up
+		assert_eq!(calculate_settlement_debt_in_collateral(1, 6, 1_000_000, 6, 3_000_000).unwrap(), 1);
+		assert_eq!(calculate_settlement_debt_in_collateral(0, 6, 1_000_000, 6, 1_000_000).unwrap(), 0);
+		assert!(calculate_settlement_debt_in_collateral(1, 6, 0, 6, 1_000_000).is_err());
+	}
+
+	#[test]
+	fn vault_settlement() {
+		assert_eq!(calculate_vault_settlement(150, 100).unwrap(), VaultSettlement {
+			collateral_to_pool: 100,
+			excess_collateral: 50,
+			deficit: 0,
+		});
+		// underwater vaults hand over everything and leave a deficit
+		assert_eq!(calculate_vault_settlement(80, 100).unwrap(), VaultSettlement {
+			collateral_to_pool: 80,
+			excess_collateral: 0,
+			deficit: 20,
+		});
+	}
+
+	#[test]
+	fn settlement_redemption() {
+		assert_eq!(calculate_settlement_redemption(25, 1_000, 100).unwrap(), 250);
+		// the last holder takes the rest of the pool
+		assert_eq!(calculate_settlement_redemption(100, 1_000, 100).unwrap(), 1_000);
+		assert_eq!(calculate_settlement_redemption(25, 1_000, 0).unwrap(), 0);
+		// never more than the pool, when some of the supply is backed elsewhere
+		assert_eq!(calculate_settlement_redemption(150, 1_000, 100).unwrap(), 1_000);
+	}
+}
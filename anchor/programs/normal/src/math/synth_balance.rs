@@ -6,6 +6,7 @@ use crate::math::constants::{
 	SPOT_RATE_PRECISION,
 	SPOT_UTILIZATION_PRECISION,
 };
+use crate::math::lending::calculate_cumulative_deposit_interest_with_lending_yield;
 use crate::math::safe_math::{ SafeDivFloor, SafeMath };
 use crate::state::oracle::{ OraclePriceData, StrictOraclePrice };
 use crate::state::position::Position;
@@ -24,7 +25,7 @@ pub fn get_spot_balance(
 
 	let cumulative_interest = match balance_type {
 		SpotBalanceType::Deposit => spot_market.cumulative_deposit_interest,
-		SpotBalanceType::Borrow => spot_market.cumulative_borrow_interest,
+		SpotBalanceType::Borrow => spot_market.cumulative_debt_interest,
 	};
 
 	let mut balance = token_amount
@@ -48,8 +49,12 @@ pub fn get_token_amount(
 	);
 
 	let cumulative_interest = match balance_type {
-		SpotBalanceType::Deposit => synth_market.cumulative_deposit_interest,
-		SpotBalanceType::Borrow => synth_market.cumulative_borrow_interest,
+		SpotBalanceType::Deposit =>
+			calculate_cumulative_deposit_interest_with_lending_yield(
+				synth_market.cumulative_deposit_interest,
+				synth_market.cumulative_lp_interest
+			)?,
+		SpotBalanceType::Borrow => synth_market.cumulative_debt_interest,
 	};
 
 	let token_amount = match balance_type {
@@ -88,7 +93,7 @@ pub fn get_interest_token_amount(
 }
 
 pub struct InterestAccumulated {
-	pub deposit_interest: u128,
+	pub debt_interest: u128,
 }
 
 pub fn calculate_utilization(
@@ -131,57 +136,111 @@ pub fn calculate_synth_market_utilization(
 	Ok(utilization)
 }
 
+/// Annualized stability fee charged on synthetic debt at `utilization`.
+/// The rate ramps from `base_rate` to `optimal_rate` up to `optimal_utilization`,
+/// then from `optimal_rate` to `max_rate` at full utilization.
+/// precision: SPOT_RATE_PRECISION
+pub fn calculate_stability_fee_rate(
+	utilization: u128,
+	optimal_utilization: u128,
+	base_rate: u128,
+	optimal_rate: u128,
+	max_rate: u128
+) -> NormalResult<u128> {
+	let utilization = utilization.min(SPOT_UTILIZATION_PRECISION);
+
+	let rate = if utilization <= optimal_utilization {
+		let slope = optimal_rate.saturating_sub(base_rate);
+
+		base_rate.safe_add(
+			slope.safe_mul(utilization)?.checked_div(optimal_utilization).unwrap_or(0)
+		)?
+	} else {
+		let slope = max_rate.saturating_sub(optimal_rate);
+		let surplus_utilization = utilization.safe_sub(optimal_utilization)?;
+		let surplus_utilization_range = SPOT_UTILIZATION_PRECISION.safe_sub(
+			optimal_utilization
+		)?;
+
+		optimal_rate.safe_add(
+			slope.safe_mul(surplus_utilization)?.safe_div(surplus_utilization_range)?
+		)?
+	};
+
+	Ok(rate)
+}
+
+pub fn calculate_synth_market_stability_fee_rate(
+	synth_market: &SynthMarket,
+	utilization: u128
+) -> NormalResult<u128> {
+	calculate_stability_fee_rate(
+		utilization,
+		synth_market.optimal_utilization.cast()?,
+		synth_market.base_stability_fee.cast()?,
+		synth_market.optimal_stability_fee.cast()?,
+		synth_market.max_stability_fee.cast()?
+	)
+}
+
+/// Fee owed on `amount` flash minted synthetic, rounded up
+/// precision: token mint precision
+pub fn calculate_flash_mint_fee(amount: u64, flash_mint_fee: u32) -> NormalResult<u64> {
+	amount
+		.cast::<u128>()?
+		.safe_mul(flash_mint_fee.cast()?)?
+		.safe_div_ceil(PERCENTAGE_PRECISION)?
+		.cast()
+}
+
+/// Growth of the cumulative debt interest over `time_since_last_update` seconds at the annualized `rate`.
+/// Rounds up so debt never accrues less than the rate implies
+pub fn calculate_debt_interest_delta(
+	cumulative_debt_interest: u128,
+	rate: u128,
+	time_since_last_update: u128
+) -> NormalResult<u128> {
+	if rate == 0 || time_since_last_update == 0 {
+		return Ok(0);
+	}
+
+	cumulative_debt_interest
+		.safe_mul(rate.safe_mul(time_since_last_update)?)?
+		.safe_div(ONE_YEAR)?
+		.safe_div(SPOT_RATE_PRECISION)?
+		.safe_add(1)
+}
+
 pub fn calculate_accumulated_interest(
-	spot_market: &SynthMarket,
+	synth_market: &SynthMarket,
 	now: i64
 ) -> NormalResult<InterestAccumulated> {
-	if now <= spot_market.last_interest_ts.cast()? {
+	if now <= synth_market.last_interest_ts.cast()? || synth_market.debt_balance == 0 {
 		return Ok(InterestAccumulated {
-			borrow_interest: 0,
-			deposit_interest: 0,
+			debt_interest: 0,
 		});
 	}
 
-	let utilization = calculate_synth_market_utilization(spot_market)?;
-
-	if utilization == 0 {
-		return Ok(InterestAccumulated {
-			borrow_interest: 0,
-			deposit_interest: 0,
-		});
-	}
+	let utilization = calculate_synth_market_utilization(synth_market)?;
 
-	// let borrow_rate = calculate_borrow_rate(spot_market, utilization)?;
+	let stability_fee_rate = calculate_synth_market_stability_fee_rate(
+		synth_market,
+		utilization
+	)?;
 
 	let time_since_last_update = now
 		.cast::<u64>()
 		.or(Err(ErrorCode::UnableToCastUnixTime))?
-		.safe_sub(spot_market.last_interest_ts)?;
-
-	// To save some compute units, have to multiply the rate by the `time_since_last_update` here
-	// and then divide out by ONE_YEAR when calculating interest accumulated below
-	// let modified_borrow_rate = borrow_rate.safe_mul(
-	// 	time_since_last_update as u128
-	// )?;
-
-	let modified_collateral_rate = modified_borrow_rate
-		.safe_mul(utilization)?
-		.safe_div(SPOT_UTILIZATION_PRECISION)?;
-
-	// let borrow_interest = spot_market.cumulative_borrow_interest
-	// 	.safe_mul(modified_borrow_rate)?
-	// 	.safe_div(ONE_YEAR)?
-	// 	.safe_div(SPOT_RATE_PRECISION)?
-	// 	.safe_add(1)?;
-
-	let collateral_interest = spot_market.cumulative_collateral_interest
-		.safe_mul(modified_collateral_rate)?
-		.safe_div(ONE_YEAR)?
-		.safe_div(SPOT_RATE_PRECISION)?;
+		.safe_sub(synth_market.last_interest_ts)?;
+
+	let debt_interest = calculate_debt_interest_delta(
+		synth_market.cumulative_debt_interest,
+		stability_fee_rate,
+		time_since_last_update.cast()?
+	)?;
 
 	Ok(InterestAccumulated {
-		borrow_interest,
-		collateral_interest,
+		debt_interest,
 	})
 }
 
@@ -275,3 +334,61 @@ pub fn get_balance_value(
 	)?;
 	Ok(value)
 }
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{
+		ONE_YEAR,
+		SPOT_CUMULATIVE_INTEREST_PRECISION,
+		SPOT_RATE_PRECISION,
+		SPOT_UTILIZATION_PRECISION,
+	};
+	use crate::math::synth_balance::{
+		calculate_debt_interest_delta,
+		calculate_flash_mint_fee,
+		calculate_stability_fee_rate,
+	};
+
+	#[test]
+	fn stability_fee_rate_curve() {
+		// 80% kink, 1% base, 5% at the kink, 50% at full utilization
+		let optimal_utilization = (SPOT_UTILIZATION_PRECISION * 8) / 10;
+		let base = SPOT_RATE_PRECISION / 100;
+		let optimal = (SPOT_RATE_PRECISION * 5) / 100;
+		let max = SPOT_RATE_PRECISION / 2;
+
+		let rate = |utilization: u128| {
+			calculate_stability_fee_rate(utilization, optimal_utilization, base, optimal, max).unwrap()
+		};
+
+		assert_eq!(rate(0), base);
+		assert_eq!(rate(optimal_utilization / 2), (SPOT_RATE_PRECISION * 3) / 100);
+		assert_eq!(rate(optimal_utilization), optimal);
+		assert_eq!(rate((SPOT_UTILIZATION_PRECISION * 9) / 10), (SPOT_RATE_PRECISION * 275) / 1000);
+		assert_eq!(rate(SPOT_UTILIZATION_PRECISION), max);
+		// over-utilized markets are charged the max rate
+		assert_eq!(rate(SPOT_UTILIZATION_PRECISION * 2), max);
+	}
+
+	#[test]
+	fn debt_interest_delta() {
+		let rate = SPOT_RATE_PRECISION / 10;
+
+		// 10% over a year
+		assert_eq!(
+			calculate_debt_interest_delta(SPOT_CUMULATIVE_INTEREST_PRECISION, rate, ONE_YEAR).unwrap(),
+			SPOT_CUMULATIVE_INTEREST_PRECISION / 10 + 1
+		);
+		assert_eq!(calculate_debt_interest_delta(SPOT_CUMULATIVE_INTEREST_PRECISION, 0, ONE_YEAR).unwrap(), 0);
+		assert_eq!(calculate_debt_interest_delta(SPOT_CUMULATIVE_INTEREST_PRECISION, rate, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn flash_mint_fee() {
+		// 9 bps
+		assert_eq!(calculate_flash_mint_fee(1_000_000_000, 900).unwrap(), 900_000);
+		// rounds up so dust loans still pay
+		assert_eq!(calculate_flash_mint_fee(1, 900).unwrap(), 1);
+		assert_eq!(calculate_flash_mint_fee(1_000_000_000, 0).unwrap(), 0);
+	}
+}
@@ -1,16 +1,28 @@
 use crate::{
+	constants::main::{ AMM_ORACLE_TWAP_MAX_STALENESS, DYNAMIC_FEE_TICK_REFERENCE_PERIOD },
+	error::NormalResult,
 	errors::ErrorCode,
 	math::{
+		casting::Cast,
+		dynamic_fee::{ calculate_dynamic_fee_rate, calculate_swap_volatility },
+		observation::transform_observation,
+		price_band::calculate_oracle_price_band,
+		safe_math::SafeMath,
 		tick_index_from_sqrt_price,
 		MAX_FEE_RATE,
 		MAX_PROTOCOL_FEE_RATE,
 		MAX_SQRT_PRICE_X64,
 		MIN_SQRT_PRICE_X64,
 	},
+	validate,
 };
 use anchor_lang::prelude::*;
 
-use super::{ oracle::{ HistoricalOracleData, OracleSource }, user::MarketType };
+use super::{
+	amm_observation::Observation,
+	oracle::{ get_index_nav_price, HistoricalOracleData, OracleSource },
+	user::MarketType,
+};
 
 #[assert_no_slop]
 #[zero_copy(unsafe)]
@@ -20,6 +32,7 @@ pub struct AMM {
 	/// The AMM's address. It is a pda of the market index
 	pub pubkey: Pubkey,
 	pub market_index: u16,
+	pub amm_bump: [u8; 1],
 
 	/// the authority that can push or pull quote asset tokens to/from the Vault when price exceed the max_price_deviance
 	pub vault_balance_authority: Pubkey,
@@ -41,6 +54,9 @@ pub struct AMM {
 	pub fee_authority: Pubkey,
 	/// the maximum percent the pool price can deviate above or below the oracle twap
 	pub max_price_variance: u16,
+	/// decimals of the synthetic and quote mints, to put the oracle price in raw token amounts
+	pub decimals_synthetic: u8,
+	pub decimals_quote: u8,
 	/// volume divided by synthetic token market cap (how much volume is created per $1 of liquidity)
 	pub liquidity_to_volume_multiplier: u64,
 
@@ -65,12 +81,34 @@ pub struct AMM {
 	/// portion of the fee rate sent to the Insurance Fund as basis points
 	pub insurance_fund_fee_rate: u16,
 
+	/// Dynamic Fees
+	///
+	/// fee_rate plus a volatility fee, bounded by min/max, while max_dynamic_fee_rate is set
+	/// Stored as hundredths of a basis point
+	pub min_dynamic_fee_rate: u16,
+	pub max_dynamic_fee_rate: u16,
+	/// Portion of the measured volatility charged on top of fee_rate as basis points
+	pub volatility_fee_multiplier: u16,
+	/// Tick the pool's recent movement is measured from, reset every
+	/// DYNAMIC_FEE_TICK_REFERENCE_PERIOD
+	pub tick_reference_index: i32,
+	pub tick_reference_timestamp: u64,
+
 	pub fee_growth_global_synthetic: u128,
 	pub fee_growth_global_quote: u128,
 
 	pub protocol_fee_owed_synthetic: u64,
 	pub protocol_fee_owed_quote: u64,
 
+	/// Observations
+	///
+	/// Accumulators snapshotted into the AMM's AMMObservations, advanced to observation_timestamp
+	/// on every swap and liquidity change
+	pub tick_cumulative: i64,
+	/// Q64.64
+	pub seconds_per_liquidity_cumulative_x64: u128,
+	pub observation_timestamp: u64,
+
 	/// Rewards
 	///
 	pub reward_authority: Pubkey,
@@ -101,7 +139,18 @@ impl Default for AMM {
 pub const NUM_REWARDS: usize = 3;
 
 impl AMM {
-	pub const LEN: usize = 8 + 261 + 384;
+	pub const LEN: usize = 8 + 314 + 384;
+
+	/// Seeds of the AMM's pda, which owns its token vaults
+	pub fn seeds(&self) -> [&[u8]; 5] {
+		[
+			&b"amm"[..],
+			self.token_mint_synthetic.as_ref(),
+			self.token_mint_quote.as_ref(),
+			self.tick_spacing_seed.as_ref(),
+			self.amm_bump.as_ref(),
+		]
+	}
 
 	pub fn is_price_inside_range(&self, price: u64) -> bool {
 		if price < 0 {
@@ -113,6 +162,80 @@ impl AMM {
 		}
 	}
 
+	/// Sqrt prices the pool may trade between, None while max_price_variance is unset
+	pub fn get_oracle_price_band(&self, timestamp: u64) -> NormalResult<Option<(u128, u128)>> {
+		if self.max_price_variance == 0 {
+			return Ok(None);
+		}
+
+		self.validate_oracle_twap_not_stale(timestamp)?;
+
+		calculate_oracle_price_band(
+			self.historical_oracle_data.last_oracle_price_twap_5min,
+			self.max_price_variance,
+			self.decimals_synthetic,
+			self.decimals_quote
+		).map(Some)
+	}
+
+	/// The oracle twap is only as fresh as the last update_amm_oracle_twap crank
+	pub fn validate_oracle_twap_not_stale(&self, timestamp: u64) -> NormalResult {
+		let since_last = timestamp
+			.cast::<i64>()?
+			.safe_sub(self.historical_oracle_data.last_oracle_price_twap_ts)?;
+
+		validate!(
+			since_last <= AMM_ORACLE_TWAP_MAX_STALENESS,
+			crate::error::ErrorCode::OracleStaleForAMM,
+			"amm {} oracle twap is {}s old",
+			self.market_index,
+			since_last
+		)?;
+
+		Ok(())
+	}
+
+	pub fn is_dynamic_fee_enabled(&self) -> bool {
+		self.max_dynamic_fee_rate != 0
+	}
+
+	/// Fee rate charged by a swap at `timestamp`, fee_rate unless dynamic fees are enabled.
+	/// The oracle std and confidence come from the update_amm_oracle_twap crank, so the max
+	/// dynamic fee is charged while they are stale
+	pub fn get_swap_fee_rate(&self, timestamp: u64) -> NormalResult<u16> {
+		if !self.is_dynamic_fee_enabled() {
+			return Ok(self.fee_rate);
+		}
+
+		if self.validate_oracle_twap_not_stale(timestamp).is_err() {
+			return Ok(self.max_dynamic_fee_rate);
+		}
+
+		let tick_reference_index = if
+			timestamp.saturating_sub(self.tick_reference_timestamp) >=
+			DYNAMIC_FEE_TICK_REFERENCE_PERIOD
+		{
+			self.tick_current_index
+		} else {
+			self.tick_reference_index
+		};
+
+		let volatility = calculate_swap_volatility(
+			self.oracle_std,
+			self.historical_oracle_data.last_oracle_price_twap_5min,
+			self.last_oracle_conf_pct,
+			self.tick_current_index.abs_diff(tick_reference_index)
+		)?;
+
+		calculate_dynamic_fee_rate(
+			self.fee_rate,
+			volatility,
+			self.volatility_fee_multiplier,
+			self.min_dynamic_fee_rate,
+			self.max_dynamic_fee_rate
+		)
+	}
+
 	pub fn input_token_mint(&self, synthetic_to_quote: bool) -> Pubkey {
 		if synthetic_to_quote {
 			self.token_mint_synthetic
@@ -165,6 +288,7 @@ impl AMM {
 		liquidity: u128,
 		reward_last_updated_timestamp: u64
 	) {
+		self.update_observation_accumulators(reward_last_updated_timestamp);
 		self.update_rewards(reward_infos, reward_last_updated_timestamp);
 		self.liquidity = liquidity;
 	}
@@ -181,6 +305,17 @@ impl AMM {
 		is_token_fee_in_synthetic: bool,
 		reward_last_updated_timestamp: u64
 	) {
+		// the first swap of a period moves the reference to where the pool started it
+		if
+			reward_last_updated_timestamp.saturating_sub(self.tick_reference_timestamp) >=
+			DYNAMIC_FEE_TICK_REFERENCE_PERIOD
+		{
+			self.tick_reference_index = self.tick_current_index;
+			self.tick_reference_timestamp = reward_last_updated_timestamp;
+		}
+
+		self.update_observation_accumulators(reward_last_updated_timestamp);
+
 		self.tick_current_index = tick_index;
 		self.sqrt_price = sqrt_price;
 		self.liquidity = liquidity;
@@ -197,6 +332,33 @@ impl AMM {
 		}
 	}
 
+	/// The accumulators carried forward to `timestamp` at the current tick and liquidity
+	pub fn get_observation(&self, timestamp: u64) -> Observation {
+		transform_observation(
+			&(Observation {
+				seconds_per_liquidity_cumulative_x64: self.seconds_per_liquidity_cumulative_x64,
+				tick_cumulative: self.tick_cumulative,
+				timestamp: self.observation_timestamp,
+				initialized: true,
+				..Observation::default()
+			}),
+			timestamp,
+			self.tick_current_index,
+			self.liquidity
+		)
+	}
+
+	/// Credits the time since the last update to the tick and liquidity that were in effect,
+	/// before a swap or liquidity change replaces them
+	fn update_observation_accumulators(&mut self, timestamp: u64) {
+		let observation = self.get_observation(timestamp);
+
+		self.tick_cumulative = observation.tick_cumulative;
+		self.seconds_per_liquidity_cumulative_x64 =
+			observation.seconds_per_liquidity_cumulative_x64;
+		self.observation_timestamp = observation.timestamp;
+	}
+
 	pub fn reset_protocol_fees_owed(&mut self) {
 		self.protocol_fee_owed_synthetic = 0;
 		self.protocol_fee_owed_quote = 0;
@@ -232,6 +394,8 @@ impl AMM {
 			OracleSource::Pyth1MPull => {
 				Ok(Some(self.get_pyth_twap(price_oracle, 1000000, true)?))
 			}
+			OracleSource::IndexNav =>
+				Ok(Some(get_index_nav_price(price_oracle, slot)?.price)),
 		}
 	}
 
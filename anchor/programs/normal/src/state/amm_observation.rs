@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::MAX_AMM_OBSERVATION_CARDINALITY;
+use crate::error::{ NormalResult, ErrorCode };
+use crate::math::amm::sqrt_price_from_tick_index;
+use crate::math::observation::{ calculate_twap_tick, observe };
+use crate::math::casting::Cast;
+use crate::math::price_band::{
+	calculate_oracle_price_band,
+	is_sqrt_price_above_band,
+	sqrt_price_x64_to_price,
+};
+use crate::state::amm::AMM;
+use crate::state::traits::Size;
+use crate::validate;
+
+/// A snapshot of an AMM's price and liquidity accumulators
+#[zero_copy(unsafe)]
+#[derive(Default, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Observation {
+	/// Seconds elapsed divided by in range liquidity, summed since the AMM was created
+	/// precision: Q64.64
+	pub seconds_per_liquidity_cumulative_x64: u128,
+	/// Tick index times seconds elapsed, summed since the AMM was created
+	pub tick_cumulative: i64,
+	pub timestamp: u64,
+	pub initialized: bool,
+	pub padding: [u8; 15],
+}
+
+/// Ring buffer of an AMM's observations, written as the AMM is swapped through. It is a pda
+/// of the AMM
+#[account(zero_copy(unsafe))]
+#[derive(Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct AMMObservations {
+	pub amm: Pubkey,
+	/// Slot of the most recent observation
+	pub index: u16,
+	/// Slots of the buffer in use
+	pub cardinality: u16,
+	/// Slots the buffer grows to the next time it wraps
+	pub cardinality_next: u16,
+	pub padding: [u8; 10],
+	pub observations: [Observation; MAX_AMM_OBSERVATION_CARDINALITY as usize],
+}
+
+impl Default for AMMObservations {
+	fn default() -> Self {
+		AMMObservations {
+			amm: Pubkey::default(),
+			index: 0,
+			cardinality: 0,
+			cardinality_next: 0,
+			padding: [0; 10],
+			observations: [Observation::default(); MAX_AMM_OBSERVATION_CARDINALITY as usize],
+		}
+	}
+}
+
+impl Size for AMMObservations {
+	const SIZE: usize = 8 + 48 + 48 * (MAX_AMM_OBSERVATION_CARDINALITY as usize);
+}
+
+impl AMMObservations {
+	pub fn initialize(&mut self, amm: Pubkey, observation: Observation) {
+		self.amm = amm;
+		self.index = 0;
+		self.cardinality = 1;
+		self.cardinality_next = 1;
+		self.observations[0] = Observation {
+			initialized: true,
+			..observation
+		};
+	}
+
+	/// Records `observation`, at most once per timestamp. Moves on to the slots added by
+	/// grow once the buffer reaches its end
+	pub fn write(&mut self, observation: Observation) {
+		let last = &self.observations[self.index as usize];
+		if last.timestamp == observation.timestamp {
+			return;
+		}
+
+		if self.cardinality_next > self.cardinality && self.index == self.cardinality - 1 {
+			self.cardinality = self.cardinality_next;
+		}
+
+		self.index = (self.index + 1) % self.cardinality;
+		self.observations[self.index as usize] = Observation {
+			initialized: true,
+			..observation
+		};
+	}
+
+	pub fn grow(&mut self, cardinality_next: u16) -> NormalResult {
+		validate!(
+			cardinality_next <= MAX_AMM_OBSERVATION_CARDINALITY,
+			ErrorCode::InvalidObservationCardinality,
+			"cardinality_next={} above max {}",
+			cardinality_next,
+			MAX_AMM_OBSERVATION_CARDINALITY
+		)?;
+
+		if cardinality_next > self.cardinality_next {
+			self.cardinality_next = cardinality_next;
+		}
+
+		Ok(())
+	}
+
+	/// The AMM's accumulators `seconds_ago` before `now`
+	pub fn observe(&self, amm: &AMM, now: u64, seconds_ago: u64) -> NormalResult<Observation> {
+		observe(
+			&self.observations[..self.cardinality as usize],
+			self.index,
+			amm.get_observation(now),
+			seconds_ago
+		)
+	}
+
+	/// Time weighted average tick over the last `twap_period` seconds
+	pub fn get_twap_tick(&self, amm: &AMM, now: u64, twap_period: u64) -> NormalResult<i32> {
+		let start = self.observe(amm, now, twap_period)?;
+		let end = amm.get_observation(now);
+
+		calculate_twap_tick(&start, &end)
+	}
+
+	/// Q64.64 sqrt price at the time weighted average tick over the last `twap_period` seconds
+	pub fn get_twap_sqrt_price(&self, amm: &AMM, now: u64, twap_period: u64) -> NormalResult<u128> {
+		Ok(sqrt_price_from_tick_index(self.get_twap_tick(amm, now, twap_period)?))
+	}
+
+	/// PRICE_PRECISION price at the time weighted average tick over the last `twap_period`
+	/// seconds, for pricing off the pool rather than an external oracle
+	pub fn get_twap_price(&self, amm: &AMM, now: u64, twap_period: u64) -> NormalResult<u64> {
+		sqrt_price_x64_to_price(
+			self.get_twap_sqrt_price(amm, now, twap_period)?,
+			amm.decimals_synthetic,
+			amm.decimals_quote
+		)
+	}
+
+	/// Fails if the AMM's spot price is more than `max_divergence` (in bps) from its twap over
+	/// the last `twap_period` seconds, so a pool pushed off its price can't be routed through
+	pub fn validate_price_against_twap(
+		&self,
+		amm: &AMM,
+		now: u64,
+		twap_period: u64,
+		max_divergence: u16
+	) -> NormalResult {
+		let twap_price = self.get_twap_price(amm, now, twap_period)?;
+		let band = calculate_oracle_price_band(
+			twap_price.cast()?,
+			max_divergence,
+			amm.decimals_synthetic,
+			amm.decimals_quote
+		)?;
+
+		validate!(
+			is_sqrt_price_above_band(amm.sqrt_price, band).is_none(),
+			ErrorCode::AmmPriceDivergedFromTwap,
+			"amm sqrt_price={} outside its twap band {:?}",
+			amm.sqrt_price,
+			band
+		)?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use crate::error::NormalResult;
+use crate::math::auction::calculate_auction_price;
+use crate::math::safe_math::SafeMath;
+use crate::state::synth_market::AuctionType;
+use crate::state::traits::Size;
+
+#[derive(
+	Clone,
+	Copy,
+	BorshSerialize,
+	BorshDeserialize,
+	PartialEq,
+	Debug,
+	Eq,
+	Default
+)]
+pub enum AuctionStatus {
+	/// accepting bids
+	#[default]
+	Active,
+	/// lot sold or debt covered, proceeds have been settled
+	Settled,
+}
+
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct Auction {
+	/// The auction's address. It is a pda of the user and vault index
+	pub pubkey: Pubkey,
+	/// The user whose vault was liquidated
+	pub user: Pubkey,
+	/// The account that paid rent for the auction, refunded when the auction is closed
+	pub initializer: Pubkey,
+	/// The current winning bidder for debt and surplus auctions
+	pub highest_bidder: Pubkey,
+	pub auction_type: AuctionType,
+	pub status: AuctionStatus,
+	pub market_index: u16,
+	pub liquidation_id: u16,
+	/// How quickly the price decays, in bps of the start price per second
+	/// For debt auctions, the min bps each bid must reduce the lot by
+	/// For surplus auctions, the min bps each bid must raise the bid by
+	pub bid_decrease_rate: u16,
+	/// Unix timestamp the price curve starts decaying from
+	pub start_ts: i64,
+	/// Unix timestamp after which no more bids are accepted
+	pub end_ts: i64,
+	/// The price the auction starts at
+	/// precision: PRICE_PRECISION
+	pub start_price: u64,
+	/// The lowest price the auction decays to, it has to be reset once reached
+	/// precision: PRICE_PRECISION
+	pub floor_price: u64,
+	/// The max amount of the lot a single bid can take
+	/// 0 if there is no limit
+	/// precision: token mint precision
+	pub max_lot_size: u64,
+	/// The amount of the lot left to be sold
+	/// precision: token mint precision
+	pub lot_amount: u64,
+	/// The amount of the lot sold so far
+	/// precision: token mint precision
+	pub lot_sold: u64,
+	/// The amount of synthetic the auction needs to raise to cover the vault's debt
+	/// precision: token mint precision
+	pub debt_amount: u64,
+	/// The amount of synthetic raised by bids so far
+	/// For surplus auctions, the governance tokens offered by the highest bid
+	/// precision: token mint precision
+	pub debt_raised: u64,
+	/// Collateral owed to the liquidator out of the lot left once the debt is covered
+	/// precision: token mint precision
+	pub liquidator_fee: u64,
+	/// Collateral owed to the insurance fund out of the lot left once the liquidator is paid
+	/// precision: token mint precision
+	pub if_fee: u64,
+	pub padding: [u8; 16],
+}
+
+impl Size for Auction {
+	const SIZE: usize = 8 + 240;
+}
+
+impl Auction {
+	pub fn is_active(&self) -> bool {
+		self.status == AuctionStatus::Active
+	}
+
+	pub fn is_expired(&self, now: i64) -> bool {
+		now > self.end_ts
+	}
+
+	/// An auction is complete once the whole lot is sold or the debt is fully covered
+	pub fn is_complete(&self) -> bool {
+		self.lot_amount == 0 || self.debt_raised >= self.debt_amount
+	}
+
+	/// Expired or decayed to its floor without clearing, the price has to be restarted
+	pub fn needs_reset(&self, now: i64) -> NormalResult<bool> {
+		if self.is_complete() {
+			return Ok(false);
+		}
+
+		Ok(self.is_expired(now) || self.get_price(now)? <= self.floor_price)
+	}
+
+	pub fn debt_remaining(&self) -> NormalResult<u64> {
+		Ok(self.debt_amount.saturating_sub(self.debt_raised))
+	}
+
+	pub fn shortfall(&self) -> NormalResult<u64> {
+		self.debt_remaining()
+	}
+
+	pub fn get_price(&self, now: i64) -> NormalResult<u64> {
+		calculate_auction_price(
+			self.start_price,
+			self.bid_decrease_rate,
+			self.start_ts,
+			now,
+			self.floor_price
+		)
+	}
+
+	pub fn has_bid(&self) -> bool {
+		self.highest_bidder != Pubkey::default()
+	}
+
+	pub fn record_bid(&mut self, lot_amount: u64, bid_amount: u64) -> NormalResult {
+		self.lot_amount = self.lot_amount.safe_sub(lot_amount)?;
+		self.lot_sold = self.lot_sold.safe_add(lot_amount)?;
+		self.debt_raised = self.debt_raised.safe_add(bid_amount)?;
+		Ok(())
+	}
+}
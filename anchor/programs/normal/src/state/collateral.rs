@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::main::SPOT_WEIGHT_PRECISION;
+use crate::error::{ ErrorCode, NormalResult };
+use crate::math::margin::MarginRequirementType;
+use crate::math::safe_math::SafeMath;
+use crate::state::oracle::OracleSource;
+use crate::validate;
+use solana_program::msg;
+
+/// An additional collateral asset a synth market accepts on top of token_mint_collateral.
+/// Held in the market's collateral registry, see SynthMarket::collateral_assets
+#[zero_copy(unsafe)]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct Collateral {
+	/// The collateral token mint. Pubkey::default() marks an unused registry slot
+	pub mint: Pubkey,
+	/// The vault holding the asset's deposits
+	pub vault: Pubkey,
+	/// oracle price data public key
+	pub oracle: Pubkey,
+	/// The total deposits of the asset across vaults
+	/// precision: token mint precision
+	pub deposit_amount: u64,
+	/// The max amount of the asset that can be deposited
+	/// 0 if there is no limit
+	/// precision: token mint precision
+	pub max_token_deposits: u64,
+	/// Seized from settled vaults for debt their primary collateral couldn't cover, paid out pro
+	/// rata alongside the market's settlement pool
+	/// precision: token mint precision
+	pub settlement_pool: u64,
+	/// The weight of the asset's value counted towards initial total collateral
+	/// precision: SPOT_WEIGHT_PRECISION
+	pub initial_asset_weight: u32,
+	/// The weight of the asset's value counted towards maintenance total collateral
+	/// precision: SPOT_WEIGHT_PRECISION
+	pub maintenance_asset_weight: u32,
+	/// The asset's token mint decimals
+	pub decimals: u32,
+	/// the oracle provider information. used to decode/scale the oracle public key
+	pub oracle_source: OracleSource,
+	pub padding: [u8; 3],
+}
+
+impl Collateral {
+	pub fn is_active(&self) -> bool {
+		self.mint != Pubkey::default()
+	}
+
+	pub fn get_asset_weight(&self, margin_type: MarginRequirementType) -> u32 {
+		match margin_type {
+			MarginRequirementType::Initial => self.initial_asset_weight,
+			MarginRequirementType::Maintenance => self.maintenance_asset_weight,
+		}
+	}
+
+	pub fn validate_max_token_deposits(&self, amount: u64) -> NormalResult {
+		if self.max_token_deposits == 0 {
+			return Ok(());
+		}
+
+		let deposit_amount = self.deposit_amount.safe_add(amount)?;
+
+		validate!(
+			deposit_amount <= self.max_token_deposits,
+			ErrorCode::MaxDeposit,
+			"collateral {} deposits {} would exceed max_token_deposits {}",
+			self.mint,
+			deposit_amount,
+			self.max_token_deposits
+		)
+	}
+}
+
+pub fn validate_collateral_weights(
+	initial_asset_weight: u32,
+	maintenance_asset_weight: u32
+) -> NormalResult {
+	validate!(
+		initial_asset_weight <= maintenance_asset_weight &&
+			maintenance_asset_weight <= SPOT_WEIGHT_PRECISION,
+		ErrorCode::InvalidCollateralWeights,
+		"initial_asset_weight={} must be <= maintenance_asset_weight={} <= {}",
+		initial_asset_weight,
+		maintenance_asset_weight,
+		SPOT_WEIGHT_PRECISION
+	)
+}
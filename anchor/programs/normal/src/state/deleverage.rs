@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::traits::Size;
+
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct DeleverageOrder {
+	/// The user whose vault the order protects. The order is a pda of the user and market index
+	pub user: Pubkey,
+	/// Keepers may deleverage the vault once its collateral ratio falls below this
+	/// precision: PERCENTAGE_PRECISION
+	pub trigger_collateral_ratio: u64,
+	/// The collateral ratio a deleverage sells collateral to restore
+	/// precision: PERCENTAGE_PRECISION
+	pub target_collateral_ratio: u64,
+	pub last_execution_ts: i64,
+	/// The max the AMM fill may be below oracle price
+	/// precision: PERCENTAGE_PRECISION
+	pub max_slippage: u32,
+	/// Share of the collateral sold paid to the keeper, capped by the market's liquidation penalty
+	/// precision: LIQUIDATION_FEE_PRECISION
+	pub keeper_tip: u32,
+	pub market_index: u16,
+	pub padding: [u8; 14],
+}
+
+impl Size for DeleverageOrder {
+	const SIZE: usize = 88;
+}
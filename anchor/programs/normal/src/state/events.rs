@@ -5,6 +5,8 @@ use crate::controller::position::PositionDirection;
 use crate::error::{ NormalResult, ErrorCode::InvalidOrder };
 use crate::math::casting::Cast;
 use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::index_market::{ FeeAccrualMethod, WeightingMethod };
+use crate::state::synth_market::{ AuctionType, MarketStatus };
 use crate::state::traits::Size;
 use crate::state::user::{ MarketType, Order };
 use anchor_lang::Discriminator;
@@ -90,9 +92,11 @@ pub struct LiquidateVaultRecord {
 	pub oracle_price: i64,
 	pub base_asset_amount: i64,
 	pub quote_asset_amount: i64,
-	/// precision: QUOTE_PRECISION
+	/// collateral owed to the liquidator, paid out of the auction's lot on settlement
+	/// precision: collateral mint precision
 	pub liquidator_fee: u64,
-	/// precision: QUOTE_PRECISION
+	/// collateral owed to the insurance fund, paid out of the auction's lot on settlement
+	/// precision: collateral mint precision
 	pub if_fee: u64,
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
@@ -105,6 +109,82 @@ pub struct VaultBankruptcyRecord {
 	pub clawback_user_payment: Option<u128>,
 }
 
+// Auction events
+
+#[event]
+#[derive(Default)]
+pub struct AuctionRecord {
+	pub ts: i64,
+	pub auction: Pubkey,
+	pub auction_type: AuctionType,
+	pub action: AuctionAction,
+	pub market_index: u16,
+	/// the user whose vault was liquidated
+	pub user: Pubkey,
+	/// the bidder for AuctionAction::Bid, the keeper otherwise
+	pub authority: Pubkey,
+	/// precision: PRICE_PRECISION
+	pub price: u64,
+	/// precision: token mint precision
+	pub lot_amount: u64,
+	/// precision: token mint precision
+	pub bid_amount: u64,
+	/// precision: token mint precision
+	pub lot_remaining: u64,
+	/// precision: token mint precision
+	pub debt_remaining: u64,
+	/// synthetic left uncovered, added to protocol debt
+	/// precision: token mint precision
+	pub shortfall: u64,
+	/// collateral paid to the liquidator on settlement
+	/// precision: token mint precision
+	pub liquidator_fee: u64,
+	/// collateral paid to the insurance fund on settlement
+	/// precision: token mint precision
+	pub if_fee: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct DebtAuctionSettleRecord {
+	pub ts: i64,
+	pub auction: Pubkey,
+	pub market_index: u16,
+	pub winner: Pubkey,
+	/// synthetic burned to cover protocol debt
+	/// precision: token mint precision
+	pub debt_covered: u64,
+	/// governance tokens minted to the winner
+	/// precision: gov token mint precision
+	pub gov_tokens_minted: u64,
+	pub protocol_debt_after: u64,
+	pub total_gov_token_inflation_after: u128,
+}
+
+#[event]
+#[derive(Default)]
+pub struct SurplusAuctionSettleRecord {
+	pub ts: i64,
+	pub auction: Pubkey,
+	pub market_index: u16,
+	pub winner: Pubkey,
+	/// insurance fund surplus sold to the winner
+	/// precision: token mint precision
+	pub lot_amount: u64,
+	/// governance tokens burned from the winning bid
+	/// precision: gov token mint precision
+	pub gov_tokens_burned: u64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+pub enum AuctionAction {
+	#[default]
+	Start,
+	Bid,
+	Reset,
+	Settle,
+}
+
 // Index events
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
@@ -120,22 +200,289 @@ pub struct CreateIndexRecord {
 	pub if_fee: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+#[event]
+#[derive(Default)]
 pub struct IndexMintRecord {
+	pub ts: i64,
 	pub market_index: u16,
 	pub user: Pubkey,
-	pub oracle_price: i64,
-	pub quote_asset_amount: i64,
-	pub ts: i64,
+	/// precision: PRICE_PRECISION
+	pub nav: u64,
+	/// precision: index token mint precision
+	pub index_amount: u64,
+	/// constituent amounts deposited, in the index's asset order
+	pub basket_amounts: Vec<u64>,
+	/// quote swapped into the basket, 0 for in-kind creations
+	/// precision: QUOTE_PRECISION
+	pub quote_amount: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+#[event]
+#[derive(Default)]
 pub struct IndexRedeemRecord {
+	pub ts: i64,
 	pub market_index: u16,
 	pub user: Pubkey,
+	/// precision: index token mint precision
+	pub index_amount: u64,
+	/// constituent amounts returned, in the index's asset order
+	pub basket_amounts: Vec<u64>,
+	/// quote received for the basket, 0 for in-kind redemptions
+	/// precision: QUOTE_PRECISION
+	pub quote_amount: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct IndexMarketRebalanceRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub weighting_method: WeightingMethod,
+	/// the new asset weights in bps, in the index's asset order
+	pub weights: Vec<u16>,
+}
+
+#[event]
+#[derive(Default)]
+pub struct CollateralAssetRecord {
+	pub ts: i64,
+	pub user: Pubkey,
+	pub market_index: u16,
+	/// index in the market's collateral registry
+	pub asset_index: u8,
+	pub mint: Pubkey,
+	/// positive for deposits, negative for withdrawals
+	/// precision: token mint precision
+	pub amount: i64,
+	/// precision: token mint precision
+	pub balance_after: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct CollateralLendingRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub action: CollateralLendingAction,
+	pub lending_program: Pubkey,
+	/// collateral lent, unlent, or earned
+	/// precision: token mint precision
+	pub amount: u64,
+	/// lending program shares received or redeemed
+	pub shares: u64,
+	/// precision: token mint precision
+	pub collateral_loan_balance: u64,
+	pub collateral_loan_shares: u64,
+	/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
+	pub cumulative_lp_interest: u128,
+	/// precision: SPOT_UTILIZATION_PRECISION
+	pub utilization: u64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+pub enum CollateralLendingAction {
+	#[default]
+	Lend,
+	Unlend,
+	AccrueYield,
+}
+
+#[event]
+#[derive(Default)]
+pub struct FlashMintRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub authority: Pubkey,
+	/// precision: token mint precision
+	pub amount: u64,
+	/// fee paid into the revenue pool on repay
+	/// precision: token mint precision
+	pub fee: u64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+pub enum PsmSwapDirection {
+	#[default]
+	/// quote in, synthetic minted
+	QuoteToSynthetic,
+	/// synthetic burned, quote out
+	SyntheticToQuote,
+}
+
+#[event]
+#[derive(Default)]
+pub struct PsmSwapRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub user: Pubkey,
+	pub direction: PsmSwapDirection,
+	/// quote paid in or received
+	/// precision: quote mint precision
+	pub quote_amount: u64,
+	/// synthetic minted or burned
+	/// precision: synthetic mint precision
+	pub synthetic_amount: u64,
+	/// precision: quote mint precision
+	pub fee: u64,
+	/// precision: quote mint precision
+	pub reserve_balance_after: u64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+pub enum AmmRebalanceDirection {
+	#[default]
+	/// pool above the band, synthetic minted and sold for collateral
+	Expand,
+	/// pool below the band, synthetic bought back with collateral and burned
+	Contract,
+}
+
+#[event]
+#[derive(Default)]
+pub struct AmmRebalanceRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub keeper: Pubkey,
+	pub direction: AmmRebalanceDirection,
+	/// synthetic minted or burned
+	/// precision: synthetic mint precision
+	pub synthetic_amount: u64,
+	/// collateral received or paid
+	/// precision: collateral mint precision
+	pub collateral_amount: u64,
+	/// the oracle price the AMM was pulled back to
+	/// precision: PRICE_PRECISION
 	pub oracle_price: i64,
-	pub base_asset_amount: i64,
+	pub sqrt_price_before: u128,
+	pub sqrt_price_after: u128,
+	/// precision: synthetic mint precision
+	pub synthetic_minted_after: u64,
+	/// precision: collateral mint precision
+	pub collateral_balance_after: u64,
+}
+
+#[event]
+pub struct SynthMarketSettlementRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub status: MarketStatus,
+	/// precision: PRICE_PRECISION
+	pub expiry_price: i64,
+	/// precision: PRICE_PRECISION
+	pub expiry_collateral_price: i64,
+	/// precision: collateral token mint precision
+	pub settlement_pool: u64,
+	/// precision: collateral token mint precision
+	pub settlement_deficit: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct VaultSettlementRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub user: Pubkey,
+	/// precision: token mint precision
+	pub debt_amount: u128,
+	/// precision: collateral token mint precision
+	pub collateral_amount: u128,
+	/// precision: collateral token mint precision
+	pub collateral_to_pool: u128,
+	/// precision: collateral token mint precision
+	pub excess_collateral: u128,
+	/// precision: collateral token mint precision
+	pub deficit: u128,
+}
+
+#[event]
+#[derive(Default)]
+pub struct VaultDeleverageRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub user: Pubkey,
+	pub keeper: Pubkey,
+	/// collateral sold through the market's AMM
+	/// precision: collateral token mint precision
+	pub collateral_sold: u64,
+	/// debt repaid with the synthetic bought
+	/// precision: token mint precision
+	pub synthetic_repaid: u64,
+	/// precision: collateral token mint precision
+	pub keeper_tip: u64,
+	/// precision: PERCENTAGE_PRECISION
+	pub collateral_ratio_before: u128,
+	/// precision: PERCENTAGE_PRECISION
+	pub collateral_ratio_after: u128,
+}
+
+#[event]
+#[derive(Default)]
+pub struct RedemptionRecord {
 	pub ts: i64,
+	pub market_index: u16,
+	pub redeemer: Pubkey,
+	/// the vault redeemed against
+	pub user: Pubkey,
+	/// debt repaid by the redeemer
+	/// precision: token mint precision
+	pub synthetic_amount: u128,
+	/// collateral paid to the redeemer
+	/// precision: collateral token mint precision
+	pub collateral_amount: u128,
+	/// collateral left in the vault as the redemption fee
+	/// precision: collateral token mint precision
+	pub fee: u128,
+	/// precision: PERCENTAGE_PRECISION
+	pub collateral_ratio_before: u128,
+	/// precision: PERCENTAGE_PRECISION
+	pub fee_rate: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct SettlementRedeemRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub user: Pubkey,
+	/// precision: token mint precision
+	pub synthetic_amount: u64,
+	/// precision: collateral token mint precision
+	pub collateral_amount: u64,
+	/// precision: collateral token mint precision
+	pub settlement_pool: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct StabilityFeeRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	/// precision: SPOT_BALANCE_PRECISION
+	pub debt_balance: u128,
+	/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
+	pub cumulative_debt_interest: u128,
+	/// fees accrued since the last update
+	/// precision: token mint precision
+	pub stability_fee: u128,
+	/// precision: token mint precision
+	pub unminted_stability_fees: u128,
+	/// precision: SPOT_UTILIZATION_PRECISION
+	pub utilization: u64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct IndexFeeClaimRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	pub recipient: Pubkey,
+	pub fee_accrual_method: FeeAccrualMethod,
+	/// precision: index token mint precision
+	pub manager_fee: u64,
+	/// precision: index token mint precision
+	pub referral_fee: u64,
+	/// precision: index token mint precision
+	pub protocol_fee: u64,
 }
 
 // Insurance events
@@ -159,6 +506,20 @@ pub struct InsuranceFundRecord {
 	pub amount: i64,
 }
 
+#[event]
+#[derive(Default)]
+pub struct InsuranceFundSurplusRecord {
+	pub ts: i64,
+	pub market_index: u16,
+	/// precision: token mint precision
+	pub insurance_vault_amount: u64,
+	/// precision: token mint precision
+	pub max_insurance: u64,
+	/// amount over max_insurance available to a surplus auction
+	/// precision: token mint precision
+	pub surplus: u64,
+}
+
 #[event]
 #[derive(Default)]
 pub struct InsuranceFundStakeRecord {
@@ -1,23 +1,11 @@
 use anchor_lang::prelude::*;
-use std::cell::Ref;
-use std::collections::BTreeMap;
 
 use crate::error::{ NormalResult, ErrorCode };
-use crate::math::casting::Cast;
-use crate::constants::main::{
-	PRICE_PRECISION,
-	PRICE_PRECISION_I64,
-	PRICE_PRECISION_U64,
-};
+use crate::math::index::calculate_total_weight;
+use crate::constants::main::INDEX_WEIGHT_PRECISION;
 use crate::math::safe_math::SafeMath;
-use crate::state::oracle::get_oracle_price;
-use switchboard::{ AggregatorAccountData, SwitchboardDecimal };
-use switchboard_on_demand::{ PullFeedAccountData, SB_ON_DEMAND_PRECISION };
-
-use crate::error::ErrorCode::{ InvalidOracle, UnableToLoadOracle };
-use crate::math::safe_unwrap::SafeUnwrap;
-use crate::state::load_ref::load_ref;
-use crate::state::synth_market::SynthMarket;
+use crate::state::oracle::OracleSource;
+use crate::state::synth_market::MarketStatus;
 use crate::state::traits::Size;
 use crate::validate;
 
@@ -37,12 +25,62 @@ pub enum IndexVisibility {
 	Public, // immutable
 }
 
-#[derive(Default, PartialEq, Debug)]
+#[derive(
+	Clone,
+	Copy,
+	BorshSerialize,
+	BorshDeserialize,
+	PartialEq,
+	Debug,
+	Eq,
+	Default
+)]
+pub enum FeeAccrualMethod {
+	/// Owed fees are paid by minting new index tokens
+	#[default]
+	Dilution,
+	/// Owed fees are paid out of the basket, lowering the NAV
+	NavDeduction,
+}
+
+#[derive(
+	Clone,
+	Copy,
+	BorshSerialize,
+	BorshDeserialize,
+	PartialEq,
+	Debug,
+	Eq,
+	Default
+)]
+pub enum WeightingMethod {
+	/// Every asset gets the same weight
+	#[default]
+	Equal,
+	/// Weights set by the rebalance authority
+	Custom,
+	/// Weighted by supply * oracle price
+	MarketCap,
+	/// Weighted by sqrt(supply * oracle price), dampening the largest assets
+	SquareRootMarketCap,
+}
+
+#[derive(
+	Clone,
+	Copy,
+	AnchorSerialize,
+	AnchorDeserialize,
+	Default,
+	PartialEq,
+	Debug
+)]
 pub struct IndexAsset {
 	pub market_index: u16,
 	/// The asset's allocation (in basis points)
 	pub weight: u16,
 	pub last_updated_ts: i64,
+	/// The vault holding the index's basket of this asset
+	pub vault: Pubkey,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -66,6 +104,8 @@ pub struct IndexMarket {
 	pub fee_authority: Pubkey,
 	pub whitelist_authority: Pubkey,
 	pub rebalance_authority: Pubkey,
+	/// Receives the referral share of the expense ratio. Pubkey::default() if there is none
+	pub referrer: Pubkey,
 
 	pub market_index: u16,
 	/// Encoded display name for the market e.g. BTC-SOL
@@ -84,6 +124,12 @@ pub struct IndexMarket {
 	/// Index
 	///
 	pub assets: Vec<IndexAsset>,
+	/// How asset weights are derived on rebalance
+	pub weighting_method: WeightingMethod,
+	/// The min seconds between rebalances
+	pub rebalance_interval: i64,
+	/// The earliest ts the index can next be rebalanced
+	pub min_rebalance_ts: i64,
 	/// The visibility of the index fund
 	pub visibility: IndexVisibility,
 	/// List of accounts allowed to purchase the index
@@ -92,14 +138,20 @@ pub struct IndexMarket {
 
 	/// Fees
 	///
-	/// Total taker fee paid in basis points
+	/// Annual expense ratio in basis points, accrued per second
 	pub expense_ratio: u64,
-	///
+	/// The protocol's share of the expense ratio in basis points
 	pub revenue_share: u64,
+	/// The referrer's share of the expense ratio in basis points
+	pub referral_share: u64,
+	/// How owed fees are paid out
+	pub fee_accrual_method: FeeAccrualMethod,
 	pub protocol_fee_owed: u64,
 	pub manager_fee_owed: u64,
 	pub referral_fee_owed: u64,
 	pub total_fees: u64,
+	/// The last ts the expense ratio was accrued into the owed fees
+	pub fees_accrued_ts: i64,
 
 	// Metrics
 	//
@@ -118,7 +170,7 @@ pub struct IndexMarket {
 	pub rebalanced_ts: i64,
 	pub updated_ts: i64,
 
-	pub padding: [u8; 43],
+	pub padding: [u8; 9],
 }
 
 impl Default for IndexMarket {
@@ -137,11 +189,17 @@ impl Default for IndexMarket {
 			oracle_source: OracleSource::default(),
 
 			assets: [],
+			weighting_method: WeightingMethod::default(),
+			rebalance_interval: 0,
 			visibility: IndexVisibility::default(),
 			whitelist: [],
 			manager_fee: 0,
 			total_manager_fees: 0,
 			min_rebalance_ts: 0,
+			fees_accrued_ts: 0,
+			fee_accrual_method: FeeAccrualMethod::default(),
+			referral_share: 0,
+			referrer: Pubkey::default(),
 			rebalanced_ts: 0,
 			updated_ts: 0,
 
@@ -151,13 +209,13 @@ impl Default for IndexMarket {
 			expiry_ts: 0,
 			expiry_price: 0,
 
-			padding: [0; 43],
+			padding: [0; 9],
 		}
 	}
 }
 
 impl Size for IndexMarket {
-	const SIZE: usize = 1216; // TODO:
+	const SIZE: usize = 1248; // TODO:
 }
 
 impl IndexMarket {
@@ -165,26 +223,57 @@ impl IndexMarket {
 		self.whitelist.contains(&account)
 	}
 
-	pub fn can_rebalance(&self) -> bool {
-		self.rebalanced_ts > self.min_rebalance_ts
+	pub fn is_blacklisted(&self, account: &Pubkey) -> bool {
+		self.blacklist.contains(account)
+	}
+
+	/// Public indexes can be held by anyone not blacklisted, private ones only by the
+	/// authority and whitelisted accounts
+	pub fn can_hold(&self, account: &Pubkey) -> bool {
+		if self.is_blacklisted(account) {
+			return false;
+		}
+
+		self.visibility == IndexVisibility::Public ||
+			account == &self.authority ||
+			self.can_invest(*account)
+	}
+
+	pub fn can_rebalance(&self, now: i64) -> bool {
+		now >= self.min_rebalance_ts
+	}
+
+	pub fn time_since_last_rebalance(&self, now: i64) -> NormalResult<i64> {
+		now.safe_sub(self.rebalanced_ts)
 	}
 
-	pub fn time_since_last_rebalance(&self) -> bool {
-		let clock = Clock::get()?;
-		let now = clock.unix_timestamp;
+	/// Index tokens outstanding, including fees owed but not yet minted
+	pub fn get_total_shares(&self, index_token_supply: u64) -> NormalResult<u64> {
+		index_token_supply
+			.safe_add(self.manager_fee_owed)?
+			.safe_add(self.protocol_fee_owed)?
+			.safe_add(self.referral_fee_owed)
+	}
 
-		self.rebalanced_ts.safe_sub(now)
+	pub fn validate_weights(&self) -> NormalResult {
+		let total_weight = self.get_total_weight()?;
+
+		validate!(
+			total_weight == INDEX_WEIGHT_PRECISION,
+			ErrorCode::InvalidIndexWeights,
+			"index market {} weights sum to {}, must be {}",
+			self.market_index,
+			total_weight,
+			INDEX_WEIGHT_PRECISION
+		)
 	}
 
 	pub fn total_assets(&self) -> u8 {
 		self.assets.len()
 	}
 
-	pub fn get_total_weight(&self) -> u8 {
-		self.assets
-			.values()
-			.map(|asset| asset.weight)
-			.sum::<u8>()
+	pub fn get_total_weight(&self) -> NormalResult<u16> {
+		calculate_total_weight(&self.assets)
 	}
 
 	pub fn update_visibility(
@@ -220,76 +309,3 @@ impl IndexMarket {
 		return Ok(());
 	}
 }
-
-pub fn get_index_fund_price(
-	price_oracle: &AccountInfo,
-	clock_slot: u64,
-	multiple: u128,
-	is_pull_oracle: bool
-) -> NormalResult<OraclePriceData> {
-	let oracle_price_data = get_oracle_price(
-		&oracle_source,
-		&ctx.accounts.oracle,
-		Clock::get()?.unix_timestamp.cast()?
-	);
-
-	let mut pyth_price_data: &[u8] = &price_oracle
-		.try_borrow_data()
-		.or(Err(crate::error::ErrorCode::UnableToLoadOracle))?;
-
-	let oracle_price: i64;
-	let oracle_conf: u64;
-	let mut has_sufficient_number_of_data_points: bool = true;
-	let mut oracle_precision: u128;
-	let published_slot: u64;
-
-	let price_message = pyth_solana_receiver_sdk::price_update::PriceUpdateV2
-		::try_deserialize(&mut pyth_price_data)
-		.unwrap();
-	oracle_price = price_message.price_message.price;
-	oracle_conf = price_message.price_message.conf;
-	oracle_precision = (10_u128).pow(
-		price_message.price_message.exponent.unsigned_abs()
-	);
-	published_slot = price_message.posted_slot;
-
-	if oracle_precision <= multiple {
-		msg!("Multiple larger than oracle precision");
-		return Err(crate::error::ErrorCode::InvalidOracle);
-	}
-	oracle_precision = oracle_precision.safe_div(multiple)?;
-
-	let mut oracle_scale_mult = 1;
-	let mut oracle_scale_div = 1;
-
-	if oracle_precision > PRICE_PRECISION {
-		oracle_scale_div = oracle_precision.safe_div(PRICE_PRECISION)?;
-	} else {
-		oracle_scale_mult = PRICE_PRECISION.safe_div(oracle_precision)?;
-	}
-
-	// TODO: calculate nav
-
-	let oracle_price_scaled = oracle_price
-		.cast::<i128>()?
-		.safe_mul(oracle_scale_mult.cast()?)?
-		.safe_div(oracle_scale_div.cast()?)?
-		.cast::<i64>()?;
-
-	let oracle_conf_scaled = oracle_conf
-		.cast::<u128>()?
-		.safe_mul(oracle_scale_mult)?
-		.safe_div(oracle_scale_div)?
-		.cast::<u64>()?;
-
-	let oracle_delay: i64 = clock_slot
-		.cast::<i64>()?
-		.safe_sub(published_slot.cast()?)?;
-
-	Ok(OraclePriceData {
-		price: oracle_price_scaled,
-		confidence: oracle_conf_scaled,
-		delay: oracle_delay,
-		has_sufficient_number_of_data_points,
-	})
-}
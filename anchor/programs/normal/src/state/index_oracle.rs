@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::oracle::OraclePriceData;
+use crate::state::traits::Size;
+
+/// NAV of an index market's basket, published by the update_index_oracle crank so the index
+/// can be read through the OracleMap like any other feed
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct IndexOracle {
+	/// The oracle's address. It is a pda of the index market index
+	pub pubkey: Pubkey,
+	pub index_market: Pubkey,
+	/// NAV per index token
+	/// precision: PRICE_PRECISION
+	pub price: i64,
+	/// NAV equivalent of the constituents' confidence intervals
+	/// precision: PRICE_PRECISION
+	pub confidence: u64,
+	/// The slot the NAV was last computed at
+	pub last_update_slot: u64,
+	pub last_update_ts: i64,
+	/// The delay of the stalest constituent oracle when the NAV was computed, in slots
+	pub max_constituent_delay: i64,
+	pub market_index: u16,
+	pub padding: [u8; 30],
+}
+
+impl Size for IndexOracle {
+	const SIZE: usize = 144;
+}
+
+impl IndexOracle {
+	/// Delay is the slots since the crank plus the stalest constituent at the crank
+	pub fn get_price_data(&self, clock_slot: u64) -> NormalResult<OraclePriceData> {
+		let delay = clock_slot
+			.cast::<i64>()?
+			.safe_sub(self.last_update_slot.cast()?)?
+			.safe_add(self.max_constituent_delay)?;
+
+		Ok(OraclePriceData {
+			price: self.price,
+			confidence: self.confidence,
+			delay,
+			has_sufficient_number_of_data_points: self.last_update_slot > 0,
+		})
+	}
+}
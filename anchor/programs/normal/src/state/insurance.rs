@@ -18,6 +18,9 @@ pub struct InsuranceFund {
 	pub revenue_settle_period: i64,
 	pub total_factor: u32, // percentage of interest for total insurance
 	pub user_factor: u32, // percentage of interest for user staked insurance
+	/// What the fund holds before the rest of a vault is surplus for a surplus auction, 0 if
+	/// uncapped
+	/// precision: vault token precision
 	pub max_insurance: u64,
 	pub paused_operations: u8,
 }
@@ -1,7 +1,12 @@
 pub mod amm;
+pub mod amm_observation;
+pub mod auction;
+pub mod collateral;
+pub mod deleverage;
 pub mod events;
 pub mod index_market_map;
 pub mod index_market;
+pub mod index_oracle;
 pub mod insurance;
 pub mod load_ref;
 pub mod oracle;
@@ -18,7 +18,7 @@ use crate::state::load_ref::load_ref;
 use crate::state::synth_market::SynthMarket;
 use crate::state::traits::Size;
 use crate::validate;
-use crate::state::oracles::index_fund::get_index_fund_price;
+use crate::state::index_oracle::IndexOracle;
 
 // #[cfg(test)]
 // mod tests;
@@ -148,6 +148,8 @@ pub enum OracleSource {
 	Pyth1MPull,
 	PythStableCoinPull,
 	SwitchboardOnDemand,
+	/// An IndexOracle published by the update_index_oracle crank
+	IndexNav,
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -201,12 +203,19 @@ pub fn get_oracle_price(
 		OracleSource::PythStableCoinPull => {
 			get_pyth_stable_coin_price(price_oracle, clock_slot, true)
 		}
-		OracleSource::IndexFund =>
-			get_index_fund_price(price_oracle, clock_slot, multiple, true),
-		OracleSource::Yield => false, // TODO:
+		OracleSource::IndexNav => get_index_nav_price(price_oracle, clock_slot),
 	}
 }
 
+pub fn get_index_nav_price(
+	price_oracle: &AccountInfo,
+	clock_slot: u64
+) -> NormalResult<OraclePriceData> {
+	let index_oracle = load_ref::<IndexOracle>(price_oracle).or(Err(UnableToLoadOracle))?;
+
+	index_oracle.get_price_data(clock_slot)
+}
+
 pub fn get_pyth_price(
 	price_oracle: &AccountInfo,
 	clock_slot: u64,
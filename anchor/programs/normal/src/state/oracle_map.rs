@@ -19,11 +19,12 @@ use crate::ids::{
 use crate::constants::main::PRICE_PRECISION_I64;
 use crate::math::oracle::{ oracle_validity, OracleValidity };
 use crate::state::oracle::{ get_oracle_price, OraclePriceData, OracleSource };
+use crate::state::index_oracle::IndexOracle;
 use crate::state::state::OracleGuardRails;
 use crate::state::synth_market::MarketType;
 use anchor_lang::prelude::{ AccountInfo, Pubkey };
 
-use anchor_lang::Key;
+use anchor_lang::{ Discriminator, Key };
 use solana_program::msg;
 use std::collections::BTreeMap;
 use std::iter::Peekable;
@@ -287,6 +288,16 @@ impl<'a> OracleMap<'a> {
 					oracle_source: OracleSource::SwitchboardOnDemand,
 				});
 
+				continue;
+			} else if is_index_oracle(account_info) {
+				let account_info = account_info_iter.next().safe_unwrap()?;
+				let pubkey = account_info.key();
+
+				oracles.insert(pubkey, AccountInfoAndOracleSource {
+					account_info: account_info.clone(),
+					oracle_source: OracleSource::IndexNav,
+				});
+
 				continue;
 			}
 
@@ -370,6 +381,12 @@ impl<'a> OracleMap<'a> {
 				account_info: account_info.clone(),
 				oracle_source: OracleSource::SwitchboardOnDemand,
 			});
+		} else if is_index_oracle(account_info) {
+			let pubkey = account_info.key();
+			oracles.insert(pubkey, AccountInfoAndOracleSource {
+				account_info: account_info.clone(),
+				oracle_source: OracleSource::IndexNav,
+			});
 		} else if account_info.key() != Pubkey::default() {
 			return Err(ErrorCode::InvalidOracle);
 		}
@@ -409,3 +426,16 @@ impl<'a> OracleMap<'a> {
 		)
 	}
 }
+
+/// Index oracles are owned by this program, so they're told apart from markets by discriminator
+fn is_index_oracle(account_info: &AccountInfo) -> bool {
+	if account_info.owner != &crate::id() {
+		return false;
+	}
+
+	account_info
+		.try_borrow_data()
+		.map_or(false, |data| {
+			data.len() >= 8 && data[..8] == IndexOracle::discriminator()
+		})
+}
@@ -9,10 +9,11 @@ pub enum SynthOperation {
 	Transfer = 0b00010000,
 	Delete = 0b00100000,
 	Liquidation = 0b01000000,
+	FlashMint = 0b10000000,
 	// Swap = 0,
 }
 
-const ALL_SYNTH_OPERATIONS: [SynthOperation; 7] = [
+const ALL_SYNTH_OPERATIONS: [SynthOperation; 8] = [
 	SynthOperation::Create,
 	SynthOperation::Delete,
 	SynthOperation::Withdraw,
@@ -20,6 +21,7 @@ const ALL_SYNTH_OPERATIONS: [SynthOperation; 7] = [
 	SynthOperation::Transfer,
 	SynthOperation::Delete,
 	SynthOperation::Liquidation,
+	SynthOperation::FlashMint,
 ];
 
 impl SynthOperation {
@@ -1,5 +1,12 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::main::MAX_COLLATERAL_ASSETS;
+use crate::error::NormalResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::math::synth_balance::{ get_signed_token_amount, get_token_amount };
+use crate::state::synth_market::SynthMarket;
+
 #[zero_copy(unsafe)]
 #[derive(Default, Debug, Eq, PartialEq)]
 #[repr(C)]
@@ -8,12 +15,21 @@ pub struct Position {
 	/// interest of corresponding market.
 	/// precision: SPOT_BALANCE_PRECISION
 	pub scaled_balance: u64,
+	/// The scaled synthetic debt minted against the position. To get the token amount, multiply by the
+	/// cumulative debt interest of the corresponding market.
+	/// precision: SPOT_BALANCE_PRECISION
+	pub scaled_debt: u64,
+	/// Deposits of the market's registry collateral, indexed like SynthMarket::collateral_assets
+	/// precision: token mint precision of each asset
+	pub collateral_balances: [u64; MAX_COLLATERAL_ASSETS],
 	/// The cumulative deposits/borrows a user has made into a market
 	/// precision: token mint precision
 	pub cumulative_deposits: i64,
 	/// The market index of the corresponding spot market
 	pub market_index: u16,
-	pub padding: [u8; 4],
+	/// 1 + the vault's bucket in SynthMarket::redemption_buckets while it has debt, 0 otherwise
+	pub redemption_bucket: u8,
+	pub padding: [u8; 3],
 }
 
 impl SpotBalance for Position {
@@ -38,7 +54,21 @@ impl SpotBalance for Position {
 
 impl Position {
 	pub fn is_available(&self) -> bool {
-		self.scaled_balance == 0
+		self.scaled_balance == 0 &&
+			self.scaled_debt == 0 &&
+			self.collateral_balances.iter().all(|balance| *balance == 0)
+	}
+
+	pub fn has_debt(&self) -> bool {
+		self.scaled_debt > 0
+	}
+
+	pub fn get_collateral_amount(&self, synth_market: &SynthMarket) -> NormalResult<u128> {
+		get_token_amount(self.scaled_balance.cast()?, synth_market, &SpotBalanceType::Deposit)
+	}
+
+	pub fn get_debt_amount(&self, synth_market: &SynthMarket) -> NormalResult<u128> {
+		get_token_amount(self.scaled_debt.cast()?, synth_market, &SpotBalanceType::Borrow)
 	}
 
 	pub fn get_token_amount(
@@ -13,7 +13,7 @@ use crate::math::safe_unwrap::SafeUnwrap;
 use crate::state::traits::Size;
 use crate::{ LAMPORTS_PER_SOL_U64, PERCENTAGE_PRECISION_U64 };
 
-use super::synth_market::AuctionConfig;
+use super::synth_market::{ AuctionConfig, MintLimiter };
 
 #[derive(
 	Default,
@@ -77,6 +77,9 @@ pub struct State {
 	pub insurance_fund: Pubkey,
 
 	pub total_debt_ceiling: u64,
+	/// limits the value of synthetic minted per rolling window across every market
+	/// precision: QUOTE_PRECISION
+	pub mint_limiter: MintLimiter,
 
 	// User
 	//
@@ -98,8 +101,11 @@ pub struct State {
 	pub liquidation_duration: u8,
 	pub initial_pct_to_liquidate: u16,
 
-	// Debt Auctions
+	// Debt/Surplus Auctions
 	pub debt_auction_config: AuctionConfig,
+	pub surplus_auction_config: AuctionConfig,
+	// governance token minted by debt auctions and burned by surplus auctions
+	pub gov_token_mint: Pubkey,
 
 	pub dca_order_padding: u16,
 
@@ -162,7 +168,7 @@ impl State {
 }
 
 impl Size for State {
-	const SIZE: usize = 992;
+	const SIZE: usize = 1104;
 }
 
 #[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug)]
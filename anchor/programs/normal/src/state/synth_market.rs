@@ -1,8 +1,26 @@
 use anchor_lang::prelude::*;
 
 use crate::{
+	constants::main::{
+		DEFAULT_DEBT_CEILING_RAMP_DURATION,
+		AMM_REBALANCE_WINDOW,
+		DEFAULT_MINT_LIMIT_WINDOW,
+		FIVE_MILLION_QUOTE,
+		MAX_COLLATERAL_ASSETS,
+		ONE_MILLION_QUOTE,
+		REDEMPTION_BUCKETS,
+		TEN_MILLION_QUOTE,
+	},
+	error::NormalResult,
 	errors::ErrorCode,
-	math::{ margin::MarginRequirementType, MAX_PROTOCOL_FEE_RATE },
+	math::{
+		casting::Cast,
+		margin::MarginRequirementType,
+		mint_limit::{ calculate_minted_in_window, calculate_ramped_debt_ceiling },
+		safe_math::SafeMath,
+		MAX_PROTOCOL_FEE_RATE,
+	},
+	validate,
 };
 
 use super::{
@@ -65,6 +83,21 @@ pub enum SyntheticTier {
 }
 
 impl SyntheticTier {
+	pub fn is_isolated(&self) -> bool {
+		matches!(self, SyntheticTier::Isolated)
+	}
+
+	/// The most insurance a market of this tier can draw, whatever its quote_max_insurance
+	/// precision: QUOTE_PRECISION
+	pub fn get_max_quote_insurance(&self) -> u64 {
+		match self {
+			SyntheticTier::A => TEN_MILLION_QUOTE,
+			SyntheticTier::B => FIVE_MILLION_QUOTE,
+			SyntheticTier::C => ONE_MILLION_QUOTE,
+			SyntheticTier::Speculative | SyntheticTier::HighlySpeculative | SyntheticTier::Isolated => 0,
+		}
+	}
+
 	pub fn is_as_safe_as(
 		&self,
 		best_contract: &ContractTier,
@@ -108,7 +141,7 @@ pub enum AuctionType {
 	Surplus,
 }
 
-#[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct AuctionConfig {
 	/// where collateral auctions should take place (3rd party AMM vs private)
 	pub auction_location: AuctionPreference,
@@ -118,6 +151,139 @@ pub struct AuctionConfig {
 	pub auction_bid_decrease_rate: u16,
 	/// May be capped to prevent overly large auctions that could affect the market price.
 	pub max_auction_lot_size: u64,
+	/// The swap program External auctions are routed through.
+	/// Pubkey::default() routes through the market's own AMM
+	pub external_swap_program: Pubkey,
+}
+
+#[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct MintLimiter {
+	/// Max that may be minted within the rolling window, 0 if there is no limit
+	pub max_mint_per_window: u64,
+	/// Length of the rolling window in seconds
+	pub window: i64,
+	/// Rolling sum of what was minted within the window
+	pub minted_in_window: u64,
+	pub last_mint_ts: i64,
+}
+
+impl MintLimiter {
+	pub fn new(window: i64) -> Self {
+		MintLimiter {
+			window,
+			..MintLimiter::default()
+		}
+	}
+
+	/// Adds `amount` to the rolling sum, failing if it takes the sum over the limit
+	pub fn record_mint(&mut self, amount: u64, now: i64) -> NormalResult {
+		let minted_in_window = calculate_minted_in_window(
+			self.minted_in_window,
+			amount,
+			self.last_mint_ts,
+			self.window,
+			now
+		)?;
+
+		if self.max_mint_per_window != 0 && minted_in_window > self.max_mint_per_window {
+			msg!(
+				"minting {} takes the rolling window to {} over the {} limit",
+				amount,
+				minted_in_window,
+				self.max_mint_per_window
+			);
+			return Err(ErrorCode::MintLimitExceeded);
+		}
+
+		self.minted_in_window = minted_in_window;
+		self.last_mint_ts = now;
+
+		Ok(())
+	}
+
+	/// What may still be minted within the window, u64::MAX if there is no limit
+	pub fn get_remaining(&self, now: i64) -> NormalResult<u64> {
+		if self.max_mint_per_window == 0 {
+			return Ok(u64::MAX);
+		}
+
+		let minted_in_window = calculate_minted_in_window(
+			self.minted_in_window,
+			0,
+			self.last_mint_ts,
+			self.window,
+			now
+		)?;
+
+		Ok(self.max_mint_per_window.saturating_sub(minted_in_window))
+	}
+}
+
+#[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AmmRebalancer {
+	/// Max synthetic minted or bought back by one rebalance, 0 if rebalancing is disabled
+	/// precision: token mint precision
+	pub max_rebalance_per_call: u64,
+	/// Rolling 24h cap on synthetic minted or bought back by rebalances
+	pub limiter: MintLimiter,
+	/// Synthetic the protocol has sold into the AMM and not yet bought back
+	/// precision: token mint precision
+	pub synthetic_minted: u64,
+	/// Collateral received for that synthetic, held in the market vault
+	/// precision: collateral mint precision
+	pub collateral_balance: u64,
+}
+
+impl Default for AmmRebalancer {
+	fn default() -> Self {
+		AmmRebalancer {
+			max_rebalance_per_call: 0,
+			limiter: MintLimiter::new(AMM_REBALANCE_WINDOW),
+			synthetic_minted: 0,
+			collateral_balance: 0,
+		}
+	}
+}
+
+impl AmmRebalancer {
+	pub fn is_enabled(&self) -> bool {
+		self.max_rebalance_per_call != 0
+	}
+
+	/// The most a rebalance at `now` may mint or buy back under both caps
+	pub fn get_max_rebalance(&self, now: i64) -> NormalResult<u64> {
+		Ok(self.max_rebalance_per_call.min(self.limiter.get_remaining(now)?))
+	}
+}
+
+#[derive(Copy, AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct PegStabilityModule {
+	/// The quote token the synthetic is pegged to 1:1
+	pub quote_mint: Pubkey,
+	/// Holds the quote deposited for minted synthetic, Pubkey::default() if the PSM is not set up
+	pub reserve_vault: Pubkey,
+	pub quote_decimals: u32,
+	/// Fee charged swapping quote in for synthetic
+	/// precision: PERCENTAGE_PRECISION
+	pub fee_in: u32,
+	/// Fee charged swapping synthetic out for quote
+	/// precision: PERCENTAGE_PRECISION
+	pub fee_out: u32,
+	/// The max quote the reserve may back synthetic with, 0 pauses swaps in
+	/// precision: quote mint precision
+	pub max_reserve: u64,
+	/// Quote backing synthetic minted through the PSM
+	/// precision: quote mint precision
+	pub reserve_balance: u64,
+	/// Fees kept in the reserve vault on top of reserve_balance, until withdrawn by the admin
+	/// precision: quote mint precision
+	pub fees_accrued: u64,
+}
+
+impl PegStabilityModule {
+	pub fn is_initialized(&self) -> bool {
+		self.reserve_vault != Pubkey::default()
+	}
 }
 
 #[account]
@@ -156,7 +322,14 @@ pub struct SynthMarket {
 	/// Used to calculate the deposit token amount from the deposit balance
 	/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
 	pub cumulative_deposit_interest: u128,
+	/// The cumulative yield earned by lending vault collateral to the lending program
+	/// Applied on top of cumulative_deposit_interest to get the collateral token amount
+	/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
 	pub cumulative_lp_interest: u128,
+	/// The cumulative stability fee charged on synthetic debt
+	/// Used to calculate the debt token amount from the debt balance
+	/// precision: SPOT_CUMULATIVE_INTEREST_PRECISION
+	pub cumulative_debt_interest: u128,
 	/// no withdraw limits/guards when deposits below this threshold
 	/// precision: token mint precision
 	pub withdraw_guard_threshold: u64,
@@ -183,6 +356,15 @@ pub struct SynthMarket {
 	/// The price at which positions will be settled. Only set if market is expired
 	/// precision = PRICE_PRECISION
 	pub expiry_price: i64,
+	/// The collateral price frozen alongside expiry_price. Only set if market is expired
+	/// precision = PRICE_PRECISION
+	pub expiry_collateral_price: i64,
+	/// Collateral from settled vaults held for synthetic holders to redeem against
+	/// precision: collateral token mint precision
+	pub settlement_pool: u64,
+	/// Collateral settled vaults were short of their debt, absorbed pro rata by holders
+	/// precision: collateral token mint precision
+	pub settlement_deficit: u64,
 	/// The maximum spot position size
 	/// if the limit is 0, there is no limit
 	/// precision: token mint precision
@@ -227,14 +409,47 @@ pub struct SynthMarket {
 	pub margin_ratio_maintenance: u32,
 
 	/// maximum amount of synthetic tokens that can be minted against the market's collateral
+	/// while a raise is ramping in, the ceiling the ramp started from
 	pub debt_ceiling: u128,
+	/// the ceiling debt_ceiling ramps toward after a raise
+	pub target_debt_ceiling: u128,
+	pub debt_ceiling_ramp_start_ts: i64,
+	/// how long a raise takes to fully phase in, in seconds
+	pub debt_ceiling_ramp_duration: i64,
+	/// limits synthetic minted per rolling window
+	/// precision: token mint precision
+	pub mint_limiter: MintLimiter,
 	/// minimum amount of synthetic tokens that can be minted against a user's collateral to avoid inefficiencies
 	pub debt_floor: u32,
 
+	// Stability Fee
+	//
+	/// The utilization where the stability fee curve kinks from the optimal to the max slope
+	/// precision: SPOT_UTILIZATION_PRECISION
+	pub optimal_utilization: u32,
+	/// The annualized stability fee at zero utilization
+	/// precision: SPOT_RATE_PRECISION
+	pub base_stability_fee: u32,
+	/// The annualized stability fee at the optimal utilization
+	/// precision: SPOT_RATE_PRECISION
+	pub optimal_stability_fee: u32,
+	/// The annualized stability fee at full utilization
+	/// precision: SPOT_RATE_PRECISION
+	pub max_stability_fee: u32,
+
 	// Oracle
 	//
 	/// the oracle provider information. used to decode/scale the oracle public key
 	pub oracle_source: OracleSource,
+	/// oracle price data public key for token_mint_collateral
+	pub collateral_oracle: Pubkey,
+	/// the oracle provider information for collateral_oracle
+	pub collateral_oracle_source: OracleSource,
+	/// token_mint_collateral's decimals
+	pub collateral_decimals: u32,
+	/// Collateral registry: additional assets that can back the market's vaults
+	/// alongside token_mint_collateral
+	pub collateral_assets: [Collateral; MAX_COLLATERAL_ASSETS],
 	/// stores historically witnessed oracle data
 	pub historical_oracle_data: HistoricalOracleData,
 	/// the pct size of the oracle confidence interval
@@ -251,8 +466,11 @@ pub struct SynthMarket {
 	/// precision: PRICE_PRECISION
 	pub oracle_std: u64,
 
-	/// The total balance lent to 3rd party protocols
+	/// The collateral lent to the lending program, including yield accrued up to the last sync
+	/// precision: token mint precision
 	pub collateral_loan_balance: u64,
+	/// The lending program shares held against collateral_loan_balance
+	pub collateral_loan_shares: u64,
 
 	/// the ratio of collateral value to debt value, which must remain above the liquidation ratio.
 	pub collateralization_ratio: u64,
@@ -268,8 +486,21 @@ pub struct SynthMarket {
 	// Vault storing collateral tokens for auction
 	pub token_vault_collateral: Pubkey,
 
-	///
+	// Collateral Lending
+	//
+	/// The lending program vault collateral is lent to
+	/// Pubkey::default() if lending is disabled
+	pub lending_program: Pubkey,
+	/// The lending program's reserve for the collateral mint
+	pub lending_reserve: Pubkey,
+	/// The market lending signer's obligation on the lending reserve, see CpiLendingAdapter
+	pub lending_obligation: Pubkey,
+	/// The share of deposited collateral currently lent out
+	/// precision: SPOT_UTILIZATION_PRECISION
 	pub collateral_lending_utilization: u64,
+	/// The max share of deposited collateral that may be lent out
+	/// precision: SPOT_UTILIZATION_PRECISION
+	pub max_collateral_lending_utilization: u64,
 
 	// AMM
 	//
@@ -294,8 +525,47 @@ pub struct SynthMarket {
 	pub outstanding_debt: u128,
 	// Unbacked synthetic tokens (result of collateral auction deficits)
 	pub protocol_debt: u64,
+	/// Stability and flash mint fees held in the market's synthetic vault, used to cover protocol
+	/// debt and then settled to the insurance fund
+	/// precision: token mint precision
+	pub revenue_pool: u128,
+	/// Stability fees accrued on synthetic debt that haven't been minted into the revenue pool yet
+	/// precision: token mint precision
+	pub unminted_stability_fees: u128,
+
+	// Flash Mint
+	//
+	/// Fee charged on flash minted synthetic, paid into the revenue pool
+	/// precision: PERCENTAGE_PRECISION
+	pub flash_mint_fee: u32,
+	/// Synthetic minted by the open flash mint, 0 outside of a flash mint
+	/// precision: token mint precision
+	pub flash_mint_amount: u64,
+
+	// Peg Stability Module
+	//
+	pub psm: PegStabilityModule,
 
-	pub padding: [u8; 43],
+	// Redemptions
+	//
+	/// Rises with redemption volume and decays over time, added to REDEMPTION_FEE_FLOOR
+	/// precision: PERCENTAGE_PRECISION
+	pub redemption_base_rate: u64,
+	pub last_redemption_ts: i64,
+
+	// AMM Rebalancing
+	//
+	/// Protocol owned synthetic liquidity used to pull the AMM back inside its oracle band
+	pub amm_rebalancer: AmmRebalancer,
+
+	/// Vaults still carrying debt. Settlement redemptions open once this reaches zero
+	pub number_of_vaults_with_debt: u32,
+	/// Vaults with debt in each redemption bucket, see calculate_redemption_bucket. Redemptions
+	/// prove they hit the lowest collateral ratios by passing every vault of the buckets they
+	/// redeem from
+	pub redemption_buckets: [u32; REDEMPTION_BUCKETS],
+
+	pub padding: [u8; 7],
 }
 
 impl Default for SynthMarket {
@@ -311,6 +581,10 @@ impl Default for SynthMarket {
 
 			oracle: Pubkey::default(),
 			oracle_source: OracleSource::default(),
+			collateral_oracle: Pubkey::default(),
+			collateral_oracle_source: OracleSource::default(),
+			collateral_decimals: 0,
+			collateral_assets: [Collateral::default(); MAX_COLLATERAL_ASSETS],
 
 			token_mint_collateral: Pubkey::default(),
 			token_vault_synthetic: Pubkey::default(),
@@ -323,8 +597,22 @@ impl Default for SynthMarket {
 			margin_ratio_maintenance: 0,
 			imf_factor: 0,
 			debt_ceiling: 0,
+			target_debt_ceiling: 0,
+			debt_ceiling_ramp_start_ts: 0,
+			debt_ceiling_ramp_duration: DEFAULT_DEBT_CEILING_RAMP_DURATION,
+			mint_limiter: MintLimiter::new(DEFAULT_MINT_LIMIT_WINDOW),
 			debt_floor: 0,
+			optimal_utilization: 0,
+			base_stability_fee: 0,
+			optimal_stability_fee: 0,
+			max_stability_fee: 0,
+			lending_program: Pubkey::default(),
+			lending_reserve: Pubkey::default(),
+			lending_obligation: Pubkey::default(),
 			collateral_lending_utilization: 0,
+			max_collateral_lending_utilization: 0,
+			collateral_loan_balance: 0,
+			collateral_loan_shares: 0,
 			collateral_action_config: AuctionConfig::default(),
 
 			amm: AMM {
@@ -366,11 +654,27 @@ impl Default for SynthMarket {
 
 			outstanding_debt: 0,
 			protocol_debt: 0,
+			revenue_pool: 0,
+			unminted_stability_fees: 0,
+
+			flash_mint_fee: 0,
+			flash_mint_amount: 0,
+
+			psm: PegStabilityModule::default(),
+
+			amm_rebalancer: AmmRebalancer::default(),
+			redemption_base_rate: 0,
+			last_redemption_ts: 0,
 
 			expiry_ts: 0,
 			expiry_price: 0,
+			expiry_collateral_price: 0,
+			settlement_pool: 0,
+			settlement_deficit: 0,
 
-			padding: [0; 43],
+			number_of_vaults_with_debt: 0,
+			redemption_buckets: [0; REDEMPTION_BUCKETS],
+			padding: [0; 7],
 		}
 	}
 }
@@ -380,16 +684,73 @@ impl Size for SynthMarket {
 }
 
 impl SynthMarket {
+	/// quote_max_insurance capped by the market's synthetic tier
+	/// precision: QUOTE_PRECISION
+	pub fn get_quote_max_insurance(&self) -> u64 {
+		self.insurance_claim.quote_max_insurance.min(self.synthetic_tier.get_max_quote_insurance())
+	}
+
+	pub fn get_collateral_asset(&self, asset_index: u8) -> NormalResult<&Collateral> {
+		self.collateral_assets
+			.get(asset_index as usize)
+			.filter(|collateral| collateral.is_active())
+			.ok_or(ErrorCode::CollateralAssetNotFound)
+	}
+
+	pub fn get_collateral_asset_mut(&mut self, asset_index: u8) -> NormalResult<&mut Collateral> {
+		self.collateral_assets
+			.get_mut(asset_index as usize)
+			.filter(|collateral| collateral.is_active())
+			.ok_or(ErrorCode::CollateralAssetNotFound)
+	}
+
 	pub fn is_operation_paused(&self, operation: SynthOperation) -> bool {
 		SynthOperation::is_operation_paused(self.paused_operations, operation)
 	}
 
-	pub fn update_debt_ceiling(&self, debt_ceiling: u64) -> NormalResult<u64> {
-		if debt_ceiling > MAX_PROTOCOL_FEE_RATE {
-			return Err(ErrorCode::ProtocolFeeRateMaxExceeded.into());
-		}
+	/// The debt ceiling in effect at `now`, partway through any raise that is ramping in
+	pub fn get_debt_ceiling(&self, now: i64) -> NormalResult<u128> {
+		calculate_ramped_debt_ceiling(
+			self.debt_ceiling,
+			self.target_debt_ceiling,
+			self.debt_ceiling_ramp_start_ts,
+			self.debt_ceiling_ramp_duration,
+			now
+		)
+	}
+
+	/// Cuts take effect immediately. Raises ramp in from the ceiling currently in effect
+	/// over debt_ceiling_ramp_duration
+	pub fn update_debt_ceiling(&mut self, debt_ceiling: u128, now: i64) -> NormalResult {
+		let current_debt_ceiling = self.get_debt_ceiling(now)?;
+
+		self.debt_ceiling = current_debt_ceiling.min(debt_ceiling);
+		self.target_debt_ceiling = debt_ceiling;
+		self.debt_ceiling_ramp_start_ts = now;
 
-		// TODO: check if within rolling window
+		Ok(())
+	}
+
+	/// Checks a mint of `amount` synthetic against the ceiling in effect and the market's
+	/// rolling mint limit, and records it
+	pub fn record_mint(&mut self, amount: u64, now: i64) -> NormalResult {
+		let outstanding_debt_after = self.outstanding_debt.safe_add(amount.cast()?)?;
+		let debt_ceiling = self.get_debt_ceiling(now)?;
+
+		validate!(
+			outstanding_debt_after <= debt_ceiling,
+			ErrorCode::DebtCeilingExceeded,
+			"minting {} takes market {} debt to {} over the {} ceiling",
+			amount,
+			self.market_index,
+			outstanding_debt_after,
+			debt_ceiling
+		)?;
+
+		self.mint_limiter.record_mint(amount, now)?;
+		self.outstanding_debt = outstanding_debt_after;
+
+		Ok(())
 	}
 
 	pub fn get_margin_ratio(
@@ -457,3 +818,45 @@ pub enum AuctionPreference {
 	/// a DEX like Orca, Serum, Jupiter, etc.
 	External,
 }
+
+#[cfg(test)]
+mod test {
+	use crate::constants::main::{ FIVE_MILLION_QUOTE, ONE_MILLION_QUOTE, TEN_MILLION_QUOTE };
+	use crate::state::insurance::InsuranceClaim;
+	use crate::state::synth_market::{ SynthMarket, SyntheticTier };
+
+	#[test]
+	fn quote_max_insurance_capped_by_tier() {
+		let mut market = SynthMarket {
+			insurance_claim: InsuranceClaim {
+				quote_max_insurance: 2 * TEN_MILLION_QUOTE,
+				..InsuranceClaim::default()
+			},
+			..SynthMarket::default()
+		};
+
+		for (synthetic_tier, expected) in [
+			(SyntheticTier::A, TEN_MILLION_QUOTE),
+			(SyntheticTier::B, FIVE_MILLION_QUOTE),
+			(SyntheticTier::C, ONE_MILLION_QUOTE),
+			(SyntheticTier::Speculative, 0),
+			(SyntheticTier::HighlySpeculative, 0),
+			(SyntheticTier::Isolated, 0),
+		] {
+			market.synthetic_tier = synthetic_tier;
+			assert_eq!(market.get_quote_max_insurance(), expected);
+		}
+
+		// a lower configured max still applies
+		market.synthetic_tier = SyntheticTier::A;
+		market.insurance_claim.quote_max_insurance = ONE_MILLION_QUOTE;
+		assert_eq!(market.get_quote_max_insurance(), ONE_MILLION_QUOTE);
+	}
+
+	#[test]
+	fn only_isolated_tier_is_isolated() {
+		assert!(SyntheticTier::Isolated.is_isolated());
+		assert!(!SyntheticTier::A.is_isolated());
+		assert!(!SyntheticTier::HighlySpeculative.is_isolated());
+	}
+}
@@ -4,8 +4,10 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{ Token, TokenAccount };
 
 use crate::{ manager::swap_manager::PostSwapUpdate, state::{ amm::AMM } };
+use crate::controller;
 
 use super::{
+	SparseSwapTickSequenceBuilder,
 	mint_synthetic_to_vault,
 	transfer_from_owner_to_vault,
 	transfer_from_vault_to_owner,
@@ -51,6 +53,66 @@ pub fn update_and_swap_amm<'info>(
 	)
 }
 
+/// Swaps an exact input through `amm` up to `sqrt_price_limit` and settles the token
+/// transfers. Returns (amount_in, amount_out); a partial fill stops at the limit.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_amm_exact_input<'info>(
+	amm: &mut Account<'info, AMM>,
+	token_authority: &Signer<'info>,
+	token_owner_account_synthetic: &Account<'info, TokenAccount>,
+	token_owner_account_quote: &Account<'info, TokenAccount>,
+	token_vault_synthetic: &Account<'info, TokenAccount>,
+	token_vault_quote: &Account<'info, TokenAccount>,
+	token_program: &Program<'info, Token>,
+	tick_arrays: Vec<AccountInfo<'info>>,
+	amount_in: u64,
+	sqrt_price_limit: u128,
+	synthetic_to_quote: bool,
+	timestamp: u64
+) -> Result<(u64, u64)> {
+	let builder = SparseSwapTickSequenceBuilder::try_from(
+		amm,
+		synthetic_to_quote,
+		tick_arrays,
+		None
+	)?;
+	let mut swap_tick_sequence = builder.build()?;
+
+	let swap_update = controller::swap::swap(
+		amm,
+		&mut swap_tick_sequence,
+		amount_in,
+		sqrt_price_limit,
+		true,
+		synthetic_to_quote,
+		timestamp
+	)?;
+
+	let (amount_in, amount_out) = if synthetic_to_quote {
+		(swap_update.amount_synthetic, swap_update.amount_quote)
+	} else {
+		(swap_update.amount_quote, swap_update.amount_synthetic)
+	};
+
+	let inside_range = amm.is_price_inside_range(swap_update.next_sqrt_price);
+
+	update_and_swap_amm(
+		amm,
+		token_authority,
+		token_owner_account_synthetic,
+		token_owner_account_quote,
+		token_vault_synthetic,
+		token_vault_quote,
+		token_program,
+		swap_update,
+		synthetic_to_quote,
+		timestamp,
+		inside_range
+	)?;
+
+	Ok((amount_in, amount_out))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn perform_swap<'info>(
 	amm: &Account<'info, AMM>,
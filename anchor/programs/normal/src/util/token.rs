@@ -1,3 +1,4 @@
+use crate::signer::get_signer_seeds;
 use crate::state::synth_market::SynthMarket;
 use crate::state::{ PositionBundle, AMM };
 use anchor_lang::prelude::*;
@@ -186,6 +187,29 @@ pub fn transfer_from_vault_to_owner<'info>(
 	)
 }
 
+pub fn transfer_from_program_vault_to_owner<'info>(
+	normal_signer: &AccountInfo<'info>,
+	signer_nonce: u8,
+	token_vault: &Account<'info, TokenAccount>,
+	token_owner_account: &Account<'info, TokenAccount>,
+	token_program: &Program<'info, Token>,
+	amount: u64
+) -> Result<()> {
+	let signature_seeds = get_signer_seeds(&signer_nonce);
+	token::transfer(
+		CpiContext::new_with_signer(
+			token_program.to_account_info(),
+			Transfer {
+				from: token_vault.to_account_info(),
+				to: token_owner_account.to_account_info(),
+				authority: normal_signer.to_account_info(),
+			},
+			&[&signature_seeds[..]]
+		),
+		amount
+	)
+}
+
 pub fn transfer_from_owner_to_amm<'info>(
 	position_authority: &Signer<'info>,
 	token_owner_account: &Account<'info, TokenAccount>,
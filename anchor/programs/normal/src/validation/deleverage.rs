@@ -0,0 +1,41 @@
+use crate::constants::main::{ MAX_DELEVERAGE_SLIPPAGE, PERCENTAGE_PRECISION };
+use crate::error::{ ErrorCode, NormalResult };
+use crate::math::casting::Cast;
+use crate::validate;
+use solana_program::msg;
+
+pub fn validate_deleverage_order(
+	trigger_collateral_ratio: u64,
+	target_collateral_ratio: u64,
+	max_slippage: u32,
+	keeper_tip: u32
+) -> NormalResult {
+	validate!(
+		trigger_collateral_ratio.cast::<u128>()? > PERCENTAGE_PRECISION,
+		ErrorCode::InvalidDeleverageOrder,
+		"trigger_collateral_ratio {} must be above {}",
+		trigger_collateral_ratio,
+		PERCENTAGE_PRECISION
+	)?;
+
+	// the gap also has to cover the keeper tip, see calculate_deleverage_collateral_to_sell
+	validate!(
+		target_collateral_ratio > trigger_collateral_ratio &&
+			target_collateral_ratio.cast::<u128>()? >
+				PERCENTAGE_PRECISION + keeper_tip.cast::<u128>()?,
+		ErrorCode::InvalidDeleverageOrder,
+		"target_collateral_ratio {} must be above trigger_collateral_ratio {}",
+		target_collateral_ratio,
+		trigger_collateral_ratio
+	)?;
+
+	validate!(
+		max_slippage <= MAX_DELEVERAGE_SLIPPAGE,
+		ErrorCode::InvalidDeleverageOrder,
+		"max_slippage {} must be <= {}",
+		max_slippage,
+		MAX_DELEVERAGE_SLIPPAGE
+	)?;
+
+	Ok(())
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use solana_program::sysvar::instructions::{
+	load_current_index_checked,
+	load_instruction_at_checked,
+};
+
+use crate::error::ErrorCode;
+use crate::instruction::{ FlashMint, FlashRepay };
+
+/// Checks the flash mint being executed is a top level instruction and that a flash_repay of
+/// the same amount for the same market follows it in the transaction, with no other flash
+/// mint opened in between
+pub fn validate_flash_repay_follows(
+	instructions_sysvar: &AccountInfo,
+	market_index: u16,
+	amount: u64
+) -> Result<()> {
+	let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+	let current_ix = load_instruction_at_checked(current_index, instructions_sysvar)?;
+
+	// under a CPI the instruction at the current index belongs to the calling program
+	if current_ix.program_id != crate::id() || !current_ix.data.starts_with(&FlashMint::DISCRIMINATOR) {
+		msg!("flash_mint invoked through program {}", current_ix.program_id);
+		return Err(ErrorCode::FlashMintCpiNotAllowed.into());
+	}
+
+	let mut index = current_index + 1;
+	loop {
+		let ix = match load_instruction_at_checked(index, instructions_sysvar) {
+			Ok(ix) => ix,
+			Err(ProgramError::InvalidArgument) => {
+				break;
+			}
+			Err(e) => {
+				return Err(e.into());
+			}
+		};
+
+		if ix.program_id == crate::id() && ix.data.len() >= 8 {
+			let (discriminator, args) = ix.data.split_at(8);
+
+			if discriminator == FlashMint::DISCRIMINATOR {
+				msg!("flash mint nested at instruction {}", index);
+				return Err(ErrorCode::FlashMintAlreadyOpen.into());
+			}
+
+			if discriminator == FlashRepay::DISCRIMINATOR {
+				let repay = FlashRepay::try_from_slice(args)?;
+
+				if repay.market_index != market_index || repay.amount != amount {
+					msg!(
+						"flash repay at instruction {} is for market {} amount {}, expected market {} amount {}",
+						index,
+						repay.market_index,
+						repay.amount,
+						market_index,
+						amount
+					);
+					return Err(ErrorCode::FlashMintMissingRepay.into());
+				}
+
+				return Ok(());
+			}
+		}
+
+		index += 1;
+	}
+
+	Err(ErrorCode::FlashMintMissingRepay.into())
+}
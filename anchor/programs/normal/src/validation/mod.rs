@@ -1,3 +1,5 @@
+pub mod deleverage;
+pub mod flash_mint;
 pub mod index_market;
 pub mod sig_verification;
 pub mod user;